@@ -0,0 +1,9 @@
+extern crate protosnirk;
+use protosnirk::parse::Parser;
+use protosnirk::lex::IterTokenizer;
+fn main() {
+    let path = std::env::args().nth(1).unwrap();
+    let text = std::fs::read_to_string(path).unwrap();
+    let mut parser = Parser::new(IterTokenizer::new(text.chars()));
+    println!("{:#?}", parser.parse_unit());
+}
@@ -0,0 +1,8 @@
+extern crate protosnirk;
+use protosnirk::lex::{IterTokenizer, Tokenizer, TokenType};
+fn main() {
+    let path = std::env::args().nth(1).unwrap();
+    let text = std::fs::read_to_string(path).unwrap();
+    let mut t = IterTokenizer::new(text.chars());
+    loop { let tok = t.next(); println!("{:?} {:?}", tok.get_type(), tok.text()); if tok.get_type() == TokenType::EOF { break } }
+}
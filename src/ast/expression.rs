@@ -4,10 +4,11 @@
 //! They are usually emitted as asm instructions operating on variables.
 
 use lex::{Token, TokenType, TokenData, Span, Location};
-use ast::{ScopedId, Identifier, UnaryOperator, BinaryOperator};
+use ast::{ScopedId, Identifier, Block, Pattern, UnaryOperator, BinaryOperator,
+          TypeExpression};
 use parse::{ParseResult, ParseError, ExpectedNextType};
 
-use std::cell::Ref;
+use std::cell::{RefCell, Ref};
 
 /// Expression types
 #[derive(Debug, PartialEq, Clone)]
@@ -24,12 +25,37 @@ pub enum Expression {
     IfExpression(IfExpression),
     /// Invocation of a funciton with standard named arg setup.
     FnCall(FnCall),
+    /// `loop` expression, which has the value given to its `break`s.
+    Loop(Loop),
+    /// `match` expression, which picks a block by matching patterns.
+    Match(Match),
+    /// Access of a struct's field, such as `p.x`, or a tuple's element,
+    /// such as `t.0`.
+    FieldAccess(FieldAccess),
+    /// Copy of a struct with some fields changed, such as `p with (x: 1)`.
+    StructUpdate(StructUpdate),
+    /// Tuple of values, such as `(1, true)`.
+    Tuple(TupleExpression),
+    /// Array of values, such as `[1, 2, 3]`.
+    Array(ArrayExpression),
+    /// Array of a repeated value, such as `[0: 5]`.
+    ArrayRepeat(ArrayRepeat),
+    /// Element of an array, such as `a[i]`.
+    Index(IndexExpression),
+    /// Slice of an array, such as `&a`.
+    Slice(SliceExpression),
+    /// Call of a method on a value, such as `a.push(value: 1)`.
+    MethodCall(MethodCall),
+    /// Anonymous function, such as `fn(x: float) => x * scale`.
+    Lambda(Lambda),
 
     // "Non-value expressions"
     // See https://github.com/immington-industries/protosnirk/issues/30
 
     /// Assignment - not considered value expression
     Assignment(Assignment),
+    /// Assignment to an element of an array, such as `a[i] = 1`
+    IndexAssignment(IndexAssignment),
 }
 
 impl Expression {
@@ -41,7 +67,8 @@ impl Expression {
     /// from being used to represent `()`.
     pub fn has_value(&self) -> bool {
         match *self {
-            Expression::Assignment(_) => false,
+            Expression::Assignment(_)
+            | Expression::IndexAssignment(_) => false,
             _ => true
         }
     }
@@ -71,6 +98,18 @@ impl Expression {
             BinaryOp(ref b) => b.span(),
             FnCall(ref f) => f.span(),
             IfExpression(ref i) => i.span(),
+            Loop(ref l) => l.span(),
+            Match(ref m) => m.span(),
+            FieldAccess(ref f) => f.span(),
+            StructUpdate(ref u) => u.span(),
+            Tuple(ref t) => t.span(),
+            Array(ref a) => a.span(),
+            ArrayRepeat(ref a) => a.span(),
+            Index(ref i) => i.span(),
+            Slice(ref s) => s.span(),
+            MethodCall(ref m) => m.span(),
+            Lambda(ref l) => l.span(),
+            IndexAssignment(ref a) => a.span(),
             UnaryOp(ref u) => u.span()
         }
     }
@@ -81,8 +120,12 @@ impl Expression {
 pub enum LiteralValue {
     /// Literals `true` and `false`
     Bool(bool),
-    /// Numeric literals
+    /// Integer literals, such as `34`
+    Int(u64),
+    /// Floating point literals, such as `3.4`
     Float(f64),
+    /// String literals, such as `"hello\n"`, with their escapes replaced
+    Str(String),
     /// `()`
     Unit
 }
@@ -91,7 +134,8 @@ pub enum LiteralValue {
 #[derive(Debug, PartialEq, Clone)]
 pub struct Literal {
     token: Token,
-    value: LiteralValue
+    value: LiteralValue,
+    id: RefCell<ScopedId>
 }
 impl Literal {
     /// Creates a new `Literal` from the given token and value.
@@ -99,7 +143,8 @@ impl Literal {
         debug_assert!(token.get_type() == TokenType::Literal,
             "Literal token created with bad token {:?}", token);
         Literal {
-            token, value
+            token, value,
+            id: RefCell::new(ScopedId::default())
         }
     }
     /// Creates a new boolean literal from the given token and boolean value.
@@ -111,7 +156,8 @@ impl Literal {
             "Literal bool created with bad token {:?}", token);
         Literal {
             token: token,
-            value: LiteralValue::Bool(value)
+            value: LiteralValue::Bool(value),
+            id: RefCell::new(ScopedId::default())
         }
     }
 
@@ -124,7 +170,8 @@ impl Literal {
             "Literal unit created with bad token {:?}", token);
         Literal {
             token,
-            value: LiteralValue::Unit
+            value: LiteralValue::Unit,
+            id: RefCell::new(ScopedId::default())
         }
     }
 
@@ -137,7 +184,36 @@ impl Literal {
             "Literal f64 called with bad token {:?}", token);
         Literal {
             token,
-            value: LiteralValue::Float(value)
+            value: LiteralValue::Float(value),
+            id: RefCell::new(ScopedId::default())
+        }
+    }
+
+    /// Creates a new string literal from the given token and unescaped value.
+    pub fn new_str(token: Token, value: String) -> Literal {
+        debug_assert!(
+            match token.data() {
+                TokenData::StringLiteral => true, _ => false
+            },
+            "Literal str called with bad token {:?}", token);
+        Literal {
+            token,
+            value: LiteralValue::Str(value),
+            id: RefCell::new(ScopedId::default())
+        }
+    }
+
+    /// Creates a new integer literal from the given token and value.
+    pub fn new_int(token: Token, value: u64) -> Literal {
+        debug_assert!(
+            match token.data() {
+                TokenData::NumberLiteral => true, _ => false
+            },
+            "Literal u64 called with bad token {:?}", token);
+        Literal {
+            token,
+            value: LiteralValue::Int(value),
+            id: RefCell::new(ScopedId::default())
         }
     }
 
@@ -150,6 +226,15 @@ impl Literal {
         &self.value
     }
 
+    /// The `ScopedId` used to infer the type of this literal.
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
     /// Gets the span of the literal [token]
     pub fn span(&self) -> Span {
         self.token.span()
@@ -287,6 +372,7 @@ impl IfExpression {
 pub struct FnCall {
     lvalue: Identifier,
     args: Vec<CallArgument>,
+    call_id: RefCell<ScopedId>,
     span: Span
 }
 
@@ -294,7 +380,7 @@ impl FnCall {
     pub fn new(span: Span,
                lvalue: Identifier,
                args: Vec<CallArgument>) -> FnCall {
-        FnCall { lvalue, args, span }
+        FnCall { lvalue, args, call_id: RefCell::default(), span }
     }
     pub fn ident(&self) -> &Identifier {
         &self.lvalue
@@ -313,6 +399,26 @@ impl FnCall {
         self.ident().set_id(id);
     }
 
+    /// Gets the `ScopedId` of this call, rather than of the called function.
+    pub fn call_id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.call_id.borrow()
+    }
+    pub fn set_call_id(&self, id: ScopedId) {
+        *self.call_id.borrow_mut() = id;
+    }
+
+    /// Gets the `ScopedId`s of the type arguments given to a generic
+    /// function by this call, which are inferred like variables.
+    pub fn type_arg_ids(&self, count: usize) -> Vec<ScopedId> {
+        let mut type_arg_id = self.call_id().pushed();
+        let mut ids = Vec::with_capacity(count);
+        for _ in 0..count {
+            ids.push(type_arg_id.clone());
+            type_arg_id.increment();
+        }
+        ids
+    }
+
     pub fn span(&self) -> Span {
         self.span
     }
@@ -342,3 +448,604 @@ impl CallArgument {
         Span::from(self.param.span() ..= self.value.span())
     }
 }
+
+/// Access of a field of a struct value, such as `p.x`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FieldAccess {
+    value: Box<Expression>,
+    field: Identifier,
+    span: Span
+}
+
+impl FieldAccess {
+    pub fn new(value: Box<Expression>, field: Identifier) -> FieldAccess {
+        FieldAccess {
+            span: Span::from(value.span() ..= field.span()),
+            value,
+            field
+        }
+    }
+
+    /// Gets the struct value whose field is accessed.
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+
+    /// Gets the name of the accessed field, or the index of the accessed
+    /// tuple element.
+    pub fn field(&self) -> &Identifier {
+        &self.field
+    }
+
+    /// Splits the field access into its value and field.
+    pub fn into_parts(self) -> (Box<Expression>, Identifier) {
+        (self.value, self.field)
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A tuple of values, such as `(1, true)`.
+///
+/// Elements of tuples are accessed like fields, with their index, such as
+/// `pair.0`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TupleExpression {
+    elements: Vec<Expression>,
+    span: Span
+}
+
+impl TupleExpression {
+    pub fn new(start: Location,
+               elements: Vec<Expression>,
+               end: Location) -> TupleExpression {
+        TupleExpression {
+            elements,
+            span: Span::from(start ..= end)
+        }
+    }
+
+    /// Gets the values of the elements of the tuple.
+    pub fn elements(&self) -> &[Expression] {
+        &self.elements
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// An array of values, such as `[1, 2, 3]`.
+///
+/// Like literals, arrays are given IDs so their types can be inferred, as
+/// empty arrays have no elements to infer them from.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArrayExpression {
+    elements: Vec<Expression>,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+
+impl ArrayExpression {
+    pub fn new(start: Location,
+               elements: Vec<Expression>,
+               end: Location) -> ArrayExpression {
+        ArrayExpression {
+            elements,
+            id: RefCell::default(),
+            span: Span::from(start ..= end)
+        }
+    }
+
+    /// Gets the values of the elements of the array.
+    pub fn elements(&self) -> &[Expression] {
+        &self.elements
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// An array of a value repeated a number of times, such as `[0: 5]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArrayRepeat {
+    value: Box<Expression>,
+    len: u64,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+
+impl ArrayRepeat {
+    pub fn new(start: Location,
+               value: Box<Expression>,
+               len: u64,
+               end: Location) -> ArrayRepeat {
+        ArrayRepeat {
+            value,
+            len,
+            id: RefCell::default(),
+            span: Span::from(start ..= end)
+        }
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    /// Gets the value which is repeated.
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+
+    /// Gets the number of times the value is repeated.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Access of an element of an array, such as `a[i]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexExpression {
+    value: Box<Expression>,
+    index: Box<Expression>,
+    span: Span
+}
+
+impl IndexExpression {
+    pub fn new(value: Box<Expression>,
+               index: Box<Expression>,
+               end: Location) -> IndexExpression {
+        IndexExpression {
+            span: Span::from(value.span().start() ..= end),
+            value,
+            index
+        }
+    }
+
+    /// Gets the array value which is indexed.
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+
+    /// Gets the index of the accessed element.
+    pub fn index(&self) -> &Expression {
+        &self.index
+    }
+
+    /// Gets the variable whose element is accessed, if the array is a
+    /// variable or an element of one.
+    pub fn root_var(&self) -> Option<&Identifier> {
+        match *self.value {
+            Expression::VariableRef(ref ident) => Some(ident),
+            Expression::Index(ref index) => index.root_var(),
+            _ => None
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A slice which views the elements of an array, such as `&a`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SliceExpression {
+    value: Box<Expression>,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+
+impl SliceExpression {
+    pub fn new(start: Location, value: Box<Expression>) -> SliceExpression {
+        SliceExpression {
+            span: Span::from(start ..= value.span().end()),
+            id: RefCell::default(),
+            value
+        }
+    }
+
+    /// Gets the array value which is viewed.
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Call of a method on a value, such as `a.push(value: 1)`.
+///
+/// Growable arrays have the builtin methods `push` and `pop`, and other types
+/// have the methods of their `impl` blocks.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MethodCall {
+    receiver: Box<Expression>,
+    method: Identifier,
+    args: Vec<CallArgument>,
+    span: Span
+}
+
+impl MethodCall {
+    pub fn new(receiver: Box<Expression>,
+               method: Identifier,
+               args: Vec<CallArgument>,
+               end: Location) -> MethodCall {
+        MethodCall {
+            span: Span::from(receiver.span().start() ..= end),
+            receiver,
+            method,
+            args
+        }
+    }
+
+    /// Gets the value whose method is called.
+    pub fn receiver(&self) -> &Expression {
+        &self.receiver
+    }
+
+    /// Gets the name of the method. Once the receiver's type is known, its
+    /// id is that of the method implemented for it.
+    pub fn method(&self) -> &Identifier {
+        &self.method
+    }
+
+    pub fn args(&self) -> &[CallArgument] {
+        &self.args
+    }
+
+    /// Gets the variable whose method is called, if the receiver is a
+    /// variable or an element of one.
+    pub fn root_var(&self) -> Option<&Identifier> {
+        match *self.receiver {
+            Expression::VariableRef(ref ident) => Some(ident),
+            Expression::Index(ref index) => index.root_var(),
+            _ => None
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Anonymous function which may capture the variables around it, such as
+/// `fn(x: float) => x * scale`.
+///
+/// The captured variables are found by the name identifier.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Lambda {
+    params: Vec<(Identifier, TypeExpression)>,
+    return_type: Option<TypeExpression>,
+    body: Box<Expression>,
+    id: RefCell<ScopedId>,
+    captures: RefCell<Vec<Identifier>>,
+    span: Span
+}
+
+impl Lambda {
+    pub fn new(start: Location,
+               params: Vec<(Identifier, TypeExpression)>,
+               return_type: Option<TypeExpression>,
+               body: Box<Expression>) -> Lambda {
+        Lambda {
+            span: Span::from(start ..= body.span().end()),
+            params,
+            return_type,
+            body,
+            id: RefCell::default(),
+            captures: RefCell::default()
+        }
+    }
+
+    pub fn params(&self) -> &[(Identifier, TypeExpression)] {
+        &self.params
+    }
+
+    /// Gets the declared return type, if it was given.
+    pub fn return_type(&self) -> Option<&TypeExpression> {
+        self.return_type.as_ref()
+    }
+
+    pub fn body(&self) -> &Expression {
+        &self.body
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    /// Gets the outer variables which are used in the body.
+    pub fn captures<'a>(&'a self) -> Ref<'a, Vec<Identifier>> {
+        self.captures.borrow()
+    }
+
+    pub fn set_captures(&self, captures: Vec<Identifier>) {
+        *self.captures.borrow_mut() = captures;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// An element of an array variable is assigned to a value, such as
+/// `a[i] = 1`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexAssignment {
+    lvalue: IndexExpression,
+    rvalue: Box<Expression>,
+}
+
+impl IndexAssignment {
+    pub fn new(lvalue: IndexExpression,
+               rvalue: Box<Expression>) -> IndexAssignment {
+        IndexAssignment { lvalue, rvalue }
+    }
+
+    /// Gets the element which is assigned.
+    pub fn lvalue(&self) -> &IndexExpression {
+        &self.lvalue
+    }
+
+    pub fn rvalue(&self) -> &Expression {
+        &self.rvalue
+    }
+
+    pub fn span(&self) -> Span {
+        Span::from(self.lvalue.span() ..= self.rvalue.span())
+    }
+}
+
+/// Creates a copy of a struct value with some of its fields replaced,
+/// such as `p with (x: 1)`.
+///
+/// The fields are given like the arguments of a function call.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StructUpdate {
+    value: Box<Expression>,
+    fields: Vec<CallArgument>,
+    span: Span
+}
+
+impl StructUpdate {
+    pub fn new(span: Span,
+               value: Box<Expression>,
+               fields: Vec<CallArgument>) -> StructUpdate {
+        StructUpdate { value, fields, span }
+    }
+
+    /// Gets the struct value which is copied.
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+
+    /// Gets the fields which are replaced in the copy.
+    pub fn fields(&self) -> &[CallArgument] {
+        &self.fields
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A range of numbers, such as `0..n` or `0..=n by 2`.
+///
+/// Ranges count upwards from `start` by `step`, which defaults to `1`.
+/// They are not values themselves and are used by `for` loops.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Range {
+    start: Box<Expression>,
+    end: Box<Expression>,
+    inclusive: bool,
+    step: Option<Box<Expression>>,
+    span: Span
+}
+impl Range {
+    pub fn new(start: Box<Expression>,
+               end: Box<Expression>,
+               inclusive: bool,
+               step: Option<Box<Expression>>) -> Range {
+        let span_end = match step {
+            Some(ref step) => step.span().end(),
+            None => end.span().end()
+        };
+        Range {
+            span: Span::from(start.span().start() ..= span_end),
+            start,
+            end,
+            inclusive,
+            step
+        }
+    }
+    pub fn start(&self) -> &Expression {
+        &self.start
+    }
+    pub fn end(&self) -> &Expression {
+        &self.end
+    }
+    /// Whether the range includes its end, as in `0..=n`.
+    pub fn is_inclusive(&self) -> bool {
+        self.inclusive
+    }
+    pub fn step(&self) -> Option<&Expression> {
+        self.step.as_ref().map(|step| step.as_ref())
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// [<label>:] loop <block>
+///
+/// Runs the block until a `break` exits it. A `loop` has the type of the
+/// values given to its `break`s, or `()` if they don't give one.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Loop {
+    label: Option<Identifier>,
+    block: Block,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+impl Loop {
+    pub fn new(start: Location,
+               label: Option<Identifier>,
+               block: Block) -> Loop {
+        Loop {
+            span: Span::from(start ..= block.span().end()),
+            label,
+            block,
+            id: RefCell::new(ScopedId::default())
+        }
+    }
+    /// The label of the loop, as in `'outer: loop`.
+    pub fn label(&self) -> Option<&Identifier> {
+        self.label.as_ref()
+    }
+    pub fn block(&self) -> &Block {
+        &self.block
+    }
+
+    /// The `ScopedId` used to infer the type of this loop.
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// match <value> <arms>
+///
+/// Runs the block of the first arm whose pattern matches the value. Like an
+/// `if` block, a `match` has a value if it's used as one, in which case each
+/// arm must have a value of the same type.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Match {
+    value: Box<Expression>,
+    arms: Vec<MatchArm>,
+    id: RefCell<ScopedId>,
+    source: RefCell<Option<ScopedId>>,
+    span: Span
+}
+impl Match {
+    pub fn new(start: Location,
+               value: Box<Expression>,
+               arms: Vec<MatchArm>) -> Match {
+        debug_assert!(!arms.is_empty(),
+                      "Attempted to create a `Match` with 0 arms");
+        let end = arms.last().map_or(value.span().end(),
+                                     |arm| arm.span().end());
+        Match {
+            span: Span::from(start ..= end),
+            value,
+            arms,
+            id: RefCell::new(ScopedId::default()),
+            source: RefCell::new(None)
+        }
+    }
+    /// The value being matched.
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+    pub fn arms(&self) -> &[MatchArm] {
+        &self.arms
+    }
+
+    /// The `ScopedId` used to infer the type of this match.
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn source<'a>(&'a self) -> Ref<'a, Option<ScopedId>> {
+        self.source.borrow()
+    }
+    pub fn set_source(&self, source: ScopedId) {
+        *self.source.borrow_mut() = Some(source);
+    }
+    pub fn has_source(&self) -> bool {
+        self.source.borrow().is_some()
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// <pattern> [if <guard>] => <expr>
+///
+/// An arm of a `match`. Inline arms are parsed into a block with a single
+/// statement.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchArm {
+    pattern: Pattern,
+    guard: Option<Expression>,
+    block: Block,
+    span: Span
+}
+impl MatchArm {
+    pub fn new(pattern: Pattern,
+               guard: Option<Expression>,
+               block: Block) -> MatchArm {
+        MatchArm {
+            span: Span::from(pattern.span().start() ..= block.span().end()),
+            pattern,
+            guard,
+            block
+        }
+    }
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+    /// The condition which must also be `true` for the arm to match.
+    pub fn guard(&self) -> Option<&Expression> {
+        self.guard.as_ref()
+    }
+    pub fn block(&self) -> &Block {
+        &self.block
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
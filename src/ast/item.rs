@@ -2,10 +2,10 @@
 //!
 //! An `Item` is a declaration made in the root context of a program
 //! -- namely declarations such as `class`, `enum`, `struct`.
-use std::cell::Ref;
+use std::cell::{Ref, RefCell};
 
 use lex::{Location, Span};
-use ast::{Identifier, Block, TypeExpression, ScopedId};
+use ast::{Identifier, Block, Expression, TypeExpression, ScopedId};
 
 /// A single "unit" of parsed code.
 #[derive(Debug, PartialEq, Clone)]
@@ -34,17 +34,36 @@ impl Unit {
 pub enum Item {
     /// Declaraion of a function
     BlockFnDeclaration(BlockFnDeclaration),
+    /// Declaration of a function defined outside of protosnirk
+    ExternFnDeclaration(ExternFnDeclaration),
     /// Declaration of a type alias
-    Typedef(Typedef)
+    Typedef(Typedef),
+    /// Declaration of a struct type
+    StructDeclaration(StructDeclaration),
+    /// Declaration of an enum type
+    EnumDeclaration(EnumDeclaration),
+    /// Declaration of a trait
+    TraitDeclaration(TraitDeclaration),
+    /// Implementation of a trait for a type
+    ImplBlock(ImplBlock),
+    /// Import of another module or one of its items
+    Import(Import),
+    /// Declaration of a value computed at compile time
+    ConstDeclaration(ConstDeclaration),
+    /// Declaration of a variable shared by the unit's functions
+    GlobalDeclaration(GlobalDeclaration)
 }
 
 /// Declaration of a function
 #[derive(Debug, Clone, PartialEq)]
 pub struct BlockFnDeclaration {
     ident: Identifier,
+    type_params: Vec<TypeParameter>,
     params: Vec<(Identifier, TypeExpression)>,
     ret_ty: TypeExpression,
     explicit_ret_ty: bool,
+    exported: bool,
+    public: bool,
     block: Block,
     span: Span
 }
@@ -53,6 +72,7 @@ impl BlockFnDeclaration {
     /// Create a new FnDeclaration
     pub fn new(start: Location,
                ident: Identifier,
+               type_params: Vec<TypeParameter>,
                params: Vec<(Identifier, TypeExpression)>,
                ret_ty: TypeExpression,
                explicit_ret_ty: bool,
@@ -61,17 +81,54 @@ impl BlockFnDeclaration {
         BlockFnDeclaration {
             span: Span::from(start ..= block.span().end()),
             ident,
+            type_params,
             params,
             ret_ty,
             explicit_ret_ty,
+            exported: false,
+            public: false,
             block
         }
     }
 
+    /// Mark the function as visible to units which import its module, with
+    /// its declaration starting at the `pub` keyword.
+    pub fn into_public(self, start: Location) -> BlockFnDeclaration {
+        BlockFnDeclaration {
+            span: Span::from(start ..= self.span.end()),
+            public: true,
+            .. self
+        }
+    }
+
+    /// Mark the function as exported with the C calling convention, with
+    /// its declaration starting at the `export` keyword.
+    pub fn into_exported(self, start: Location) -> BlockFnDeclaration {
+        BlockFnDeclaration {
+            span: Span::from(start ..= self.span.end()),
+            exported: true,
+            .. self
+        }
+    }
+
     /// Get the identifier of the function
     pub fn ident(&self) -> &Identifier {
         &self.ident
     }
+    /// Get the type parameters of a generic function, such as `T` in
+    /// `fn first<T>(values: &[T]) -> T`.
+    pub fn type_params(&self) -> &[TypeParameter] {
+        &self.type_params
+    }
+    /// Whether the function is a method, taking `self` as its first
+    /// parameter
+    pub fn is_method(&self) -> bool {
+        self.params.first()
+            .map_or(false, |&(ref param, _)| param.name() == "self")
+    }
+    pub fn is_generic(&self) -> bool {
+        !self.type_params.is_empty()
+    }
     pub fn params(&self) -> &[(Identifier, TypeExpression)] {
         &self.params
     }
@@ -81,6 +138,15 @@ impl BlockFnDeclaration {
     pub fn has_explicit_return_type(&self) -> bool {
         self.explicit_ret_ty
     }
+    /// Whether the function is exported under its own name, such as
+    /// `export fn area(w: float, h: float) -> float`.
+    pub fn is_exported(&self) -> bool {
+        self.exported
+    }
+    /// Whether the function can be imported by other units
+    pub fn is_public(&self) -> bool {
+        self.public
+    }
     pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
         self.ident.id()
     }
@@ -101,10 +167,238 @@ impl BlockFnDeclaration {
     }
 }
 
+/// Declaration of a foreign function, such as `extern fn sqrt(x: float) -> float`.
+///
+/// Extern functions have no body and are called with the C calling
+/// convention, with their arguments passed in the order of their params.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternFnDeclaration {
+    ident: Identifier,
+    params: Vec<(Identifier, TypeExpression)>,
+    ret_ty: TypeExpression,
+    span: Span
+}
+
+impl ExternFnDeclaration {
+    pub fn new(start: Location,
+               ident: Identifier,
+               params: Vec<(Identifier, TypeExpression)>,
+               ret_ty: TypeExpression,
+               end: Location)
+               -> ExternFnDeclaration {
+        ExternFnDeclaration {
+            span: Span::from(start ..= end),
+            ident,
+            params,
+            ret_ty
+        }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.ident.id()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        self.ident.set_id(id)
+    }
+
+    /// Get the name of the function, which is also its C symbol
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    pub fn params(&self) -> &[(Identifier, TypeExpression)] {
+        &self.params
+    }
+
+    pub fn return_type(&self) -> &TypeExpression {
+        &self.ret_ty
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Import of the items of another module, such as `import math`, whose
+/// items are named like `math::square`, or `import math::square`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    path: Identifier,
+    /// IDs given to the imported items, by their names in their module.
+    imported: RefCell<Vec<(String, ScopedId)>>,
+    span: Span
+}
+
+impl Import {
+    pub fn new(start: Location, path: Identifier) -> Import {
+        Import {
+            span: Span::from(start ..= path.span().end()),
+            path,
+            imported: RefCell::new(Vec::new())
+        }
+    }
+
+    pub fn path(&self) -> &Identifier {
+        &self.path
+    }
+
+    /// Get the name of the imported module
+    pub fn module_name(&self) -> &str {
+        self.path.name().split("::").next()
+            .expect("Split always has a first part")
+    }
+
+    /// Get the name of the imported item, if only one item is imported
+    pub fn item_name(&self) -> Option<&str> {
+        self.path.name().splitn(2, "::").nth(1)
+    }
+
+    /// Get the name an item of the module is given in the importing unit.
+    pub fn local_name(&self, item: &str) -> String {
+        if self.item_name().is_some() {
+            item.to_string()
+        }
+        else {
+            format!("{}::{}", self.module_name(), item)
+        }
+    }
+
+    pub fn imported<'a>(&'a self) -> Ref<'a, Vec<(String, ScopedId)>> {
+        self.imported.borrow()
+    }
+
+    pub fn add_imported(&self, item: String, id: ScopedId) {
+        self.imported.borrow_mut().push((item, id));
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Declaration of a named value which is computed at compile time, such as
+/// `const LIMIT: i64 = 10 * 10`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstDeclaration {
+    ident: Identifier,
+    type_expr: TypeExpression,
+    value: Box<Expression>,
+    span: Span
+}
+
+impl ConstDeclaration {
+    pub fn new(start: Location,
+               ident: Identifier,
+               type_expr: TypeExpression,
+               value: Box<Expression>)
+               -> ConstDeclaration {
+        ConstDeclaration {
+            span: Span::from(start ..= value.span().end()),
+            ident,
+            type_expr,
+            value
+        }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.ident.id()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        self.ident.set_id(id)
+    }
+
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    pub fn type_expr(&self) -> &TypeExpression {
+        &self.type_expr
+    }
+
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Declaration of a variable of the unit, such as `let mut count: i64 = 0`,
+/// which its functions can use.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalDeclaration {
+    ident: Identifier,
+    mutable: bool,
+    type_expr: TypeExpression,
+    value: Box<Expression>,
+    span: Span
+}
+
+impl GlobalDeclaration {
+    pub fn new(start: Location,
+               ident: Identifier,
+               mutable: bool,
+               type_expr: TypeExpression,
+               value: Box<Expression>)
+               -> GlobalDeclaration {
+        GlobalDeclaration {
+            span: Span::from(start ..= value.span().end()),
+            ident,
+            mutable,
+            type_expr,
+            value
+        }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.ident.id()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        self.ident.set_id(id)
+    }
+
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    pub fn is_mut(&self) -> bool {
+        self.mutable
+    }
+
+    pub fn type_expr(&self) -> &TypeExpression {
+        &self.type_expr
+    }
+
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
 /// Declaration of a type alias
 #[derive(Debug, Clone, PartialEq)]
 pub struct Typedef {
     alias_ident: Identifier,
+    type_params: Vec<Identifier>,
     type_expr: TypeExpression,
     span: Span
 }
@@ -112,11 +406,13 @@ pub struct Typedef {
 impl Typedef {
     pub fn new(start: Location,
                alias_ident: Identifier,
+               type_params: Vec<Identifier>,
                type_expr: TypeExpression)
                -> Typedef {
         Typedef {
             span: Span::from(start ..= type_expr.span().end()),
             alias_ident,
+            type_params,
             type_expr
         }
     }
@@ -137,10 +433,367 @@ impl Typedef {
         self.alias_ident.name()
     }
 
+    /// Get the type parameters of a generic typedef, such as `T` in
+    /// `typedef Pair<T> = (T, T)`.
+    pub fn type_params(&self) -> &[Identifier] {
+        &self.type_params
+    }
+
+    pub fn type_expr(&self) -> &TypeExpression {
+        &self.type_expr
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Declaration of a struct type with named fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDeclaration {
+    ident: Identifier,
+    fields: Vec<(Identifier, TypeExpression)>,
+    public: bool,
+    span: Span
+}
+
+impl StructDeclaration {
+    pub fn new(start: Location,
+               ident: Identifier,
+               fields: Vec<(Identifier, TypeExpression)>,
+               end: Location)
+               -> StructDeclaration {
+        StructDeclaration {
+            span: Span::from(start ..= end),
+            ident,
+            fields,
+            public: false
+        }
+    }
+
+    /// Mark the struct as visible to units which import its module.
+    pub fn into_public(self, start: Location) -> StructDeclaration {
+        StructDeclaration {
+            span: Span::from(start ..= self.span.end()),
+            public: true,
+            .. self
+        }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.ident.id()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        self.ident.set_id(id)
+    }
+
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    /// Get the fields of the struct, in the order they were declared
+    pub fn fields(&self) -> &[(Identifier, TypeExpression)] {
+        &self.fields
+    }
+
+    /// Whether the struct can be imported by other units
+    pub fn is_public(&self) -> bool {
+        self.public
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Declaration of an enum type, whose values are one of its variants
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDeclaration {
+    ident: Identifier,
+    variants: Vec<EnumVariant>,
+    public: bool,
+    span: Span
+}
+
+impl EnumDeclaration {
+    pub fn new(start: Location,
+               ident: Identifier,
+               variants: Vec<EnumVariant>,
+               end: Location)
+               -> EnumDeclaration {
+        EnumDeclaration {
+            span: Span::from(start ..= end),
+            ident,
+            variants,
+            public: false
+        }
+    }
+
+    /// Mark the enum as visible to units which import its module.
+    pub fn into_public(self, start: Location) -> EnumDeclaration {
+        EnumDeclaration {
+            span: Span::from(start ..= self.span.end()),
+            public: true,
+            .. self
+        }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.ident.id()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        self.ident.set_id(id)
+    }
+
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    /// Get the variants of the enum, in the order they were declared
+    pub fn variants(&self) -> &[EnumVariant] {
+        &self.variants
+    }
+
+    /// Whether the enum and its variants can be imported by other units
+    pub fn is_public(&self) -> bool {
+        self.public
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A variant of an enum, which may carry named fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    ident: Identifier,
+    fields: Vec<(Identifier, TypeExpression)>,
+    span: Span
+}
+
+impl EnumVariant {
+    pub fn new(ident: Identifier,
+               fields: Vec<(Identifier, TypeExpression)>,
+               end: Location)
+               -> EnumVariant {
+        EnumVariant {
+            span: Span::from(ident.span().start() ..= end),
+            ident,
+            fields
+        }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.ident.id()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        self.ident.set_id(id)
+    }
+
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    /// Get the fields of the variant, in the order they were declared
+    pub fn fields(&self) -> &[(Identifier, TypeExpression)] {
+        &self.fields
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A type parameter of a generic function, with the traits its type
+/// arguments must implement, such as `T: Shape`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeParameter {
+    ident: Identifier,
+    bounds: Vec<Identifier>
+}
+
+impl TypeParameter {
+    pub fn new(ident: Identifier, bounds: Vec<Identifier>) -> TypeParameter {
+        TypeParameter { ident, bounds }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    /// Get the traits which the type parameter must implement
+    pub fn bounds(&self) -> &[Identifier] {
+        &self.bounds
+    }
+
+    pub fn span(&self) -> Span {
+        match self.bounds.last() {
+            Some(bound) => Span::from(self.ident.span() ..= bound.span()),
+            None => self.ident.span()
+        }
+    }
+}
+
+/// Declaration of a trait, whose methods are implemented for types by
+/// `impl` blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitDeclaration {
+    ident: Identifier,
+    methods: Vec<MethodSignature>,
+    span: Span
+}
+
+impl TraitDeclaration {
+    pub fn new(start: Location,
+               ident: Identifier,
+               methods: Vec<MethodSignature>,
+               end: Location)
+               -> TraitDeclaration {
+        TraitDeclaration {
+            span: Span::from(start ..= end),
+            ident,
+            methods
+        }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.ident.id()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        self.ident.set_id(id)
+    }
+
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    /// Get the signatures of the trait's methods, in the order they were
+    /// declared
+    pub fn methods(&self) -> &[MethodSignature] {
+        &self.methods
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Signature of a method declared by a trait, such as
+/// `fn scale(self, factor: float) -> float`.
+///
+/// The `self` receiver is the first parameter, with the type `Self`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodSignature {
+    ident: Identifier,
+    params: Vec<(Identifier, TypeExpression)>,
+    ret_ty: TypeExpression,
+    span: Span
+}
+
+impl MethodSignature {
+    pub fn new(start: Location,
+               ident: Identifier,
+               params: Vec<(Identifier, TypeExpression)>,
+               ret_ty: TypeExpression,
+               end: Location)
+               -> MethodSignature {
+        MethodSignature {
+            span: Span::from(start ..= end),
+            ident,
+            params,
+            ret_ty
+        }
+    }
+
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+
+    pub fn params(&self) -> &[(Identifier, TypeExpression)] {
+        &self.params
+    }
+
+    pub fn return_type(&self) -> &TypeExpression {
+        &self.ret_ty
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Implementation of a trait's methods for a type, such as
+/// `impl Shape for Circle`, or of methods and associated functions of the
+/// type itself, such as `impl Circle`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImplBlock {
+    trait_ident: Option<Identifier>,
+    type_expr: TypeExpression,
+    methods: Vec<BlockFnDeclaration>,
+    span: Span
+}
+
+impl ImplBlock {
+    pub fn new(start: Location,
+               trait_ident: Option<Identifier>,
+               type_expr: TypeExpression,
+               methods: Vec<BlockFnDeclaration>,
+               end: Location)
+               -> ImplBlock {
+        ImplBlock {
+            span: Span::from(start ..= end),
+            trait_ident,
+            type_expr,
+            methods
+        }
+    }
+
+    /// Get the identifier of the implemented trait, if this is not an
+    /// inherent impl
+    pub fn trait_ident(&self) -> Option<&Identifier> {
+        self.trait_ident.as_ref()
+    }
+
+    /// Get the type which the trait is implemented for
     pub fn type_expr(&self) -> &TypeExpression {
         &self.type_expr
     }
 
+    /// Get the functions of the impl. Those whose first parameter is `self`
+    /// are methods, and inherent impls may also have associated functions.
+    pub fn methods(&self) -> &[BlockFnDeclaration] {
+        &self.methods
+    }
+
     pub fn span(&self) -> Span {
         self.span
     }
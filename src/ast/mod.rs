@@ -15,6 +15,7 @@ mod expression;
 mod item;
 mod stmt;
 mod operator;
+mod pattern;
 pub mod types;
 pub mod visit;
 
@@ -23,6 +24,7 @@ pub use self::expression::*;
 pub use self::item::*;
 pub use self::stmt::*;
 pub use self::operator::*;
+pub use self::pattern::*;
 pub use self::types::*;
 
 use std::cell::{RefCell, Ref};
@@ -26,6 +26,10 @@ pub enum BinaryOperator {
     LessThanEquals,
     /// Numeric greater than equals test
     GreaterThanEquals,
+    /// Boolean and, which short-circuits
+    And,
+    /// Boolean or, which short-circuits
+    Or,
 }
 
 /// Unary operators
@@ -35,4 +39,6 @@ pub enum UnaryOperator {
     Negation,
     /// No-op
     Addition,
+    /// Boolean not
+    Not,
 }
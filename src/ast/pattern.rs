@@ -0,0 +1,187 @@
+//! Patterns which values can be matched against.
+
+use lex::{Span, Location};
+use ast::{Identifier, Literal, LiteralValue};
+
+/// A pattern used by an arm of a `match`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pattern {
+    /// `_`, which matches any value.
+    Wildcard(Span),
+    /// A single value, such as `true` or `-1`.
+    Literal(LiteralPattern),
+    /// A range of numbers, such as `0..10` or `1..=9`.
+    Range(RangePattern),
+    /// A variant of an enum, such as `Circle(radius: r)`.
+    Variant(VariantPattern),
+}
+
+impl Pattern {
+    /// Whether this pattern is a range of values.
+    pub fn is_range(&self) -> bool {
+        match *self {
+            Pattern::Range(_) => true,
+            _ => false
+        }
+    }
+    pub fn span(&self) -> Span {
+        match *self {
+            Pattern::Wildcard(span) => span,
+            Pattern::Literal(ref literal) => literal.span(),
+            Pattern::Range(ref range) => range.span(),
+            Pattern::Variant(ref variant) => variant.span()
+        }
+    }
+}
+
+/// A literal in a pattern, which may be negated.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LiteralPattern {
+    literal: Literal,
+    negative: bool,
+    span: Span
+}
+
+impl LiteralPattern {
+    pub fn new(start: Location, literal: Literal, negative: bool)
+               -> LiteralPattern {
+        LiteralPattern {
+            span: Span::from(start ..= literal.span().end()),
+            literal,
+            negative
+        }
+    }
+    pub fn literal(&self) -> &Literal {
+        &self.literal
+    }
+    /// Whether the literal is negated, as in `-1`.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The value of an integer pattern, including its sign.
+    pub fn int_value(&self) -> Option<i128> {
+        match *self.literal.value() {
+            LiteralValue::Int(value) if self.negative => Some(-(value as i128)),
+            LiteralValue::Int(value) => Some(value as i128),
+            _ => None
+        }
+    }
+
+    /// The value of a floating point pattern, including its sign.
+    pub fn float_value(&self) -> Option<f64> {
+        match *self.literal.value() {
+            LiteralValue::Float(value) if self.negative => Some(-value),
+            LiteralValue::Float(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A range of numbers in a pattern.
+///
+/// Unlike ranges in `for` loops, range patterns only hold literals.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RangePattern {
+    start: LiteralPattern,
+    end: LiteralPattern,
+    inclusive: bool,
+    span: Span
+}
+
+impl RangePattern {
+    pub fn new(start: LiteralPattern, end: LiteralPattern, inclusive: bool)
+               -> RangePattern {
+        RangePattern {
+            span: Span::from(start.span() ..= end.span()),
+            start,
+            end,
+            inclusive
+        }
+    }
+    pub fn start(&self) -> &LiteralPattern {
+        &self.start
+    }
+    pub fn end(&self) -> &LiteralPattern {
+        &self.end
+    }
+    /// Whether the range includes its end, as in `1..=9`.
+    pub fn is_inclusive(&self) -> bool {
+        self.inclusive
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A variant of an enum in a pattern, which binds some of its fields to
+/// new variables.
+#[derive(Debug, PartialEq, Clone)]
+pub struct VariantPattern {
+    ident: Identifier,
+    fields: Vec<FieldPattern>,
+    span: Span
+}
+
+impl VariantPattern {
+    pub fn new(ident: Identifier, fields: Vec<FieldPattern>, end: Location)
+               -> VariantPattern {
+        VariantPattern {
+            span: Span::from(ident.span().start() ..= end),
+            ident,
+            fields
+        }
+    }
+    /// The name of the variant, which is given the `ScopedId` of the variant.
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+    pub fn name(&self) -> &str {
+        self.ident.name()
+    }
+    pub fn fields(&self) -> &[FieldPattern] {
+        &self.fields
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// `<field>: <binding>`
+///
+/// A field of a variant pattern, which is bound to a new variable unless the
+/// binding is `_`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FieldPattern {
+    field: Identifier,
+    binding: Option<Identifier>,
+    span: Span
+}
+
+impl FieldPattern {
+    pub fn new(field: Identifier, binding: Option<Identifier>, end: Location)
+               -> FieldPattern {
+        FieldPattern {
+            span: Span::from(field.span().start() ..= end),
+            field,
+            binding
+        }
+    }
+    pub fn field(&self) -> &Identifier {
+        &self.field
+    }
+    /// The variable the field is bound to, or `None` for `_`.
+    pub fn binding(&self) -> Option<&Identifier> {
+        self.binding.as_ref()
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
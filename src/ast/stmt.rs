@@ -5,7 +5,7 @@
 /// `Expression`s are because of their ability to use indentation.
 
 use lex::{Span, Location};
-use ast::{Expression, Identifier, TypeExpression, Block, ScopedId};
+use ast::{Expression, Identifier, TypeExpression, Block, Range, ScopedId};
 
 use std::cell::{RefCell, Ref};
 
@@ -15,9 +15,14 @@ pub enum Statement {
     Expression(Expression),
     Return(Return),
     Declaration(Declaration),
+    TupleDeclaration(TupleDeclaration),
     DoBlock(DoBlock),
-    IfBlock(IfBlock)
-    // match, loop, while, for
+    IfBlock(IfBlock),
+    WhileBlock(WhileBlock),
+    ForLoop(ForLoop),
+    Break(Break),
+    Continue(Continue)
+    // match, loop
 }
 impl Statement {
     pub fn has_value(&self) -> bool {
@@ -27,7 +32,8 @@ impl Statement {
             Return(ref return_) => return_.has_value(),
             DoBlock(ref do_block) => do_block.has_source(),
             IfBlock(ref if_block) => if_block.has_source(),
-            Declaration(_) => false
+            Declaration(_) | TupleDeclaration(_) | WhileBlock(_) | ForLoop(_)
+            | Break(_) | Continue(_) => false
         }
     }
 
@@ -38,7 +44,12 @@ impl Statement {
             Return(ref r) => r.span(),
             DoBlock(ref d) => d.span(),
             IfBlock(ref i) => i.span(),
-            Declaration(ref d) => d.span()
+            Declaration(ref d) => d.span(),
+            TupleDeclaration(ref t) => t.span(),
+            WhileBlock(ref w) => w.span(),
+            ForLoop(ref f) => f.span(),
+            Break(ref b) => b.span(),
+            Continue(ref c) => c.span()
         }
     }
 }
@@ -88,6 +99,7 @@ impl Return {
 #[derive(Debug, PartialEq, Clone)]
 pub struct Declaration {
     mutable: bool,
+    constant: bool,
     ident: Identifier,
     value: Box<Expression>,
     type_decl: Option<TypeExpression>,
@@ -103,11 +115,18 @@ impl Declaration {
             span: Span::from(start ..= value.span().end()),
             ident,
             mutable,
+            constant: false,
             type_decl,
             value
         }
     }
 
+    /// Mark the variable as a `const`, whose value is computed at compile
+    /// time.
+    pub fn into_const(self) -> Declaration {
+        Declaration { constant: true, .. self }
+    }
+
     pub fn name(&self) -> &str {
         &self.ident.name()
     }
@@ -117,6 +136,9 @@ impl Declaration {
     pub fn is_mut(&self) -> bool {
         self.mutable
     }
+    pub fn is_const(&self) -> bool {
+        self.constant
+    }
     pub fn ident(&self) -> &Identifier {
         &self.ident
     }
@@ -138,6 +160,53 @@ impl Declaration {
     }
 }
 
+/// Declaration of variables from the elements of a tuple, such as
+/// `let (x, y) = point`.
+///
+/// Elements which are not bound to a variable are written `_`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TupleDeclaration {
+    mutable: bool,
+    bindings: Vec<Option<Identifier>>,
+    value: Box<Expression>,
+    type_decl: Option<TypeExpression>,
+    span: Span
+}
+impl TupleDeclaration {
+    pub fn new(start: Location,
+               bindings: Vec<Option<Identifier>>,
+               mutable: bool,
+               type_decl: Option<TypeExpression>,
+               value: Box<Expression>) -> TupleDeclaration {
+        TupleDeclaration {
+            span: Span::from(start ..= value.span().end()),
+            bindings,
+            mutable,
+            type_decl,
+            value
+        }
+    }
+
+    /// Gets the variables bound to each element of the tuple, or `None` for
+    /// elements which are ignored.
+    pub fn bindings(&self) -> &[Option<Identifier>] {
+        &self.bindings
+    }
+    pub fn value(&self) -> &Expression {
+        &self.value
+    }
+    pub fn is_mut(&self) -> bool {
+        self.mutable
+    }
+    pub fn type_decl(&self) -> Option<&TypeExpression> {
+        self.type_decl.as_ref()
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
 /// Do <block> statement.
 #[derive(Debug, PartialEq, Clone)]
 pub struct DoBlock {
@@ -302,3 +371,169 @@ impl Conditional {
         self.span
     }
 }
+
+/// [<label>:] while <condition> <block>
+///
+/// Runs the block for as long as the condition is `true`.
+/// `while` loops are always of type `()`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WhileBlock {
+    label: Option<Identifier>,
+    condition: Expression,
+    block: Block,
+    span: Span
+}
+
+impl WhileBlock {
+    pub fn new(start: Location,
+               label: Option<Identifier>,
+               condition: Expression,
+               block: Block) -> WhileBlock {
+        WhileBlock {
+            span: Span::from(start ..= block.span().end()),
+            label,
+            condition,
+            block
+        }
+    }
+    pub fn label(&self) -> Option<&Identifier> {
+        self.label.as_ref()
+    }
+    pub fn condition(&self) -> &Expression {
+        &self.condition
+    }
+    pub fn block(&self) -> &Block {
+        &self.block
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// [<label>:] for <ident> in <range> <block>
+///
+/// Runs the block once for each number in the range, binding it to a new
+/// immutable variable. `for` loops are always of type `()`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForLoop {
+    label: Option<Identifier>,
+    ident: Identifier,
+    range: Range,
+    block: Block,
+    span: Span
+}
+
+impl ForLoop {
+    pub fn new(start: Location,
+               label: Option<Identifier>,
+               ident: Identifier,
+               range: Range,
+               block: Block) -> ForLoop {
+        ForLoop {
+            span: Span::from(start ..= block.span().end()),
+            label,
+            ident,
+            range,
+            block
+        }
+    }
+    pub fn label(&self) -> Option<&Identifier> {
+        self.label.as_ref()
+    }
+    pub fn ident(&self) -> &Identifier {
+        &self.ident
+    }
+    pub fn range(&self) -> &Range {
+        &self.range
+    }
+    pub fn block(&self) -> &Block {
+        &self.block
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// `break [<label>] [<value>]` statement, which exits the innermost loop, or
+/// the loop with the given label.
+///
+/// A `break` out of a `loop` may give the `loop` a value.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Break {
+    label: Option<Identifier>,
+    value: Option<Box<Expression>>,
+    source: RefCell<Option<ScopedId>>,
+    span: Span
+}
+
+impl Break {
+    pub fn new<V>(start: Location,
+                  label: Option<Identifier>,
+                  value: V) -> Break
+               where V: Into<Option<Box<Expression>>> {
+        let value = value.into();
+        let end = if let Some(ref value) = value {
+            value.span().end()
+        }
+        else if let Some(ref label) = label {
+            label.span().end()
+        }
+        else {
+            start.offset(4)
+        };
+        Break {
+            span: Span::from(start ..= end),
+            label,
+            value,
+            source: RefCell::new(None)
+        }
+    }
+    pub fn label(&self) -> Option<&Identifier> {
+        self.label.as_ref()
+    }
+    pub fn value(&self) -> Option<&Expression> {
+        self.value.as_ref().map(|value| value.as_ref())
+    }
+
+    /// The `ScopedId` of the `loop` which this `break` gives a value to.
+    pub fn source<'a>(&'a self) -> Ref<'a, Option<ScopedId>> {
+        self.source.borrow()
+    }
+    pub fn set_source(&self, source: ScopedId) {
+        *self.source.borrow_mut() = Some(source);
+    }
+    pub fn has_source(&self) -> bool {
+        self.source.borrow().is_some()
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// `continue [<label>]` statement, which skips to the next iteration of the
+/// innermost loop, or the loop with the given label.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Continue {
+    label: Option<Identifier>,
+    span: Span
+}
+
+impl Continue {
+    pub fn new(start: Location, label: Option<Identifier>) -> Continue {
+        let end = match label {
+            Some(ref label) => label.span().end(),
+            None => start.offset(7)
+        };
+        Continue { span: Span::from(start ..= end), label }
+    }
+    pub fn label(&self) -> Option<&Identifier> {
+        self.label.as_ref()
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
@@ -6,28 +6,49 @@
 // let anonStruct: struct(x: int, y: int) // anonymous named structs
 // let tuple4: (array: [int], sizedArray: [int: 6] sizedArraySlice: &[int: 5], slice: &[int])
 
-use std::cell::Ref;
+use std::cell::{RefCell, Ref};
 
 use ast::{ScopedId, Identifier, Span};
+use lex::Location;
 
 /// Represents type expressions in protosnirk.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum TypeExpression {
     /// Named type, in this case `float` or `bool`
     Named(NamedTypeExpression),
+    /// Tuple type, such as `(int, bool)`
+    Tuple(TupleTypeExpression),
+    /// Fixed-size array type, such as `[int: 5]`
+    Array(ArrayTypeExpression),
+    /// Growable array type, such as `[int]`
+    Growable(GrowableTypeExpression),
+    /// Slice type, such as `&[int]`
+    Slice(SliceTypeExpression),
+    /// Function type, such as `fn(x: int) -> bool`
+    Function(FnTypeExpression),
 }
 
 impl TypeExpression {
     pub fn id(&self) -> Ref<ScopedId> {
         match self {
-            &TypeExpression::Named(ref named) => named.id()
+            &TypeExpression::Named(ref named) => named.id(),
+            &TypeExpression::Tuple(ref tuple) => tuple.id(),
+            &TypeExpression::Array(ref array) => array.id(),
+            &TypeExpression::Growable(ref growable) => growable.id(),
+            &TypeExpression::Slice(ref slice) => slice.id(),
+            &TypeExpression::Function(ref fn_ty) => fn_ty.id()
         }
     }
 
     pub fn span(&self) -> Span {
         use self::TypeExpression::*;
         match self {
-            Named(ref n) => n.span()
+            Named(ref n) => n.span(),
+            Tuple(ref t) => t.span(),
+            Array(ref a) => a.span(),
+            Growable(ref g) => g.span(),
+            Slice(ref s) => s.span(),
+            Function(ref f) => f.span()
         }
     }
 }
@@ -35,17 +56,35 @@ impl TypeExpression {
 /// A named type expression.
 ///
 /// This is what most types in protosnirk will be made of.
-/// This includes `float`, `bool`, etc.
-/// Later, generic/const parameters will be added.
+/// This includes `float`, `bool`, etc., and uses of generic typedefs with
+/// their type arguments, such as `Pair<int>`.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct NamedTypeExpression {
     ident: Identifier,
+    args: Vec<TypeExpression>,
+    span: Span
 }
 impl NamedTypeExpression {
     /// Create a new `NamedTypeExpression` with
     /// the given name and default `TypeId`.
     pub fn new(ident: Identifier) -> NamedTypeExpression {
-        NamedTypeExpression { ident }
+        NamedTypeExpression {
+            span: ident.span(),
+            ident,
+            args: Vec::new()
+        }
+    }
+
+    /// Create a new `NamedTypeExpression` with type arguments, such as
+    /// `Pair<int>`.
+    pub fn with_args(ident: Identifier,
+                     args: Vec<TypeExpression>,
+                     end: Location) -> NamedTypeExpression {
+        NamedTypeExpression {
+            span: Span::from(ident.span().start() ..= end),
+            ident,
+            args
+        }
     }
 
     /// Gets the identifier of this type.
@@ -57,6 +96,11 @@ impl NamedTypeExpression {
         self.ident.name()
     }
 
+    /// Gets the type arguments given to a generic typedef.
+    pub fn args(&self) -> &[TypeExpression] {
+        &self.args
+    }
+
     pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
         self.ident.id()
     }
@@ -66,6 +110,218 @@ impl NamedTypeExpression {
     }
 
     pub fn span(&self) -> Span {
-        self.ident.span()
+        self.span
+    }
+}
+
+/// A tuple type expression, such as `(int, bool)`.
+///
+/// Tuple types are not declared, so their `ScopedId` is given to them when
+/// the types of their elements are known.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TupleTypeExpression {
+    elements: Vec<TypeExpression>,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+impl TupleTypeExpression {
+    pub fn new(start: Location,
+               elements: Vec<TypeExpression>,
+               end: Location) -> TupleTypeExpression {
+        TupleTypeExpression {
+            elements,
+            id: RefCell::default(),
+            span: Span::from(start ..= end)
+        }
+    }
+
+    /// Gets the types of the elements of the tuple.
+    pub fn elements(&self) -> &[TypeExpression] {
+        &self.elements
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A fixed-size array type expression, such as `[int: 5]`.
+///
+/// Like tuple types, array types are given their `ScopedId` when the type of
+/// their elements is known.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ArrayTypeExpression {
+    element: Box<TypeExpression>,
+    len: u64,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+impl ArrayTypeExpression {
+    pub fn new(start: Location,
+               element: Box<TypeExpression>,
+               len: u64,
+               end: Location) -> ArrayTypeExpression {
+        ArrayTypeExpression {
+            element,
+            len,
+            id: RefCell::default(),
+            span: Span::from(start ..= end)
+        }
+    }
+
+    /// Gets the type of the elements of the array.
+    pub fn element(&self) -> &TypeExpression {
+        &self.element
+    }
+
+    /// Gets the number of elements in the array.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A growable array type expression, such as `[int]`.
+///
+/// Like array types, growable array types are given their `ScopedId` when
+/// the type of their elements is known.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GrowableTypeExpression {
+    element: Box<TypeExpression>,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+impl GrowableTypeExpression {
+    pub fn new(start: Location,
+               element: Box<TypeExpression>,
+               end: Location) -> GrowableTypeExpression {
+        GrowableTypeExpression {
+            element,
+            id: RefCell::default(),
+            span: Span::from(start ..= end)
+        }
+    }
+
+    /// Gets the type of the elements of the array.
+    pub fn element(&self) -> &TypeExpression {
+        &self.element
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A slice type expression, such as `&[int]`.
+///
+/// Slices can only be the types of parameters and variables.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SliceTypeExpression {
+    element: Box<TypeExpression>,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+impl SliceTypeExpression {
+    pub fn new(start: Location,
+               element: Box<TypeExpression>,
+               end: Location) -> SliceTypeExpression {
+        SliceTypeExpression {
+            element,
+            id: RefCell::default(),
+            span: Span::from(start ..= end)
+        }
+    }
+
+    /// Gets the type of the elements which are viewed.
+    pub fn element(&self) -> &TypeExpression {
+        &self.element
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A function type expression, such as `fn(x: int, y: int) -> int`.
+///
+/// Like the types of declared functions, function types include the names
+/// of their parameters. They are given their `ScopedId` when the types of
+/// their parameters and return type are known.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FnTypeExpression {
+    params: Vec<(Identifier, TypeExpression)>,
+    return_type: Box<TypeExpression>,
+    id: RefCell<ScopedId>,
+    span: Span
+}
+impl FnTypeExpression {
+    pub fn new(start: Location,
+               params: Vec<(Identifier, TypeExpression)>,
+               return_type: Box<TypeExpression>,
+               end: Location) -> FnTypeExpression {
+        FnTypeExpression {
+            params,
+            return_type,
+            id: RefCell::default(),
+            span: Span::from(start ..= end)
+        }
+    }
+
+    /// Gets the names and types of the parameters of the function.
+    pub fn params(&self) -> &[(Identifier, TypeExpression)] {
+        &self.params
+    }
+
+    /// Gets the return type of the function, which is `()` if it was not
+    /// written.
+    pub fn return_type(&self) -> &TypeExpression {
+        &self.return_type
+    }
+
+    pub fn id<'a>(&'a self) -> Ref<'a, ScopedId> {
+        self.id.borrow()
+    }
+
+    pub fn set_id(&self, id: ScopedId) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
     }
 }
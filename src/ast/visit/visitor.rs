@@ -16,14 +16,46 @@ pub trait ItemVisitor {
             Item::BlockFnDeclaration(ref block_fn_decl) => {
                 self.visit_block_fn_decl(block_fn_decl);
             },
+            Item::ExternFnDeclaration(ref extern_fn) => {
+                self.visit_extern_fn_decl(extern_fn);
+            },
             Item::Typedef(ref typedef) => {
                 self.visit_typedef(typedef);
+            },
+            Item::StructDeclaration(ref struct_decl) => {
+                self.visit_struct_decl(struct_decl);
+            },
+            Item::EnumDeclaration(ref enum_decl) => {
+                self.visit_enum_decl(enum_decl);
+            },
+            Item::TraitDeclaration(ref trait_decl) => {
+                self.visit_trait_decl(trait_decl);
+            },
+            Item::ImplBlock(ref impl_block) => {
+                self.visit_impl_block(impl_block);
+            },
+            Item::Import(ref import) => {
+                self.visit_import(import);
+            },
+            Item::ConstDeclaration(ref const_decl) => {
+                self.visit_const_decl(const_decl);
+            },
+            Item::GlobalDeclaration(ref global) => {
+                self.visit_global_decl(global);
             }
         }
     }
 
     fn visit_block_fn_decl(&mut self, block_fn_decl: &BlockFnDeclaration);
+    fn visit_extern_fn_decl(&mut self, extern_fn: &ExternFnDeclaration);
     fn visit_typedef(&mut self, typedef: &Typedef);
+    fn visit_struct_decl(&mut self, struct_decl: &StructDeclaration);
+    fn visit_enum_decl(&mut self, enum_decl: &EnumDeclaration);
+    fn visit_trait_decl(&mut self, trait_decl: &TraitDeclaration);
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock);
+    fn visit_import(&mut self, import: &Import);
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration);
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration);
 }
 
 /// A visitor which can visit type expressions in code.
@@ -33,10 +65,30 @@ pub trait TypeVisitor {
             TypeExpression::Named(ref named_ty) => {
                 self.visit_named_type_expr(named_ty);
             },
+            TypeExpression::Tuple(ref tuple_ty) => {
+                self.visit_tuple_type_expr(tuple_ty);
+            },
+            TypeExpression::Array(ref array_ty) => {
+                self.visit_array_type_expr(array_ty);
+            },
+            TypeExpression::Growable(ref growable_ty) => {
+                self.visit_growable_type_expr(growable_ty);
+            },
+            TypeExpression::Slice(ref slice_ty) => {
+                self.visit_slice_type_expr(slice_ty);
+            },
+            TypeExpression::Function(ref fn_ty) => {
+                self.visit_fn_type_expr(fn_ty);
+            },
         }
     }
 
     fn visit_named_type_expr(&mut self, named_ty: &NamedTypeExpression);
+    fn visit_tuple_type_expr(&mut self, tuple_ty: &TupleTypeExpression);
+    fn visit_array_type_expr(&mut self, array_ty: &ArrayTypeExpression);
+    fn visit_growable_type_expr(&mut self, growable_ty: &GrowableTypeExpression);
+    fn visit_slice_type_expr(&mut self, slice_ty: &SliceTypeExpression);
+    fn visit_fn_type_expr(&mut self, fn_ty: &FnTypeExpression);
 }
 
 /// A visitor which can visit blocks of code.
@@ -60,6 +112,9 @@ pub trait StatementVisitor : ExpressionVisitor {
             Statement::Declaration(ref decl) =>{
                 self.visit_declaration(decl);
             }
+            Statement::TupleDeclaration(ref tuple_decl) => {
+                self.visit_tuple_declaration(tuple_decl);
+            }
             Statement::Return(ref return_) => {
                 self.visit_return_stmt(return_);
             },
@@ -68,13 +123,30 @@ pub trait StatementVisitor : ExpressionVisitor {
             },
             Statement::IfBlock(ref if_block) => {
                 self.visit_if_block(if_block);
+            },
+            Statement::WhileBlock(ref while_block) => {
+                self.visit_while_block(while_block);
+            },
+            Statement::ForLoop(ref for_loop) => {
+                self.visit_for_loop(for_loop);
+            },
+            Statement::Break(ref break_) => {
+                self.visit_break_stmt(break_);
+            },
+            Statement::Continue(ref continue_) => {
+                self.visit_continue_stmt(continue_);
             }
         }
     }
     fn visit_return_stmt(&mut self, return_: &Return);
     fn visit_declaration(&mut self, decl: &Declaration);
+    fn visit_tuple_declaration(&mut self, tuple_decl: &TupleDeclaration);
     fn visit_if_block(&mut self, if_block: &IfBlock);
     fn visit_do_block(&mut self, do_block: &DoBlock);
+    fn visit_while_block(&mut self, while_block: &WhileBlock);
+    fn visit_for_loop(&mut self, for_loop: &ForLoop);
+    fn visit_break_stmt(&mut self, break_: &Break);
+    fn visit_continue_stmt(&mut self, continue_: &Continue);
 }
 
 /// A visitor which can visit expressions of code.
@@ -99,9 +171,45 @@ pub trait ExpressionVisitor {
             Expression::FnCall(ref fn_call) => {
                 self.visit_fn_call(fn_call);
             },
+            Expression::Loop(ref loop_expr) => {
+                self.visit_loop_expr(loop_expr);
+            },
+            Expression::Match(ref match_expr) => {
+                self.visit_match_expr(match_expr);
+            },
+            Expression::FieldAccess(ref field_access) => {
+                self.visit_field_access(field_access);
+            },
+            Expression::StructUpdate(ref struct_update) => {
+                self.visit_struct_update(struct_update);
+            },
+            Expression::Tuple(ref tuple) => {
+                self.visit_tuple_expr(tuple);
+            },
+            Expression::Array(ref array) => {
+                self.visit_array_expr(array);
+            },
+            Expression::ArrayRepeat(ref repeat) => {
+                self.visit_array_repeat(repeat);
+            },
+            Expression::Index(ref index) => {
+                self.visit_index_expr(index);
+            },
+            Expression::Slice(ref slice) => {
+                self.visit_slice_expr(slice);
+            },
+            Expression::MethodCall(ref method_call) => {
+                self.visit_method_call(method_call);
+            },
+            Expression::Lambda(ref lambda) => {
+                self.visit_lambda(lambda);
+            },
             Expression::Assignment(ref assign) => {
                 self.visit_assignment(assign);
             },
+            Expression::IndexAssignment(ref assign) => {
+                self.visit_index_assignment(assign);
+            },
         }
     }
     fn visit_literal_expr(&mut self, literal: &Literal);
@@ -110,5 +218,17 @@ pub trait ExpressionVisitor {
     fn visit_unary_op(&mut self, unary_op: &UnaryOperation);
     fn visit_binary_op(&mut self, bin_op: &BinaryOperation);
     fn visit_fn_call(&mut self, fn_call: &FnCall);
+    fn visit_loop_expr(&mut self, loop_expr: &Loop);
+    fn visit_match_expr(&mut self, match_expr: &Match);
+    fn visit_field_access(&mut self, field_access: &FieldAccess);
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate);
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression);
+    fn visit_array_expr(&mut self, array: &ArrayExpression);
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat);
+    fn visit_index_expr(&mut self, index: &IndexExpression);
+    fn visit_slice_expr(&mut self, slice: &SliceExpression);
+    fn visit_method_call(&mut self, method_call: &MethodCall);
+    fn visit_lambda(&mut self, lambda: &Lambda);
     fn visit_assignment(&mut self, assign: &Assignment);
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment);
 }
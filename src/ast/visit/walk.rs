@@ -12,6 +12,15 @@ pub fn walk_unit<V>(visitor: &mut V, unit: &Unit)
     }
 }
 
+/// Visit each method of an `ImplBlock`.
+#[inline]
+pub fn walk_impl_block<V>(visitor: &mut V, impl_block: &ImplBlock)
+                       where V: ItemVisitor {
+    for method in impl_block.methods() {
+        visitor.visit_block_fn_decl(method);
+    }
+}
+
 /// Visit the `BlockFnDeclaration`'s block.
 #[inline]
 pub fn walk_fn_decl<V>(visitor: &mut V, fn_decl: &BlockFnDeclaration)
@@ -75,3 +84,190 @@ pub fn walk_if_block<V>(visitor: &mut V, if_block: &IfBlock)
         visitor.visit_block(block);
     }
 }
+
+#[inline]
+pub fn walk_while_block<V>(visitor: &mut V, while_block: &WhileBlock)
+                           where V: BlockVisitor + ExpressionVisitor {
+    visitor.visit_expression(while_block.condition());
+    visitor.visit_block(while_block.block());
+}
+
+/// Visit the `start`, `end`, and `step` of the range.
+#[inline]
+pub fn walk_range<V>(visitor: &mut V, range: &Range)
+                     where V: ExpressionVisitor {
+    visitor.visit_expression(range.start());
+    visitor.visit_expression(range.end());
+    if let Some(step) = range.step() {
+        visitor.visit_expression(step);
+    }
+}
+
+#[inline]
+pub fn walk_for_loop<V>(visitor: &mut V, for_loop: &ForLoop)
+                        where V: BlockVisitor + ExpressionVisitor {
+    walk_range(visitor, for_loop.range());
+    visitor.visit_block(for_loop.block());
+}
+
+#[inline]
+pub fn walk_loop_expr<V>(visitor: &mut V, loop_expr: &Loop)
+                        where V: BlockVisitor {
+    visitor.visit_block(loop_expr.block());
+}
+
+#[inline]
+pub fn walk_break<V>(visitor: &mut V, break_: &Break)
+                    where V: ExpressionVisitor {
+    if let Some(value) = break_.value() {
+        visitor.visit_expression(value);
+    }
+}
+
+/// Visit the literals of a pattern.
+#[inline]
+pub fn walk_pattern<V>(visitor: &mut V, pattern: &Pattern)
+                      where V: ExpressionVisitor {
+    match *pattern {
+        Pattern::Wildcard(_) => {},
+        Pattern::Literal(ref literal) => {
+            visitor.visit_literal_expr(literal.literal());
+        },
+        Pattern::Range(ref range) => {
+            visitor.visit_literal_expr(range.start().literal());
+            visitor.visit_literal_expr(range.end().literal());
+        },
+        // Variant patterns have no expressions.
+        Pattern::Variant(_) => {}
+    }
+}
+
+/// Visit the value, and the pattern, guard, and block of each arm.
+#[inline]
+pub fn walk_match_expr<V>(visitor: &mut V, match_expr: &Match)
+                         where V: BlockVisitor + ExpressionVisitor {
+    visitor.visit_expression(match_expr.value());
+    for arm in match_expr.arms() {
+        walk_pattern(visitor, arm.pattern());
+        if let Some(guard) = arm.guard() {
+            visitor.visit_expression(guard);
+        }
+        visitor.visit_block(arm.block());
+    }
+}
+
+/// Visit the struct value of the field access.
+#[inline]
+pub fn walk_field_access<V>(visitor: &mut V, field_access: &FieldAccess)
+                           where V: ExpressionVisitor {
+    visitor.visit_expression(field_access.value());
+}
+
+/// Visit the struct value and each updated field's value.
+#[inline]
+pub fn walk_struct_update<V>(visitor: &mut V, struct_update: &StructUpdate)
+                            where V: ExpressionVisitor {
+    visitor.visit_expression(struct_update.value());
+    for field in struct_update.fields() {
+        visitor.visit_expression(field.expression());
+    }
+}
+
+/// Visit each element of the tuple.
+#[inline]
+pub fn walk_tuple_expr<V>(visitor: &mut V, tuple: &TupleExpression)
+                         where V: ExpressionVisitor {
+    for element in tuple.elements() {
+        visitor.visit_expression(element);
+    }
+}
+
+/// Visit the type of each element of the tuple type.
+#[inline]
+pub fn walk_tuple_type_expr<V>(visitor: &mut V, tuple_ty: &TupleTypeExpression)
+                              where V: TypeVisitor {
+    for element in tuple_ty.elements() {
+        visitor.visit_type_expr(element);
+    }
+}
+
+/// Visit each element of the array.
+#[inline]
+pub fn walk_array_expr<V>(visitor: &mut V, array: &ArrayExpression)
+                         where V: ExpressionVisitor {
+    for element in array.elements() {
+        visitor.visit_expression(element);
+    }
+}
+
+/// Visit the array and the index of an element access.
+#[inline]
+pub fn walk_index_expr<V>(visitor: &mut V, index: &IndexExpression)
+                         where V: ExpressionVisitor {
+    visitor.visit_expression(index.value());
+    visitor.visit_expression(index.index());
+}
+
+/// Visit the array which is viewed by a slice.
+#[inline]
+pub fn walk_slice_expr<V>(visitor: &mut V, slice: &SliceExpression)
+                         where V: ExpressionVisitor {
+    visitor.visit_expression(slice.value());
+}
+
+/// Visit the receiver and the arguments of a method call.
+#[inline]
+pub fn walk_method_call<V>(visitor: &mut V, method_call: &MethodCall)
+                          where V: ExpressionVisitor {
+    visitor.visit_expression(method_call.receiver());
+    for arg in method_call.args() {
+        visitor.visit_expression(arg.expression());
+    }
+}
+
+/// Visit the body of a lambda.
+#[inline]
+pub fn walk_lambda<V>(visitor: &mut V, lambda: &Lambda)
+                     where V: ExpressionVisitor {
+    visitor.visit_expression(lambda.body());
+}
+
+/// Visit the element and the value of an index assignment.
+#[inline]
+pub fn walk_index_assignment<V>(visitor: &mut V, assign: &IndexAssignment)
+                               where V: ExpressionVisitor {
+    visitor.visit_index_expr(assign.lvalue());
+    visitor.visit_expression(assign.rvalue());
+}
+
+/// Visit the element type of the array type.
+#[inline]
+pub fn walk_array_type_expr<V>(visitor: &mut V, array_ty: &ArrayTypeExpression)
+                              where V: TypeVisitor {
+    visitor.visit_type_expr(array_ty.element());
+}
+
+/// Visit the element type of the growable array type.
+#[inline]
+pub fn walk_growable_type_expr<V>(visitor: &mut V,
+                                  growable_ty: &GrowableTypeExpression)
+                                 where V: TypeVisitor {
+    visitor.visit_type_expr(growable_ty.element());
+}
+
+/// Visit the element type of the slice type.
+#[inline]
+pub fn walk_slice_type_expr<V>(visitor: &mut V, slice_ty: &SliceTypeExpression)
+                              where V: TypeVisitor {
+    visitor.visit_type_expr(slice_ty.element());
+}
+
+/// Visit the parameter types and then the return type of the function type.
+#[inline]
+pub fn walk_fn_type_expr<V>(visitor: &mut V, fn_ty: &FnTypeExpression)
+                           where V: TypeVisitor {
+    for &(ref _param, ref param_ty) in fn_ty.params() {
+        visitor.visit_type_expr(param_ty);
+    }
+    visitor.visit_type_expr(fn_ty.return_type());
+}
@@ -0,0 +1,740 @@
+//! Computes the values of `const`s at compile time.
+
+use std::collections::{HashMap, HashSet};
+
+use lex::Span;
+use ast::{*, visit::*};
+use check::{CheckerError, ErrorCollector, TypeMapping};
+use identify::{ConcreteType, int_type_range};
+
+/// The most function calls and loop iterations run while computing a const,
+/// so that consts which never finish are reported instead of hanging.
+const MAX_STEPS: usize = 100_000;
+
+/// The most nested calls to functions made while computing a const, which
+/// keeps the compiler from running out of stack before `MAX_STEPS`.
+const MAX_CALL_DEPTH: usize = 128;
+
+/// A value computed at compile time.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ConstValue {
+    Bool(bool),
+    /// An integer, and the name of its type.
+    Int(i128, &'static str),
+    Float(f64),
+}
+
+/// Mapping of the `ScopedId`s of consts to their values.
+pub type ConstValues = HashMap<ScopedId, ConstValue>;
+
+/// Errors stop the evaluation of a const. `None` is used for errors which
+/// have already been reported, such as those of another const it uses.
+type EvalResult<T> = Result<T, Option<CheckerError>>;
+
+/// How evaluation of a block finished.
+#[derive(Debug)]
+enum Flow {
+    /// The block ended, with the value of its last expression.
+    Value(Option<ConstValue>),
+    /// A `return` was reached inside of a function.
+    Return(Option<ConstValue>),
+    /// A `break` out of the innermost loop, or the loop with the label.
+    Break(Option<String>),
+    /// A `continue` of the innermost loop, or the loop with the label.
+    Continue(Option<String>)
+}
+
+/// Computes the values of `const` items and `const` local variables.
+///
+/// Consts can use literals, arithmetic, comparisons, `if`s, other consts,
+/// and calls to functions of the unit whose bodies can also be computed.
+/// Run after type inference, as the types of literals and consts determine
+/// how their arithmetic overflows.
+///
+/// The initial values of globals are also computed when they can be, so that
+/// they don't need to be initialized when the program runs.
+pub struct ConstEvaluator<'err, 'types, 'unit> {
+    errors: &'err mut ErrorCollector,
+    types: &'types TypeMapping,
+    /// Const items of the unit, which can be used before they're declared.
+    const_items: HashMap<ScopedId, &'unit ConstDeclaration>,
+    /// Functions of the unit which can be called by consts.
+    fns: HashMap<ScopedId, &'unit BlockFnDeclaration>,
+    values: ConstValues,
+    /// Initial values of the globals which could be computed.
+    global_values: ConstValues,
+    /// Consts whose errors have been reported.
+    failed: HashSet<ScopedId>,
+    /// Consts currently being computed, which can't be used by themselves.
+    evaluating: Vec<ScopedId>,
+    /// Variables of the function calls being computed.
+    frames: Vec<HashMap<ScopedId, ConstValue>>,
+    /// Calls and loop iterations left to compute the current const.
+    steps_left: usize
+}
+
+impl<'err, 'types, 'unit> ConstEvaluator<'err, 'types, 'unit> {
+    pub fn new(errors: &'err mut ErrorCollector,
+               types: &'types TypeMapping,
+               unit: &'unit Unit)
+               -> ConstEvaluator<'err, 'types, 'unit> {
+        let mut const_items = HashMap::new();
+        let mut fns = HashMap::new();
+        for item in unit.items() {
+            match *item {
+                Item::ConstDeclaration(ref const_decl) => {
+                    const_items.insert(const_decl.id().clone(), const_decl);
+                },
+                Item::BlockFnDeclaration(ref block_fn)
+                        if !block_fn.is_generic() => {
+                    fns.insert(block_fn.id().clone(), block_fn);
+                },
+                _ => {}
+            }
+        }
+        ConstEvaluator {
+            errors, types, const_items, fns,
+            values: HashMap::new(),
+            global_values: HashMap::new(),
+            failed: HashSet::new(),
+            evaluating: Vec::new(),
+            frames: Vec::new(),
+            steps_left: MAX_STEPS
+        }
+    }
+
+    /// Get the values of the consts, and the initial values of the globals
+    /// which could be computed.
+    pub fn into_values(self) -> (ConstValues, ConstValues) {
+        (self.values, self.global_values)
+    }
+
+    /// Use up one of the steps which computing a const may take.
+    fn take_step(&mut self, span: Span) -> EvalResult<()> {
+        if self.steps_left == 0 {
+            debug!("Emitting error: out of steps computing const");
+            return Err(Some(CheckerError::new(
+                vec![span],
+                format!("Computing the value takes more than {} calls and loop iterations at compile time",
+                    MAX_STEPS)
+            )))
+        }
+        self.steps_left -= 1;
+        Ok(())
+    }
+
+    /// Compute a const, reporting any errors. Consts are only computed once.
+    fn evaluate_const(&mut self, id: &ScopedId, name: &str,
+                      value: &Expression) -> EvalResult<ConstValue> {
+        if let Some(known) = self.values.get(id) {
+            return Ok(*known)
+        }
+        if self.failed.contains(id) {
+            return Err(None)
+        }
+        if self.evaluating.contains(id) {
+            debug!("Emitting error: const {} depends on itself", name);
+            return Err(Some(CheckerError::new(
+                vec![value.span()],
+                format!("Const {} depends on itself", name)
+            )))
+        }
+        trace!("Computing const {}", name);
+        self.evaluating.push(id.clone());
+        // Consts can't use the variables of the function using them.
+        let frames = ::std::mem::replace(&mut self.frames, vec![HashMap::new()]);
+        let result = self.evaluate_typed(id, name, value);
+        self.frames = frames;
+        self.evaluating.pop();
+        match result {
+            Ok(computed) => {
+                trace!("Const {} has value {:?}", name, computed);
+                self.values.insert(id.clone(), computed);
+                Ok(computed)
+            },
+            Err(error) => {
+                self.failed.insert(id.clone());
+                if let Some(error) = error {
+                    self.errors.add_error(error);
+                }
+                Err(None)
+            }
+        }
+    }
+
+    /// Compute the value of a const, which must be a number or bool.
+    fn evaluate_typed(&mut self, id: &ScopedId, name: &str,
+                      value: &Expression) -> EvalResult<ConstValue> {
+        match self.types.get(id) {
+            Some(&ConcreteType::Named(ref named))
+                    if named.name() == "bool" || named.name() == "float" ||
+                       int_type_range(named.name()).is_some() => {
+                self.eval_expr(value)
+            },
+            Some(other) => {
+                debug!("Emitting error: const {} has type {}", name, other);
+                Err(Some(CheckerError::new(
+                    vec![value.span()],
+                    format!("Const {} must be a number or bool, not {}",
+                        name, other)
+                )))
+            },
+            // Type errors have already been reported.
+            None => Err(None)
+        }
+    }
+
+    fn eval_expr(&mut self, expr: &Expression) -> EvalResult<ConstValue> {
+        match *expr {
+            Expression::Literal(ref literal) => self.eval_literal(literal, false),
+            Expression::VariableRef(ref ident) => self.eval_var_ref(ident),
+            Expression::BinaryOp(ref bin_op) => self.eval_binary_op(bin_op),
+            Expression::UnaryOp(ref unary_op) => self.eval_unary_op(unary_op),
+            Expression::IfExpression(ref if_expr) => {
+                if try!(self.eval_condition(if_expr.condition())) {
+                    self.eval_expr(if_expr.true_expr())
+                }
+                else {
+                    self.eval_expr(if_expr.else_expr())
+                }
+            },
+            Expression::FnCall(ref fn_call) => self.eval_fn_call(fn_call),
+            ref other => {
+                debug!("Emitting error: non-constant expression");
+                Err(Some(CheckerError::new(
+                    vec![other.span()],
+                    format!("Expression cannot be computed at compile time")
+                )))
+            }
+        }
+    }
+
+    /// Compute a literal, which may be `negated` so that the smallest
+    /// value of a signed type, such as `-128`, is in range.
+    fn eval_literal(&mut self, literal: &Literal, negated: bool)
+                    -> EvalResult<ConstValue> {
+        let type_name = match self.types.get(&literal.id()) {
+            Some(&ConcreteType::Named(ref named)) => named.name().to_string(),
+            _ => return Err(None)
+        };
+        match *literal.value() {
+            LiteralValue::Bool(value) => Ok(ConstValue::Bool(value)),
+            LiteralValue::Float(value) =>
+                Ok(ConstValue::Float(if negated { -value } else { value })),
+            // Integer literals can be inferred to be floats.
+            LiteralValue::Int(value) if type_name == "float" =>
+                Ok(ConstValue::Float(if negated { -(value as f64) }
+                                     else { value as f64 })),
+            LiteralValue::Int(value) => {
+                let (name, _min, _max) = try!(int_type_range(&type_name)
+                    .ok_or(None));
+                check_int(if negated { -(value as i128) } else { value as i128 },
+                          name, literal.span())
+            },
+            LiteralValue::Str(_) | LiteralValue::Unit => {
+                debug!("Emitting error: non-constant literal");
+                Err(Some(CheckerError::new(
+                    vec![literal.span()],
+                    format!("Only numbers and bools can be computed at compile time")
+                )))
+            }
+        }
+    }
+
+    fn eval_var_ref(&mut self, ident: &Identifier) -> EvalResult<ConstValue> {
+        let id = ident.id().clone();
+        if let Some(local) = self.frames.last().and_then(|frame| frame.get(&id)) {
+            return Ok(*local)
+        }
+        if let Some(known) = self.values.get(&id) {
+            return Ok(*known)
+        }
+        if self.failed.contains(&id) {
+            return Err(None)
+        }
+        if let Some(const_decl) = self.const_items.get(&id).cloned() {
+            return self.evaluate_const(&id, const_decl.name(),
+                                       const_decl.value())
+        }
+        debug!("Emitting error: {} is not a const", ident.name());
+        Err(Some(CheckerError::new(
+            vec![ident.span()],
+            format!("Variable {} is not a const and cannot be used at compile time",
+                ident.name())
+        )))
+    }
+
+    fn eval_condition(&mut self, condition: &Expression) -> EvalResult<bool> {
+        match try!(self.eval_expr(condition)) {
+            ConstValue::Bool(value) => Ok(value),
+            // Conditions have been checked to be bools.
+            other => panic!("Condition had non-bool value {:?}", other)
+        }
+    }
+
+    fn eval_binary_op(&mut self, bin_op: &BinaryOperation)
+                      -> EvalResult<ConstValue> {
+        use self::BinaryOperator::*;
+        let left = try!(self.eval_expr(bin_op.left()));
+        // `&&` and `||` only compute their right side if needed.
+        match (bin_op.operator(), left) {
+            (And, ConstValue::Bool(false)) => return Ok(left),
+            (Or, ConstValue::Bool(true)) => return Ok(left),
+            _ => {}
+        }
+        let right = try!(self.eval_expr(bin_op.right()));
+        let span = bin_op.span();
+        match (left, right) {
+            (ConstValue::Bool(left), ConstValue::Bool(right)) => {
+                match bin_op.operator() {
+                    Equality => Ok(ConstValue::Bool(left == right)),
+                    NonEquality => Ok(ConstValue::Bool(left != right)),
+                    And | Or => Ok(ConstValue::Bool(right)),
+                    other => panic!("Invalid bool operator {:?}", other)
+                }
+            },
+            (ConstValue::Int(left, ty), ConstValue::Int(right, _)) => {
+                let result = match bin_op.operator() {
+                    Equality => return Ok(ConstValue::Bool(left == right)),
+                    NonEquality => return Ok(ConstValue::Bool(left != right)),
+                    LessThan => return Ok(ConstValue::Bool(left < right)),
+                    GreaterThan => return Ok(ConstValue::Bool(left > right)),
+                    LessThanEquals => return Ok(ConstValue::Bool(left <= right)),
+                    GreaterThanEquals => return Ok(ConstValue::Bool(left >= right)),
+                    Division | Modulus if right == 0 => {
+                        debug!("Emitting error: division by zero");
+                        return Err(Some(CheckerError::new(
+                            vec![span],
+                            format!("Division by zero")
+                        )))
+                    },
+                    Addition => left.checked_add(right),
+                    Subtraction => left.checked_sub(right),
+                    Multiplication => left.checked_mul(right),
+                    Division => left.checked_div(right),
+                    Modulus => left.checked_rem(right),
+                    other => panic!("Invalid int operator {:?}", other)
+                };
+                match result {
+                    Some(result) => check_int(result, ty, span),
+                    None => Err(Some(overflow_error(ty, span)))
+                }
+            },
+            (ConstValue::Float(left), ConstValue::Float(right)) => {
+                Ok(match bin_op.operator() {
+                    Equality => ConstValue::Bool(left == right),
+                    NonEquality => ConstValue::Bool(left != right),
+                    LessThan => ConstValue::Bool(left < right),
+                    GreaterThan => ConstValue::Bool(left > right),
+                    LessThanEquals => ConstValue::Bool(left <= right),
+                    GreaterThanEquals => ConstValue::Bool(left >= right),
+                    Addition => ConstValue::Float(left + right),
+                    Subtraction => ConstValue::Float(left - right),
+                    Multiplication => ConstValue::Float(left * right),
+                    Division => ConstValue::Float(left / right),
+                    Modulus => ConstValue::Float(left % right),
+                    other => panic!("Invalid float operator {:?}", other)
+                })
+            },
+            (left, right) =>
+                panic!("Mismatched operands {:?} and {:?}", left, right)
+        }
+    }
+
+    fn eval_unary_op(&mut self, unary_op: &UnaryOperation)
+                     -> EvalResult<ConstValue> {
+        if let (UnaryOperator::Negation, &Expression::Literal(ref literal)) =
+                (unary_op.operator(), unary_op.inner()) {
+            return self.eval_literal(literal, true)
+        }
+        let inner = try!(self.eval_expr(unary_op.inner()));
+        match (unary_op.operator(), inner) {
+            (UnaryOperator::Addition, _) => Ok(inner),
+            (UnaryOperator::Negation, ConstValue::Int(value, ty)) =>
+                check_int(-value, ty, unary_op.span()),
+            (UnaryOperator::Negation, ConstValue::Float(value)) =>
+                Ok(ConstValue::Float(-value)),
+            (UnaryOperator::Not, ConstValue::Bool(value)) =>
+                Ok(ConstValue::Bool(!value)),
+            (operator, value) =>
+                panic!("Invalid operator {:?} for {:?}", operator, value)
+        }
+    }
+
+    fn eval_fn_call(&mut self, fn_call: &FnCall) -> EvalResult<ConstValue> {
+        let block_fn = match self.fns.get(&fn_call.id()).cloned() {
+            Some(block_fn) => block_fn,
+            None => {
+                debug!("Emitting error: cannot call {}", fn_call.text());
+                return Err(Some(CheckerError::new(
+                    vec![fn_call.span()],
+                    format!("Cannot call {} at compile time", fn_call.text())
+                )))
+            }
+        };
+        try!(self.take_step(fn_call.span()));
+        if self.frames.len() > MAX_CALL_DEPTH {
+            debug!("Emitting error: too many calls computing const");
+            return Err(Some(CheckerError::new(
+                vec![fn_call.span()],
+                format!("Call to {} is nested more than {} calls deep at compile time",
+                    fn_call.text(), MAX_CALL_DEPTH)
+            )))
+        }
+        let mut frame = HashMap::new();
+        for arg in fn_call.args() {
+            let value = try!(self.eval_expr(arg.expression()));
+            let param = block_fn.params().iter()
+                .find(|&&(ref param, _)| param.name() == arg.name().name())
+                .expect("Call had unknown argument");
+            frame.insert(param.0.id().clone(), value);
+        }
+        trace!("Calling {} at compile time", block_fn.name());
+        self.frames.push(frame);
+        let result = self.eval_block(block_fn.block());
+        self.frames.pop();
+        let value = match try!(result) {
+            Flow::Value(value) | Flow::Return(value) => value,
+            other => panic!("Loop control {:?} left a fn", other)
+        };
+        value.ok_or_else(|| {
+            debug!("Emitting error: {} has no value", fn_call.text());
+            Some(CheckerError::new(
+                vec![fn_call.span()],
+                format!("Call to {} has no value to use at compile time",
+                    fn_call.text())
+            ))
+        })
+    }
+
+    fn eval_block(&mut self, block: &Block) -> EvalResult<Flow> {
+        let mut value = None;
+        for stmt in block.stmts() {
+            value = None;
+            match *stmt {
+                Statement::Expression(Expression::Assignment(ref assign)) => {
+                    let assigned = try!(self.eval_expr(assign.rvalue()));
+                    try!(self.assign_local(assign.lvalue(), assigned));
+                },
+                Statement::Expression(ref expr) => {
+                    value = Some(try!(self.eval_expr(expr)));
+                },
+                Statement::Return(ref return_) => {
+                    let returned = match return_.value() {
+                        Some(returned) => Some(try!(self.eval_expr(returned))),
+                        None => None
+                    };
+                    return Ok(Flow::Return(returned))
+                },
+                Statement::Declaration(ref decl) if decl.is_const() => {
+                    try!(self.evaluate_const(&decl.id(), decl.name(),
+                                             decl.value()));
+                },
+                Statement::Declaration(ref decl) => {
+                    let declared = try!(self.eval_expr(decl.value()));
+                    self.frames.last_mut().expect("Computing without a frame")
+                        .insert(decl.id().clone(), declared);
+                },
+                Statement::DoBlock(ref do_block) => {
+                    match try!(self.eval_block(do_block.block())) {
+                        Flow::Value(block_value) => value = block_value,
+                        returned => return Ok(returned)
+                    }
+                },
+                Statement::IfBlock(ref if_block) => {
+                    match try!(self.eval_if_block(if_block)) {
+                        Flow::Value(block_value) => value = block_value,
+                        returned => return Ok(returned)
+                    }
+                },
+                Statement::WhileBlock(ref while_block) => {
+                    match try!(self.eval_while_block(while_block)) {
+                        Flow::Value(_) => {},
+                        returned => return Ok(returned)
+                    }
+                },
+                // Only `break`s out of `loop`s have values.
+                Statement::Break(ref break_) if break_.value().is_none() => {
+                    return Ok(Flow::Break(
+                        break_.label().map(|label| label.name().to_string())))
+                },
+                Statement::Continue(ref continue_) => {
+                    return Ok(Flow::Continue(
+                        continue_.label().map(|label| label.name().to_string())))
+                },
+                ref other => {
+                    debug!("Emitting error: non-constant statement");
+                    return Err(Some(CheckerError::new(
+                        vec![other.span()],
+                        format!("Statement cannot be computed at compile time")
+                    )))
+                }
+            }
+        }
+        Ok(Flow::Value(value))
+    }
+
+    fn eval_if_block(&mut self, if_block: &IfBlock) -> EvalResult<Flow> {
+        for conditional in if_block.conditionals() {
+            if try!(self.eval_condition(conditional.condition())) {
+                return self.eval_block(conditional.block())
+            }
+        }
+        match if_block.else_block() {
+            Some(else_block) => self.eval_block(else_block),
+            None => Ok(Flow::Value(None))
+        }
+    }
+
+    /// Run a `while` loop, which ends when its condition is `false` or it's
+    /// broken out of.
+    fn eval_while_block(&mut self, while_block: &WhileBlock)
+                        -> EvalResult<Flow> {
+        let label = while_block.label().map(|label| label.name());
+        while try!(self.eval_condition(while_block.condition())) {
+            try!(self.take_step(while_block.span()));
+            match try!(self.eval_block(while_block.block())) {
+                Flow::Value(_) => {},
+                Flow::Continue(ref target) if is_target(target, label) => {},
+                Flow::Break(ref target) if is_target(target, label) => break,
+                other => return Ok(other)
+            }
+        }
+        Ok(Flow::Value(None))
+    }
+
+    fn assign_local(&mut self, lvalue: &Identifier, value: ConstValue)
+                    -> EvalResult<()> {
+        let frame = self.frames.last_mut().expect("Computing without a frame");
+        match frame.get_mut(&lvalue.id()) {
+            Some(local) => {
+                *local = value;
+                Ok(())
+            },
+            None => {
+                debug!("Emitting error: assigning to {}", lvalue.name());
+                Err(Some(CheckerError::new(
+                    vec![lvalue.span()],
+                    format!("Variable {} cannot be assigned at compile time",
+                        lvalue.name())
+                )))
+            }
+        }
+    }
+}
+
+/// Whether a `break` or `continue` to the `target` label is of the loop with
+/// `label`. Unlabeled ones are of the innermost loop.
+fn is_target(target: &Option<String>, label: Option<&str>) -> bool {
+    match *target {
+        Some(ref target) => Some(target.as_str()) == label,
+        None => true
+    }
+}
+
+/// Check that an integer fits in its type.
+fn check_int(value: i128, ty: &'static str, span: Span)
+             -> EvalResult<ConstValue> {
+    let (_, min, max) = int_type_range(ty).expect("Unknown int type");
+    if value < min || value > max {
+        return Err(Some(overflow_error(ty, span)))
+    }
+    Ok(ConstValue::Int(value, ty))
+}
+
+fn overflow_error(ty: &str, span: Span) -> CheckerError {
+    debug!("Emitting error: overflow of {}", ty);
+    CheckerError::new(
+        vec![span],
+        format!("Arithmetic overflow: value does not fit in {}", ty)
+    )
+}
+
+impl<'err, 'types, 'unit> UnitVisitor for ConstEvaluator<'err, 'types, 'unit> {
+    fn visit_unit(&mut self, unit: &Unit) {
+        trace!("Visiting a unit");
+        visit::walk_unit(self, unit);
+    }
+}
+
+impl<'err, 'types, 'unit> ItemVisitor for ConstEvaluator<'err, 'types, 'unit> {
+    fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
+        trace!("Visiting declaration of fn {}", block_fn.name());
+        self.visit_block(block_fn.block());
+    }
+
+    fn visit_extern_fn_decl(&mut self, _extern_fn: &ExternFnDeclaration) { }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) { }
+
+    fn visit_import(&mut self, _import: &Import) { }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting declaration of const {}", const_decl.name());
+        self.steps_left = MAX_STEPS;
+        // Errors are reported by `evaluate_const`.
+        let _ = self.evaluate_const(&const_decl.id(), const_decl.name(),
+                                    const_decl.value());
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting declaration of global {}", global.name());
+        // Globals which can't be computed are initialized when the program
+        // runs, so their errors aren't reported.
+        let frames = ::std::mem::replace(&mut self.frames, vec![HashMap::new()]);
+        self.steps_left = MAX_STEPS;
+        let result = self.eval_expr(global.value());
+        self.frames = frames;
+        match result {
+            Ok(initial) => {
+                trace!("Global {} has initial value {:?}",
+                    global.name(), initial);
+                self.global_values.insert(global.id().clone(), initial);
+            },
+            Err(_) => {
+                trace!("Global {} will be initialized lazily", global.name());
+                self.visit_expression(global.value());
+            }
+        }
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        visit::walk_impl_block(self, impl_block);
+    }
+
+    fn visit_typedef(&mut self, _typedef: &Typedef) { }
+
+    fn visit_struct_decl(&mut self, _struct_decl: &StructDeclaration) { }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDeclaration) { }
+}
+
+impl<'err, 'types, 'unit> BlockVisitor for ConstEvaluator<'err, 'types, 'unit> {
+    fn visit_block(&mut self, block: &Block) {
+        visit::walk_block(self, block);
+    }
+}
+
+impl<'err, 'types, 'unit> StatementVisitor
+    for ConstEvaluator<'err, 'types, 'unit> {
+
+    fn visit_return_stmt(&mut self, return_: &Return) {
+        visit::walk_return(self, return_);
+    }
+
+    fn visit_declaration(&mut self, decl: &Declaration) {
+        if decl.is_const() {
+            trace!("Visiting declaration of const {}", decl.name());
+            self.steps_left = MAX_STEPS;
+            // Errors are reported by `evaluate_const`.
+            let _ = self.evaluate_const(&decl.id(), decl.name(), decl.value());
+        }
+        else {
+            self.visit_expression(decl.value());
+        }
+    }
+
+    fn visit_tuple_declaration(&mut self, decl: &TupleDeclaration) {
+        self.visit_expression(decl.value());
+    }
+
+    fn visit_if_block(&mut self, if_block: &IfBlock) {
+        visit::walk_if_block(self, if_block);
+    }
+
+    fn visit_do_block(&mut self, do_block: &DoBlock) {
+        visit::walk_do_block(self, do_block);
+    }
+
+    fn visit_while_block(&mut self, while_block: &WhileBlock) {
+        visit::walk_while_block(self, while_block);
+    }
+
+    fn visit_for_loop(&mut self, for_loop: &ForLoop) {
+        visit::walk_for_loop(self, for_loop);
+    }
+
+    fn visit_break_stmt(&mut self, break_: &Break) {
+        visit::walk_break(self, break_);
+    }
+
+    fn visit_continue_stmt(&mut self, _continue: &Continue) { }
+}
+
+impl<'err, 'types, 'unit> ExpressionVisitor
+    for ConstEvaluator<'err, 'types, 'unit> {
+
+    fn visit_literal_expr(&mut self, _literal: &Literal) { }
+
+    fn visit_var_ref(&mut self, _ident: &Identifier) { }
+
+    fn visit_if_expr(&mut self, if_expr: &IfExpression) {
+        visit::walk_if_expr(self, if_expr);
+    }
+
+    fn visit_unary_op(&mut self, unary_op: &UnaryOperation) {
+        visit::walk_unary_op(self, unary_op);
+    }
+
+    fn visit_binary_op(&mut self, bin_op: &BinaryOperation) {
+        visit::walk_bin_op(self, bin_op);
+    }
+
+    fn visit_fn_call(&mut self, fn_call: &FnCall) {
+        for arg in fn_call.args() {
+            self.visit_expression(arg.expression());
+        }
+    }
+
+    fn visit_loop_expr(&mut self, loop_expr: &Loop) {
+        visit::walk_loop_expr(self, loop_expr);
+    }
+
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression) {
+        visit::walk_tuple_expr(self, tuple);
+    }
+
+    fn visit_array_expr(&mut self, array: &ArrayExpression) {
+        visit::walk_array_expr(self, array);
+    }
+
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat) {
+        self.visit_expression(repeat.value());
+    }
+
+    fn visit_index_expr(&mut self, index: &IndexExpression) {
+        visit::walk_index_expr(self, index);
+    }
+
+    fn visit_slice_expr(&mut self, slice: &SliceExpression) {
+        visit::walk_slice_expr(self, slice);
+    }
+
+    fn visit_method_call(&mut self, method_call: &MethodCall) {
+        visit::walk_method_call(self, method_call);
+    }
+
+    fn visit_lambda(&mut self, lambda: &Lambda) {
+        visit::walk_lambda(self, lambda);
+    }
+
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment) {
+        visit::walk_index_assignment(self, assign);
+    }
+
+    fn visit_field_access(&mut self, field_access: &FieldAccess) {
+        visit::walk_field_access(self, field_access);
+    }
+
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate) {
+        visit::walk_struct_update(self, struct_update);
+    }
+
+    fn visit_match_expr(&mut self, match_expr: &Match) {
+        visit::walk_match_expr(self, match_expr);
+    }
+
+    fn visit_assignment(&mut self, assign: &Assignment) {
+        self.visit_expression(assign.rvalue());
+    }
+}
@@ -0,0 +1,2 @@
+mod const_evaluator;
+pub use self::const_evaluator::{ConstEvaluator, ConstValue, ConstValues};
@@ -0,0 +1,277 @@
+//! Checks that globals can be initialized.
+
+use std::collections::{HashMap, HashSet};
+
+use lex::Span;
+use ast::{*, visit::*};
+use check::{CheckerError, ConstValues, ErrorCollector};
+
+/// Checks that the initializers of globals don't use the globals being
+/// initialized, either directly or through the functions and methods
+/// they call.
+///
+/// Globals whose initial value was computed by the `ConstEvaluator` don't
+/// run their initializers, so they're not checked.
+pub struct GlobalInitChecker<'err, 'consts> {
+    errors: &'err mut ErrorCollector,
+    global_values: &'consts ConstValues,
+    /// Names and spans of the globals which are initialized lazily.
+    globals: HashMap<ScopedId, (String, Span)>,
+    /// Globals in the order they're declared.
+    global_order: Vec<ScopedId>,
+    /// Functions and globals used by each function and initializer.
+    uses: HashMap<ScopedId, HashSet<ScopedId>>,
+    /// Function or global whose uses are being found.
+    current: ScopedId
+}
+
+impl<'err, 'consts> GlobalInitChecker<'err, 'consts> {
+    pub fn new(errors: &'err mut ErrorCollector,
+               global_values: &'consts ConstValues)
+               -> GlobalInitChecker<'err, 'consts> {
+        GlobalInitChecker {
+            errors, global_values,
+            globals: HashMap::new(),
+            global_order: Vec::new(),
+            uses: HashMap::new(),
+            current: ScopedId::default()
+        }
+    }
+
+    fn add_use(&mut self, id: &ScopedId) {
+        if self.current.is_default() || id.is_default() {
+            return
+        }
+        self.uses.entry(self.current.clone())
+            .or_default()
+            .insert(id.clone());
+    }
+
+    /// Find the globals an initializer uses on its way back to `target`.
+    fn find_cycle(&self, from: &ScopedId, target: &ScopedId,
+                  visited: &mut HashSet<ScopedId>,
+                  path: &mut Vec<ScopedId>) -> bool {
+        let uses = match self.uses.get(from) {
+            Some(uses) => uses,
+            None => return false
+        };
+        for used in uses {
+            if used == target {
+                return true
+            }
+            if !visited.insert(used.clone()) {
+                continue
+            }
+            let is_global = self.globals.contains_key(used);
+            if is_global {
+                path.push(used.clone());
+            }
+            if self.find_cycle(used, target, visited, path) {
+                return true
+            }
+            if is_global {
+                path.pop();
+            }
+        }
+        false
+    }
+
+    fn check_cycles(&mut self) {
+        let mut reported = HashSet::new();
+        for global_id in self.global_order.clone() {
+            if reported.contains(&global_id) {
+                continue
+            }
+            let mut path = vec![global_id.clone()];
+            if !self.find_cycle(&global_id, &global_id,
+                                &mut HashSet::new(), &mut path) {
+                continue
+            }
+            let (name, span) = self.globals[&global_id].clone();
+            let names = path.iter().chain(Some(&global_id))
+                .map(|id| self.globals[id].0.as_str())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            debug!("Emitting error: global {} depends on itself", name);
+            self.errors.add_error(CheckerError::new(
+                vec![span],
+                format!("Initialization of global {} depends on itself: {}",
+                    name, names)
+            ));
+            reported.extend(path);
+        }
+    }
+}
+
+impl<'err, 'consts> UnitVisitor for GlobalInitChecker<'err, 'consts> {
+    fn visit_unit(&mut self, unit: &Unit) {
+        trace!("Visiting a unit");
+        for item in unit.items() {
+            if let Item::GlobalDeclaration(ref global) = *item {
+                if !self.global_values.contains_key(&global.id()) {
+                    self.globals.insert(global.id().clone(),
+                        (global.name().to_string(), global.span()));
+                    self.global_order.push(global.id().clone());
+                }
+            }
+        }
+        visit::walk_unit(self, unit);
+        self.check_cycles();
+    }
+}
+
+impl<'err, 'consts> ItemVisitor for GlobalInitChecker<'err, 'consts> {
+    fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
+        trace!("Visiting declaration of fn {}", block_fn.name());
+        self.current = block_fn.id().clone();
+        self.visit_block(block_fn.block());
+    }
+
+    fn visit_extern_fn_decl(&mut self, _extern_fn: &ExternFnDeclaration) { }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) { }
+
+    fn visit_import(&mut self, _import: &Import) { }
+
+    fn visit_const_decl(&mut self, _const_decl: &ConstDeclaration) { }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting declaration of global {}", global.name());
+        self.current = global.id().clone();
+        self.visit_expression(global.value());
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        visit::walk_impl_block(self, impl_block);
+    }
+
+    fn visit_typedef(&mut self, _typedef: &Typedef) { }
+
+    fn visit_struct_decl(&mut self, _struct_decl: &StructDeclaration) { }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDeclaration) { }
+}
+
+impl<'err, 'consts> BlockVisitor for GlobalInitChecker<'err, 'consts> {
+    fn visit_block(&mut self, block: &Block) {
+        visit::walk_block(self, block);
+    }
+}
+
+impl<'err, 'consts> StatementVisitor for GlobalInitChecker<'err, 'consts> {
+    fn visit_return_stmt(&mut self, return_: &Return) {
+        visit::walk_return(self, return_);
+    }
+
+    fn visit_declaration(&mut self, decl: &Declaration) {
+        self.visit_expression(decl.value());
+    }
+
+    fn visit_tuple_declaration(&mut self, decl: &TupleDeclaration) {
+        self.visit_expression(decl.value());
+    }
+
+    fn visit_if_block(&mut self, if_block: &IfBlock) {
+        visit::walk_if_block(self, if_block);
+    }
+
+    fn visit_do_block(&mut self, do_block: &DoBlock) {
+        visit::walk_do_block(self, do_block);
+    }
+
+    fn visit_while_block(&mut self, while_block: &WhileBlock) {
+        visit::walk_while_block(self, while_block);
+    }
+
+    fn visit_for_loop(&mut self, for_loop: &ForLoop) {
+        visit::walk_for_loop(self, for_loop);
+    }
+
+    fn visit_break_stmt(&mut self, break_: &Break) {
+        visit::walk_break(self, break_);
+    }
+
+    fn visit_continue_stmt(&mut self, _continue: &Continue) { }
+}
+
+impl<'err, 'consts> ExpressionVisitor for GlobalInitChecker<'err, 'consts> {
+    fn visit_literal_expr(&mut self, _literal: &Literal) { }
+
+    fn visit_var_ref(&mut self, ident: &Identifier) {
+        self.add_use(&ident.id());
+    }
+
+    fn visit_if_expr(&mut self, if_expr: &IfExpression) {
+        visit::walk_if_expr(self, if_expr);
+    }
+
+    fn visit_unary_op(&mut self, unary_op: &UnaryOperation) {
+        visit::walk_unary_op(self, unary_op);
+    }
+
+    fn visit_binary_op(&mut self, bin_op: &BinaryOperation) {
+        visit::walk_bin_op(self, bin_op);
+    }
+
+    fn visit_fn_call(&mut self, fn_call: &FnCall) {
+        self.add_use(&fn_call.id());
+        for arg in fn_call.args() {
+            self.visit_expression(arg.expression());
+        }
+    }
+
+    fn visit_loop_expr(&mut self, loop_expr: &Loop) {
+        visit::walk_loop_expr(self, loop_expr);
+    }
+
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression) {
+        visit::walk_tuple_expr(self, tuple);
+    }
+
+    fn visit_array_expr(&mut self, array: &ArrayExpression) {
+        visit::walk_array_expr(self, array);
+    }
+
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat) {
+        self.visit_expression(repeat.value());
+    }
+
+    fn visit_index_expr(&mut self, index: &IndexExpression) {
+        visit::walk_index_expr(self, index);
+    }
+
+    fn visit_slice_expr(&mut self, slice: &SliceExpression) {
+        visit::walk_slice_expr(self, slice);
+    }
+
+    fn visit_method_call(&mut self, method_call: &MethodCall) {
+        self.add_use(&method_call.method().id());
+        visit::walk_method_call(self, method_call);
+    }
+
+    fn visit_lambda(&mut self, lambda: &Lambda) {
+        visit::walk_lambda(self, lambda);
+    }
+
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment) {
+        visit::walk_index_assignment(self, assign);
+    }
+
+    fn visit_field_access(&mut self, field_access: &FieldAccess) {
+        visit::walk_field_access(self, field_access);
+    }
+
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate) {
+        visit::walk_struct_update(self, struct_update);
+    }
+
+    fn visit_match_expr(&mut self, match_expr: &Match) {
+        visit::walk_match_expr(self, match_expr);
+    }
+
+    fn visit_assignment(&mut self, assign: &Assignment) {
+        // Globals are initialized before they're assigned to.
+        self.add_use(&assign.lvalue().id());
+        self.visit_expression(assign.rvalue());
+    }
+}
@@ -0,0 +1,2 @@
+mod init_checker;
+pub use self::init_checker::GlobalInitChecker;
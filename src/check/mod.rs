@@ -3,9 +3,15 @@
 //! This will be moved in the future.
 
 mod collector;
+mod consts;
+mod globals;
 mod errors;
+mod patterns;
 mod types;
 
 pub use self::collector::ErrorCollector;
 pub use self::errors::CheckerError;
+pub use self::consts::{ConstEvaluator, ConstValue, ConstValues};
+pub use self::globals::GlobalInitChecker;
+pub use self::patterns::MatchChecker;
 pub use self::types::{TypeConcretifier, TypeMapping};
@@ -0,0 +1,422 @@
+//! Checks that `match` expressions cover every value they could be given.
+
+use ast::{*, visit::*};
+use check::{CheckerError, ErrorCollector, TypeMapping};
+use identify::{ConcreteType, EnumType};
+
+/// The most gaps listed when a match is not exhaustive.
+const MAX_LISTED_GAPS: usize = 3;
+
+/// The values a matched type can take.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Domain<'a> {
+    /// `true` or `false`.
+    Bool,
+    /// Integers between a minimum and maximum, inclusive.
+    Int(i128, i128),
+    /// The variants of an enum, numbered by their tags.
+    Variants(&'a EnumType),
+    /// Values which can only be fully covered by `_`, such as floats.
+    Opaque,
+}
+
+impl<'a> Domain<'a> {
+    fn of(concrete: Option<&'a ConcreteType>) -> Domain<'a> {
+        let name = match concrete {
+            Some(&ConcreteType::Named(ref named)) => named.name(),
+            Some(&ConcreteType::Enum(ref enum_ty)) =>
+                return Domain::Variants(enum_ty),
+            _ => return Domain::Opaque
+        };
+        match name {
+            "bool" => Domain::Bool,
+            "i8" => Domain::Int(i8::min_value() as i128, i8::max_value() as i128),
+            "i16" => Domain::Int(i16::min_value() as i128, i16::max_value() as i128),
+            "i32" => Domain::Int(i32::min_value() as i128, i32::max_value() as i128),
+            "i64" => Domain::Int(i64::min_value() as i128, i64::max_value() as i128),
+            "u8" => Domain::Int(0, u8::max_value() as i128),
+            "u16" => Domain::Int(0, u16::max_value() as i128),
+            "u32" => Domain::Int(0, u32::max_value() as i128),
+            "u64" => Domain::Int(0, u64::max_value() as i128),
+            _ => Domain::Opaque
+        }
+    }
+
+    /// The full range of values in the domain.
+    fn bounds(&self) -> (i128, i128) {
+        match *self {
+            Domain::Bool => (0, 1),
+            Domain::Int(min, max) => (min, max),
+            Domain::Variants(enum_ty) =>
+                (0, enum_ty.variants().len() as i128 - 1),
+            // Opaque values are covered all at once, by `_`.
+            Domain::Opaque => (0, 0)
+        }
+    }
+
+    fn describe(&self, (start, end): (i128, i128)) -> String {
+        match *self {
+            Domain::Bool => if start == 0 && end == 0 { "`false`".into() }
+                            else if start == 1 { "`true`".into() }
+                            else { "`false` and `true`".into() },
+            Domain::Int(..) if start == end => format!("`{}`", start),
+            Domain::Int(..) => format!("`{}..={}`", start, end),
+            Domain::Variants(enum_ty) => {
+                enum_ty.variants()[start as usize ..= end as usize].iter()
+                    .map(|variant| format!("`{}`", variant.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            },
+            Domain::Opaque => "`_`".into()
+        }
+    }
+}
+
+/// Sorted, non-overlapping, inclusive ranges of values which have been
+/// matched by earlier arms.
+#[derive(Debug, Default)]
+struct Coverage {
+    ranges: Vec<(i128, i128)>
+}
+
+impl Coverage {
+    fn covers(&self, (start, end): (i128, i128)) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= start && end <= hi)
+    }
+
+    fn add(&mut self, range: (i128, i128)) {
+        self.ranges.push(range);
+        self.ranges.sort();
+        let mut merged: Vec<(i128, i128)> = Vec::with_capacity(self.ranges.len());
+        for &(start, end) in &self.ranges {
+            if let Some(last) = merged.last_mut() {
+                if start <= last.1.saturating_add(1) {
+                    last.1 = last.1.max(end);
+                    continue
+                }
+            }
+            merged.push((start, end));
+        }
+        self.ranges = merged;
+    }
+
+    /// The ranges of a domain which are not covered.
+    fn gaps(&self, (min, max): (i128, i128)) -> Vec<(i128, i128)> {
+        let mut gaps = Vec::new();
+        let mut next = min;
+        for &(start, end) in &self.ranges {
+            if start > next {
+                gaps.push((next, start - 1));
+            }
+            if end >= max {
+                return gaps
+            }
+            next = next.max(end + 1);
+        }
+        gaps.push((next, max));
+        gaps
+    }
+}
+
+/// Checks that `match` arms are exhaustive, reachable, and that range
+/// patterns are not empty.
+///
+/// Run after type inference, as the type of the matched value determines
+/// which values must be covered.
+pub struct MatchChecker<'err, 'types> {
+    errors: &'err mut ErrorCollector,
+    types: &'types TypeMapping,
+}
+
+impl<'err, 'types> MatchChecker<'err, 'types> {
+    pub fn new(errors: &'err mut ErrorCollector, types: &'types TypeMapping)
+               -> MatchChecker<'err, 'types> {
+        MatchChecker { errors, types }
+    }
+
+    /// Find the domain of a match from the type of its literal or variant
+    /// patterns.
+    fn domain_of(&self, match_expr: &Match) -> Domain<'types> {
+        for arm in match_expr.arms() {
+            let literal = match *arm.pattern() {
+                Pattern::Wildcard(_) => continue,
+                Pattern::Literal(ref literal) => literal,
+                Pattern::Range(ref range) => range.start(),
+                Pattern::Variant(ref variant) =>
+                    return Domain::of(self.types.get(&variant.ident().id()))
+            };
+            return Domain::of(self.types.get(&literal.literal().id()))
+        }
+        Domain::Opaque
+    }
+
+    /// The inclusive range of values matched by a pattern, or `None` if it
+    /// matches no values.
+    fn pattern_range(&mut self, domain: Domain, pattern: &Pattern)
+                     -> Option<(i128, i128)> {
+        match *pattern {
+            Pattern::Wildcard(_) => Some(domain.bounds()),
+            Pattern::Literal(ref literal) => match domain {
+                Domain::Opaque => Some(domain.bounds()),
+                _ => pattern_value(literal).map(|value| (value, value))
+            },
+            Pattern::Range(ref range) => {
+                let empty = match domain {
+                    Domain::Opaque => {
+                        let start = pattern_float(range.start());
+                        let end = pattern_float(range.end());
+                        if range.is_inclusive() { start > end }
+                        else { start >= end }
+                    },
+                    _ => {
+                        let start = pattern_value(range.start()).unwrap_or(0);
+                        let end = pattern_value(range.end()).unwrap_or(0);
+                        if range.is_inclusive() { start > end }
+                        else { start >= end }
+                    }
+                };
+                if empty {
+                    debug!("Found empty range pattern");
+                    self.errors.add_error(CheckerError::new(
+                        vec![range.span()],
+                        format!("Range pattern is empty")
+                    ));
+                    return None
+                }
+                match domain {
+                    Domain::Opaque => Some(domain.bounds()),
+                    _ => {
+                        let start = pattern_value(range.start()).unwrap_or(0);
+                        let end = pattern_value(range.end()).unwrap_or(0);
+                        Some((start, if range.is_inclusive() { end }
+                                     else { end - 1 }))
+                    }
+                }
+            },
+            Pattern::Variant(ref variant) => match domain {
+                Domain::Variants(enum_ty) => enum_ty.variant(variant.name())
+                    .map(|(tag, _variant)| (tag as i128, tag as i128)),
+                _ => Some(domain.bounds())
+            }
+        }
+    }
+}
+
+/// The integer value of a literal pattern, with `true` as 1.
+fn pattern_value(literal: &LiteralPattern) -> Option<i128> {
+    match *literal.literal().value() {
+        LiteralValue::Bool(value) => Some(value as i128),
+        _ => literal.int_value()
+    }
+}
+
+/// The value of a numeric literal pattern as a float.
+fn pattern_float(literal: &LiteralPattern) -> f64 {
+    literal.float_value()
+        .or_else(|| literal.int_value().map(|value| value as f64))
+        .unwrap_or(0f64)
+}
+
+impl<'err, 'types> UnitVisitor for MatchChecker<'err, 'types> {
+    fn visit_unit(&mut self, unit: &Unit) {
+        trace!("Visiting a unit");
+        visit::walk_unit(self, unit);
+    }
+}
+
+impl<'err, 'types> ItemVisitor for MatchChecker<'err, 'types> {
+    fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
+        trace!("Visiting declaration of fn {}", block_fn.name());
+        self.visit_block(block_fn.block());
+    }
+
+    fn visit_extern_fn_decl(&mut self, _extern_fn: &ExternFnDeclaration) { }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) { }
+
+    fn visit_import(&mut self, _import: &Import) { }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting declaration of const {}", const_decl.name());
+        self.visit_expression(const_decl.value());
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting declaration of global {}", global.name());
+        self.visit_expression(global.value());
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        visit::walk_impl_block(self, impl_block);
+    }
+
+    fn visit_typedef(&mut self, _typedef: &Typedef) { }
+
+    fn visit_struct_decl(&mut self, _struct_decl: &StructDeclaration) { }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDeclaration) { }
+}
+
+impl<'err, 'types> BlockVisitor for MatchChecker<'err, 'types> {
+    fn visit_block(&mut self, block: &Block) {
+        visit::walk_block(self, block);
+    }
+}
+
+impl<'err, 'types> StatementVisitor for MatchChecker<'err, 'types> {
+    fn visit_return_stmt(&mut self, return_: &Return) {
+        visit::walk_return(self, return_);
+    }
+
+    fn visit_declaration(&mut self, decl: &Declaration) {
+        self.visit_expression(decl.value());
+    }
+
+    fn visit_tuple_declaration(&mut self, decl: &TupleDeclaration) {
+        self.visit_expression(decl.value());
+    }
+
+    fn visit_if_block(&mut self, if_block: &IfBlock) {
+        visit::walk_if_block(self, if_block);
+    }
+
+    fn visit_do_block(&mut self, do_block: &DoBlock) {
+        visit::walk_do_block(self, do_block);
+    }
+
+    fn visit_while_block(&mut self, while_block: &WhileBlock) {
+        visit::walk_while_block(self, while_block);
+    }
+
+    fn visit_for_loop(&mut self, for_loop: &ForLoop) {
+        visit::walk_for_loop(self, for_loop);
+    }
+
+    fn visit_break_stmt(&mut self, break_: &Break) {
+        visit::walk_break(self, break_);
+    }
+
+    fn visit_continue_stmt(&mut self, _continue: &Continue) { }
+}
+
+impl<'err, 'types> ExpressionVisitor for MatchChecker<'err, 'types> {
+    fn visit_literal_expr(&mut self, _literal: &Literal) { }
+
+    fn visit_var_ref(&mut self, _ident: &Identifier) { }
+
+    fn visit_if_expr(&mut self, if_expr: &IfExpression) {
+        visit::walk_if_expr(self, if_expr);
+    }
+
+    fn visit_unary_op(&mut self, unary_op: &UnaryOperation) {
+        visit::walk_unary_op(self, unary_op);
+    }
+
+    fn visit_binary_op(&mut self, bin_op: &BinaryOperation) {
+        visit::walk_bin_op(self, bin_op);
+    }
+
+    fn visit_fn_call(&mut self, fn_call: &FnCall) {
+        for arg in fn_call.args() {
+            self.visit_expression(arg.expression());
+        }
+    }
+
+    fn visit_loop_expr(&mut self, loop_expr: &Loop) {
+        visit::walk_loop_expr(self, loop_expr);
+    }
+
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression) {
+        visit::walk_tuple_expr(self, tuple);
+    }
+
+    fn visit_array_expr(&mut self, array: &ArrayExpression) {
+        visit::walk_array_expr(self, array);
+    }
+
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat) {
+        self.visit_expression(repeat.value());
+    }
+
+    fn visit_index_expr(&mut self, index: &IndexExpression) {
+        visit::walk_index_expr(self, index);
+    }
+
+    fn visit_slice_expr(&mut self, slice: &SliceExpression) {
+        visit::walk_slice_expr(self, slice);
+    }
+
+    fn visit_method_call(&mut self, method_call: &MethodCall) {
+        visit::walk_method_call(self, method_call);
+    }
+
+    fn visit_lambda(&mut self, lambda: &Lambda) {
+        visit::walk_lambda(self, lambda);
+    }
+
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment) {
+        visit::walk_index_assignment(self, assign);
+    }
+
+    fn visit_field_access(&mut self, field_access: &FieldAccess) {
+        visit::walk_field_access(self, field_access);
+    }
+
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate) {
+        visit::walk_struct_update(self, struct_update);
+    }
+
+    fn visit_match_expr(&mut self, match_expr: &Match) {
+        trace!("Checking match");
+        visit::walk_match_expr(self, match_expr);
+
+        let domain = self.domain_of(match_expr);
+        let mut coverage = Coverage::default();
+        for arm in match_expr.arms() {
+            let range = match self.pattern_range(domain, arm.pattern()) {
+                Some(range) => range,
+                None => continue
+            };
+            if coverage.covers(range) {
+                debug!("Found unreachable match arm");
+                self.errors.add_error(CheckerError::new(
+                    vec![arm.pattern().span()],
+                    format!("Unreachable pattern")
+                ));
+                continue
+            }
+            // A guard may reject any value, so it doesn't cover anything.
+            // Only `_` covers an opaque domain.
+            let covers_domain = match (domain, arm.pattern()) {
+                (Domain::Opaque, &Pattern::Wildcard(_)) => true,
+                (Domain::Opaque, _) => false,
+                _ => true
+            };
+            if arm.guard().is_none() && covers_domain {
+                coverage.add(range);
+            }
+        }
+
+        let gaps = coverage.gaps(domain.bounds());
+        if gaps.is_empty() {
+            return
+        }
+        debug!("Found non-exhaustive match");
+        let mut missing = gaps.iter().take(MAX_LISTED_GAPS)
+            .map(|&gap| domain.describe(gap))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if gaps.len() > MAX_LISTED_GAPS {
+            missing.push_str(&format!(" and {} more",
+                                      gaps.len() - MAX_LISTED_GAPS));
+        }
+        self.errors.add_error(CheckerError::new(
+            vec![match_expr.value().span()],
+            format!("Match is not exhaustive: {} not covered", missing)
+        ));
+    }
+
+    fn visit_assignment(&mut self, assign: &Assignment) {
+        self.visit_expression(assign.rvalue());
+    }
+}
@@ -0,0 +1,2 @@
+mod match_checker;
+pub use self::match_checker::MatchChecker;
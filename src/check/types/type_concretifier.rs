@@ -4,7 +4,7 @@
 use lex::Span;
 use ast::{*, visit::*};
 use check::{CheckerError, ErrorCollector};
-use identify::{ConcreteType, TypeGraph, TypeScopeBuilder};
+use identify::{ConcreteType, TypeGraph, TypeScopeBuilder, int_type_range};
 
 use std::collections::HashMap;
 
@@ -17,7 +17,10 @@ pub struct TypeConcretifier<'err, 'builder, 'graph> {
     errors: &'err mut ErrorCollector,
     graph: &'graph mut TypeGraph,
     builder: &'builder TypeScopeBuilder,
-    results: HashMap<ScopedId, ConcreteType>
+    results: HashMap<ScopedId, ConcreteType>,
+    /// `ScopedId` of the current function, whose type parameters' bounds
+    /// are known inside of it
+    current_fn: ScopedId
 }
 
 impl<'err, 'builder, 'graph> TypeConcretifier<'err, 'builder, 'graph> {
@@ -27,7 +30,8 @@ impl<'err, 'builder, 'graph> TypeConcretifier<'err, 'builder, 'graph> {
                -> TypeConcretifier<'err, 'builder, 'graph> {
         TypeConcretifier {
             builder, errors, graph,
-            results: HashMap::new()
+            results: HashMap::new(),
+            current_fn: ScopedId::default()
         }
     }
 
@@ -57,7 +61,18 @@ impl<'err, 'builder, 'graph> TypeConcretifier<'err, 'builder, 'graph> {
             },
             Err(possibles) => {
                 debug!("Encountered an error in type inferring");
-                if !possibles.is_empty() {
+                if possibles.len() == 1 {
+                    debug!("Non-numeric type used as a number");
+                    let type_name = self.builder.get_type(&possibles[0])
+                        .map(|concrete| concrete.to_string())
+                        .unwrap_or_else(|| format!("{:?}", possibles[0]));
+                    self.errors.add_error(CheckerError::new(
+                        vec![span],
+                        format!("Expected a numeric type for {} - got {}",
+                                context, type_name)
+                    ));
+                }
+                else if !possibles.is_empty() {
                     debug!("Conflicts in determining a type");
                     self.errors.add_error(CheckerError::new(
                         vec![span],
@@ -78,6 +93,81 @@ impl<'err, 'builder, 'graph> TypeConcretifier<'err, 'builder, 'graph> {
         }
     }
 
+    /// Infer the type of a literal, and check that an integer literal fits
+    /// in its type.
+    fn infer_literal(&mut self, literal: &Literal, negative: bool, span: Span) {
+        let id = literal.id().clone();
+        let sign = if negative { "-" } else { "" };
+        if !self.infer_var(&id, span,
+                format!("literal {}{}", sign, literal.text())) {
+            return
+        }
+        let value = match *literal.value() {
+            LiteralValue::Int(value) if negative => -(value as i128),
+            LiteralValue::Int(value) => value as i128,
+            _ => return
+        };
+        let range = match self.results.get(&id) {
+            Some(&ConcreteType::Named(ref named)) => int_type_range(named.name()),
+            _ => None
+        };
+        if let Some((type_name, min, max)) = range {
+            if value < min || value > max {
+                debug!("Emitting error: literal {} does not fit in {}",
+                    value, type_name);
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Literal {} does not fit in {}", value, type_name)
+                ));
+            }
+        }
+    }
+
+    /// Record the type of a struct or enum being constructed or matched,
+    /// which is known from identification.
+    ///
+    /// Returns `false` if the `ScopedId` is not a struct or variant.
+    fn known_item_type(&mut self, id: &ScopedId) -> bool {
+        match self.builder.get_type(id) {
+            Some(concrete @ &ConcreteType::Record(_)) |
+            Some(concrete @ &ConcreteType::Enum(_)) => {
+                self.results.insert(id.clone(), concrete.clone());
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Check that the type arguments of a call to a generic function
+    /// implement the traits bounding their type parameters.
+    fn check_bounds(&mut self, fn_call: &FnCall, type_params: &[String]) {
+        let type_arg_ids = fn_call.type_arg_ids(type_params.len());
+        for (param, type_arg_id) in type_params.iter().zip(type_arg_ids) {
+            let type_arg = match self.results.get(&type_arg_id) {
+                Some(type_arg) => type_arg.clone(),
+                None => continue
+            };
+            for trait_name in self.builder.fn_bounds(&fn_call.id(), param) {
+                // Type parameters are bound inside of the generic function.
+                let implemented = match type_arg {
+                    ConcreteType::Parameter(ref outer) =>
+                        self.builder.fn_bounds(&self.current_fn, outer.name())
+                            .contains(trait_name),
+                    ref other => self.builder.implements(other, trait_name)
+                };
+                if !implemented {
+                    debug!("Emitting error: {} does not implement {}",
+                        type_arg, trait_name);
+                    self.errors.add_error(CheckerError::new(
+                        vec![fn_call.span()],
+                        format!("Type {} does not implement trait {} required by {}",
+                            type_arg, trait_name, fn_call.text())
+                    ));
+                }
+            }
+        }
+    }
+
     fn _infer_type(&mut self, _id: &ScopedId) -> bool {
         unimplemented!("Type expression and function types are known during
             identification and need no further resolution");
@@ -98,6 +188,7 @@ impl<'err, 'builder, 'graph> ItemVisitor
 
     fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
         trace!("Visiting declaration of fn {}", block_fn.name());
+        self.current_fn = block_fn.id().clone();
         self.infer_var(&block_fn.id(), block_fn.span(),
             format!("fn {}", block_fn.name()));
 
@@ -114,11 +205,69 @@ impl<'err, 'builder, 'graph> ItemVisitor
         self.visit_block(block_fn.block());
     }
 
+    fn visit_extern_fn_decl(&mut self, extern_fn: &ExternFnDeclaration) {
+        trace!("Visiting declaration of extern fn {}", extern_fn.name());
+        // Extern fn signatures are known from identification.
+        if let Some(fn_type) = self.builder.get_type(&extern_fn.id()) {
+            self.results.insert(extern_fn.id().clone(), fn_type.clone());
+        }
+    }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) {
+        // Trait signatures are known from identification.
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting declaration of const {}", const_decl.name());
+        self.current_fn = const_decl.id().clone();
+        self.visit_expression(const_decl.value());
+        self.infer_var(&const_decl.id(), const_decl.span(),
+            format!("const {}", const_decl.name()));
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting declaration of global {}", global.name());
+        self.current_fn = global.id().clone();
+        self.visit_expression(global.value());
+        self.infer_var(&global.id(), global.span(),
+            format!("global {}", global.name()));
+    }
+
+    fn visit_import(&mut self, import: &Import) {
+        trace!("Visiting import of {}", import.path().name());
+        // Imported items' types are known from their module.
+        for &(ref _item, ref id) in import.imported().iter() {
+            if let Some(item_ty) = self.builder.get_type(id) {
+                self.results.insert(id.clone(), item_ty.clone());
+            }
+        }
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        trace!("Visiting an impl block");
+        visit::walk_impl_block(self, impl_block);
+    }
+
     fn visit_typedef(&mut self, typedef: &Typedef) {
         trace!("Visiting typedef {}", typedef.name());
         self.infer_var(&typedef.id(), typedef.span(),
             format!("typedef {}", typedef.name()));
     }
+
+    fn visit_struct_decl(&mut self, struct_decl: &StructDeclaration) {
+        trace!("Visiting struct {}", struct_decl.name());
+        // Struct types are known from identification.
+        self.known_item_type(&struct_decl.id());
+    }
+
+    fn visit_enum_decl(&mut self, enum_decl: &EnumDeclaration) {
+        trace!("Visiting enum {}", enum_decl.name());
+        // Enum types are known from identification.
+        self.known_item_type(&enum_decl.id());
+        for variant in enum_decl.variants() {
+            self.known_item_type(&variant.id());
+        }
+    }
 }
 
 impl<'err, 'builder, 'graph> BlockVisitor
@@ -162,22 +311,65 @@ impl<'err, 'builder, 'graph> StatementVisitor
         visit::walk_do_block(self, do_block);
     }
 
+    fn visit_while_block(&mut self, while_block: &WhileBlock) {
+        trace!("Visiting while block");
+        visit::walk_while_block(self, while_block);
+    }
+
+    fn visit_for_loop(&mut self, for_loop: &ForLoop) {
+        trace!("Visiting for loop");
+        visit::walk_range(self, for_loop.range());
+        if let Some(step) = for_loop.range().step() {
+            if is_non_positive_literal(step) {
+                debug!("Emitting error: for loop step is not positive");
+                self.errors.add_error(CheckerError::new(
+                    vec![step.span()],
+                    format!("Step of a for loop must be positive")
+                ));
+            }
+        }
+        let ident = for_loop.ident();
+        self.infer_var(&ident.id(), ident.span(),
+                       format!("loop variable {}", ident.name()));
+        self.visit_block(for_loop.block());
+    }
+
+    fn visit_break_stmt(&mut self, break_: &Break) {
+        visit::walk_break(self, break_);
+    }
+
+    fn visit_continue_stmt(&mut self, _continue: &Continue) { }
+
     fn visit_declaration(&mut self, decl: &Declaration) {
         trace!("Visiting declaration of {}", decl.name());
         self.visit_expression(decl.value());
         self.infer_var(&decl.id(), decl.span(),
                        format!("definition of variable {}", decl.name()));
     }
+
+    fn visit_tuple_declaration(&mut self, decl: &TupleDeclaration) {
+        trace!("Visiting tuple declaration");
+        self.visit_expression(decl.value());
+        for binding in decl.bindings().iter().filter_map(|b| b.as_ref()) {
+            self.infer_var(&binding.id(), binding.span(),
+                format!("definition of variable {}", binding.name()));
+        }
+    }
 }
 
 impl<'err, 'builder, 'graph> ExpressionVisitor
     for TypeConcretifier<'err, 'builder, 'graph> {
 
-    fn visit_literal_expr(&mut self, _literal: &Literal) {
-        // Literal types are all known.
+    fn visit_literal_expr(&mut self, literal: &Literal) {
+        // Integer literals are typed by their context.
+        self.infer_literal(literal, false, literal.span());
     }
 
     fn visit_var_ref(&mut self, ident: &Identifier) {
+        // Variants without fields have the type of their enum.
+        if self.known_item_type(&ident.id()) {
+            return
+        }
         self.infer_var(&ident.id(), ident.span(),
             format!("Variable {}", ident.name()));
     }
@@ -186,7 +378,57 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
         visit::walk_if_expr(self, if_expr);
     }
 
+    fn visit_match_expr(&mut self, match_expr: &Match) {
+        trace!("Visiting match");
+        self.visit_expression(match_expr.value());
+        for arm in match_expr.arms() {
+            // Literals in patterns may be negated.
+            match *arm.pattern() {
+                Pattern::Literal(ref literal) =>
+                    self.infer_literal(literal.literal(),
+                        literal.is_negative(), literal.span()),
+                Pattern::Range(ref range) => {
+                    for literal in &[range.start(), range.end()] {
+                        self.infer_literal(literal.literal(),
+                            literal.is_negative(), literal.span());
+                    }
+                },
+                _ => {}
+            }
+            if let Some(guard) = arm.guard() {
+                self.visit_expression(guard);
+            }
+            self.visit_block(arm.block());
+        }
+        for arm in match_expr.arms() {
+            if let Pattern::Variant(ref variant) = *arm.pattern() {
+                self.known_item_type(&variant.ident().id());
+                for binding in variant.fields().iter()
+                        .filter_map(|field| field.binding()) {
+                    self.infer_var(&binding.id(), binding.span(),
+                        format!("pattern variable {}", binding.name()));
+                }
+            }
+        }
+        if match_expr.has_source() {
+            self.infer_var(&match_expr.id(), match_expr.span(),
+                format!("match"));
+        }
+    }
+
+    fn visit_loop_expr(&mut self, loop_expr: &Loop) {
+        visit::walk_loop_expr(self, loop_expr);
+        self.infer_var(&loop_expr.id(), loop_expr.span(),
+            format!("loop"));
+    }
+
     fn visit_unary_op(&mut self, unary_op: &UnaryOperation) {
+        // Negated literals are checked with their sign.
+        if let (UnaryOperator::Negation, &Expression::Literal(ref literal)) =
+                (unary_op.operator(), unary_op.inner()) {
+            self.infer_literal(literal, true, unary_op.span());
+            return
+        }
         visit::walk_unary_op(self, unary_op);
     }
 
@@ -194,9 +436,92 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
         visit::walk_bin_op(self, binary_op);
     }
 
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression) {
+        visit::walk_tuple_expr(self, tuple);
+    }
+
+    fn visit_array_expr(&mut self, array: &ArrayExpression) {
+        visit::walk_array_expr(self, array);
+        self.infer_var(&array.id(), array.span(), format!("array"));
+    }
+
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat) {
+        self.visit_expression(repeat.value());
+        self.infer_var(&repeat.id(), repeat.span(), format!("array"));
+    }
+
+    fn visit_index_expr(&mut self, index: &IndexExpression) {
+        visit::walk_index_expr(self, index);
+    }
+
+    fn visit_slice_expr(&mut self, slice: &SliceExpression) {
+        visit::walk_slice_expr(self, slice);
+        self.infer_var(&slice.id(), slice.span(), format!("slice"));
+    }
+
+    fn visit_method_call(&mut self, method_call: &MethodCall) {
+        visit::walk_method_call(self, method_call);
+    }
+
+    fn visit_lambda(&mut self, lambda: &Lambda) {
+        for &(ref param, ref _param_ty) in lambda.params() {
+            if !param.id().is_default() {
+                self.infer_var(&param.id(), param.span(),
+                    format!("param {}", param.name()));
+            }
+        }
+        visit::walk_lambda(self, lambda);
+        self.infer_var(&lambda.id(), lambda.span(), format!("lambda"));
+        // The closure may be called after the array a slice views is gone.
+        for capture in lambda.captures().iter() {
+            if let Some(&ConcreteType::Slice(_)) = self.results.get(&capture.id()) {
+                debug!("Emitting error: lambda captures slice {}",
+                    capture.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![capture.span()],
+                    format!("Lambdas cannot capture the slice {}, which could \
+                             outlive the array it views", capture.name())
+                ));
+            }
+        }
+    }
+
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment) {
+        visit::walk_index_assignment(self, assign);
+    }
+
+    fn visit_field_access(&mut self, field_access: &FieldAccess) {
+        visit::walk_field_access(self, field_access);
+    }
+
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate) {
+        visit::walk_struct_update(self, struct_update);
+    }
+
     fn visit_fn_call(&mut self, fn_call: &FnCall) {
+        // Struct and variant constructors have the type they construct
+        // rather than a fn type.
+        if self.known_item_type(&fn_call.id()) {
+            for arg in fn_call.args() {
+                self.visit_expression(arg.expression());
+            }
+            return
+        }
         self.infer_var(&fn_call.id(), fn_call.span(),
             format!("Call to {}", fn_call.text()));
+        // Calls to generic functions infer their type arguments.
+        if let Some(type_params) = self.builder.fn_type_params(&fn_call.id()) {
+            let type_arg_ids = fn_call.type_arg_ids(type_params.len());
+            for (param, type_arg_id) in type_params.iter().zip(type_arg_ids) {
+                if self.graph.variable(&type_arg_id).is_none() {
+                    continue
+                }
+                self.infer_var(&type_arg_id, fn_call.span(),
+                    format!("type parameter {} of {}", param, fn_call.text()));
+            }
+            let type_params = type_params.to_vec();
+            self.check_bounds(fn_call, &type_params);
+        }
         for arg in fn_call.args() {
             self.visit_expression(arg.expression());
         }
@@ -211,3 +536,24 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
                     assign.lvalue().name()));
     }
 }
+
+/// Whether an expression is a literal number which is zero or negative.
+fn is_non_positive_literal(expr: &Expression) -> bool {
+    match *expr {
+        Expression::Literal(ref literal) => match *literal.value() {
+            LiteralValue::Int(value) => value == 0,
+            LiteralValue::Float(value) => value <= 0.0,
+            _ => false
+        },
+        Expression::UnaryOp(ref unary_op) => {
+            if let (UnaryOperator::Negation, &Expression::Literal(_)) =
+                    (unary_op.operator(), unary_op.inner()) {
+                true
+            }
+            else {
+                false
+            }
+        },
+        _ => false
+    }
+}
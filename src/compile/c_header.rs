@@ -0,0 +1,75 @@
+//! Generating C headers for the exported functions of a unit.
+
+use ast::{Unit, Item};
+use identify::ConcreteType;
+use check::TypeMapping;
+
+/// Generate a C header declaring the `export fn`s of a unit, for the shared
+/// library compiled from it.
+///
+/// # Examples
+/// ```txt
+/// export fn area(w: float, h: float) -> float
+/// ```
+/// is declared as
+/// ```txt
+/// double area(double w, double h);
+/// ```
+pub fn generate_c_header(unit: &Unit, types: &TypeMapping, name: &str)
+                         -> String {
+    let guard = format!("{}_H", name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() }
+                 else { '_' })
+        .collect::<String>());
+    let mut header = format!("#ifndef {}\n#define {}\n\n", guard, guard);
+    header.push_str("#include <stdbool.h>\n#include <stdint.h>\n\n");
+    header.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+
+    for item in unit.items() {
+        let block_fn = match *item {
+            Item::BlockFnDeclaration(ref block_fn)
+                if block_fn.is_exported() => block_fn,
+            _ => continue
+        };
+        trace!("Declaring exported fn {} in header", block_fn.name());
+        let fn_type = match types.get(&block_fn.id()) {
+            Some(&ConcreteType::Function(ref fn_type)) => fn_type,
+            other => panic!("Exported fn had non-fn type {:?}", other)
+        };
+        let params = fn_type.params().iter()
+            .map(|&(ref param_name, ref param_ty)|
+                format!("{} {}", c_type_name(param_ty), param_name))
+            .collect::<Vec<_>>();
+        header.push_str(&format!("{} {}({});\n",
+            c_type_name(fn_type.return_ty()),
+            block_fn.name(),
+            if params.is_empty() { "void".to_string() }
+            else { params.join(", ") }));
+    }
+
+    header.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n");
+    header.push_str(&format!("#endif // {}\n", guard));
+    header
+}
+
+/// The C equivalent of a type which can be exported.
+fn c_type_name(ty: &ConcreteType) -> &'static str {
+    let name = match *ty {
+        ConcreteType::Named(ref named) => named.name(),
+        ref other => panic!("Unexpected exported type {}", other)
+    };
+    match name {
+        "()" => "void",
+        "bool" => "bool",
+        "float" => "double",
+        "i8" => "int8_t",
+        "i16" => "int16_t",
+        "i32" => "int32_t",
+        "i64" => "int64_t",
+        "u8" => "uint8_t",
+        "u16" => "uint16_t",
+        "u32" => "uint32_t",
+        "u64" => "uint64_t",
+        other => panic!("Unexpected exported type {}", other)
+    }
+}
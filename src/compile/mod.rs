@@ -1,5 +1,9 @@
 mod module_compiler;
 mod module_provider;
+mod output;
+mod c_header;
 
 pub use self::module_provider::{ModuleProvider, SimpleModuleProvider};
 pub use self::module_compiler::ModuleCompiler;
+pub use self::output::{OutputMode, write_module};
+pub use self::c_header::generate_c_header;
@@ -1,14 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use ast::{*, visit::*};
-use identify::ConcreteType;
-use check::TypeMapping;
+use identify::{ConcreteType, EnumType, FnType, GrowableType, NamedType,
+               RecordType, TupleType, module_symbol};
+use check::{ConstValue, ConstValues, TypeMapping};
 use compile::ModuleProvider;
 
-use llvm_sys::{LLVMIntPredicate, LLVMRealPredicate, LLVMTypeKind};
+use llvm_sys::{LLVMIntPredicate, LLVMRealPredicate, LLVMTypeKind, LLVMLinkage,
+               LLVMVisibility};
 use llvm_sys::analysis::LLVMVerifierFailureAction;
 
-use llvm::{Module, Value, Type, Builder, Context};
+use llvm::{Module, Value, Type, Builder, Context, BasicBlock, TargetData};
 
 //#[derive(Debug)]
 // https://github.com/immington-industries/protosnirk/issues/52
@@ -22,6 +24,66 @@ pub struct ModuleCompiler<'ctx, 'b, M: ModuleProvider<'ctx>> where 'ctx: 'b {
     current_type: Type<'ctx>,
     types: TypeMapping,
     scope_manager: &'b mut HashMap<ScopedId, Value<'ctx>>,
+    /// Declared functions, which are in the scope manager as themselves
+    /// rather than as allocas.
+    functions: HashSet<ScopedId>,
+    /// Jump targets of the loops being compiled, innermost last.
+    loop_blocks: Vec<LoopBlocks<'ctx>>,
+    /// Type parameters of generic functions, which are compiled once for
+    /// each set of type arguments they are called with.
+    generic_fns: HashMap<ScopedId, Vec<String>>,
+    /// Instances of generic functions which have been declared but not
+    /// compiled yet.
+    pending_instances: Vec<FnInstance>,
+    /// Type arguments of the generic function instance being compiled.
+    substitution: HashMap<String, ConcreteType>,
+    /// LLVM names and types of the methods implemented for each type.
+    methods: HashMap<(ConcreteType, String), (String, FnType)>,
+    /// Functions defined outside of protosnirk, which are called with the
+    /// C calling convention.
+    foreign_fns: HashSet<ScopedId>,
+    /// Names of the functions which are visible outside of the module.
+    exported_fns: HashSet<String>,
+    /// Names of the `pub` functions, which other units can link to.
+    public_fns: HashSet<String>,
+    /// Names of the `pub` structs and enums, whose methods other units can
+    /// link to.
+    public_types: HashSet<String>,
+    /// Name of the module being compiled, if it is imported by other units.
+    module_name: Option<String>,
+    /// Values of the unit's consts, which are used in place of computing
+    /// them.
+    consts: ConstValues,
+    /// Initial values of the globals which could be computed.
+    global_values: ConstValues,
+    /// Globals which are initialized when they're first used.
+    lazy_globals: HashMap<ScopedId, LazyGlobal<'ctx>>,
+}
+
+/// An instance of a generic function with concrete type arguments.
+struct FnInstance {
+    fn_id: ScopedId,
+    name: String,
+    type_args: HashMap<String, ConcreteType>,
+}
+
+/// The function which initializes a global, and the flag it sets once the
+/// global has been initialized.
+#[derive(Clone)]
+struct LazyGlobal<'ctx> {
+    init_fn: Value<'ctx>,
+    initialized: Value<'ctx>,
+}
+
+/// The blocks which `break` and `continue` jump to in a loop.
+struct LoopBlocks<'ctx> {
+    label: Option<String>,
+    continue_block: BasicBlock<'ctx>,
+    break_block: BasicBlock<'ctx>,
+    /// Values given to a `loop` by its `break`s.
+    break_values: Vec<Value<'ctx>>,
+    /// Blocks which each `break` value came from.
+    break_value_blocks: Vec<BasicBlock<'ctx>>,
 }
 
 impl<'ctx, 'b, M: ModuleProvider<'ctx>> ModuleCompiler<'ctx, 'b, M> {
@@ -41,92 +103,1621 @@ impl<'ctx, 'b, M: ModuleProvider<'ctx>> ModuleCompiler<'ctx, 'b, M> {
             scope_manager,
             optimizations,
             current_type: Type::void(&context),
+            functions: HashSet::new(),
+            loop_blocks: Vec::new(),
+            generic_fns: HashMap::new(),
+            pending_instances: Vec::new(),
+            substitution: HashMap::new(),
+            methods: HashMap::new(),
+            foreign_fns: HashSet::new(),
+            exported_fns: HashSet::new(),
+            public_fns: HashSet::new(),
+            public_types: HashSet::new(),
+            module_name: None,
+            consts: HashMap::new(),
+            global_values: HashMap::new(),
+            lazy_globals: HashMap::new(),
         }
     }
+
+    /// Compile the unit as a module imported by other units.
+    ///
+    /// Its functions are named with the module's name so that they can be
+    /// linked with those of the other units.
+    pub fn set_module_name(&mut self, name: &str) {
+        self.module_name = Some(name.to_string());
+    }
+
+    /// Use the values of consts, and the initial values of globals, computed
+    /// by the `ConstEvaluator`.
+    pub fn set_consts(&mut self, consts: ConstValues,
+                      global_values: ConstValues) {
+        self.consts = consts;
+        self.global_values = global_values;
+    }
+
+    /// Declare a function of another module which the unit imports, which
+    /// is linked as `symbol`.
+    pub fn declare_imported_fn(&mut self, id: &ScopedId, symbol: &str) {
+        trace!("Declaring imported fn {}", symbol);
+        let fn_type = match self.type_of(id) {
+            Some(ConcreteType::Function(ref fn_ty)) => self.llvm_fn_type(fn_ty),
+            other => panic!("Imported fn had non-fn type {:?}", other)
+        };
+        let fn_ref = self.current_module().add_function(symbol, &fn_type);
+        self.scope_manager.insert(id.clone(), fn_ref);
+        self.functions.insert(id.clone());
+    }
+
+    /// Declare a method of an imported module's type, which is linked as
+    /// `symbol`.
+    pub fn declare_imported_method(&mut self, self_ty: &ConcreteType,
+                                   name: &str, fn_type: &FnType,
+                                   symbol: &str) {
+        trace!("Declaring imported method {}", symbol);
+        let llvm_fn_type = self.llvm_fn_type(fn_type);
+        self.current_module().add_function(symbol, &llvm_fn_type);
+        self.methods.insert((self_ty.clone(), name.to_string()),
+                            (symbol.to_string(), fn_type.clone()));
+    }
     pub fn decompose(self) -> (M, TypeMapping) {
         (self.module_provider, self.types)
     }
 
+    /// Use the computed value of a const as an LLVM constant.
+    fn build_const_value(&mut self, id: &ScopedId) {
+        let const_type = self.llvm_type_of(id);
+        let const_value = match self.consts.get(id) {
+            Some(value) => llvm_const(&const_type, value),
+            None => panic!("Const {:?} had no value", id)
+        };
+        self.current_type = const_type;
+        self.ir_code.push(const_value);
+    }
+
+    /// Declare a global variable, and the function which initializes it if
+    /// its initial value could not be computed.
+    fn declare_global(&mut self, global: &GlobalDeclaration) {
+        trace!("Declaring global {}", global.name());
+        let global_type = self.llvm_type_of(&global.id());
+        let global_var = self.current_module()
+            .add_global(global.name(), &global_type);
+        global_var.set_linkage(LLVMLinkage::LLVMInternalLinkage);
+        if let Some(initial) = self.global_values.get(&global.id()) {
+            global_var.set_initializer(&llvm_const(&global_type, initial));
+            global_var.set_global_constant(!global.is_mut());
+        }
+        else {
+            global_var.set_initializer(&global_type.const_null());
+            let flag_type = Type::int1(&self.context);
+            let initialized = self.current_module().add_global(
+                &format!("{}.initialized", global.name()), &flag_type);
+            initialized.set_linkage(LLVMLinkage::LLVMInternalLinkage);
+            initialized.set_initializer(&flag_type.const_int(0, false));
+            let init_fn_type = Type::function(&Type::void(&self.context),
+                                              Vec::new(), false);
+            let init_fn = self.current_module().add_function(
+                &format!("{}.init", global.name()), &init_fn_type);
+            self.lazy_globals.insert(global.id().clone(),
+                                     LazyGlobal { init_fn, initialized });
+        }
+        self.scope_manager.insert(global.id().clone(), global_var);
+    }
+
+    /// Compile the function which initializes a global the first time it is
+    /// called.
+    fn compile_global_init(&mut self, global: &GlobalDeclaration) {
+        let lazy = match self.lazy_globals.get(&global.id()) {
+            Some(lazy) => lazy.clone(),
+            None => return
+        };
+        trace!("Compiling initializer of global {}", global.name());
+        let entry_block = self.context.append_basic_block(&lazy.init_fn,
+                                                          "entry");
+        let init_block = self.context.append_basic_block(&lazy.init_fn,
+                                                         "init");
+        let done_block = self.context.append_basic_block(&lazy.init_fn,
+                                                         "done");
+        self.builder.position_at_end(&entry_block);
+        let initialized = self.builder.build_load(&lazy.initialized,
+                                                  "initialized");
+        self.builder.build_cond_br(&initialized, &done_block, &init_block);
+        self.builder.position_at_end(&done_block);
+        self.builder.build_ret_void();
+
+        // The flag is set first, so a global is only initialized once.
+        self.builder.position_at_end(&init_block);
+        self.builder.build_store(&Type::int1(&self.context).const_int(1, false),
+                                 &lazy.initialized);
+        self.visit_expression(global.value());
+        let initial = self.ir_code.pop()
+            .expect("Did not have initial value of global");
+        let global_var = self.scope_manager[&global.id()].clone();
+        self.builder.build_store(&initial, &global_var);
+        self.builder.build_ret_void();
+
+        let name = format!("{}.init", global.name());
+        if !lazy.init_fn.verify(LLVMVerifierFailureAction::LLVMPrintMessageAction) {
+            error!("Failed to verify {}", name);
+            error!("Current module IR:\n{}", self.current_module().print_to_string());
+            panic!("Validation error for {}", name);
+        }
+        if self.optimizations {
+            trace!("Running optimizations on fn {}", name);
+            self.module_provider.pass_manager().run(&lazy.init_fn);
+        }
+    }
+
+    /// Get the storage of a variable, initializing it first if it's a global
+    /// which is initialized lazily.
+    fn var_ptr(&mut self, id: &ScopedId) -> Option<Value<'ctx>> {
+        if let Some(lazy) = self.lazy_globals.get(id) {
+            self.builder.build_call(&lazy.init_fn, Vec::new(), "");
+        }
+        self.scope_manager.get(id).cloned()
+    }
+
     fn current_module(&self) -> &Module<'ctx> {
         self.module_provider.module()
     }
 
+    /// Create an alloca in the entry block of the current function.
+    ///
+    /// This keeps allocas inside of loops from growing the stack on each
+    /// iteration, and allows LLVM to promote them to registers.
+    fn build_entry_alloca(&self, ty: &Type<'ctx>, name: &str) -> Value<'ctx> {
+        let current_block = self.builder.insert_block();
+        let entry_block = current_block.get_parent()
+            .expect("Builder was not in a function")
+            .get_entry_basic_block();
+        match entry_block.get_terminator() {
+            Some(terminator) => self.builder.position_before(&terminator),
+            None => self.builder.position_at_end(&entry_block)
+        }
+        let alloca = self.builder.build_alloca(ty, name);
+        self.builder.position_at_end(&current_block);
+        alloca
+    }
+
+    /// Move the builder to a new block after a jump such as `break`.
+    ///
+    /// The new block is unreachable, but code after the jump can still be
+    /// emitted into it.
+    fn start_unreachable_block(&self, name: &str) {
+        let function = self.builder.insert_block().get_parent()
+            .expect("Builder was not in a function");
+        let block = self.context.append_basic_block(&function, name);
+        self.builder.position_at_end(&block);
+    }
+
+    /// Enter a loop with the given jump targets.
+    fn begin_loop(&mut self, label: Option<&Identifier>,
+                  continue_block: &BasicBlock<'ctx>,
+                  break_block: &BasicBlock<'ctx>) {
+        self.loop_blocks.push(LoopBlocks {
+            label: label.map(|label| label.name().to_string()),
+            continue_block: continue_block.clone(),
+            break_block: break_block.clone(),
+            break_values: Vec::new(),
+            break_value_blocks: Vec::new()
+        });
+    }
+
+    /// Find the loop with the given label, or the innermost loop.
+    fn target_loop(&mut self, label: Option<&Identifier>)
+                   -> &mut LoopBlocks<'ctx> {
+        let ix = match label {
+            Some(label) => self.loop_blocks.iter().rposition(|blocks|
+                blocks.label.as_ref().map(|name| &**name) == Some(label.name()))
+                .expect("Could not find labelled loop"),
+            None => self.loop_blocks.len().checked_sub(1)
+                .expect("Jump statement was not in a loop")
+        };
+        &mut self.loop_blocks[ix]
+    }
+
+    /// Get the concrete type of an id, with the type arguments of the
+    /// generic function being compiled substituted in.
+    fn type_of(&self, id: &ScopedId) -> Option<ConcreteType> {
+        self.types.get(id).map(|ty| ty.substitute(&self.substitution))
+    }
+
+    /// Get the type arguments of a call to a generic function.
+    fn call_type_args(&self, fn_call: &FnCall)
+                      -> Option<HashMap<String, ConcreteType>> {
+        let type_params = match self.generic_fns.get(&fn_call.id()) {
+            Some(type_params) => type_params,
+            None => return None
+        };
+        let type_arg_ids = fn_call.type_arg_ids(type_params.len());
+        Some(type_params.iter().zip(type_arg_ids)
+            .map(|(param, type_arg_id)| {
+                let type_arg = self.type_of(&type_arg_id)
+                    .expect("Type argument of call was not inferred");
+                (param.clone(), type_arg)
+            })
+            .collect())
+    }
+
+    /// Get the type of the function called by a call, which is an instance
+    /// of a generic function with the call's type arguments.
+    fn instance_type(&self, fn_call: &FnCall, fn_ty: &FnType) -> FnType {
+        match self.call_type_args(fn_call) {
+            Some(type_args) =>
+                match ConcreteType::Function(fn_ty.clone())
+                        .substitute(&type_args) {
+                    ConcreteType::Function(instance_ty) => instance_ty,
+                    _ => unreachable!("Substituted fn type was not a fn")
+                },
+            None => fn_ty.clone()
+        }
+    }
+
+    /// Get the instance of a generic function for a call, declaring it to be
+    /// compiled later if it has not been used yet.
+    fn instance_fn_ref(&mut self, fn_call: &FnCall, fn_ty: &FnType,
+                       type_args: HashMap<String, ConcreteType>)
+                       -> Value<'ctx> {
+        let type_params = &self.generic_fns[&fn_call.id()];
+        let type_arg_names = type_params.iter()
+            .map(|param| type_args[param].to_string())
+            .collect::<Vec<_>>();
+        let name = format!("{}<{}>", fn_call.text(), type_arg_names.join(", "));
+        if let Some(existing) = self.current_module().get_function(&name) {
+            return existing
+        }
+        trace!("Declaring instance {}", name);
+        let fn_ref = self.current_module().add_function(
+            &name, &self.llvm_fn_type(fn_ty));
+        self.pending_instances.push(FnInstance {
+            fn_id: fn_call.id().clone(),
+            name,
+            type_args
+        });
+        fn_ref
+    }
+
     fn llvm_type_of(&self, id: &ScopedId) -> Type<'ctx> {
         trace!("Finding type of ID {:?}", id);
-        let concrete = self.types.get(id)
+        let concrete = self.type_of(id)
             .expect("Attempted to find unknown type");
-        self.llvm_type_of_concrete(concrete)
+        self.llvm_type_of_concrete(&concrete)
     }
 
     fn llvm_type_of_concrete(&self, concrete: &ConcreteType) -> Type<'ctx> {
         match concrete {
+            &ConcreteType::Parameter(ref param) => {
+                let type_arg = self.substitution.get(param.name())
+                    .expect("Type parameter was used outside a generic fn");
+                self.llvm_type_of_concrete(type_arg)
+            },
             &ConcreteType::Named(ref name) => {
                 match name.name() {
                     "()" => Type::void(&self.context),
                     "bool" => Type::int1(&self.context),
                     "float" => Type::double(&self.context),
+                    "i8" | "u8" => Type::int8(&self.context),
+                    "i16" | "u16" => Type::int16(&self.context),
+                    "i32" | "u32" => Type::int32(&self.context),
+                    "i64" | "u64" => Type::int64(&self.context),
+                    "str" => self.str_type(),
                     other => panic!("Unexpected concrete type {}", other)
                 }
             },
+            &ConcreteType::Record(ref record) => {
+                if let Some(existing) =
+                        self.current_module().get_type_by_name(record.name()) {
+                    return existing
+                }
+                let fields = record.fields().iter()
+                    .map(|&(ref _name, ref field_ty)|
+                        self.llvm_type_of_concrete(field_ty))
+                    .collect::<Vec<_>>();
+                let struct_type = Type::named_structure(&self.context,
+                                                        record.name());
+                struct_type.set_struct_body(fields, false);
+                struct_type
+            },
+            // Enums are a tag followed by enough words to hold the fields of
+            // any variant.
+            &ConcreteType::Enum(ref enum_ty) => {
+                if let Some(existing) =
+                        self.current_module().get_type_by_name(enum_ty.name()) {
+                    return existing
+                }
+                let layout = TargetData::from_target_layout(
+                    &self.current_module().get_data_layout_str());
+                let payload_size = enum_ty.variants().iter()
+                    .map(|variant| layout.abi_size_of(
+                        &self.llvm_payload_type(variant)))
+                    .max()
+                    .unwrap_or(0);
+                let payload_words = (payload_size + 7) / 8;
+                let enum_type = Type::named_structure(&self.context,
+                                                      enum_ty.name());
+                enum_type.set_struct_body(vec![
+                    self.tag_type(),
+                    Type::array(&Type::int64(&self.context),
+                                payload_words as u32)
+                ], false);
+                enum_type
+            },
+            // Tuples are anonymous structs of their elements.
+            &ConcreteType::Tuple(ref tuple) => {
+                let elements = tuple.elements().iter()
+                    .map(|element_ty| self.llvm_type_of_concrete(element_ty))
+                    .collect::<Vec<_>>();
+                Type::structure(&self.context, elements, false)
+            },
+            &ConcreteType::Array(ref array) => {
+                Type::array(&self.llvm_type_of_concrete(array.element()),
+                            array.len() as u32)
+            },
+            // Growable arrays are pointers to their elements, length,
+            // capacity, and whether they've been sliced, which are stored on
+            // the heap.
+            &ConcreteType::Growable(ref growable) => {
+                Type::pointer(&self.growable_header_type(growable.element()),
+                              0)
+            },
+            // Slices are a pointer to their first element and their length.
+            &ConcreteType::Slice(ref slice) => {
+                let element_type = self.llvm_type_of_concrete(slice.element());
+                Type::structure(&self.context, vec![
+                    Type::pointer(&element_type, 0),
+                    Type::int64(&self.context)
+                ], false)
+            },
+            // Function values are a pointer to their code and a pointer to
+            // the environment their code is given.
             &ConcreteType::Function(ref fn_ty) => {
-                let mut params = Vec::new();
-                for &(ref _name, ref param_ty) in fn_ty.params() {
-                    params.push(self.llvm_type_of_concrete(param_ty));
+                Type::structure(&self.context, vec![
+                    Type::pointer(&self.llvm_code_type(fn_ty), 0),
+                    self.env_type()
+                ], false)
+            }
+        }
+    }
+
+    /// The type of a function with the given signature.
+    fn llvm_fn_type(&self, fn_ty: &FnType) -> Type<'ctx> {
+        let mut params = Vec::new();
+        for &(ref _name, ref param_ty) in fn_ty.params() {
+            params.push(self.llvm_type_of_concrete(param_ty));
+        }
+        Type::function(
+            &self.llvm_type_of_concrete(fn_ty.return_ty()),
+            params, false)
+    }
+
+    /// The type of a function defined in C, which is given the chars of
+    /// strings rather than the strings themselves.
+    fn llvm_extern_fn_type(&self, fn_ty: &FnType) -> Type<'ctx> {
+        let chars_type = Type::pointer(&Type::int8(&self.context), 0);
+        let mut params = Vec::new();
+        for &(ref _name, ref param_ty) in fn_ty.params() {
+            params.push(match *param_ty {
+                ConcreteType::Named(ref named) if named.name() == "str" =>
+                    chars_type.clone(),
+                _ => self.llvm_type_of_concrete(param_ty)
+            });
+        }
+        Type::function(
+            &self.llvm_type_of_concrete(fn_ty.return_ty()),
+            params, false)
+    }
+
+    /// The type of the code of a function value, which is given the
+    /// environment of the value before the parameters.
+    fn llvm_code_type(&self, fn_ty: &FnType) -> Type<'ctx> {
+        let mut params = vec![self.env_type()];
+        for &(ref _name, ref param_ty) in fn_ty.params() {
+            params.push(self.llvm_type_of_concrete(param_ty));
+        }
+        Type::function(
+            &self.llvm_type_of_concrete(fn_ty.return_ty()),
+            params, false)
+    }
+
+    /// The type of the environment of a function value, which is a pointer
+    /// to the values captured by a closure.
+    fn env_type(&self) -> Type<'ctx> {
+        Type::pointer(&Type::int8(&self.context), 0)
+    }
+
+    /// Build a function value from its code and environment.
+    fn build_fn_value(&self, fn_ty: &FnType, code: &Value<'ctx>,
+                      env: &Value<'ctx>) -> Value<'ctx> {
+        let value_type = self.llvm_type_of_concrete(
+            &ConcreteType::Function(fn_ty.clone()));
+        let with_code = self.builder.build_insert_value(
+            &value_type.get_undef(), code, 0, "fn_code");
+        self.builder.build_insert_value(&with_code, env, 1, "fn_value")
+    }
+
+    /// Build the value of a declared function.
+    ///
+    /// Its code is a function which ignores the environment and calls the
+    /// declared function. It is created the first time the function is used
+    /// as a value.
+    fn build_declared_fn_value(&mut self, name: &str, fn_ref: &Value<'ctx>,
+                               fn_ty: &FnType) -> Value<'ctx> {
+        let code_name = format!("{}.value", name);
+        let code = match self.current_module().get_function(&code_name) {
+            Some(code) => code,
+            None => {
+                trace!("Creating the value of fn {}", name);
+                let code = self.current_module().add_function(
+                    &code_name, &self.llvm_code_type(fn_ty));
+                let current_block = self.builder.insert_block();
+                let entry_block = self.context.append_basic_block(&code, "entry");
+                self.builder.position_at_end(&entry_block);
+                let args = code.get_params().into_iter().skip(1)
+                    .collect::<Vec<_>>();
+                let return_type = self.llvm_type_of_concrete(fn_ty.return_ty());
+                if return_type.get_kind() == LLVMTypeKind::LLVMVoidTypeKind {
+                    self.builder.build_call(fn_ref, args, "");
+                    self.builder.build_ret_void();
+                }
+                else {
+                    let call = self.builder.build_call(fn_ref, args,
+                        &format!("call_{}", name));
+                    self.builder.build_ret(&call);
+                }
+                self.builder.position_at_end(&current_block);
+                code
+            }
+        };
+        self.build_fn_value(fn_ty, &code, &self.env_type().const_null())
+    }
+
+    /// The type which a growable array points to, which holds a pointer to
+    /// its elements, its length, and its capacity.
+    fn growable_header_type(&self, element: &ConcreteType) -> Type<'ctx> {
+        let element_type = self.llvm_type_of_concrete(element);
+        Type::structure(&self.context, vec![
+            Type::pointer(&element_type, 0),
+            Type::int64(&self.context),
+            Type::int64(&self.context),
+            Type::int1(&self.context)
+        ], false)
+    }
+
+    /// The type of the tag of an enum, which is the index of its variant.
+    fn tag_type(&self) -> Type<'ctx> {
+        Type::int32(&self.context)
+    }
+
+    /// The type of the fields of an enum variant, which are stored in the
+    /// payload of the enum.
+    fn llvm_payload_type(&self, variant: &RecordType) -> Type<'ctx> {
+        let fields = variant.fields().iter()
+            .map(|&(ref _name, ref field_ty)|
+                self.llvm_type_of_concrete(field_ty))
+            .collect::<Vec<_>>();
+        Type::structure(&self.context, fields, false)
+    }
+
+    /// Find the concrete type of an expression, if it has a value.
+    fn concrete_type_of(&self, expr: &Expression) -> Option<ConcreteType> {
+        match *expr {
+            Expression::Literal(ref literal) =>
+                self.type_of(&literal.id()),
+            Expression::VariableRef(ref ident) =>
+                self.type_of(&ident.id()),
+            Expression::BinaryOp(ref binary_op) =>
+                self.concrete_type_of(binary_op.left()),
+            Expression::UnaryOp(ref unary_op) =>
+                self.concrete_type_of(unary_op.inner()),
+            Expression::IfExpression(ref if_expr) =>
+                self.concrete_type_of(if_expr.true_expr()),
+            Expression::FnCall(ref fn_call) => {
+                match self.type_of(&fn_call.id()) {
+                    Some(ConcreteType::Function(ref fn_ty)) =>
+                        Some(self.instance_type(fn_call, fn_ty)
+                            .return_ty().clone()),
+                    Some(record @ ConcreteType::Record(_)) => Some(record),
+                    Some(enum_ty @ ConcreteType::Enum(_)) => Some(enum_ty),
+                    _ => None
+                }
+            },
+            Expression::FieldAccess(ref field_access) => {
+                match self.concrete_type_of(field_access.value()) {
+                    Some(ConcreteType::Record(ref record)) =>
+                        record.field(field_access.field().name())
+                            .map(|(_ix, field_ty)| field_ty.clone()),
+                    Some(ConcreteType::Tuple(ref tuple)) =>
+                        tuple.element(field_access.field().name())
+                            .map(|(_ix, element_ty)| element_ty.clone()),
+                    Some(ConcreteType::Array(_))
+                    | Some(ConcreteType::Growable(_))
+                    | Some(ConcreteType::Slice(_)) =>
+                        Some(ConcreteType::Named(NamedType::new(
+                            "i64".to_string()))),
+                    _ => None
+                }
+            },
+            Expression::Array(ref array) =>
+                self.type_of(&array.id()),
+            Expression::ArrayRepeat(ref repeat) =>
+                self.type_of(&repeat.id()),
+            Expression::Index(ref index) =>
+                self.concrete_type_of(index.value())
+                    .and_then(|value_ty| element_type_of(&value_ty).cloned()),
+            Expression::Slice(ref slice) =>
+                self.type_of(&slice.id()),
+            Expression::MethodCall(ref method_call) => {
+                let method = method_call.method().name();
+                match self.concrete_type_of(method_call.receiver()) {
+                    Some(ConcreteType::Growable(growable)) => match method {
+                        "pop" => Some(growable.element().clone()),
+                        _ => None
+                    },
+                    Some(receiver_ty) =>
+                        self.methods.get(&(receiver_ty, method.to_string()))
+                            .map(|&(_, ref fn_ty)| fn_ty.return_ty().clone()),
+                    None => None
+                }
+            },
+            Expression::Tuple(ref tuple) => tuple.elements().iter()
+                .map(|element| self.concrete_type_of(element))
+                .collect::<Option<Vec<_>>>()
+                .map(|elements| ConcreteType::Tuple(TupleType::new(elements))),
+            Expression::StructUpdate(ref struct_update) =>
+                self.concrete_type_of(struct_update.value()),
+            Expression::Loop(ref loop_expr) =>
+                self.type_of(&loop_expr.id()),
+            Expression::Match(ref match_expr) =>
+                self.type_of(&match_expr.id()),
+            Expression::Lambda(ref lambda) =>
+                self.type_of(&lambda.id()),
+            Expression::Assignment(_)
+            | Expression::IndexAssignment(_) => None
+        }
+    }
+
+    /// Build the constant value of a literal in a pattern.
+    fn build_pattern_literal(&mut self, pattern: &LiteralPattern)
+                             -> Value<'ctx> {
+        self.visit_literal_expr(pattern.literal());
+        let value = self.ir_code.pop()
+            .expect("Did not get IR value from pattern literal");
+        if !pattern.is_negative() {
+            return value
+        }
+        // Negating a constant gives a constant.
+        if self.current_type.get_kind() == LLVMTypeKind::LLVMDoubleTypeKind {
+            self.builder.build_fneg(&value, "pattern_negate")
+        }
+        else {
+            self.builder.build_neg(&value, "pattern_negate")
+        }
+    }
+
+    /// Build the check of whether a value matches a pattern, or `None` if
+    /// the pattern matches every value.
+    fn build_pattern_check(&mut self, pattern: &Pattern, value: &Value<'ctx>,
+                           is_unsigned: bool) -> Option<Value<'ctx>> {
+        use llvm_sys::LLVMIntPredicate::*;
+        use llvm_sys::LLVMRealPredicate::*;
+        let is_float = value.get_type().get_kind()
+            == LLVMTypeKind::LLVMDoubleTypeKind;
+        match *pattern {
+            Pattern::Wildcard(_) => None,
+            Pattern::Literal(ref literal) => {
+                let literal_value = self.build_pattern_literal(literal);
+                Some(if is_float {
+                    self.builder.build_fcmp(LLVMRealOEQ,
+                        value, &literal_value, "match_eq")
+                }
+                else {
+                    self.builder.build_icmp(LLVMIntEQ,
+                        value, &literal_value, "match_eq")
+                })
+            },
+            Pattern::Variant(ref variant) => {
+                let tag = self.build_variant_tag(variant);
+                Some(self.builder.build_icmp(LLVMIntEQ, value, &tag,
+                                             "match_variant"))
+            },
+            Pattern::Range(ref range) => {
+                let start_value = self.build_pattern_literal(range.start());
+                let end_value = self.build_pattern_literal(range.end());
+                let inclusive = range.is_inclusive();
+                let (above_start, below_end) = if is_float {
+                    let end_pred = if inclusive { LLVMRealOLE }
+                                   else { LLVMRealOLT };
+                    (self.builder.build_fcmp(LLVMRealOGE,
+                        value, &start_value, "match_start"),
+                     self.builder.build_fcmp(end_pred,
+                        value, &end_value, "match_end"))
                 }
-                Type::function(
-                    &self.llvm_type_of_concrete(fn_ty.return_ty()),
-                    params, false)
+                else {
+                    let (start_pred, end_pred) = match (inclusive, is_unsigned) {
+                        (true, true) => (LLVMIntUGE, LLVMIntULE),
+                        (true, false) => (LLVMIntSGE, LLVMIntSLE),
+                        (false, true) => (LLVMIntUGE, LLVMIntULT),
+                        (false, false) => (LLVMIntSGE, LLVMIntSLT)
+                    };
+                    (self.builder.build_icmp(start_pred,
+                        value, &start_value, "match_start"),
+                     self.builder.build_icmp(end_pred,
+                        value, &end_value, "match_end"))
+                };
+                Some(self.builder.build_and(&above_start, &below_end,
+                                            "match_range"))
             }
         }
     }
-}
 
-impl<'ctx, 'b, M> UnitVisitor for ModuleCompiler<'ctx, 'b, M>
-    where M: ModuleProvider<'ctx>, 'ctx: 'b {
+    /// Build the block of a match arm, which jumps to the end of the match.
+    fn build_match_arm(&mut self, arm: &MatchArm, valued_match: bool,
+                       end_block: &BasicBlock<'ctx>,
+                       incoming_values: &mut Vec<Value<'ctx>>,
+                       incoming_blocks: &mut Vec<BasicBlock<'ctx>>) {
+        trace!("Checking match arm");
+        self.visit_block(arm.block());
+        if valued_match {
+            let value = self.ir_code.pop()
+                .expect("Did not get value from valued match arm");
+            incoming_values.push(value);
+            // The block may have created more basic blocks.
+            incoming_blocks.push(self.builder.insert_block());
+        }
+        self.builder.build_br(end_block);
+    }
 
-    fn visit_unit(&mut self, unit: &Unit) {
-        trace!("Checking a unit");
+    /// Build an `and` or `or`, which only evaluates its right side if the
+    /// left side doesn't decide the result.
+    fn build_short_circuit(&mut self, binary_op: &BinaryOperation) {
+        let is_and = binary_op.operator() == BinaryOperator::And;
+        let (right_name, end_name, phi_name) =
+            if is_and { ("and_right", "and_end", "and_phi") }
+            else { ("or_right", "or_end", "or_phi") };
+        self.visit_expression(binary_op.left());
+        let left_value = self.ir_code.pop()
+            .expect("Could not generate lvalue of binary op");
+        let left_block = self.builder.insert_block();
+        let function = left_block.get_parent()
+            .expect("Just inserted a block");
+        let right_block = self.context.append_basic_block(&function, right_name);
+        let end_block = self.context.append_basic_block(&function, end_name);
+        // `false and x` and `true or x` skip `x`.
+        if is_and {
+            self.builder.build_cond_br(&left_value, &right_block, &end_block);
+        }
+        else {
+            self.builder.build_cond_br(&left_value, &end_block, &right_block);
+        }
 
-        visit::walk_unit(self, unit);
+        self.builder.position_at_end(&right_block);
+        self.visit_expression(binary_op.right());
+        let right_value = self.ir_code.pop()
+            .expect("Could not generate rvalue of binary op");
+        // The right side may have created more basic blocks.
+        let right_end_block = self.builder.insert_block();
+        self.builder.build_br(&end_block);
 
-        // The final ir_code value should be a reference to the function
-        match self.current_module()
-                .verify(LLVMVerifierFailureAction::LLVMPrintMessageAction) {
+        self.builder.position_at_end(&end_block);
+        let bool_type = Type::int1(&self.context);
+        let skipped_value = bool_type.const_int(if is_and { 0 } else { 1 }, false);
+        let phi = self.builder.build_phi(&bool_type, phi_name);
+        phi.add_incoming(vec![skipped_value, right_value],
+                         vec![left_block, right_end_block]);
+        self.ir_code.push(phi);
+        self.current_type = bool_type;
+    }
 
-            Ok(_) => (),
-            Err(_) => {
-                info!("Module:");
-                self.current_module().dump();
+    /// The type of strings, which are a pointer to their chars and a length.
+    fn str_type(&self) -> Type<'ctx> {
+        let chars_type = Type::pointer(&Type::int8(&self.context), 0);
+        Type::structure(&self.context,
+                        vec![chars_type, Type::int64(&self.context)],
+                        false)
+    }
+
+    /// Build a string from a pointer to its chars and its length.
+    fn build_str_value(&self, chars: &Value<'ctx>, len: &Value<'ctx>,
+                       name: &str) -> Value<'ctx> {
+        let empty = self.str_type().get_undef();
+        let with_chars = self.builder.build_insert_value(&empty, chars, 0, "");
+        self.builder.build_insert_value(&with_chars, len, 1, name)
+    }
+
+    /// Get a function from the C standard library, declaring it if needed.
+    fn libc_function(&self, name: &str, return_type: Type<'ctx>,
+                     params: Vec<Type<'ctx>>) -> Value<'ctx> {
+        self.current_module().get_function(name).unwrap_or_else(|| {
+            let fn_type = Type::function(&return_type, params, false);
+            self.current_module().add_function(name, &fn_type)
+        })
+    }
+
+    /// Build `==` on strings, which compares their lengths and then chars.
+    fn build_str_equals(&mut self, left: &Value<'ctx>, right: &Value<'ctx>)
+                        -> Value<'ctx> {
+        use llvm_sys::LLVMIntPredicate::*;
+        let left_len = self.builder.build_extract_value(left, 1, "left_len");
+        let right_len = self.builder.build_extract_value(right, 1, "right_len");
+        let same_len = self.builder.build_icmp(LLVMIntEQ,
+            &left_len, &right_len, "same_len");
+        let len_block = self.builder.insert_block();
+        let function = len_block.get_parent()
+            .expect("Just inserted a block");
+        let chars_block = self.context.append_basic_block(&function,
+                                                          "str_eq_chars");
+        let end_block = self.context.append_basic_block(&function,
+                                                        "str_eq_end");
+        self.builder.build_cond_br(&same_len, &chars_block, &end_block);
+
+        // Only compare the chars of strings with the same length.
+        self.builder.position_at_end(&chars_block);
+        let chars_type = Type::pointer(&Type::int8(&self.context), 0);
+        let memcmp = self.libc_function("memcmp", Type::int32(&self.context),
+            vec![chars_type.clone(), chars_type, Type::int64(&self.context)]);
+        let left_chars = self.builder.build_extract_value(left, 0, "left_chars");
+        let right_chars = self.builder.build_extract_value(right, 0, "right_chars");
+        let compared = self.builder.build_call(&memcmp,
+            vec![left_chars, right_chars, left_len], "str_cmp");
+        let same_chars = self.builder.build_icmp(LLVMIntEQ,
+            &compared, &Type::int32(&self.context).const_int(0, false),
+            "same_chars");
+        self.builder.build_br(&end_block);
+
+        self.builder.position_at_end(&end_block);
+        let bool_type = Type::int1(&self.context);
+        let phi = self.builder.build_phi(&bool_type, "str_eq");
+        phi.add_incoming(vec![bool_type.const_int(0, false), same_chars],
+                         vec![len_block, chars_block]);
+        phi
+    }
+
+    /// Build `+` on strings, which copies both into a new string.
+    ///
+    /// The chars are null terminated so that they can be passed to C. They're
+    /// never freed, since strings don't track when they're last used.
+    fn build_str_concat(&self, left: &Value<'ctx>, right: &Value<'ctx>)
+                        -> Value<'ctx> {
+        let left_chars = self.builder.build_extract_value(left, 0, "left_chars");
+        let left_len = self.builder.build_extract_value(left, 1, "left_len");
+        let right_chars = self.builder.build_extract_value(right, 0, "right_chars");
+        let right_len = self.builder.build_extract_value(right, 1, "right_len");
+        let len = self.builder.build_add(&left_len, &right_len, "concat_len");
+        let one = Type::int64(&self.context).const_int(1, false);
+        let alloc_len = self.builder.build_add(&len, &one, "concat_alloc_len");
+        let chars = self.builder.build_array_malloc(
+            &Type::int8(&self.context), &alloc_len, "concat_chars");
+
+        let chars_type = Type::pointer(&Type::int8(&self.context), 0);
+        let memcpy = self.libc_function("memcpy", chars_type.clone(),
+            vec![chars_type.clone(), chars_type, Type::int64(&self.context)]);
+        self.builder.build_call(&memcpy,
+            vec![chars.clone(), left_chars, left_len.clone()], "");
+        let right_start = self.builder.build_gep(&chars, vec![left_len],
+                                                 "concat_right");
+        self.builder.build_call(&memcpy,
+            vec![right_start, right_chars, right_len], "");
+        let end = self.builder.build_gep(&chars, vec![len.clone()],
+                                         "concat_end");
+        self.builder.build_store(&Type::int8(&self.context).const_int(0, false),
+                                 &end);
+        self.build_str_value(&chars, &len, "concat")
+    }
+
+    /// Find the struct type of an expression whose fields are used.
+    fn record_type_of(&self, expr: &Expression) -> RecordType {
+        match self.concrete_type_of(expr) {
+            Some(ConcreteType::Record(record)) => record,
+            other => panic!("Expected a struct type, got {:?}", other)
+        }
+    }
+
+    /// Find the type of the elements of an expression which is indexed.
+    fn element_type_of(&self, expr: &Expression) -> ConcreteType {
+        self.concrete_type_of(expr)
+            .and_then(|value_ty| element_type_of(&value_ty).cloned())
+            .expect("Expected an array, growable array, or slice type")
+    }
+
+    /// Build a pointer to an array value.
+    ///
+    /// Arrays in variables, or elements of them, are used in place. Other
+    /// arrays are stored in a temporary.
+    fn build_array_ptr(&mut self, expr: &Expression) -> Value<'ctx> {
+        match *expr {
+            Expression::VariableRef(ref ident) => {
+                self.var_ptr(&ident.id())
+                    .expect("Attempted to index var without alloca")
+            },
+            Expression::Index(ref index) => self.build_element_ptr(index),
+            ref value => {
+                self.visit_expression(value);
+                let array_value = self.ir_code.pop()
+                    .expect("Could not generate array of index");
+                let array_alloca = self.build_entry_alloca(&self.current_type,
+                                                           "array_tmp");
+                self.builder.build_store(&array_value, &array_alloca);
+                array_alloca
+            }
+        }
+    }
+
+    /// Build a pointer to the first element of an array, growable array, or
+    /// slice, and its length.
+    ///
+    /// The elements of arrays are used in place, like `build_array_ptr`.
+    fn build_elements(&mut self, expr: &Expression)
+                      -> (Value<'ctx>, Value<'ctx>) {
+        let value_ty = self.concrete_type_of(expr)
+            .expect("Could not find type of array");
+        if let ConcreteType::Array(ref array) = value_ty {
+            let array_ptr = self.build_array_ptr(expr);
+            return self.array_elements(&array_ptr, array.len())
+        }
+        self.visit_expression(expr);
+        let value = self.ir_code.pop()
+            .expect("Could not generate array value");
+        self.elements_of_value(&value, &value_ty)
+    }
+
+    /// Get a pointer to the first element of an array and its length.
+    fn array_elements(&self, array_ptr: &Value<'ctx>, len: u64)
+                      -> (Value<'ctx>, Value<'ctx>) {
+        let int_type = Type::int64(&self.context);
+        let zero = int_type.const_int(0, false);
+        let first = self.builder.build_in_bounds_gep(array_ptr,
+            vec![zero.clone(), zero], "first_ptr");
+        (first, int_type.const_int(len, false))
+    }
+
+    /// Get a pointer to the first element of a value of an array, growable
+    /// array, or slice type, and its length.
+    ///
+    /// Array values are stored in a temporary.
+    fn elements_of_value(&mut self, value: &Value<'ctx>,
+                         value_ty: &ConcreteType) -> (Value<'ctx>, Value<'ctx>) {
+        match *value_ty {
+            ConcreteType::Array(ref array) => {
+                let array_alloca = self.build_entry_alloca(&value.get_type(),
+                                                           "array_tmp");
+                self.builder.build_store(value, &array_alloca);
+                self.array_elements(&array_alloca, array.len())
+            },
+            ConcreteType::Growable(_) => {
+                let data_ptr = self.builder.build_struct_gep(value, 0,
+                                                             "data_ptr");
+                let len_ptr = self.builder.build_struct_gep(value, 1,
+                                                            "len_ptr");
+                (self.builder.build_load(&data_ptr, "data"),
+                 self.builder.build_load(&len_ptr, "len"))
+            },
+            ConcreteType::Slice(_) => {
+                (self.builder.build_extract_value(value, 0, "slice_data"),
+                 self.builder.build_extract_value(value, 1, "slice_len"))
+            },
+            ref other => panic!("Expected an array type, got {:?}", other)
+        }
+    }
+
+    /// Build a pointer to an element of an array, growable array, or slice.
+    ///
+    /// The index is checked to be in bounds, unless it's a constant index of
+    /// an array which has already been checked.
+    fn build_element_ptr(&mut self, index: &IndexExpression) -> Value<'ctx> {
+        use llvm_sys::LLVMIntPredicate::*;
+        let value_ty = self.concrete_type_of(index.value())
+            .expect("Could not find type of indexed value");
+        // The index of a growable array is found first, in case it pushes
+        // to the array and moves its elements.
+        let (first, len, index_value) = match value_ty {
+            ConcreteType::Growable(_) => {
+                self.visit_expression(index.index());
+                let index_value = self.ir_code.pop()
+                    .expect("Could not generate index of array");
+                let (first, len) = self.build_elements(index.value());
+                (first, len, index_value)
+            },
+            _ => {
+                let (first, len) = self.build_elements(index.value());
+                self.visit_expression(index.index());
+                let index_value = self.ir_code.pop()
+                    .expect("Could not generate index of array");
+                (first, len, index_value)
+            }
+        };
+        let is_constant = match (&value_ty, index.index()) {
+            (&ConcreteType::Array(ref array), &Expression::Literal(ref literal)) =>
+                match *literal.value() {
+                    LiteralValue::Int(value) => value < array.len(),
+                    _ => false
+                },
+            _ => false
+        };
+        if !is_constant {
+            // Negative indices are large when compared unsigned.
+            let in_bounds = self.builder.build_icmp(LLVMIntULT,
+                &index_value, &len, "in_bounds");
+            self.build_check(&in_bounds, "index");
+        }
+        self.builder.build_in_bounds_gep(&first, vec![index_value],
+                                         "element_ptr")
+    }
+
+    /// Stop the program if a condition is false.
+    fn build_check(&mut self, condition: &Value<'ctx>, name: &str) {
+        let function = self.builder.insert_block().get_parent()
+            .expect("Builder was not in a function");
+        let ok_block = self.context.append_basic_block(&function,
+            &format!("{}_ok", name));
+        let fail_block = self.context.append_basic_block(&function,
+            &format!("{}_fail", name));
+        self.builder.build_cond_br(condition, &ok_block, &fail_block);
+        self.builder.position_at_end(&fail_block);
+        self.build_abort();
+        self.builder.position_at_end(&ok_block);
+    }
+
+    /// Stop the program, such as when an array index is out of bounds.
+    fn build_abort(&self) {
+        let abort = self.libc_function("abort", Type::void(&self.context),
+                                       vec![]);
+        self.builder.build_call(&abort, Vec::new(), "");
+        self.builder.build_unreachable();
+    }
+
+    /// Store a value in each of the first `len` elements after `first`.
+    ///
+    /// This is done in a loop, rather than building a large constant.
+    fn build_fill(&mut self, first: &Value<'ctx>, value: &Value<'ctx>,
+                  len: u64) {
+        use llvm_sys::LLVMIntPredicate::*;
+        if len == 0 {
+            return
+        }
+        let start_block = self.builder.insert_block();
+        let function = start_block.get_parent()
+            .expect("Builder was not in a function");
+        let loop_block = self.context.append_basic_block(&function,
+                                                         "repeat_loop");
+        let end_block = self.context.append_basic_block(&function,
+                                                        "repeat_end");
+        self.builder.build_br(&loop_block);
+
+        self.builder.position_at_end(&loop_block);
+        let int_type = Type::int64(&self.context);
+        let index = self.builder.build_phi(&int_type, "repeat_index");
+        let element_ptr = self.builder.build_in_bounds_gep(first,
+            vec![index.clone()], "repeat_ptr");
+        self.builder.build_store(value, &element_ptr);
+        let next_index = self.builder.build_add(&index,
+            &int_type.const_int(1, false), "repeat_next");
+        let done = self.builder.build_icmp(LLVMIntEQ, &next_index,
+            &int_type.const_int(len, false), "repeat_done");
+        self.builder.build_cond_br(&done, &end_block, &loop_block);
+        index.add_incoming(vec![int_type.const_int(0, false), next_index],
+                           vec![start_block, loop_block.clone()]);
+
+        self.builder.position_at_end(&end_block);
+    }
+
+    /// The size of a type in bytes.
+    fn size_of(&self, ty: &Type<'ctx>) -> Value<'ctx> {
+        let layout = TargetData::from_target_layout(
+            &self.current_module().get_data_layout_str());
+        Type::int64(&self.context).const_int(layout.abi_size_of(ty), false)
+    }
+
+    /// Build a new growable array with room for `len` elements, which holds
+    /// `len` elements.
+    ///
+    /// Returns the growable array and a pointer to its first element.
+    fn build_growable(&mut self, growable: &GrowableType, len: u64)
+                      -> (Value<'ctx>, Value<'ctx>) {
+        let element_type = self.llvm_type_of_concrete(growable.element());
+        let header_type = self.growable_header_type(growable.element());
+        let int_type = Type::int64(&self.context);
+        let header = self.builder.build_malloc(&header_type, "growable");
+        let data = self.builder.build_array_malloc(&element_type,
+            &int_type.const_int(len, false), "growable_data");
+        let data_ptr = self.builder.build_struct_gep(&header, 0, "data_ptr");
+        self.builder.build_store(&data, &data_ptr);
+        let len_ptr = self.builder.build_struct_gep(&header, 1, "len_ptr");
+        self.builder.build_store(&int_type.const_int(len, false), &len_ptr);
+        let cap_ptr = self.builder.build_struct_gep(&header, 2, "cap_ptr");
+        self.builder.build_store(&int_type.const_int(len, false), &cap_ptr);
+        let sliced_ptr = self.builder.build_struct_gep(&header, 3, "sliced_ptr");
+        self.builder.build_store(&Type::int1(&self.context).const_int(0, false),
+                                 &sliced_ptr);
+        (header, data)
+    }
+
+    /// Build `push` on a growable array, which adds an element to its end.
+    ///
+    /// When the array is full, its elements are copied to a new allocation
+    /// with twice the capacity. The old elements are freed, unless the array
+    /// has been sliced, so that slices of them stay valid.
+    fn build_push(&mut self, header: &Value<'ctx>, value: &Value<'ctx>,
+                  growable: &GrowableType) {
+        use llvm_sys::LLVMIntPredicate::*;
+        let int_type = Type::int64(&self.context);
+        let element_type = self.llvm_type_of_concrete(growable.element());
+        let data_ptr = self.builder.build_struct_gep(header, 0, "data_ptr");
+        let len_ptr = self.builder.build_struct_gep(header, 1, "len_ptr");
+        let cap_ptr = self.builder.build_struct_gep(header, 2, "cap_ptr");
+        let len = self.builder.build_load(&len_ptr, "len");
+        let cap = self.builder.build_load(&cap_ptr, "cap");
+        let full = self.builder.build_icmp(LLVMIntEQ, &len, &cap, "full");
+
+        let function = self.builder.insert_block().get_parent()
+            .expect("Builder was not in a function");
+        let grow_block = self.context.append_basic_block(&function, "grow");
+        let push_block = self.context.append_basic_block(&function, "push");
+        self.builder.build_cond_br(&full, &grow_block, &push_block);
+
+        self.builder.position_at_end(&grow_block);
+        let is_empty = self.builder.build_icmp(LLVMIntEQ, &cap,
+            &int_type.const_int(0, false), "no_capacity");
+        let doubled = self.builder.build_mul(&cap,
+            &int_type.const_int(2, false), "doubled_capacity");
+        // An empty array starts with room for four elements.
+        let was_empty = self.builder.build_zext(&is_empty, &int_type,
+                                                "was_empty");
+        let initial = self.builder.build_mul(&was_empty,
+            &int_type.const_int(4, false), "initial_capacity");
+        let new_cap = self.builder.build_add(&doubled, &initial,
+                                             "new_capacity");
+        let new_data = self.builder.build_array_malloc(&element_type,
+            &new_cap, "grown_data");
+        let old_data = self.builder.build_load(&data_ptr, "old_data");
+        let bytes_type = Type::pointer(&Type::int8(&self.context), 0);
+        let memcpy = self.libc_function("memcpy", bytes_type.clone(),
+            vec![bytes_type.clone(), bytes_type.clone(), int_type.clone()]);
+        let new_bytes = self.builder.build_bit_cast(&new_data, &bytes_type,
+                                                    "grown_bytes");
+        let old_bytes = self.builder.build_bit_cast(&old_data, &bytes_type,
+                                                    "old_bytes");
+        let size = self.builder.build_mul(&len, &self.size_of(&element_type),
+                                          "copy_size");
+        self.builder.build_call(&memcpy, vec![new_bytes, old_bytes, size], "");
+        self.builder.build_store(&new_data, &data_ptr);
+        self.builder.build_store(&new_cap, &cap_ptr);
+        let sliced_ptr = self.builder.build_struct_gep(header, 3, "sliced_ptr");
+        let sliced = self.builder.build_load(&sliced_ptr, "sliced");
+        let free_block = self.context.append_basic_block(&function, "free_old");
+        self.builder.build_cond_br(&sliced, &push_block, &free_block);
+
+        self.builder.position_at_end(&free_block);
+        self.builder.build_free(&old_data);
+        self.builder.build_br(&push_block);
+
+        self.builder.position_at_end(&push_block);
+        let data = self.builder.build_load(&data_ptr, "data");
+        let element_ptr = self.builder.build_in_bounds_gep(&data,
+            vec![len.clone()], "pushed_ptr");
+        self.builder.build_store(value, &element_ptr);
+        let new_len = self.builder.build_add(&len,
+            &int_type.const_int(1, false), "pushed_len");
+        self.builder.build_store(&new_len, &len_ptr);
+    }
+
+    /// Build `pop` on a growable array, which removes and returns its last
+    /// element. Popping an empty array stops the program.
+    fn build_pop(&mut self, header: &Value<'ctx>) -> Value<'ctx> {
+        use llvm_sys::LLVMIntPredicate::*;
+        let int_type = Type::int64(&self.context);
+        let len_ptr = self.builder.build_struct_gep(header, 1, "len_ptr");
+        let len = self.builder.build_load(&len_ptr, "len");
+        let has_elements = self.builder.build_icmp(LLVMIntNE, &len,
+            &int_type.const_int(0, false), "has_elements");
+        self.build_check(&has_elements, "pop");
+        let new_len = self.builder.build_sub(&len,
+            &int_type.const_int(1, false), "popped_len");
+        self.builder.build_store(&new_len, &len_ptr);
+        let data_ptr = self.builder.build_struct_gep(header, 0, "data_ptr");
+        let data = self.builder.build_load(&data_ptr, "data");
+        let element_ptr = self.builder.build_in_bounds_gep(&data,
+            vec![new_len], "popped_ptr");
+        self.builder.build_load(&element_ptr, "popped")
+    }
+
+    /// Build a call to the method implemented for the receiver's type, which
+    /// is passed as the first argument.
+    fn build_trait_method_call(&mut self, method_call: &MethodCall,
+                               receiver_ty: ConcreteType) {
+        let method = method_call.method().name();
+        let (name, fn_ty) = self.methods.get(&(receiver_ty, method.to_string()))
+            .expect("Called a method which was not implemented")
+            .clone();
+        self.visit_expression(method_call.receiver());
+        let mut arg_values = vec![self.ir_code.pop()
+            .expect("Could not generate receiver of method call")];
+        for &(ref param, _) in &fn_ty.params()[1..] {
+            let arg = method_call.args().iter()
+                .find(|arg| arg.name().name() == param)
+                .expect("Missing argument of method call");
+            self.visit_expression(arg.expression());
+            arg_values.push(self.ir_code.pop()
+                .expect("Could not generate argument of method call"));
+        }
+        let fn_ref = self.current_module().get_function(&name)
+            .expect("Method was not declared");
+        let fn_return_type = self.llvm_type_of_concrete(fn_ty.return_ty());
+        if fn_return_type.get_kind() == LLVMTypeKind::LLVMVoidTypeKind {
+            trace!("Building call void {}", name);
+            self.builder.build_call(&fn_ref, arg_values, "");
+        }
+        else {
+            trace!("Building call {}", name);
+            let call = self.builder.build_call(&fn_ref, arg_values,
+                &format!("call_{}", method));
+            self.ir_code.push(call);
+        }
+        self.current_type = fn_return_type;
+    }
+
+    /// Build `==` on arrays, growable arrays, or slices, which compares
+    /// their lengths and then each of their elements until one is different.
+    fn build_elements_equals(&mut self, left: &Value<'ctx>,
+                             right: &Value<'ctx>, concrete: &ConcreteType)
+                             -> Value<'ctx> {
+        use llvm_sys::LLVMIntPredicate::*;
+        let element_ty = element_type_of(concrete)
+            .expect("Expected an array type").clone();
+        let (left_first, left_len) = self.elements_of_value(left, concrete);
+        let (right_first, right_len) = self.elements_of_value(right, concrete);
+        let bool_type = Type::int1(&self.context);
+        let int_type = Type::int64(&self.context);
+        let zero = int_type.const_int(0, false);
+
+        let len_block = self.builder.insert_block();
+        let function = len_block.get_parent()
+            .expect("Builder was not in a function");
+        let empty_block = self.context.append_basic_block(&function,
+                                                          "array_eq_empty");
+        let loop_block = self.context.append_basic_block(&function,
+                                                         "array_eq_loop");
+        let end_block = self.context.append_basic_block(&function,
+                                                        "array_eq_end");
+        let same_len = self.builder.build_icmp(LLVMIntEQ, &left_len,
+            &right_len, "same_len");
+        self.builder.build_cond_br(&same_len, &empty_block, &end_block);
+
+        // Arrays with no elements are equal.
+        self.builder.position_at_end(&empty_block);
+        let has_elements = self.builder.build_icmp(LLVMIntNE, &left_len,
+            &zero, "has_elements");
+        self.builder.build_cond_br(&has_elements, &loop_block, &end_block);
+
+        self.builder.position_at_end(&loop_block);
+        let index = self.builder.build_phi(&int_type, "array_eq_index");
+        let left_ptr = self.builder.build_in_bounds_gep(&left_first,
+            vec![index.clone()], "left_element_ptr");
+        let right_ptr = self.builder.build_in_bounds_gep(&right_first,
+            vec![index.clone()], "right_element_ptr");
+        let left_element = self.builder.build_load(&left_ptr, "left_element");
+        let right_element = self.builder.build_load(&right_ptr,
+                                                    "right_element");
+        let equal = self.build_equals(&left_element, &right_element,
+                                      &element_ty);
+        // Comparing the elements may have added blocks.
+        let compared_block = self.builder.insert_block();
+        let next_index = self.builder.build_add(&index,
+            &int_type.const_int(1, false), "array_eq_next");
+        let more = self.builder.build_icmp(LLVMIntULT, &next_index,
+            &left_len, "array_eq_more");
+        let keep_going = self.builder.build_and(&equal, &more,
+                                                "array_eq_continue");
+        self.builder.build_cond_br(&keep_going, &loop_block, &end_block);
+        index.add_incoming(vec![zero, next_index],
+                           vec![empty_block.clone(), compared_block.clone()]);
+
+        // The last comparison is false if any element was different.
+        self.builder.position_at_end(&end_block);
+        let phi = self.builder.build_phi(&bool_type, "array_eq");
+        phi.add_incoming(vec![bool_type.const_int(0, false),
+                              bool_type.const_int(1, false),
+                              equal],
+                         vec![len_block, empty_block, compared_block]);
+        phi
+    }
+
+    /// Find the index and type of a field of a struct or an element of a
+    /// tuple.
+    fn field_of(&self, expr: &Expression, field: &str)
+                -> (usize, ConcreteType) {
+        match self.concrete_type_of(expr) {
+            Some(ConcreteType::Record(record)) => record.field(field)
+                .map(|(ix, field_ty)| (ix, field_ty.clone()))
+                .expect("Accessed unknown field of struct"),
+            Some(ConcreteType::Tuple(tuple)) => tuple.element(field)
+                .map(|(ix, element_ty)| (ix, element_ty.clone()))
+                .expect("Accessed unknown element of tuple"),
+            other => panic!("Expected a struct or tuple type, got {:?}", other)
+        }
+    }
+
+    /// Build a struct from the fields given to its constructor.
+    fn build_struct_construction(&mut self, fn_call: &FnCall,
+                                 record: &RecordType) {
+        trace!("Building construction of struct {}", record.name());
+        let struct_type = self.llvm_type_of_concrete(
+            &ConcreteType::Record(record.clone()));
+        let mut struct_value = struct_type.get_undef();
+        // Fields are given in the order they are declared, like fn params.
+        for (ix, &(ref name, _)) in record.fields().iter().enumerate() {
+            let arg = fn_call.args().iter()
+                .find(|arg| arg.name().name() == name)
+                .expect("Struct construction was missing a field");
+            self.visit_expression(arg.expression());
+            let field_value = self.ir_code.pop()
+                .expect("Could not generate value of struct field");
+            struct_value = self.builder.build_insert_value(&struct_value,
+                &field_value, ix as u32, &format!("init_{}", name));
+        }
+        self.current_type = struct_type;
+        self.ir_code.push(struct_value);
+    }
+
+    /// Build a variant of an enum from the fields given to its constructor.
+    ///
+    /// The enum is built in memory, as its payload is cast to the type of
+    /// the variant's fields.
+    fn build_variant_construction(&mut self, name: &str, enum_ty: &EnumType,
+                                  args: &[CallArgument]) {
+        trace!("Building construction of variant {}", name);
+        let (tag, variant) = enum_ty.variant(name)
+            .expect("Constructed an unknown variant");
+        let enum_type = self.llvm_type_of_concrete(
+            &ConcreteType::Enum(enum_ty.clone()));
+        let enum_alloca = self.build_entry_alloca(&enum_type,
+            &format!("{}_alloca", name));
+        let tag_ptr = self.builder.build_struct_gep(&enum_alloca, 0,
+                                                    "tag_ptr");
+        self.builder.build_store(
+            &self.tag_type().const_int(tag as u64, false), &tag_ptr);
+        if !variant.fields().is_empty() {
+            let variant_ptr = self.build_payload_ptr(&enum_alloca, variant);
+            for (ix, &(ref field_name, _)) in
+                    variant.fields().iter().enumerate() {
+                let arg = args.iter()
+                    .find(|arg| arg.name().name() == field_name)
+                    .expect("Variant construction was missing a field");
+                self.visit_expression(arg.expression());
+                let field_value = self.ir_code.pop()
+                    .expect("Could not generate value of variant field");
+                let field_ptr = self.builder.build_struct_gep(&variant_ptr,
+                    ix as u32, &format!("{}_{}_ptr", name, field_name));
+                self.builder.build_store(&field_value, &field_ptr);
+            }
+        }
+        let enum_value = self.builder.build_load(&enum_alloca, name);
+        self.current_type = enum_type;
+        self.ir_code.push(enum_value);
+    }
+
+    /// Cast a pointer to the payload of an enum to a pointer to the fields
+    /// of one of its variants.
+    fn build_payload_ptr(&mut self, enum_ptr: &Value<'ctx>,
+                         variant: &RecordType) -> Value<'ctx> {
+        let payload_ptr = self.builder.build_struct_gep(enum_ptr, 1,
+                                                        "payload_ptr");
+        let variant_type = Type::pointer(&self.llvm_payload_type(variant), 0);
+        self.builder.build_pointer_cast(&payload_ptr, &variant_type,
+                                        &format!("{}_ptr", variant.name()))
+    }
+
+    /// Find the enum and tag of the variant in a pattern.
+    fn variant_of_pattern(&self, pattern: &VariantPattern)
+                          -> (EnumType, usize) {
+        let enum_ty = match self.type_of(&pattern.ident().id()) {
+            Some(ConcreteType::Enum(enum_ty)) => enum_ty,
+            other => panic!("Expected an enum type for variant {}, got {:?}",
+                            pattern.name(), other)
+        };
+        let (tag, _variant) = enum_ty.variant(pattern.name())
+            .expect("Matched an unknown variant");
+        (enum_ty, tag)
+    }
+
+    /// Build the tag of the variant in a pattern.
+    fn build_variant_tag(&self, pattern: &VariantPattern) -> Value<'ctx> {
+        let (_enum_ty, tag) = self.variant_of_pattern(pattern);
+        self.tag_type().const_int(tag as u64, false)
+    }
+
+    /// Give the variables bound by a variant pattern the fields of the
+    /// matched value.
+    ///
+    /// Bound fields are not copied: their variables point into a copy of the
+    /// matched value.
+    fn build_pattern_bindings(&mut self, pattern: &Pattern,
+                              value: &Value<'ctx>) {
+        let pattern = match *pattern {
+            Pattern::Variant(ref pattern) => pattern,
+            _ => return
+        };
+        if pattern.fields().iter().all(|field| field.binding().is_none()) {
+            return
+        }
+        trace!("Binding fields of variant {}", pattern.name());
+        let (enum_ty, tag) = self.variant_of_pattern(pattern);
+        let variant = &enum_ty.variants()[tag];
+        let enum_alloca = self.build_entry_alloca(&value.get_type(),
+            &format!("{}_value", pattern.name()));
+        self.builder.build_store(value, &enum_alloca);
+        let variant_ptr = self.build_payload_ptr(&enum_alloca, variant);
+        for field in pattern.fields() {
+            let binding = match field.binding() {
+                Some(binding) => binding,
+                None => continue
+            };
+            let (field_ix, _field_ty) = variant.field(field.field().name())
+                .expect("Bound an unknown field of variant");
+            let field_ptr = self.builder.build_struct_gep(&variant_ptr,
+                field_ix as u32, &format!("{}_ptr", binding.name()));
+            self.scope_manager.insert(binding.id().clone(), field_ptr);
+        }
+    }
+
+    /// Build `==` on two enums of the same type.
+    ///
+    /// Enums are equal if they are the same variant and the fields of the
+    /// variant are equal.
+    fn build_enum_equals(&mut self, left: &Value<'ctx>, right: &Value<'ctx>,
+                         enum_ty: &EnumType) -> Value<'ctx> {
+        use llvm_sys::LLVMIntPredicate::*;
+        let left_tag = self.builder.build_extract_value(left, 0, "left_tag");
+        let right_tag = self.builder.build_extract_value(right, 0, "right_tag");
+        let same_tag = self.builder.build_icmp(LLVMIntEQ,
+            &left_tag, &right_tag, "same_tag");
+        let tag_block = self.builder.insert_block();
+        let function = tag_block.get_parent()
+            .expect("Builder was not in a function");
+        let fields_block = self.context.append_basic_block(&function,
+                                                           "enum_eq_fields");
+        let end_block = self.context.append_basic_block(&function,
+                                                        "enum_eq_end");
+        self.builder.build_cond_br(&same_tag, &fields_block, &end_block);
+
+        let bool_type = Type::int1(&self.context);
+        let mut incoming_values = vec![bool_type.const_int(0, false)];
+        let mut incoming_blocks = vec![tag_block];
+
+        self.builder.position_at_end(&fields_block);
+        let left_alloca = self.build_entry_alloca(&left.get_type(), "left_enum");
+        let right_alloca = self.build_entry_alloca(&right.get_type(),
+                                                   "right_enum");
+        self.builder.build_store(left, &left_alloca);
+        self.builder.build_store(right, &right_alloca);
+        let variant_blocks = enum_ty.variants().iter()
+            .map(|_variant| self.context.append_basic_block(&function,
+                                                            "enum_eq_variant"))
+            .collect::<Vec<_>>();
+        let unreachable_block = self.context.append_basic_block(&function,
+            "enum_eq_none");
+        let switch = self.builder.build_switch(&left_tag, &unreachable_block,
+            variant_blocks.len() as u32);
+        for (tag, variant_block) in variant_blocks.iter().enumerate() {
+            switch.add_case(&self.tag_type().const_int(tag as u64, false),
+                            variant_block);
+        }
+        self.builder.position_at_end(&unreachable_block);
+        self.builder.build_unreachable();
+
+        for (variant, variant_block) in
+                enum_ty.variants().iter().zip(&variant_blocks) {
+            self.builder.position_at_end(variant_block);
+            let mut equal = bool_type.const_int(1, false);
+            if !variant.fields().is_empty() {
+                let left_ptr = self.build_payload_ptr(&left_alloca, variant);
+                let right_ptr = self.build_payload_ptr(&right_alloca, variant);
+                let left_fields = self.builder.build_load(&left_ptr,
+                                                          "left_fields");
+                let right_fields = self.builder.build_load(&right_ptr,
+                                                           "right_fields");
+                let record = ConcreteType::Record(variant.clone());
+                equal = self.build_equals(&left_fields, &right_fields,
+                                          &record);
             }
+            incoming_values.push(equal);
+            // Comparing fields may have created more basic blocks.
+            incoming_blocks.push(self.builder.insert_block());
+            self.builder.build_br(&end_block);
+        }
+
+        self.builder.position_at_end(&end_block);
+        let phi = self.builder.build_phi(&bool_type, "enum_eq");
+        phi.add_incoming(incoming_values, incoming_blocks);
+        phi
+    }
+
+    /// Build `==` on two values of the same type.
+    ///
+    /// Structs, tuples, and arrays are equal if all of their fields or
+    /// elements are.
+    fn build_equals(&mut self, left: &Value<'ctx>, right: &Value<'ctx>,
+                    concrete: &ConcreteType) -> Value<'ctx> {
+        use llvm_sys::LLVMIntPredicate::*;
+        use llvm_sys::LLVMRealPredicate::*;
+        match *concrete {
+            ConcreteType::Record(ref record) => {
+                let mut equal = Type::int1(&self.context).const_int(1, false);
+                for (ix, &(ref name, ref field_ty)) in
+                        record.fields().iter().enumerate() {
+                    let left_field = self.builder.build_extract_value(left,
+                        ix as u32, &format!("left_{}", name));
+                    let right_field = self.builder.build_extract_value(right,
+                        ix as u32, &format!("right_{}", name));
+                    let field_equal = self.build_equals(&left_field,
+                                                        &right_field,
+                                                        field_ty);
+                    equal = self.builder.build_and(&equal, &field_equal,
+                                                   "struct_eq");
+                }
+                equal
+            },
+            ConcreteType::Tuple(ref tuple) => {
+                let mut equal = Type::int1(&self.context).const_int(1, false);
+                for (ix, element_ty) in tuple.elements().iter().enumerate() {
+                    let left_element = self.builder.build_extract_value(left,
+                        ix as u32, &format!("left_{}", ix));
+                    let right_element = self.builder.build_extract_value(right,
+                        ix as u32, &format!("right_{}", ix));
+                    let element_equal = self.build_equals(&left_element,
+                                                          &right_element,
+                                                          element_ty);
+                    equal = self.builder.build_and(&equal, &element_equal,
+                                                   "tuple_eq");
+                }
+                equal
+            },
+            ConcreteType::Array(_)
+            | ConcreteType::Growable(_)
+            | ConcreteType::Slice(_) =>
+                self.build_elements_equals(left, right, concrete),
+            ConcreteType::Enum(ref enum_ty) =>
+                self.build_enum_equals(left, right, enum_ty),
+            // Function values are equal if they have the same code and
+            // environment.
+            ConcreteType::Function(_) => {
+                let mut equal = Type::int1(&self.context).const_int(1, false);
+                for ix in 0..2 {
+                    let left_ptr = self.builder.build_extract_value(left,
+                        ix, "left_fn");
+                    let right_ptr = self.builder.build_extract_value(right,
+                        ix, "right_fn");
+                    let ptr_equal = self.builder.build_icmp(LLVMIntEQ,
+                        &left_ptr, &right_ptr, "eqtmp");
+                    equal = self.builder.build_and(&equal, &ptr_equal, "fn_eq");
+                }
+                equal
+            },
+            ConcreteType::Named(ref named) if named.name() == "str" =>
+                self.build_str_equals(left, right),
+            ConcreteType::Named(ref named) if named.name() == "float" =>
+                self.builder.build_fcmp(LLVMRealOEQ, left, right, "eqtmp"),
+            _ => self.builder.build_icmp(LLVMIntEQ, left, right, "eqtmp")
         }
     }
+
+    /// Whether an expression is of an unsigned integer type.
+    fn is_unsigned(&self, expr: &Expression) -> bool {
+        self.concrete_type_of(expr)
+            .map_or(false, |concrete| is_unsigned_type(&concrete))
+    }
 }
 
-impl<'ctx, 'b, M> ItemVisitor for ModuleCompiler<'ctx, 'b, M>
+/// The type of the elements of an array, growable array, or slice type.
+fn element_type_of(concrete: &ConcreteType) -> Option<&ConcreteType> {
+    match *concrete {
+        ConcreteType::Array(ref array) => Some(array.element()),
+        ConcreteType::Growable(ref growable) => Some(growable.element()),
+        ConcreteType::Slice(ref slice) => Some(slice.element()),
+        _ => None
+    }
+}
+
+/// Whether a concrete type is an unsigned integer type.
+fn is_unsigned_type(concrete: &ConcreteType) -> bool {
+    match *concrete {
+        ConcreteType::Named(ref named) => match named.name() {
+            "u8" | "u16" | "u32" | "u64" => true,
+            _ => false
+        },
+        _ => false
+    }
+}
+
+/// An LLVM constant of a value computed at compile time.
+fn llvm_const<'ctx>(ty: &Type<'ctx>, value: &ConstValue) -> Value<'ctx> {
+    match *value {
+        ConstValue::Bool(value) => ty.const_int(value as u64, false),
+        // Negative values are truncated to the width of their type.
+        ConstValue::Int(value, _ty) => ty.const_int(value as u64, true),
+        ConstValue::Float(value) => ty.const_real(value)
+    }
+}
+
+/// The name of an associated function, which is namespaced by its type, i.e.
+/// `Point::origin`.
+fn associated_fn_name(impl_block: &ImplBlock, block_fn: &BlockFnDeclaration)
+                      -> String {
+    match *impl_block.type_expr() {
+        TypeExpression::Named(ref named) =>
+            format!("{}::{}", named.ident().name(), block_fn.name()),
+        ref other => panic!("Associated fn of non-named type {:?}", other)
+    }
+}
+
+impl<'ctx, 'b, M> ModuleCompiler<'ctx, 'b, M>
     where M: ModuleProvider<'ctx>, 'ctx: 'b {
 
-    fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
-        trace!("Checking declaration of {}", block_fn.name());
+    /// Declare the LLVM function of a method, which is named after the type
+    /// it's implemented for and takes the receiver first.
+    fn declare_method(&mut self, method: &BlockFnDeclaration) {
+        let fn_ty = match self.type_of(&method.id()) {
+            Some(ConcreteType::Function(fn_ty)) => fn_ty,
+            other => panic!("Method had non-fn type {:?}", other)
+        };
+        let self_ty = fn_ty.params()[0].1.clone();
+        let name = format!("{}.{}", self_ty, method.name());
+        trace!("Declaring method {}", name);
+        self.current_module().add_function(&name, &self.llvm_fn_type(&fn_ty));
+        self.methods.insert((self_ty, method.name().to_string()),
+                            (name, fn_ty));
+    }
+
+    /// Declare a function defined outside of protosnirk under its own name,
+    /// so that it is resolved by the JIT or linker.
+    fn declare_extern_fn(&mut self, extern_fn: &ExternFnDeclaration) {
+        trace!("Declaring extern fn {}", extern_fn.name());
+        let fn_type = match self.type_of(&extern_fn.id()) {
+            Some(ConcreteType::Function(ref fn_ty)) =>
+                self.llvm_extern_fn_type(fn_ty),
+            other => panic!("Extern fn had non-fn type {:?}", other)
+        };
+        // The C standard library functions used by the compiler may already
+        // be declared.
+        let fn_ref = self.current_module().get_function(extern_fn.name())
+            .unwrap_or_else(|| self.current_module()
+                .add_function(extern_fn.name(), &fn_type));
+        self.scope_manager.insert(extern_fn.id().clone(), fn_ref);
+        self.functions.insert(extern_fn.id().clone());
+        self.foreign_fns.insert(extern_fn.id().clone());
+    }
+
+    /// Declare the LLVM function of an associated function, which is named
+    /// with the type it's namespaced by.
+    fn declare_associated_fn(&mut self, impl_block: &ImplBlock,
+                             block_fn: &BlockFnDeclaration) {
+        let name = associated_fn_name(impl_block, block_fn);
+        trace!("Declaring associated fn {}", name);
+        let fn_type = match self.type_of(&block_fn.id()) {
+            Some(ConcreteType::Function(ref fn_ty)) => self.llvm_fn_type(fn_ty),
+            other => panic!("Block fn had non-fn type {:?}", other)
+        };
+        let fn_ref = self.current_module().add_function(&name, &fn_type);
+        self.scope_manager.insert(block_fn.id().clone(), fn_ref);
+        self.functions.insert(block_fn.id().clone());
+    }
 
-        let fn_type = self.llvm_type_of(&block_fn.id());
-        let fn_ret_type_kind = fn_type.return_type()
-            .expect("Block fn's LLVM type did not have a return type")
-            .get_kind();
+    /// Compile the params and block of a function into its declared LLVM
+    /// function.
+    fn compile_fn_body(&mut self, block_fn: &BlockFnDeclaration,
+                       fn_ref: &Value<'ctx>, name: &str) {
+        let fn_ret_type_kind = match self.type_of(&block_fn.id()) {
+            Some(ConcreteType::Function(ref fn_ty)) =>
+                self.llvm_type_of_concrete(fn_ty.return_ty()).get_kind(),
+            other => panic!("Block fn had non-fn type {:?}", other)
+        };
         let fn_returns_void =
             fn_ret_type_kind == LLVMTypeKind::LLVMVoidTypeKind;
-        let fn_ref = self.current_module().add_function(
-            block_fn.name(), &fn_type);
-
-        // Gotta insert the fn ref first so it can be called recursively
-        self.scope_manager.insert(block_fn.id().clone(), fn_ref.clone());
-        trace!("Inserted {} into the scope manager",
-            block_fn.name());
 
         // Gonna be fancy and have a separate basic block for parameters
         let entry_block = self.context.append_basic_block(&fn_ref, "entry");
         let start_block = self.context.append_basic_block(&fn_ref, "start");
         self.builder.position_at_end(&entry_block);
-        trace!("Ready to build {}", block_fn.name());
+        trace!("Ready to build {}", name);
 
         let fn_params = fn_ref.get_params();
         trace!("fn has {} params", fn_params.len());
@@ -148,42 +1739,240 @@ impl<'ctx, 'b, M> ItemVisitor for ModuleCompiler<'ctx, 'b, M>
         self.builder.build_br(&start_block);
         self.builder.position_at_end(&start_block);
 
-        trace!("Moving to check the block");
+        trace!("Moving to check the block");
+
+        // Compile the function
+        self.visit_block(&block_fn.block());
+
+        if !fn_returns_void {
+            if let Some(remaining_expr) = self.ir_code.pop() {
+                trace!("Found final expression, appending a return");
+                self.builder.build_ret(&remaining_expr);
+            }
+            else {
+                trace!(
+                    "No IR code remaining, assuming the last stmt was return");
+                self.builder.build_unreachable();
+            }
+        }
+        else {
+            trace!("block fn returns void, appending ret void");
+            self.builder.build_ret_void();
+        }
+
+
+        if !fn_ref.verify(LLVMVerifierFailureAction::LLVMPrintMessageAction) {
+            error!("Failed to verify {}", name);
+            error!("Current module IR:\n{}", self.current_module().print_to_string());
+            panic!("Validation error for {}", name);
+        }
+
+        if self.optimizations {
+            trace!("Running optimizations on fn {}", name);
+            self.module_provider.pass_manager().run(&fn_ref);
+        }
+    }
+}
+
+impl<'ctx, 'b, M> UnitVisitor for ModuleCompiler<'ctx, 'b, M>
+    where M: ModuleProvider<'ctx>, 'ctx: 'b {
+
+    fn visit_unit(&mut self, unit: &Unit) {
+        trace!("Checking a unit");
+
+        for item in unit.items() {
+            match *item {
+                Item::BlockFnDeclaration(ref block_fn) => {
+                    if block_fn.is_generic() {
+                        let type_params = block_fn.type_params().iter()
+                            .map(|param| param.name().to_string())
+                            .collect();
+                        self.generic_fns.insert(block_fn.id().clone(),
+                                                type_params);
+                    }
+                },
+                // Methods and associated functions are declared first so
+                // that they can be called before their impl.
+                Item::ExternFnDeclaration(ref extern_fn) => {
+                    self.declare_extern_fn(extern_fn);
+                },
+                Item::ImplBlock(ref impl_block) => {
+                    for method in impl_block.methods() {
+                        if method.is_method() {
+                            self.declare_method(method);
+                        }
+                        else {
+                            self.declare_associated_fn(impl_block, method);
+                        }
+                    }
+                },
+                Item::GlobalDeclaration(ref global) => {
+                    self.declare_global(global);
+                },
+                Item::StructDeclaration(ref struct_decl)
+                        if struct_decl.is_public() => {
+                    self.public_types.insert(struct_decl.name().to_string());
+                },
+                Item::EnumDeclaration(ref enum_decl) if enum_decl.is_public() => {
+                    self.public_types.insert(enum_decl.name().to_string());
+                },
+                _ => {}
+            }
+        }
+
+        visit::walk_unit(self, unit);
+
+        // Initializers can call any of the unit's functions, which have all
+        // been declared now.
+        for item in unit.items() {
+            if let Item::GlobalDeclaration(ref global) = *item {
+                self.compile_global_init(global);
+            }
+        }
+
+        // Instances of generic fns are compiled once they've been called,
+        // and may call other instances.
+        while let Some(instance) = self.pending_instances.pop() {
+            let block_fn = unit.items().iter()
+                .filter_map(|item| match *item {
+                    Item::BlockFnDeclaration(ref block_fn) => Some(block_fn),
+                    _ => None
+                })
+                .find(|block_fn| *block_fn.id() == instance.fn_id)
+                .expect("Could not find declaration of generic fn");
+            trace!("Compiling instance {}", instance.name);
+            let fn_ref = self.current_module().get_function(&instance.name)
+                .expect("Instance of generic fn was not declared");
+            self.substitution = instance.type_args;
+            self.compile_fn_body(block_fn, &fn_ref, &instance.name);
+            self.substitution.clear();
+        }
+
+        // Only exported functions are visible outside of a shared library,
+        // and only `pub` functions outside of their unit. The program's
+        // `main` is kept for its caller.
+        for function in self.current_module().functions() {
+            let name = function.get_name();
+            if function.is_declaration() || self.exported_fns.contains(&name) {
+                continue
+            }
+            if self.public_fns.contains(&name)
+                    || (self.module_name.is_none() && name == "main") {
+                function.set_visibility(LLVMVisibility::LLVMHiddenVisibility);
+            }
+            else {
+                function.set_linkage(LLVMLinkage::LLVMInternalLinkage);
+            }
+            if let Some(ref module_name) = self.module_name {
+                function.set_name(&module_symbol(module_name, &name));
+            }
+        }
+
+        // The final ir_code value should be a reference to the function
+        match self.current_module()
+                .verify(LLVMVerifierFailureAction::LLVMPrintMessageAction) {
+
+            Ok(_) => (),
+            Err(_) => {
+                info!("Module:");
+                self.current_module().dump();
+            }
+        }
+    }
+}
+
+impl<'ctx, 'b, M> ItemVisitor for ModuleCompiler<'ctx, 'b, M>
+    where M: ModuleProvider<'ctx>, 'ctx: 'b {
+
+    fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
+        trace!("Checking declaration of {}", block_fn.name());
+        if block_fn.is_generic() {
+            trace!("Skipping generic fn {} until it is called", block_fn.name());
+            return
+        }
+
+        let fn_type = match self.type_of(&block_fn.id()) {
+            Some(ConcreteType::Function(ref fn_ty)) => self.llvm_fn_type(fn_ty),
+            other => panic!("Block fn had non-fn type {:?}", other)
+        };
+        let fn_ref = self.current_module().add_function(
+            block_fn.name(), &fn_type);
+        if block_fn.is_exported() {
+            self.exported_fns.insert(block_fn.name().to_string());
+        }
+        if block_fn.is_public() {
+            self.public_fns.insert(block_fn.name().to_string());
+        }
+
+        // Gotta insert the fn ref first so it can be called recursively
+        self.scope_manager.insert(block_fn.id().clone(), fn_ref.clone());
+        self.functions.insert(block_fn.id().clone());
+        trace!("Inserted {} into the scope manager",
+            block_fn.name());
 
-        // Compile the function
-        self.visit_block(&block_fn.block());
+        self.compile_fn_body(block_fn, &fn_ref, block_fn.name());
+    }
 
-        if !fn_returns_void {
-            if let Some(remaining_expr) = self.ir_code.pop() {
-                trace!("Found final expression, appending a return");
-                self.builder.build_ret(&remaining_expr);
-            }
-            else {
-                trace!(
-                    "No IR code remaining, assuming the last stmt was return");
-            }
-        }
-        else {
-            trace!("block fn returns void, appending ret void");
-            self.builder.build_ret_void();
-        }
+    fn visit_extern_fn_decl(&mut self, _extern_fn: &ExternFnDeclaration) {
+        // skip, extern fns are declared in `visit_unit`.
+    }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) {
+        // skip, traits are not compiled.
+    }
 
+    fn visit_import(&mut self, _import: &Import) {
+        // skip, imported fns are declared by the pipeline.
+    }
 
-        if !fn_ref.verify(LLVMVerifierFailureAction::LLVMPrintMessageAction) {
-            error!("Failed to verify {}", block_fn.name());
-            error!("Current module IR:\n{}", self.current_module().print_to_string());
-            panic!("Validation error for {}", block_fn.name());
-        }
+    fn visit_const_decl(&mut self, _const_decl: &ConstDeclaration) {
+        // skip, consts are folded into the expressions using them.
+    }
 
-        if self.optimizations {
-            trace!("Running optimizations on fn {}", block_fn.name());
-            self.module_provider.pass_manager().run(&fn_ref);
+    fn visit_global_decl(&mut self, _global: &GlobalDeclaration) {
+        // skip, globals are declared in `visit_unit`.
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        trace!("Checking an impl block");
+        let is_public = match *impl_block.type_expr() {
+            TypeExpression::Named(ref named) =>
+                self.public_types.contains(named.ident().name()),
+            _ => false
+        };
+        for method in impl_block.methods() {
+            if !method.is_method() {
+                let fn_ref = self.scope_manager[&method.id()].clone();
+                let name = associated_fn_name(impl_block, method);
+                self.compile_fn_body(method, &fn_ref, &name);
+                continue
+            }
+            let fn_ty = match self.type_of(&method.id()) {
+                Some(ConcreteType::Function(fn_ty)) => fn_ty,
+                other => panic!("Method had non-fn type {:?}", other)
+            };
+            let name = self.methods[&(fn_ty.params()[0].1.clone(),
+                                      method.name().to_string())].0.clone();
+            let fn_ref = self.current_module().get_function(&name)
+                .expect("Method was not declared");
+            if is_public {
+                self.public_fns.insert(name.clone());
+            }
+            self.compile_fn_body(method, &fn_ref, &name);
         }
     }
 
     fn visit_typedef(&mut self, _typedef: &Typedef) {
         // skip, typedef is not compiled.
     }
+
+    fn visit_struct_decl(&mut self, _struct_decl: &StructDeclaration) {
+        // skip, struct types are created when they are used.
+    }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDeclaration) {
+        // skip, enum types are created when they are used.
+    }
 }
 
 impl<'ctx, 'b, M> BlockVisitor for ModuleCompiler<'ctx, 'b, M>
@@ -221,6 +2010,8 @@ impl<'ctx, 'b, M> StatementVisitor for ModuleCompiler<'ctx, 'b, M>
         let mut condition_blocks = Vec::with_capacity(condition_count);
         let mut incoming_values =
             Vec::with_capacity(if valued_if { condition_count } else {0});
+        let mut incoming_blocks =
+            Vec::with_capacity(if valued_if { condition_count } else {0});
 
         trace!("Preparing to emit {} conditionals", condition_count);
         // Populate a list of the future blocks to have
@@ -247,9 +2038,6 @@ impl<'ctx, 'b, M> StatementVisitor for ModuleCompiler<'ctx, 'b, M>
             );
         }
 
-        let int1_type = Type::int1(self.context);
-        let int1_zero = int1_type.const_int(0u64, false);
-
         trace!("Creating end block");
         condition_blocks.push(self.context.append_basic_block(&function,
                                                                      "if_end"));
@@ -260,13 +2048,9 @@ impl<'ctx, 'b, M> StatementVisitor for ModuleCompiler<'ctx, 'b, M>
             self.visit_expression(conditional.condition());
             let cond_value = self.ir_code.pop()
                 .expect("Did not get IR value from if block condition");
-            let cond_cmp_name = format!("if_{}_cmp", ix);
-            let cond_cmp = self.builder.build_icmp(LLVMIntPredicate::LLVMIntEQ,
-                    &cond_value, &int1_zero, &cond_cmp_name);
 
-            trace!("Building a break to next blocks {} -> {}, {}",
-                cond_cmp_name, ix, ix + 1);
-            self.builder.build_cond_br(&cond_cmp,
+            trace!("Building a break to next blocks {}, {}", ix, ix + 1);
+            self.builder.build_cond_br(&cond_value,
                                                  &condition_blocks[ix],
                                                  &condition_blocks[ix + 1]);
 
@@ -280,6 +2064,8 @@ impl<'ctx, 'b, M> StatementVisitor for ModuleCompiler<'ctx, 'b, M>
                 let value = self.ir_code.pop()
                     .expect("Did not get value from valued if block");
                 incoming_values.push(value);
+                // The block may have created more basic blocks.
+                incoming_blocks.push(self.builder.insert_block());
             }
 
             // After block, go to done
@@ -302,6 +2088,7 @@ impl<'ctx, 'b, M> StatementVisitor for ModuleCompiler<'ctx, 'b, M>
                 let value = self.ir_code.pop()
                     .expect("Did not get value from else of valued if block");
                 incoming_values.push(value);
+                incoming_blocks.push(self.builder.insert_block());
             }
             // Branch to end after else
             let last_ix = condition_blocks.len() - 1;
@@ -316,17 +2103,11 @@ impl<'ctx, 'b, M> StatementVisitor for ModuleCompiler<'ctx, 'b, M>
 
         // If we need to push a value, create a phi
         if valued_if {
-            let mut incoming_conditions = condition_blocks
-                .chunks(2)
-                .map(|c| c[0].clone())
-                .collect::<Vec<_>>();
-            incoming_conditions.push(condition_blocks.pop()
-                .expect("No condition blocks"));
             trace!("Generating phi node with {} values and {} edges",
-                incoming_values.len(), incoming_conditions.len());
+                incoming_values.len(), incoming_blocks.len());
             let phi_type = self.llvm_type_of(&if_block.id());
             let phi = self.builder.build_phi(&phi_type, "if_phi");
-            phi.add_incoming(incoming_values, incoming_conditions);
+            phi.add_incoming(incoming_values, incoming_blocks);
             self.ir_code.push(phi);
             self.current_type = phi_type;
         }
@@ -334,15 +2115,45 @@ impl<'ctx, 'b, M> StatementVisitor for ModuleCompiler<'ctx, 'b, M>
 
     fn visit_declaration(&mut self, decl: &Declaration) {
         trace!("Checking declaration for {}", decl.name());
-        self.visit_expression(decl.value());
+        // Local consts are still stored so that lambdas can capture them.
+        if decl.is_const() {
+            self.build_const_value(&decl.id());
+        }
+        else {
+            self.visit_expression(decl.value());
+        }
         let decl_value = self.ir_code.pop()
             .expect("Did not have rvalue of declaration");
-        let builder = self.builder;
-        let alloca = builder.build_alloca(&self.current_type, decl.name());
-        builder.build_store(&decl_value, &alloca);
+        let alloca = self.build_entry_alloca(&self.current_type, decl.name());
+        self.builder.build_store(&decl_value, &alloca);
         self.scope_manager.insert(decl.id().clone(), alloca);
     }
 
+    fn visit_tuple_declaration(&mut self, decl: &TupleDeclaration) {
+        trace!("Checking tuple declaration");
+        let tuple = match self.concrete_type_of(decl.value()) {
+            Some(ConcreteType::Tuple(tuple)) => tuple,
+            other => panic!("Expected a tuple type, got {:?}", other)
+        };
+        self.visit_expression(decl.value());
+        let tuple_value = self.ir_code.pop()
+            .expect("Did not have rvalue of tuple declaration");
+        for (ix, binding) in decl.bindings().iter().enumerate() {
+            let binding = match *binding {
+                Some(ref binding) => binding,
+                None => continue
+            };
+            let element_type = self.llvm_type_of_concrete(
+                &tuple.elements()[ix]);
+            let element_value = self.builder.build_extract_value(
+                &tuple_value, ix as u32, binding.name());
+            let alloca = self.build_entry_alloca(&element_type,
+                                                 binding.name());
+            self.builder.build_store(&element_value, &alloca);
+            self.scope_manager.insert(binding.id().clone(), alloca);
+        }
+    }
+
     fn visit_return_stmt(&mut self, return_: &Return) {
         trace!("Checking return statement");
         if let Some(ref return_expr) = return_.value() {
@@ -354,6 +2165,177 @@ impl<'ctx, 'b, M> StatementVisitor for ModuleCompiler<'ctx, 'b, M>
         else {
             self.builder.build_ret_void();
         }
+        self.start_unreachable_block("after_return");
+        self.current_type = Type::void(&self.context);
+    }
+
+    fn visit_while_block(&mut self, while_block: &WhileBlock) {
+        trace!("Checking while block");
+        let function = self.builder.insert_block().get_parent()
+            .expect("Just inserted a block");
+        let cond_block = self.context.append_basic_block(&function, "while_cond");
+        let body_block = self.context.append_basic_block(&function, "while_body");
+        let end_block = self.context.append_basic_block(&function, "while_end");
+
+        self.builder.build_br(&cond_block);
+
+        // Check the condition on each iteration
+        self.builder.position_at_end(&cond_block);
+        self.visit_expression(while_block.condition());
+        let cond_value = self.ir_code.pop()
+            .expect("Did not get IR value from while condition");
+        self.builder.build_cond_br(&cond_value, &body_block, &end_block);
+
+        // Emit the body, which jumps back to the condition
+        self.builder.position_at_end(&body_block);
+        self.begin_loop(while_block.label(), &cond_block, &end_block);
+        self.visit_block(while_block.block());
+        self.loop_blocks.pop();
+        self.builder.build_br(&cond_block);
+
+        self.builder.position_at_end(&end_block);
+        self.current_type = Type::void(&self.context);
+    }
+
+    fn visit_for_loop(&mut self, for_loop: &ForLoop) {
+        trace!("Checking for loop over {}", for_loop.ident().name());
+        use llvm_sys::LLVMIntPredicate::*;
+        use llvm_sys::LLVMRealPredicate::*;
+        let ident = for_loop.ident();
+        let range = for_loop.range();
+        let var_type = self.llvm_type_of(&ident.id());
+        let is_float = var_type.get_kind() == LLVMTypeKind::LLVMDoubleTypeKind;
+        let is_unsigned = self.type_of(&ident.id())
+            .map_or(false, |var_ty| is_unsigned_type(&var_ty));
+
+        // The bounds of the range are evaluated once, before the loop.
+        self.visit_expression(range.start());
+        let start_value = self.ir_code.pop()
+            .expect("Did not get IR value from range start");
+        self.visit_expression(range.end());
+        let end_value = self.ir_code.pop()
+            .expect("Did not get IR value from range end");
+        let step_value = if let Some(step) = range.step() {
+            self.visit_expression(step);
+            self.ir_code.pop()
+                .expect("Did not get IR value from range step")
+        }
+        else if is_float {
+            var_type.const_real(1f64)
+        }
+        else {
+            var_type.const_int(1u64, false)
+        };
+
+        let var_alloca = self.build_entry_alloca(&var_type, ident.name());
+        self.builder.build_store(&start_value, &var_alloca);
+        self.scope_manager.insert(ident.id().clone(), var_alloca.clone());
+
+        let function = self.builder.insert_block().get_parent()
+            .expect("Just inserted a block");
+        let body_block = self.context.append_basic_block(&function, "for_body");
+        let step_block = self.context.append_basic_block(&function, "for_step");
+        let next_block = self.context.append_basic_block(&function, "for_next");
+        let end_block = self.context.append_basic_block(&function, "for_end");
+
+        // Skip the loop if the range is empty, or if the step is not
+        // positive, which would never reach the end.
+        let inclusive = range.is_inclusive();
+        let (in_range, step_positive) = if is_float {
+            let pred = if inclusive { LLVMRealOLE } else { LLVMRealOLT };
+            (self.builder.build_fcmp(pred, &start_value, &end_value,
+                                     "for_in_range"),
+             self.builder.build_fcmp(LLVMRealOGT, &step_value,
+                                     &var_type.const_real(0f64),
+                                     "for_step_positive"))
+        }
+        else {
+            let pred = match (inclusive, is_unsigned) {
+                (true, true) => LLVMIntULE,
+                (true, false) => LLVMIntSLE,
+                (false, true) => LLVMIntULT,
+                (false, false) => LLVMIntSLT
+            };
+            let step_pred = if is_unsigned { LLVMIntUGT } else { LLVMIntSGT };
+            (self.builder.build_icmp(pred, &start_value, &end_value,
+                                     "for_in_range"),
+             self.builder.build_icmp(step_pred, &step_value,
+                                     &var_type.const_int(0u64, false),
+                                     "for_step_positive"))
+        };
+        let enter_loop = self.builder.build_and(&in_range, &step_positive,
+                                                "for_enter");
+        self.builder.build_cond_br(&enter_loop, &body_block, &end_block);
+
+        self.builder.position_at_end(&body_block);
+        self.begin_loop(for_loop.label(), &step_block, &end_block);
+        self.visit_block(for_loop.block());
+        self.loop_blocks.pop();
+        self.builder.build_br(&step_block);
+
+        // Continue while there's room for another step before the end.
+        // Comparing the distance to the end, rather than the next value,
+        // keeps the loop variable from overflowing on the last iteration.
+        // Inside the loop `var <= end`, so the distance is never negative.
+        self.builder.position_at_end(&step_block);
+        let current_value = self.builder.build_load(&var_alloca, "for_current");
+        let continue_loop = if is_float {
+            let remaining = self.builder.build_fsub(
+                &end_value, &current_value, "for_remaining");
+            let pred = if inclusive { LLVMRealOGE } else { LLVMRealOGT };
+            self.builder.build_fcmp(pred, &remaining, &step_value, "for_continue")
+        }
+        else {
+            let remaining = self.builder.build_sub(
+                &end_value, &current_value, "for_remaining");
+            let pred = if inclusive { LLVMIntUGE } else { LLVMIntUGT };
+            self.builder.build_icmp(pred, &remaining, &step_value, "for_continue")
+        };
+        self.builder.build_cond_br(&continue_loop, &next_block, &end_block);
+
+        self.builder.position_at_end(&next_block);
+        let next_value = if is_float {
+            self.builder.build_fadd(&current_value, &step_value, "for_next")
+        }
+        else {
+            self.builder.build_add(&current_value, &step_value, "for_next")
+        };
+        self.builder.build_store(&next_value, &var_alloca);
+        self.builder.build_br(&body_block);
+
+        self.builder.position_at_end(&end_block);
+        self.current_type = Type::void(&self.context);
+    }
+
+    fn visit_break_stmt(&mut self, break_: &Break) {
+        trace!("Checking break statement");
+        let value = if let Some(value) = break_.value() {
+            self.visit_expression(value);
+            self.ir_code.pop()
+        }
+        else {
+            None
+        };
+        let value_block = self.builder.insert_block();
+        let break_block = {
+            let target = self.target_loop(break_.label());
+            if let Some(value) = value {
+                target.break_values.push(value);
+                target.break_value_blocks.push(value_block);
+            }
+            target.break_block.clone()
+        };
+        self.builder.build_br(&break_block);
+        self.start_unreachable_block("after_break");
+        self.current_type = Type::void(&self.context);
+    }
+
+    fn visit_continue_stmt(&mut self, continue_: &Continue) {
+        trace!("Checking continue statement");
+        let continue_block = self.target_loop(continue_.label())
+            .continue_block.clone();
+        self.builder.build_br(&continue_block);
+        self.start_unreachable_block("after_continue");
         self.current_type = Type::void(&self.context);
     }
 }
@@ -371,10 +2353,27 @@ impl<'ctx, 'b, M> ExpressionVisitor for ModuleCompiler<'ctx, 'b, M>
                      .const_int(bool_value, false),
                  Type::int1(&self.context))
             },
+            &LiteralValue::Int(i) => {
+                // Integer literals may be inferred to be floats.
+                let int_type = self.llvm_type_of(&literal.id());
+                if int_type.get_kind() == LLVMTypeKind::LLVMDoubleTypeKind {
+                    (int_type.const_real(i as f64), int_type)
+                }
+                else {
+                    (int_type.const_int(i, false), int_type)
+                }
+            },
             &LiteralValue::Float(f) => {
                 (Type::double(&self.context).const_real(f),
                 Type::double(&self.context))
             },
+            &LiteralValue::Str(ref value) => {
+                let chars = self.builder.build_global_string_ptr(value, "str");
+                let len = Type::int64(&self.context)
+                    .const_int(value.len() as u64, false);
+                (self.build_str_value(&chars, &len, "str_literal"),
+                 self.str_type())
+            },
             &LiteralValue::Unit => {
                 // Not directly used.
                 //Type::void(self.context).const_null()
@@ -388,9 +2387,33 @@ impl<'ctx, 'b, M> ExpressionVisitor for ModuleCompiler<'ctx, 'b, M>
     fn visit_var_ref(&mut self, ident_ref: &Identifier) {
         trace!("Checking variable ref {} ({:?})",
             ident_ref.name(), ident_ref.id());
-        let var_alloca = self.scope_manager.get(&ident_ref.id())
-            .expect("Attempted to check var ref but had no alloca for it")
-            .clone();
+        // Variants without fields are referred to by name, and have no
+        // storage.
+        if !self.scope_manager.contains_key(&ident_ref.id()) {
+            if let Some(ConcreteType::Enum(enum_ty)) =
+                    self.type_of(&ident_ref.id()) {
+                return self.build_variant_construction(ident_ref.name(),
+                                                       &enum_ty, &[])
+            }
+        }
+        if self.consts.contains_key(&ident_ref.id()) {
+            return self.build_const_value(&ident_ref.id())
+        }
+        // Declared functions are used as values without an environment.
+        if self.functions.contains(&ident_ref.id()) {
+            let fn_ref = self.scope_manager[&ident_ref.id()].clone();
+            let fn_ty = match self.type_of(&ident_ref.id()) {
+                Some(ConcreteType::Function(fn_ty)) => fn_ty,
+                other => panic!("Declared fn had non-fn type {:?}", other)
+            };
+            let fn_value = self.build_declared_fn_value(ident_ref.name(),
+                                                        &fn_ref, &fn_ty);
+            self.current_type = fn_value.get_type();
+            self.ir_code.push(fn_value);
+            return
+        }
+        let var_alloca = self.var_ptr(&ident_ref.id())
+            .expect("Attempted to check var ref but had no alloca for it");
         let load_name = format!("load_{}", ident_ref.name());
         trace!("Creating {}", load_name);
         let builder = self.builder;
@@ -404,9 +2427,8 @@ impl<'ctx, 'b, M> ExpressionVisitor for ModuleCompiler<'ctx, 'b, M>
         self.visit_expression(assign.rvalue());
         let rvalue = self.ir_code.pop()
             .expect("Could not generate rvalue of assignment");
-        let var_alloca = self.scope_manager.get(&assign.lvalue().id())
-            .expect("Could not find existing var for assignment!")
-            .clone();
+        let var_alloca = self.var_ptr(&assign.lvalue().id())
+            .expect("Could not find existing var for assignment!");
         let builder = self.builder;
         builder.build_store(&rvalue, &var_alloca);
     }
@@ -418,13 +2440,19 @@ impl<'ctx, 'b, M> ExpressionVisitor for ModuleCompiler<'ctx, 'b, M>
         let builder = self.builder;
         let (value, type_) = match unary_op.operator() {
             UnaryOperator::Negation => {
-                let double_type = Type::double(&self.context);
-                let literal_zero = double_type.const_real(0f64);
-                (builder.build_fsub(&inner_value, &literal_zero, "negate"), double_type)
+                let inner_type = self.current_type.clone();
+                if inner_type.get_kind() == LLVMTypeKind::LLVMDoubleTypeKind {
+                    (builder.build_fneg(&inner_value, "negate"), inner_type)
+                }
+                else {
+                    (builder.build_neg(&inner_value, "negate"), inner_type)
+                }
             },
             // The unary + operator is always a no-op.
             UnaryOperator::Addition =>
-                (inner_value, self.current_type.clone())
+                (inner_value, self.current_type.clone()),
+            UnaryOperator::Not =>
+                (builder.build_not(&inner_value, "not"), self.current_type.clone())
         };
         self.current_type = type_;
         self.ir_code.push(value);
@@ -432,6 +2460,12 @@ impl<'ctx, 'b, M> ExpressionVisitor for ModuleCompiler<'ctx, 'b, M>
 
     fn visit_binary_op(&mut self, binary_op: &BinaryOperation) {
         trace!("Checking binary operation {:?}", binary_op.operator());
+        match binary_op.operator() {
+            BinaryOperator::And | BinaryOperator::Or => {
+                return self.build_short_circuit(binary_op)
+            },
+            _ => {}
+        }
         trace!("Checking {:?} lvalue", binary_op.operator());
         self.visit_expression(binary_op.left());
         let left_register = self.ir_code.pop()
@@ -442,74 +2476,486 @@ impl<'ctx, 'b, M> ExpressionVisitor for ModuleCompiler<'ctx, 'b, M>
             .expect("Could not generate rvalue of binary op");
         let builder = self.builder;
         trace!("Appending binary operation");
+        use llvm_sys::LLVMIntPredicate::*;
         use llvm_sys::LLVMRealPredicate::*;
+        // Both operands have the same type.
+        let operand_type = self.current_type.clone();
+        let is_float =
+            operand_type.get_kind() == LLVMTypeKind::LLVMDoubleTypeKind;
+        let is_unsigned = self.is_unsigned(binary_op.left());
+        if operand_type.get_kind() == LLVMTypeKind::LLVMStructTypeKind
+                || operand_type.get_kind() == LLVMTypeKind::LLVMArrayTypeKind
+                || operand_type.get_kind() == LLVMTypeKind::LLVMPointerTypeKind {
+            trace!("Building string, struct, or array operation");
+            let concrete = self.concrete_type_of(binary_op.left())
+                .expect("Could not find type of binary op operand");
+            let (value, type_) = match binary_op.operator() {
+                BinaryOperator::Equality => {
+                    (self.build_equals(&left_register, &right_register,
+                                       &concrete),
+                     Type::int1(&self.context))
+                },
+                BinaryOperator::NonEquality => {
+                    let equal = self.build_equals(&left_register,
+                                                  &right_register,
+                                                  &concrete);
+                    (self.builder.build_not(&equal, "netmp"),
+                     Type::int1(&self.context))
+                },
+                BinaryOperator::Addition => {
+                    (self.build_str_concat(&left_register, &right_register),
+                     operand_type)
+                },
+                other => unreachable!("Invalid operator {:?} on {}",
+                                      other, concrete)
+            };
+            self.current_type = type_;
+            self.ir_code.push(value);
+            return
+        }
+        // Comparisons pick a predicate based on the type of the operands.
+        let compare = |int_pred: LLVMIntPredicate,
+                       uint_pred: LLVMIntPredicate,
+                       real_pred: LLVMRealPredicate,
+                       name: &str| {
+            if is_float {
+                builder.build_fcmp(real_pred,
+                    &left_register, &right_register, name)
+            }
+            else if is_unsigned {
+                builder.build_icmp(uint_pred,
+                    &left_register, &right_register, name)
+            }
+            else {
+                builder.build_icmp(int_pred,
+                    &left_register, &right_register, name)
+            }
+        };
         let (bin_op_value, bin_op_type) = match binary_op.operator() {
             BinaryOperator::Addition => {
-                (builder.build_fadd(&left_register, &right_register, "add"),
-                Type::double(&self.context))
+                (if is_float {
+                    builder.build_fadd(&left_register, &right_register, "add")
+                }
+                else {
+                    builder.build_add(&left_register, &right_register, "add")
+                },
+                operand_type)
             },
             BinaryOperator::Subtraction => {
-                (builder.build_fsub(&left_register, &right_register, "sub"),
-                Type::double(&self.context))
+                (if is_float {
+                    builder.build_fsub(&left_register, &right_register, "sub")
+                }
+                else {
+                    builder.build_sub(&left_register, &right_register, "sub")
+                },
+                operand_type)
             },
             BinaryOperator::Multiplication => {
-                (builder.build_fmul(&left_register, &right_register, "mul"),
-                Type::double(&self.context))
+                (if is_float {
+                    builder.build_fmul(&left_register, &right_register, "mul")
+                }
+                else {
+                    builder.build_mul(&left_register, &right_register, "mul")
+                },
+                operand_type)
             },
             BinaryOperator::Division => {
-                (builder.build_fdiv(&left_register, &right_register, "div"),
-                Type::double(&self.context))
+                (if is_float {
+                    builder.build_fdiv(&left_register, &right_register, "div")
+                }
+                else if is_unsigned {
+                    builder.build_udiv(&left_register, &right_register, "div")
+                }
+                else {
+                    builder.build_sdiv(&left_register, &right_register, "div")
+                },
+                operand_type)
             },
             BinaryOperator::Modulus => {
-                (builder.build_frem(&left_register, &right_register, "rem"),
-                Type::double(&self.context))
-            },
-            BinaryOperator::Equality => {
-                let eq_type_kind = left_register.get_type().get_kind();
-
-                (if eq_type_kind == LLVMTypeKind::LLVMDoubleTypeKind {
-                    // Not sure about NaN == NaN here.
-                    self.builder.build_fcmp(LLVMRealPredicate::LLVMRealOEQ,
-                        &left_register, &right_register, "eq_double")
+                (if is_float {
+                    builder.build_frem(&left_register, &right_register, "rem")
                 }
-                else if eq_type_kind == LLVMTypeKind::LLVMIntegerTypeKind {
-                    self.builder.build_icmp(LLVMIntPredicate::LLVMIntEQ,
-                        &left_register, &right_register, "eq_int")
+                else if is_unsigned {
+                    builder.build_urem(&left_register, &right_register, "rem")
                 }
                 else {
-                    panic!("Unexpected type for equality check");
+                    builder.build_srem(&left_register, &right_register, "rem")
                 },
+                operand_type)
+            },
+            BinaryOperator::Equality => {
+                // Not sure about NaN == NaN here.
+                (compare(LLVMIntEQ, LLVMIntEQ, LLVMRealOEQ, "eqtmp"),
                 Type::int1(&self.context))
             },
-           BinaryOperator::NonEquality => {
-                (builder.build_fcmp(LLVMRealONE, &left_register, &right_register, "neqtmp"),
+            BinaryOperator::NonEquality => {
+                (compare(LLVMIntNE, LLVMIntNE, LLVMRealONE, "neqtmp"),
                 Type::int1(&self.context))
             },
-           BinaryOperator::LessThan => {
-                (builder.build_fcmp(LLVMRealOLT, &left_register, &right_register, "lttmp"),
+            BinaryOperator::LessThan => {
+                (compare(LLVMIntSLT, LLVMIntULT, LLVMRealOLT, "lttmp"),
                 Type::int1(&self.context))
             },
-           BinaryOperator::LessThanEquals => {
-                (builder.build_fcmp(LLVMRealOLE, &left_register, &right_register, "letmp"),
+            BinaryOperator::LessThanEquals => {
+                (compare(LLVMIntSLE, LLVMIntULE, LLVMRealOLE, "letmp"),
                 Type::int1(&self.context))
             },
-           BinaryOperator::GreaterThan => {
-                (builder.build_fcmp(LLVMRealOGT, &left_register, &right_register, "gttmp"),
+            BinaryOperator::GreaterThan => {
+                (compare(LLVMIntSGT, LLVMIntUGT, LLVMRealOGT, "gttmp"),
                 Type::int1(&self.context))
             },
-           BinaryOperator::GreaterThanEquals => {
-                (builder.build_fcmp(LLVMRealOGE, &left_register, &right_register, "getmp"),
+            BinaryOperator::GreaterThanEquals => {
+                (compare(LLVMIntSGE, LLVMIntUGE, LLVMRealOGE, "getmp"),
                 Type::int1(&self.context))
+            },
+            BinaryOperator::And | BinaryOperator::Or => {
+                unreachable!("Short-circuiting operators are built separately")
             }
         };
         self.current_type = bin_op_type;
         self.ir_code.push(bin_op_value);
     }
 
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression) {
+        trace!("Checking tuple of {} elements", tuple.elements().len());
+        let mut element_types = Vec::with_capacity(tuple.elements().len());
+        let mut element_values = Vec::with_capacity(tuple.elements().len());
+        for element in tuple.elements() {
+            self.visit_expression(element);
+            element_values.push(self.ir_code.pop()
+                .expect("Could not generate value of tuple element"));
+            element_types.push(self.current_type.clone());
+        }
+        let tuple_type = Type::structure(&self.context, element_types, false);
+        let mut tuple_value = tuple_type.get_undef();
+        for (ix, element_value) in element_values.iter().enumerate() {
+            tuple_value = self.builder.build_insert_value(&tuple_value,
+                element_value, ix as u32, &format!("init_{}", ix));
+        }
+        self.current_type = tuple_type;
+        self.ir_code.push(tuple_value);
+    }
+
+    fn visit_array_expr(&mut self, array: &ArrayExpression) {
+        trace!("Checking array of {} elements", array.elements().len());
+        let array_type = self.llvm_type_of(&array.id());
+        if let Some(ConcreteType::Growable(growable)) = self.type_of(&array.id()) {
+            let len = array.elements().len() as u64;
+            let (header, data) = self.build_growable(&growable, len);
+            for (ix, element) in array.elements().iter().enumerate() {
+                self.visit_expression(element);
+                let element_value = self.ir_code.pop()
+                    .expect("Could not generate value of array element");
+                let element_ptr = self.builder.build_in_bounds_gep(&data,
+                    vec![Type::int64(&self.context).const_int(ix as u64, false)],
+                    &format!("init_{}", ix));
+                self.builder.build_store(&element_value, &element_ptr);
+            }
+            self.current_type = array_type;
+            self.ir_code.push(header);
+            return
+        }
+        let mut array_value = array_type.get_undef();
+        for (ix, element) in array.elements().iter().enumerate() {
+            self.visit_expression(element);
+            let element_value = self.ir_code.pop()
+                .expect("Could not generate value of array element");
+            array_value = self.builder.build_insert_value(&array_value,
+                &element_value, ix as u32, &format!("init_{}", ix));
+        }
+        self.current_type = array_type;
+        self.ir_code.push(array_value);
+    }
+
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat) {
+        trace!("Checking array of {} repeated values", repeat.len());
+        let array_type = self.llvm_type_of(&repeat.id());
+        self.visit_expression(repeat.value());
+        let value = self.ir_code.pop()
+            .expect("Could not generate repeated value of array");
+        if let Some(ConcreteType::Growable(growable)) = self.type_of(&repeat.id()) {
+            let (header, data) = self.build_growable(&growable, repeat.len());
+            self.build_fill(&data, &value, repeat.len());
+            self.current_type = array_type;
+            self.ir_code.push(header);
+            return
+        }
+        let array_alloca = self.build_entry_alloca(&array_type, "repeat");
+        let (first, _len) = self.array_elements(&array_alloca, repeat.len());
+        self.build_fill(&first, &value, repeat.len());
+        let array_value = self.builder.build_load(&array_alloca, "repeated");
+        self.current_type = array_type;
+        self.ir_code.push(array_value);
+    }
+
+    fn visit_index_expr(&mut self, index: &IndexExpression) {
+        trace!("Checking index of array");
+        let element_ty = self.element_type_of(index.value());
+        let element_ptr = self.build_element_ptr(index);
+        let element_value = self.builder.build_load(&element_ptr, "element");
+        self.current_type = self.llvm_type_of_concrete(&element_ty);
+        self.ir_code.push(element_value);
+    }
+
+    fn visit_slice_expr(&mut self, slice: &SliceExpression) {
+        trace!("Checking slice of array");
+        let slice_type = self.llvm_type_of(&slice.id());
+        let (first, len) = match self.concrete_type_of(slice.value()) {
+            // Pushing to a sliced array keeps its old elements.
+            Some(growable_ty @ ConcreteType::Growable(_)) => {
+                self.visit_expression(slice.value());
+                let header = self.ir_code.pop()
+                    .expect("Could not generate sliced growable array");
+                let sliced_ptr = self.builder.build_struct_gep(&header, 3,
+                                                               "sliced_ptr");
+                self.builder.build_store(
+                    &Type::int1(&self.context).const_int(1, false), &sliced_ptr);
+                self.elements_of_value(&header, &growable_ty)
+            },
+            _ => self.build_elements(slice.value())
+        };
+        let with_data = self.builder.build_insert_value(
+            &slice_type.get_undef(), &first, 0, "slice_data");
+        let slice_value = self.builder.build_insert_value(&with_data, &len, 1,
+                                                          "slice");
+        self.current_type = slice_type;
+        self.ir_code.push(slice_value);
+    }
+
+    fn visit_method_call(&mut self, method_call: &MethodCall) {
+        trace!("Checking call of method {}", method_call.method().name());
+        let growable = match self.concrete_type_of(method_call.receiver()) {
+            Some(ConcreteType::Growable(growable)) => growable,
+            Some(receiver_ty) =>
+                return self.build_trait_method_call(method_call, receiver_ty),
+            None => panic!("Called a method of a value with unknown type")
+        };
+        self.visit_expression(method_call.receiver());
+        let header = self.ir_code.pop()
+            .expect("Could not generate receiver of method call");
+        match method_call.method().name() {
+            "push" => {
+                let arg = method_call.args().first()
+                    .expect("Pushed without a value");
+                self.visit_expression(arg.expression());
+                let value = self.ir_code.pop()
+                    .expect("Could not generate pushed value");
+                self.build_push(&header, &value, &growable);
+            },
+            "pop" => {
+                let popped = self.build_pop(&header);
+                self.current_type =
+                    self.llvm_type_of_concrete(growable.element());
+                self.ir_code.push(popped);
+            },
+            other => panic!("Called unknown method {}", other)
+        }
+    }
+
+    fn visit_lambda(&mut self, lambda: &Lambda) {
+        trace!("Checking lambda");
+        let fn_ty = match self.type_of(&lambda.id()) {
+            Some(ConcreteType::Function(fn_ty)) => fn_ty,
+            other => panic!("Lambda had non-fn type {:?}", other)
+        };
+        // The captured values are copied into the environment when the
+        // closure is created.
+        let captures = lambda.captures().clone();
+        let capture_values = captures.iter().map(|capture| {
+            let var_alloca = self.scope_manager.get(&capture.id())
+                .expect("Could not find captured var")
+                .clone();
+            self.builder.build_load(&var_alloca,
+                                    &format!("capture_{}", capture.name()))
+        }).collect::<Vec<_>>();
+        let env_struct_type = Type::structure(&self.context,
+            capture_values.iter().map(|value| value.get_type())
+                .collect::<Vec<_>>(),
+            false);
+
+        let outer_block = self.builder.insert_block();
+        let code = self.current_module().add_function(
+            "closure", &self.llvm_code_type(&fn_ty));
+        let entry_block = self.context.append_basic_block(&code, "entry");
+        let start_block = self.context.append_basic_block(&code, "start");
+        self.builder.position_at_end(&entry_block);
+
+        // Captures and params are in allocas like the params of declared
+        // functions. The outer function's allocas are restored afterwards.
+        let code_params = code.get_params();
+        let mut outer_vars = Vec::new();
+        if !captures.is_empty() {
+            let env_ptr = self.builder.build_bit_cast(&code_params[0],
+                &Type::pointer(&env_struct_type, 0), "env_ptr");
+            let env = self.builder.build_load(&env_ptr, "env");
+            for (ix, capture) in captures.iter().enumerate() {
+                let value = self.builder.build_extract_value(&env, ix as u32,
+                    capture.name());
+                let alloca = self.builder.build_alloca(&value.get_type(),
+                                                       capture.name());
+                self.builder.build_store(&value, &alloca);
+                let outer = self.scope_manager.insert(capture.id().clone(),
+                                                      alloca);
+                outer_vars.push((capture.id().clone(), outer));
+            }
+        }
+        for (&(ref param, _), ref ir_param) in
+                lambda.params().iter().zip(code_params.iter().skip(1)) {
+            ir_param.set_name(param.name());
+            let param_type = self.llvm_type_of(&param.id());
+            let alloca = self.builder.build_alloca(&param_type, param.name());
+            self.builder.build_store(ir_param, &alloca);
+            self.scope_manager.insert(param.id().clone(), alloca);
+        }
+        self.builder.build_br(&start_block);
+        self.builder.position_at_end(&start_block);
+
+        let outer_loops = ::std::mem::replace(&mut self.loop_blocks, Vec::new());
+        let outer_values = self.ir_code.len();
+        self.visit_expression(lambda.body());
+        let return_type = self.llvm_type_of_concrete(fn_ty.return_ty());
+        if return_type.get_kind() == LLVMTypeKind::LLVMVoidTypeKind {
+            self.builder.build_ret_void();
+        }
+        else if self.ir_code.len() > outer_values {
+            let body_value = self.ir_code.pop().expect("Checked expect");
+            self.builder.build_ret(&body_value);
+        }
+        else {
+            trace!("No lambda value remaining, assuming it returned");
+            self.builder.build_unreachable();
+        }
+        self.loop_blocks = outer_loops;
+        for (var_id, outer) in outer_vars {
+            match outer {
+                Some(outer) => self.scope_manager.insert(var_id, outer),
+                None => self.scope_manager.remove(&var_id)
+            };
+        }
+
+        if !code.verify(LLVMVerifierFailureAction::LLVMPrintMessageAction) {
+            error!("Current module IR:\n{}", self.current_module().print_to_string());
+            panic!("Validation error for lambda");
+        }
+        if self.optimizations {
+            self.module_provider.pass_manager().run(&code);
+        }
+        self.builder.position_at_end(&outer_block);
+
+        let env = if captures.is_empty() {
+            self.env_type().const_null()
+        }
+        else {
+            let env_struct = capture_values.iter().enumerate()
+                .fold(env_struct_type.get_undef(), |env_struct, (ix, value)|
+                    self.builder.build_insert_value(&env_struct, value,
+                        ix as u32, "env_value"));
+            let env_ptr = self.builder.build_malloc(&env_struct_type, "env");
+            self.builder.build_store(&env_struct, &env_ptr);
+            self.builder.build_bit_cast(&env_ptr, &self.env_type(), "env")
+        };
+        let fn_value = self.build_fn_value(&fn_ty, &code, &env);
+        self.current_type = fn_value.get_type();
+        self.ir_code.push(fn_value);
+    }
+
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment) {
+        trace!("Checking assignment to an element");
+        self.visit_expression(assign.rvalue());
+        let rvalue = self.ir_code.pop()
+            .expect("Could not generate rvalue of assignment");
+        let element_ptr = self.build_element_ptr(assign.lvalue());
+        self.builder.build_store(&rvalue, &element_ptr);
+    }
+
+    fn visit_field_access(&mut self, field_access: &FieldAccess) {
+        let field = field_access.field();
+        trace!("Checking access of field {}", field.name());
+        let value_ty = self.concrete_type_of(field_access.value());
+        match value_ty {
+            // The length of an array is known from its type.
+            Some(ConcreteType::Array(ref array)) => {
+                let int_type = Type::int64(&self.context);
+                self.ir_code.push(int_type.const_int(array.len(), false));
+                self.current_type = int_type;
+                return
+            },
+            Some(ConcreteType::Growable(_)) | Some(ConcreteType::Slice(_)) => {
+                let (_first, len) = self.build_elements(field_access.value());
+                self.ir_code.push(len);
+                self.current_type = Type::int64(&self.context);
+                return
+            },
+            // Strings are a `{ i8*, i64 }` of their bytes and length.
+            Some(ConcreteType::Named(ref named)) if named.name() == "str" => {
+                self.visit_expression(field_access.value());
+                let str_value = self.ir_code.pop()
+                    .expect("Could not generate str of length");
+                let len = self.builder.build_extract_value(&str_value, 1, "len");
+                self.ir_code.push(len);
+                self.current_type = Type::int64(&self.context);
+                return
+            },
+            _ => {}
+        }
+        let (field_ix, field_ty) = self.field_of(field_access.value(),
+                                                 field.name());
+        let field_type = self.llvm_type_of_concrete(&field_ty);
+        let field_name = format!("field_{}", field.name());
+        let field_value = match *field_access.value() {
+            // Fields of variables are loaded without loading the whole struct.
+            Expression::VariableRef(ref ident) => {
+                let var_alloca = self.var_ptr(&ident.id())
+                    .expect("Attempted to access field of var without alloca");
+                let field_ptr = self.builder.build_struct_gep(&var_alloca,
+                    field_ix as u32, &format!("{}_{}_ptr", ident.name(),
+                                              field.name()));
+                self.builder.build_load(&field_ptr, &field_name)
+            },
+            ref value => {
+                self.visit_expression(value);
+                let struct_value = self.ir_code.pop()
+                    .expect("Could not generate struct of field access");
+                self.builder.build_extract_value(&struct_value,
+                    field_ix as u32, &field_name)
+            }
+        };
+        self.current_type = field_type;
+        self.ir_code.push(field_value);
+    }
+
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate) {
+        trace!("Checking struct update");
+        let record = self.record_type_of(struct_update.value());
+        self.visit_expression(struct_update.value());
+        let mut struct_value = self.ir_code.pop()
+            .expect("Could not generate struct of update");
+        let struct_type = self.current_type.clone();
+        for field in struct_update.fields() {
+            let name = field.name().name();
+            let (field_ix, _) = record.field(name)
+                .expect("Updated unknown field of struct");
+            self.visit_expression(field.expression());
+            let field_value = self.ir_code.pop()
+                .expect("Could not generate value of struct field");
+            struct_value = self.builder.build_insert_value(&struct_value,
+                &field_value, field_ix as u32, &format!("update_{}", name));
+        }
+        self.current_type = struct_type;
+        self.ir_code.push(struct_value);
+    }
+
     fn visit_fn_call(&mut self, fn_call: &FnCall) {
         trace!("Checking call to {}", fn_call.text());
-        let fn_type = match self.types[&fn_call.id()].clone() {
-            ConcreteType::Function(fn_type) => fn_type,
+        let fn_type = match self.type_of(&fn_call.id()) {
+            Some(ConcreteType::Function(fn_type)) =>
+                self.instance_type(fn_call, &fn_type),
+            Some(ConcreteType::Record(record)) =>
+                return self.build_struct_construction(fn_call, &record),
+            Some(ConcreteType::Enum(enum_ty)) =>
+                return self.build_variant_construction(fn_call.text(),
+                                                       &enum_ty,
+                                                       fn_call.args()),
             _other => panic!("Function call's ident had non-fn type")
         };
 
@@ -517,34 +2963,225 @@ impl<'ctx, 'b, M> ExpressionVisitor for ModuleCompiler<'ctx, 'b, M>
 
         let mut arg_values = Vec::with_capacity(fn_call.args().len());
 
-        for (_ix, &(ref name, _)) in fn_type.params().iter().enumerate() {
+        for (_ix, &(ref name, ref param_ty)) in fn_type.params().iter().enumerate() {
             for arg in fn_call.args() {
                 if arg.name().name() == name {
                     self.visit_expression(arg.expression());
-                    arg_values.push(self.ir_code.pop()
-                        .expect("Could not get alloca for named var of fn arg"));
+                    let arg_value = self.ir_code.pop()
+                        .expect("Could not get alloca for named var of fn arg");
+                    // C functions are given the chars of strings.
+                    let is_str = match *param_ty {
+                        ConcreteType::Named(ref named) => named.name() == "str",
+                        _ => false
+                    };
+                    if is_str && self.foreign_fns.contains(&fn_call.id()) {
+                        arg_values.push(self.builder.build_extract_value(
+                            &arg_value, 0, "chars"));
+                    }
+                    else {
+                        arg_values.push(arg_value);
+                    }
                     break
                 }
             }
         }
 
-        let fn_ref = &self.scope_manager[&fn_call.id()];
+        // Function values in variables are called with their environment.
+        let fn_ref = if let Some(type_args) = self.call_type_args(fn_call) {
+            self.instance_fn_ref(fn_call, &fn_type, type_args)
+        }
+        else if self.functions.contains(&fn_call.id()) {
+            self.scope_manager[&fn_call.id()].clone()
+        }
+        else {
+            let fn_alloca = self.var_ptr(&fn_call.id())
+                .expect("Attempted to call var without alloca");
+            let fn_value = self.builder.build_load(&fn_alloca,
+                &format!("load_{}", fn_call.text()));
+            let env = self.builder.build_extract_value(&fn_value, 1, "env");
+            arg_values.insert(0, env);
+            self.builder.build_extract_value(&fn_value, 0, "code")
+        };
         let fn_return_type = self.llvm_type_of_concrete(fn_type.return_ty());
         trace!("Got a function ref to call");
         if fn_return_type.get_kind() == LLVMTypeKind::LLVMVoidTypeKind {
             trace!("Building call void {}", fn_call.text());
-            let call = self.builder.build_call(fn_ref, arg_values, "");
+            let call = self.builder.build_call(&fn_ref, arg_values, "");
             call.set_name("");
         }
         else {
             let name = format!("call_{}", fn_call.text());
             trace!("Building call {}", name);
-            let call = self.builder.build_call(fn_ref, arg_values, &name);
+            let call = self.builder.build_call(&fn_ref, arg_values, &name);
             self.ir_code.push(call);
         };
         self.current_type = fn_return_type;
     }
 
+    fn visit_loop_expr(&mut self, loop_expr: &Loop) {
+        trace!("Checking loop");
+        let function = self.builder.insert_block().get_parent()
+            .expect("Just inserted a block");
+        let body_block = self.context.append_basic_block(&function, "loop_body");
+        let end_block = self.context.append_basic_block(&function, "loop_end");
+        self.builder.build_br(&body_block);
+
+        self.builder.position_at_end(&body_block);
+        self.begin_loop(loop_expr.label(), &body_block, &end_block);
+        self.visit_block(loop_expr.block());
+        let loop_blocks = self.loop_blocks.pop()
+            .expect("Loop blocks were removed while checking loop");
+        self.builder.build_br(&body_block);
+
+        // The loop's value comes from whichever `break` exited it.
+        self.builder.position_at_end(&end_block);
+        if loop_blocks.break_values.is_empty() {
+            self.current_type = Type::void(&self.context);
+        }
+        else {
+            trace!("Generating phi node with {} break values",
+                loop_blocks.break_values.len());
+            let phi_type = self.llvm_type_of(&loop_expr.id());
+            let phi = self.builder.build_phi(&phi_type, "loop_phi");
+            phi.add_incoming(loop_blocks.break_values,
+                             loop_blocks.break_value_blocks);
+            self.ir_code.push(phi);
+            self.current_type = phi_type;
+        }
+    }
+
+    fn visit_match_expr(&mut self, match_expr: &Match) {
+        trace!("Checking match");
+        let valued_match = match_expr.has_source();
+        let is_unsigned = self.is_unsigned(match_expr.value());
+        let is_enum = match self.concrete_type_of(match_expr.value()) {
+            Some(ConcreteType::Enum(_)) => true,
+            _ => false
+        };
+        self.visit_expression(match_expr.value());
+        let match_value = self.ir_code.pop()
+            .expect("Did not get IR value from match value");
+        // Enums are matched by the tag of their variant.
+        let match_test = if is_enum {
+            self.builder.build_extract_value(&match_value, 0, "match_tag")
+        }
+        else {
+            match_value.clone()
+        };
+        let is_float = match_value.get_type().get_kind()
+            == LLVMTypeKind::LLVMDoubleTypeKind;
+
+        let function = self.builder.insert_block().get_parent()
+            .expect("Just inserted a block");
+        let end_block = self.context.append_basic_block(&function, "match_end");
+        let mut incoming_values = Vec::new();
+        let mut incoming_blocks = Vec::new();
+
+        // Matches on single integers can jump straight to their arm.
+        let use_switch = !is_float && match_expr.arms().iter().all(|arm|
+            arm.guard().is_none() && !arm.pattern().is_range());
+        if use_switch {
+            trace!("Building switch for match");
+            let arm_blocks = match_expr.arms().iter()
+                .map(|_arm| self.context.append_basic_block(&function,
+                                                            "match_arm"))
+                .collect::<Vec<_>>();
+            let wildcard_ix = match_expr.arms().iter().position(|arm|
+                match *arm.pattern() {
+                    Pattern::Wildcard(_) => true,
+                    _ => false
+                });
+            let default_block = match wildcard_ix {
+                Some(ix) => arm_blocks[ix].clone(),
+                None => self.context.append_basic_block(&function,
+                                                        "match_none")
+            };
+            let case_count = match_expr.arms().len()
+                - wildcard_ix.map_or(0, |_ix| 1);
+            let switch = self.builder.build_switch(&match_test,
+                &default_block, case_count as u32);
+            for (arm, arm_block) in match_expr.arms().iter().zip(&arm_blocks) {
+                let case_value = match *arm.pattern() {
+                    Pattern::Literal(ref literal) =>
+                        self.build_pattern_literal(literal),
+                    Pattern::Variant(ref variant) =>
+                        self.build_variant_tag(variant),
+                    _ => continue
+                };
+                switch.add_case(&case_value, arm_block);
+            }
+            if wildcard_ix.is_none() {
+                // Matches are exhaustive, so the default is never taken.
+                self.builder.position_at_end(&default_block);
+                self.builder.build_unreachable();
+            }
+            for (arm, arm_block) in match_expr.arms().iter().zip(&arm_blocks) {
+                self.builder.position_at_end(arm_block);
+                self.build_pattern_bindings(arm.pattern(), &match_value);
+                self.build_match_arm(arm, valued_match, &end_block,
+                    &mut incoming_values, &mut incoming_blocks);
+            }
+        }
+        else {
+            // Each arm checks its pattern and guard, falling through to the
+            // next arm if either fails.
+            for arm in match_expr.arms() {
+                let arm_block = self.context.append_basic_block(&function,
+                                                                "match_arm");
+                let next_block = self.context.append_basic_block(&function,
+                                                                 "match_next");
+                let guard_block = if arm.guard().is_some() {
+                    self.context.append_basic_block(&function, "match_guard")
+                }
+                else {
+                    arm_block.clone()
+                };
+                match self.build_pattern_check(arm.pattern(), &match_test,
+                                               is_unsigned) {
+                    Some(matched) => {
+                        self.builder.build_cond_br(&matched,
+                                                   &guard_block, &next_block);
+                    },
+                    None => {
+                        self.builder.build_br(&guard_block);
+                    }
+                }
+                // Bound fields can be used by the guard.
+                self.builder.position_at_end(&guard_block);
+                self.build_pattern_bindings(arm.pattern(), &match_value);
+                if let Some(guard) = arm.guard() {
+                    trace!("Checking match guard");
+                    self.visit_expression(guard);
+                    let guard_value = self.ir_code.pop()
+                        .expect("Did not get IR value from match guard");
+                    self.builder.build_cond_br(&guard_value,
+                                               &arm_block, &next_block);
+                }
+
+                self.builder.position_at_end(&arm_block);
+                self.build_match_arm(arm, valued_match, &end_block,
+                    &mut incoming_values, &mut incoming_blocks);
+                self.builder.position_at_end(&next_block);
+            }
+            // Matches are exhaustive, so no value falls through every arm.
+            self.builder.build_unreachable();
+        }
+
+        self.builder.position_at_end(&end_block);
+        if valued_match {
+            trace!("Generating phi node with {} values",
+                incoming_values.len());
+            let phi_type = self.llvm_type_of(&match_expr.id());
+            let phi = self.builder.build_phi(&phi_type, "match_phi");
+            phi.add_incoming(incoming_values, incoming_blocks);
+            self.ir_code.push(phi);
+            self.current_type = phi_type;
+        }
+        else {
+            self.current_type = Type::void(&self.context);
+        }
+    }
+
     fn visit_if_expr(&mut self, if_expr: &IfExpression) {
         // Build conditional expr
         self.visit_expression(if_expr.condition());
@@ -41,6 +41,13 @@ impl<'ctx> SimpleModuleProvider<'ctx> {
             fn_pass_manager: pass_manager
         }
     }
+
+    /// Take the module, such as to link it into another module.
+    pub fn into_module(self) -> Module<'ctx> {
+        // The pass manager refers to the module.
+        drop(self.fn_pass_manager);
+        self.module
+    }
 }
 
 impl<'ctx> ModuleProvider<'ctx> for SimpleModuleProvider<'ctx> {
@@ -0,0 +1,66 @@
+//! Writing compiled modules to files.
+
+use llvm::{self, Module, TargetMachine};
+use llvm_sys::target_machine::{
+    LLVMCodeGenOptLevel, LLVMRelocMode, LLVMCodeModel, LLVMCodeGenFileType};
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// The kinds of files a module can be written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// An object file, i.e. `unit.o`.
+    Object,
+    /// A shared library, i.e. `libunit.so`, which exports the module's
+    /// `export fn`s. The system C compiler is used to link it.
+    SharedLibrary
+}
+
+/// Compile a module for the native target and write it to `path`.
+pub fn write_module(module: &Module, mode: OutputMode, path: &Path)
+                    -> Result<(), String> {
+    if !llvm::initialize_native_target() ||
+       !llvm::initialize_native_asm_printer() {
+        return Err("Unable to initialize the native target".to_string())
+    }
+    // Shared libraries can be loaded at any address.
+    let machine = try!(TargetMachine::native(
+        LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+        LLVMRelocMode::LLVMRelocPIC,
+        LLVMCodeModel::LLVMCodeModelDefault));
+    match mode {
+        OutputMode::Object => {
+            trace!("Writing object file {}", path.display());
+            machine.emit_to_file(module, &path.to_string_lossy(),
+                                 LLVMCodeGenFileType::LLVMObjectFile)
+        },
+        OutputMode::SharedLibrary => {
+            let object_path = path.with_extension("o");
+            trace!("Writing object file {} for shared library {}",
+                object_path.display(), path.display());
+            try!(machine.emit_to_file(module, &object_path.to_string_lossy(),
+                                      LLVMCodeGenFileType::LLVMObjectFile));
+            let linked = link_shared_library(&object_path, path);
+            let _ = fs::remove_file(&object_path);
+            linked
+        }
+    }
+}
+
+/// Link an object file into a shared library with the system C compiler.
+fn link_shared_library(object_path: &Path, path: &Path) -> Result<(), String> {
+    trace!("Linking shared library {}", path.display());
+    let output = try!(Command::new("cc")
+        .arg("-shared")
+        .arg("-o").arg(path)
+        .arg(object_path)
+        .output()
+        .map_err(|err| format!("Unable to run the linker: {}", err)));
+    if !output.status.success() {
+        return Err(format!("Unable to link {}: {}", path.display(),
+            String::from_utf8_lossy(&output.stderr)))
+    }
+    Ok(())
+}
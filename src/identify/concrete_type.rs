@@ -1,5 +1,8 @@
 //! Concrete type definitions.
 
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
 /// A fully qualified type.
 ///
 /// These are first identified in `identify/types`,
@@ -15,6 +18,158 @@ pub enum ConcreteType {
     Named(NamedType),
     /// Function types contain ordered, named arguments and a return type.
     Function(FnType),
+    /// Record types are declared `struct`s, with ordered, named fields.
+    Record(RecordType),
+    /// Enum types are declared `enum`s, whose values are one of their
+    /// ordered variants.
+    Enum(EnumType),
+    /// Tuple types contain ordered, unnamed elements.
+    Tuple(TupleType),
+    /// Array types contain a fixed number of elements of the same type.
+    Array(ArrayType),
+    /// Growable array types contain any number of elements of the same
+    /// type, which are stored on the heap.
+    Growable(GrowableType),
+    /// Slice types view the elements of an array.
+    Slice(SliceType),
+    /// Type parameters of generic functions and typedefs, which are
+    /// substituted with type arguments when they are used.
+    Parameter(NamedType),
+}
+
+impl ConcreteType {
+    /// Whether this type mentions the type parameter with the given name.
+    pub fn mentions_parameter(&self, name: &str) -> bool {
+        match *self {
+            ConcreteType::Parameter(ref param) => param.name() == name,
+            ConcreteType::Named(_) |
+            ConcreteType::Record(_) |
+            ConcreteType::Enum(_) => false,
+            ConcreteType::Tuple(ref tuple) => tuple.elements().iter()
+                .any(|element| element.mentions_parameter(name)),
+            ConcreteType::Array(ref array) =>
+                array.element().mentions_parameter(name),
+            ConcreteType::Growable(ref growable) =>
+                growable.element().mentions_parameter(name),
+            ConcreteType::Slice(ref slice) =>
+                slice.element().mentions_parameter(name),
+            ConcreteType::Function(ref fn_ty) =>
+                fn_ty.params().iter()
+                    .any(|&(_, ref param_ty)| param_ty.mentions_parameter(name))
+                || fn_ty.return_ty().mentions_parameter(name)
+        }
+    }
+
+    /// Replace the type parameters in this type with the given type
+    /// arguments. Parameters without arguments are kept.
+    pub fn substitute(&self, args: &HashMap<String, ConcreteType>)
+                      -> ConcreteType {
+        match *self {
+            ConcreteType::Parameter(ref param) =>
+                args.get(param.name()).cloned().unwrap_or_else(|| self.clone()),
+            ConcreteType::Named(_) |
+            ConcreteType::Record(_) |
+            ConcreteType::Enum(_) => self.clone(),
+            ConcreteType::Tuple(ref tuple) =>
+                ConcreteType::Tuple(TupleType::new(tuple.elements().iter()
+                    .map(|element| element.substitute(args))
+                    .collect())),
+            ConcreteType::Array(ref array) =>
+                ConcreteType::Array(ArrayType::new(
+                    array.element().substitute(args), array.len())),
+            ConcreteType::Growable(ref growable) =>
+                ConcreteType::Growable(GrowableType::new(
+                    growable.element().substitute(args))),
+            ConcreteType::Slice(ref slice) =>
+                ConcreteType::Slice(SliceType::new(
+                    slice.element().substitute(args))),
+            ConcreteType::Function(ref fn_ty) =>
+                ConcreteType::Function(FnType::new(
+                    fn_ty.params().iter()
+                        .map(|&(ref name, ref param_ty)|
+                            (name.clone(), param_ty.substitute(args)))
+                        .collect(),
+                    fn_ty.return_ty().substitute(args)))
+        }
+    }
+
+    /// Find the type arguments which make this type, which may mention type
+    /// parameters, into the `actual` type. Arguments which are already bound
+    /// must match.
+    ///
+    /// Returns `false` if the types have different shapes.
+    pub fn bind_parameters(&self, actual: &ConcreteType,
+                           args: &mut HashMap<String, ConcreteType>) -> bool {
+        match (self, actual) {
+            (&ConcreteType::Parameter(ref param), _) => {
+                if let Some(bound) = args.get(param.name()) {
+                    return bound == actual
+                }
+                args.insert(param.name().to_string(), actual.clone());
+                true
+            },
+            (&ConcreteType::Tuple(ref tuple), &ConcreteType::Tuple(ref other)) =>
+                tuple.elements().len() == other.elements().len()
+                && tuple.elements().iter().zip(other.elements())
+                    .all(|(element, other)| element.bind_parameters(other, args)),
+            (&ConcreteType::Array(ref array), &ConcreteType::Array(ref other)) =>
+                array.len() == other.len()
+                && array.element().bind_parameters(other.element(), args),
+            (&ConcreteType::Growable(ref growable),
+             &ConcreteType::Growable(ref other)) =>
+                growable.element().bind_parameters(other.element(), args),
+            (&ConcreteType::Slice(ref slice), &ConcreteType::Slice(ref other)) =>
+                slice.element().bind_parameters(other.element(), args),
+            (&ConcreteType::Function(ref fn_ty),
+             &ConcreteType::Function(ref other)) =>
+                fn_ty.params().len() == other.params().len()
+                && fn_ty.params().iter().zip(other.params())
+                    .all(|(&(ref name, ref param_ty),
+                           &(ref other_name, ref other_ty))|
+                        name == other_name
+                        && param_ty.bind_parameters(other_ty, args))
+                && fn_ty.return_ty().bind_parameters(other.return_ty(), args),
+            _ => self == actual
+        }
+    }
+}
+
+impl Display for ConcreteType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ConcreteType::Named(ref named) => f.write_str(named.name()),
+            ConcreteType::Parameter(ref param) => f.write_str(param.name()),
+            ConcreteType::Record(ref record) => f.write_str(record.name()),
+            ConcreteType::Enum(ref enum_ty) => f.write_str(enum_ty.name()),
+            ConcreteType::Tuple(ref tuple) => {
+                try!(f.write_str("("));
+                for (ix, element) in tuple.elements().iter().enumerate() {
+                    if ix != 0 {
+                        try!(f.write_str(", "));
+                    }
+                    try!(write!(f, "{}", element));
+                }
+                f.write_str(")")
+            },
+            ConcreteType::Array(ref array) =>
+                write!(f, "[{}: {}]", array.element(), array.len()),
+            ConcreteType::Growable(ref growable) =>
+                write!(f, "[{}]", growable.element()),
+            ConcreteType::Slice(ref slice) =>
+                write!(f, "&[{}]", slice.element()),
+            ConcreteType::Function(ref fn_ty) => {
+                try!(f.write_str("fn("));
+                for (ix, &(ref name, ref param_ty)) in
+                        fn_ty.params().iter().enumerate() {
+                    if ix != 0 {
+                        try!(f.write_str(", "));
+                    }
+                    try!(write!(f, "{}: {}", name, param_ty));
+                }
+                write!(f, ") -> {}", fn_ty.return_ty())
+            }
+        }
+    }
 }
 
 /// A named type.
@@ -51,3 +206,128 @@ impl FnType {
         &*self.ret
     }
 }
+
+/// A record type, declared as a `struct`.
+///
+/// The types of the fields are kept so that later passes do not need to
+/// look up the declaration of the struct.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct RecordType {
+    name: String,
+    fields: Vec<(String, ConcreteType)>
+}
+impl RecordType {
+    pub fn new(name: String, fields: Vec<(String, ConcreteType)>) -> RecordType {
+        RecordType { name, fields }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn fields(&self) -> &[(String, ConcreteType)] {
+        &self.fields
+    }
+    /// Find the index and type of the field with the given name.
+    pub fn field(&self, name: &str) -> Option<(usize, &ConcreteType)> {
+        self.fields.iter()
+            .position(|&(ref field_name, _)| field_name == name)
+            .map(|ix| (ix, &self.fields[ix].1))
+    }
+}
+
+/// An enum type, declared as an `enum`.
+///
+/// Each variant is a `RecordType` named after the variant, whose fields are
+/// the fields carried by the variant. A variant's tag is its index.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct EnumType {
+    name: String,
+    variants: Vec<RecordType>
+}
+impl EnumType {
+    pub fn new(name: String, variants: Vec<RecordType>) -> EnumType {
+        EnumType { name, variants }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn variants(&self) -> &[RecordType] {
+        &self.variants
+    }
+    /// Find the tag and fields of the variant with the given name.
+    pub fn variant(&self, name: &str) -> Option<(usize, &RecordType)> {
+        self.variants.iter()
+            .position(|variant| variant.name() == name)
+            .map(|ix| (ix, &self.variants[ix]))
+    }
+}
+
+/// A tuple type, such as `(int, bool)`.
+///
+/// Tuple types are not declared, so two tuple types are the same if their
+/// elements are.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct TupleType {
+    elements: Vec<ConcreteType>
+}
+impl TupleType {
+    pub fn new(elements: Vec<ConcreteType>) -> TupleType {
+        TupleType { elements }
+    }
+    pub fn elements(&self) -> &[ConcreteType] {
+        &self.elements
+    }
+    /// Find the element at the index written as a field, such as `0`.
+    pub fn element(&self, index: &str) -> Option<(usize, &ConcreteType)> {
+        index.parse::<usize>().ok()
+            .and_then(|ix| self.elements.get(ix).map(|element| (ix, element)))
+    }
+}
+
+/// A fixed-size array type, such as `[int: 5]`.
+///
+/// Like tuple types, two array types are the same if their elements and
+/// lengths are.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ArrayType {
+    element: Box<ConcreteType>,
+    len: u64
+}
+impl ArrayType {
+    pub fn new(element: ConcreteType, len: u64) -> ArrayType {
+        ArrayType { element: Box::new(element), len }
+    }
+    pub fn element(&self) -> &ConcreteType {
+        &self.element
+    }
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+/// A growable array type, such as `[int]`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct GrowableType {
+    element: Box<ConcreteType>
+}
+impl GrowableType {
+    pub fn new(element: ConcreteType) -> GrowableType {
+        GrowableType { element: Box::new(element) }
+    }
+    pub fn element(&self) -> &ConcreteType {
+        &self.element
+    }
+}
+
+/// A slice type, such as `&[int]`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct SliceType {
+    element: Box<ConcreteType>
+}
+impl SliceType {
+    pub fn new(element: ConcreteType) -> SliceType {
+        SliceType { element: Box::new(element) }
+    }
+    pub fn element(&self) -> &ConcreteType {
+        &self.element
+    }
+}
@@ -44,11 +44,14 @@
 mod names;
 mod concrete_type;
 mod types;
-pub use self::types::{TypeGraph, InferenceSource, ASTTypeChecker};
+pub use self::types::{TypeGraph, InferenceSource, ASTTypeChecker,
+                      int_type_range};
 mod scope_builder;
 mod type_scope_builder;
+mod module_interface;
 pub use self::scope_builder::{ScopeBuilder, NameScopeBuilder};
 pub use self::type_scope_builder::TypeScopeBuilder;
+pub use self::module_interface::{ModuleInterface, module_symbol};
 pub use self::concrete_type::*;
 pub use self::names::OriginManager;
 
@@ -86,13 +89,15 @@ impl<'var_scope, 'ty_scope, 'err> UnitVisitor
         // This could be handled by ScopeBuilder.
         let first_id = ScopedId::default().pushed();
         debug!("Calling ItemVarIdentifier");
-        ItemVarIdentifier::new(self.errors, self.var_scope, first_id.clone())
+        ItemVarIdentifier::new(self.errors, self.var_scope,
+                               self.type_scope.modules(), first_id.clone())
                           .visit_unit(unit);
         debug!("Calling ItemTypeIdentifier");
         ItemTypeIdentifier::new(self.errors, self.type_scope)
                            .visit_unit(unit);
         debug!("Calling ExpressionVarIdentifier");
-        ExpressionVarIdentifier::new(self.errors, self.var_scope, first_id)
+        ExpressionVarIdentifier::new(self.errors, self.var_scope,
+                                     self.type_scope.modules(), first_id)
                                 .visit_unit(unit);
         debug!("Calling ExprTypeIdentifier");
         ExprTypeIdentifier::new(self.errors, self.type_scope)
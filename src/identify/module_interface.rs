@@ -0,0 +1,105 @@
+//! The items which a module provides to the units importing it.
+
+use std::collections::{HashMap, HashSet};
+
+use identify::{ConcreteType, FnType};
+
+/// The items of a checked module which other units can import.
+///
+/// `pub` functions, structs, and enums can be imported. The variants of an
+/// enum are imported along with it, and the methods of a struct or enum can
+/// be called on its values by any unit importing the module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleInterface {
+    name: String,
+    /// Types of the module's items, in the order they're declared.
+    items: Vec<(String, ConcreteType)>,
+    /// Names of the module's functions in its compiled LLVM module.
+    symbols: HashMap<String, String>,
+    /// Types and linked names of the methods of the module's `pub` types.
+    methods: HashMap<(ConcreteType, String), (FnType, String)>,
+    /// Names of the items which are not `pub`.
+    private: HashSet<String>
+}
+
+impl ModuleInterface {
+    pub fn new(name: String) -> ModuleInterface {
+        ModuleInterface {
+            name,
+            items: Vec::new(),
+            symbols: HashMap::new(),
+            methods: HashMap::new(),
+            private: HashSet::new()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add a struct or enum of the module.
+    pub fn add_type(&mut self, name: String, ty: ConcreteType) {
+        self.items.push((name, ty));
+    }
+
+    /// Add a function of the module, which is linked as `symbol`.
+    pub fn add_fn(&mut self, name: String, fn_type: FnType, symbol: String) {
+        self.symbols.insert(name.clone(), symbol);
+        self.items.push((name, ConcreteType::Function(fn_type)));
+    }
+
+    /// Add a method of one of the module's types, which is linked as
+    /// `symbol`.
+    pub fn add_method(&mut self, self_ty: ConcreteType, name: String,
+                      fn_type: FnType, symbol: String) {
+        self.methods.insert((self_ty, name), (fn_type, symbol));
+    }
+
+    /// Add an item of the module which cannot be imported.
+    pub fn add_private(&mut self, name: String) {
+        self.private.insert(name);
+    }
+
+    /// Whether the module has an item which cannot be imported.
+    pub fn is_private(&self, name: &str) -> bool {
+        self.private.contains(name)
+    }
+
+    pub fn items(&self) -> &[(String, ConcreteType)] {
+        &self.items
+    }
+
+    /// Get the type of an item, or the type of the enum a variant is part of.
+    pub fn item(&self, name: &str) -> Option<&ConcreteType> {
+        self.items.iter()
+            .find(|&&(ref item_name, _)| item_name == name)
+            .or_else(|| self.items.iter().find(|&&(_, ref ty)| match *ty {
+                ConcreteType::Enum(ref enum_ty) =>
+                    enum_ty.variant(name).is_some(),
+                _ => false
+            }))
+            .map(|&(_, ref ty)| ty)
+    }
+
+    /// Get the type and linked name of a method of one of the module's types.
+    pub fn method(&self, self_ty: &ConcreteType, name: &str)
+                  -> Option<&(FnType, String)> {
+        self.methods.get(&(self_ty.clone(), name.to_string()))
+    }
+
+    pub fn methods(&self) -> &HashMap<(ConcreteType, String), (FnType, String)> {
+        &self.methods
+    }
+
+    /// Get the name of a function in the module's compiled LLVM module.
+    pub fn symbol(&self, name: &str) -> Option<&str> {
+        self.symbols.get(name).map(String::as_str)
+    }
+}
+
+/// The name of a function of a module in its LLVM module, such as
+/// `math::square`, which keeps it from conflicting with the functions of
+/// other modules once they're linked.
+pub fn module_symbol(module: &str, name: &str) -> String {
+    format!("{}::{}", module, name)
+}
@@ -2,14 +2,19 @@
 
 use lex::Span;
 use ast::{*, visit::*};
-use identify::{NameScopeBuilder, OriginManager};
+use identify::{NameScopeBuilder, OriginManager, ModuleInterface};
 use check::{CheckerError, ErrorCollector};
 
+use std::collections::{HashMap, HashSet};
+
 /// Identifies variables in blocks.
 #[derive(Debug)]
-pub struct ExpressionVarIdentifier<'err, 'builder> {
+pub struct ExpressionVarIdentifier<'err, 'builder, 'modules> {
     errors: &'err mut ErrorCollector,
     builder: &'builder mut NameScopeBuilder,
+    /// Modules imported by the unit, for reporting uses of their private
+    /// items.
+    modules: &'modules HashMap<String, ModuleInterface>,
     /// `ScopedId` to give to expressions
     current_id: ScopedId,
     /// `ScopedId` of the current function which we
@@ -17,28 +22,255 @@ pub struct ExpressionVarIdentifier<'err, 'builder> {
     /// Stack of lvalues which can be assigned to the current expression.
     /// For example, a block in a function which returns a value would have
     /// an lvalue of the function's ID.
-    lvalues: OriginManager
+    lvalues: OriginManager,
+    /// Loops the current statement is inside of, innermost last.
+    loops: Vec<LoopScope>,
+    /// Variables which were declared `mut` and can be assigned to.
+    mutable_vars: HashSet<ScopedId>,
+    /// Lambdas the current expression is inside of, innermost last.
+    closures: Vec<ClosureScope>
 }
-impl<'err, 'builder> ExpressionVarIdentifier<'err, 'builder> {
+impl<'err, 'builder, 'modules> ExpressionVarIdentifier<'err, 'builder, 'modules> {
     pub fn new(errors: &'err mut ErrorCollector,
                builder: &'builder mut NameScopeBuilder,
+               modules: &'modules HashMap<String, ModuleInterface>,
                current_id: ScopedId)
-               -> ExpressionVarIdentifier<'err, 'builder> {
+               -> ExpressionVarIdentifier<'err, 'builder, 'modules> {
         ExpressionVarIdentifier {
             errors,
             builder,
+            modules,
             current_id,
             current_fn_id: ScopedId::default(),
-            lvalues: OriginManager::new()
+            lvalues: OriginManager::new(),
+            loops: Vec::new(),
+            mutable_vars: HashSet::new(),
+            closures: Vec::new()
+        }
+    }
+
+    /// Record that the variable is used by the lambdas it's declared outside
+    /// of, and return whether the innermost lambda captures it.
+    ///
+    /// Items in the global scope are not captured.
+    fn capture(&mut self, ident: &Identifier) -> bool {
+        let level = match self.builder.scope_level(ident.name()) {
+            Some(level) if level > 0 => level,
+            _ => return false
+        };
+        let var_id = ident.id().clone();
+        for closure in self.closures.iter_mut().filter(|c| c.depth > level) {
+            if !closure.captures.iter().any(|c| *c.id() == var_id) {
+                trace!("Capturing {} in a lambda", ident.name());
+                closure.captures.push(ident.clone());
+            }
+        }
+        self.closures.last().map_or(false, |closure| closure.depth > level)
+    }
+
+    /// Report the use of an item of an imported module which is not `pub`,
+    /// such as `math::helper`, and return whether it was one.
+    fn check_private_item(&mut self, name: &str, span: Span) -> bool {
+        let mut path = name.splitn(2, "::");
+        let (module, item) = match (path.next(), path.next()) {
+            (Some(module), Some(item)) => (module, item),
+            _ => return false
+        };
+        match self.modules.get(module) {
+            Some(interface) if interface.is_private(item) => {
+                debug!("Emitting error: {} is private", name);
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Item {} of module {} is not pub", item, module)
+                ));
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Whether the variable is captured by the innermost lambda.
+    fn is_captured(&self, var_id: &ScopedId) -> bool {
+        self.closures.last().map_or(false, |closure|
+            closure.captures.iter().any(|c| *c.id() == *var_id))
+    }
+
+    /// Enter a loop which `break` and `continue` can refer to.
+    ///
+    /// `loop_id` is given for `loop` expressions, which `break` can give a
+    /// value to.
+    fn begin_loop(&mut self, label: Option<&Identifier>,
+                  loop_id: Option<ScopedId>) {
+        if let Some(label) = label {
+            let existing = self.loops.iter()
+                .filter_map(|scope| scope.label.as_ref())
+                .find(|existing| existing.name() == label.name())
+                .map(|existing| existing.span());
+            if let Some(existing_span) = existing {
+                debug!("Found a reused loop label");
+                self.errors.add_error(CheckerError::new(
+                    vec![label.span(), existing_span],
+                    format!("Loop label '{} is already in use", label.name())
+                ));
+            }
+        }
+        self.loops.push(LoopScope {
+            label: label.cloned(),
+            loop_id,
+            value_break: None,
+            empty_break: None
+        });
+    }
+
+    fn end_loop(&mut self) -> LoopScope {
+        self.loops.pop().expect("Ended a loop which was not begun")
+    }
+
+    /// Check that the variable whose array is changed, such as by assigning
+    /// to an element, was declared `mut`.
+    ///
+    /// Arrays which are not in a variable can't be changed.
+    fn check_mutable_root(&mut self, root: Option<&Identifier>, span: Span,
+                          action: &str, temporary_message: &str) {
+        match root {
+            Some(root) => {
+                let root_id = root.id().clone();
+                if self.is_captured(&root_id) {
+                    debug!("Found change to captured var {}", root.name());
+                    self.errors.add_error(CheckerError::new(
+                        vec![span],
+                        format!("Cannot {} captured variable {}",
+                            action, root.name())
+                    ));
+                }
+                // Unknown variables have already been reported.
+                else if !root_id.is_default() && !self.mutable_vars.contains(&root_id) {
+                    debug!("Found change to immutable var {}", root.name());
+                    let mut spans = vec![span];
+                    if let Some(origin_span) = self.builder.info_for(&root_id) {
+                        spans.push(*origin_span);
+                    }
+                    self.errors.add_error(CheckerError::new(
+                        spans,
+                        format!("Cannot {} immutable variable {}",
+                            action, root.name())
+                    ));
+                }
+            },
+            None => {
+                debug!("Found change to a temporary array");
+                self.errors.add_error(CheckerError::new(
+                    vec![span], temporary_message.to_string()
+                ));
+            }
+        }
+    }
+
+    /// Find the index of the loop that a `break` or `continue` refers to.
+    fn target_loop(&mut self, keyword: &str, label: Option<&Identifier>,
+                   span: Span) -> Option<usize> {
+        if self.loops.is_empty() {
+            debug!("Found {} outside of a loop", keyword);
+            self.errors.add_error(CheckerError::new(
+                vec![span],
+                format!("`{}` used outside of a loop", keyword)
+            ));
+            return None
+        }
+        let label = match label {
+            Some(label) => label,
+            None => return Some(self.loops.len() - 1)
+        };
+        let found = self.loops.iter().rposition(|scope|
+            scope.label.as_ref()
+                .map_or(false, |existing| existing.name() == label.name()));
+        if found.is_none() {
+            debug!("Found {} to unknown label {}", keyword, label.name());
+            self.errors.add_error(CheckerError::new(
+                vec![label.span()],
+                format!("Unknown loop label '{}", label.name())
+            ));
+        }
+        found
+    }
+
+    /// Identify the variant of a pattern, and define the variables its
+    /// fields are bound to in the current scope.
+    fn identify_variant_pattern(&mut self, variant: &VariantPattern) {
+        trace!("Identifying variant pattern {}", variant.name());
+        if let Some(variant_id) = self.builder.get(variant.name()).cloned() {
+            variant.ident().set_id(variant_id);
+        }
+        else {
+            debug!("Emitting error: unknown variant {}", variant.name());
+            self.errors.add_error(CheckerError::new(
+                vec![variant.ident().span()],
+                format!("Unknown variant {}", variant.name())
+            ));
+        }
+        for field in variant.fields() {
+            let binding = match field.binding() {
+                Some(binding) => binding,
+                None => continue
+            };
+            if let Some(var_id) = self.builder.get(binding.name()) {
+                let origin_span = self.builder.info_for(var_id)
+                    .expect("checked expect");
+                debug!("Found an already defined pattern variable");
+                self.errors.add_error(CheckerError::new(
+                    vec![binding.span(), *origin_span],
+                    format!("Variable {} is already declared", binding.name())
+                ));
+                continue
+            }
+            let var_id = self.current_id.clone();
+            self.builder.define_local(binding.name().into(),
+                                      var_id.clone(),
+                                      binding.span());
+            trace!("Created id {:?} for pattern var {}", var_id, binding.name());
+            binding.set_id(var_id);
+            self.current_id.increment();
         }
     }
 }
 
-impl<'err, 'builder> UnitVisitor for ExpressionVarIdentifier<'err, 'builder> {
+/// A lambda whose body is being identified.
+#[derive(Debug)]
+struct ClosureScope {
+    /// Number of builder scopes outside of the lambda.
+    depth: usize,
+    /// Variables from outside of the lambda which it uses.
+    captures: Vec<Identifier>
+}
+
+/// A loop which `break` and `continue` may refer to.
+#[derive(Debug)]
+struct LoopScope {
+    label: Option<Identifier>,
+    /// The `ScopedId` of a `loop` expression.
+    loop_id: Option<ScopedId>,
+    /// The first `break` which gave the loop a value.
+    value_break: Option<Span>,
+    /// The first `break` which did not give the loop a value.
+    empty_break: Option<Span>
+}
+
+impl<'err, 'builder, 'modules> UnitVisitor
+    for ExpressionVarIdentifier<'err, 'builder, 'modules> {
+
     fn visit_unit(&mut self, unit: &Unit) {
         trace!("Visiting a unit");
         self.builder.new_scope();
 
+        // Functions can assign to `mut` globals declared after them.
+        for item in unit.items() {
+            if let Item::GlobalDeclaration(ref global) = *item {
+                if global.is_mut() && !global.id().is_default() {
+                    self.mutable_vars.insert(global.id().clone());
+                }
+            }
+        }
+
         // Keep the current_id and builder scope in line with the functions.
         visit::walk_unit(self, unit);
 
@@ -47,7 +279,9 @@ impl<'err, 'builder> UnitVisitor for ExpressionVarIdentifier<'err, 'builder> {
     }
 }
 
-impl<'err, 'builder> ItemVisitor for ExpressionVarIdentifier<'err, 'builder> {
+impl<'err, 'builder, 'modules> ItemVisitor
+    for ExpressionVarIdentifier<'err, 'builder, 'modules> {
+
     fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
         trace!("Visiting fn definition {}", block_fn.name());
         if block_fn.id().is_default() {
@@ -97,12 +331,73 @@ impl<'err, 'builder> ItemVisitor for ExpressionVarIdentifier<'err, 'builder> {
         // pushing handled by `visit_block`, we reset current_id on next item.
     }
 
+    fn visit_extern_fn_decl(&mut self, _extern_fn: &ExternFnDeclaration) {
+        // skip, only visiting expressions
+    }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) {
+        // skip, only visiting expressions
+    }
+
+    fn visit_import(&mut self, _import: &Import) {
+        // skip, only visiting expressions
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting const {}", const_decl.name());
+        if const_decl.id().is_default() {
+            debug!("Skipping const {} because it has no ID",
+                const_decl.name());
+            return
+        }
+        self.current_id = const_decl.id().clone();
+        self.current_id.push();
+        self.builder.new_scope();
+        self.lvalues.add_source(const_decl.id().clone());
+        self.visit_expression(const_decl.value());
+        if self.lvalues.has_top_source(&const_decl.id()) {
+            self.lvalues.pop_source();
+        }
+        self.builder.pop();
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting global {}", global.name());
+        if global.id().is_default() {
+            debug!("Skipping global {} because it has no ID", global.name());
+            return
+        }
+        self.current_id = global.id().clone();
+        self.current_id.push();
+        self.builder.new_scope();
+        self.lvalues.add_source(global.id().clone());
+        self.visit_expression(global.value());
+        if self.lvalues.has_top_source(&global.id()) {
+            self.lvalues.pop_source();
+        }
+        self.builder.pop();
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        visit::walk_impl_block(self, impl_block);
+    }
+
     fn visit_typedef(&mut self, _typedef: &Typedef) {
         // skip, only visiting expressions
     }
+
+    fn visit_struct_decl(&mut self, _struct_decl: &StructDeclaration) {
+        // skip, only visiting expressions
+    }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDeclaration) {
+        // skip, only visiting expressions
+    }
 }
 
-impl<'err, 'builder> BlockVisitor for ExpressionVarIdentifier<'err, 'builder> {
+impl<'err, 'builder, 'modules> BlockVisitor
+    for ExpressionVarIdentifier<'err, 'builder, 'modules> {
+
     fn visit_block(&mut self, block: &Block) {
         trace!("Visiting a block");
         // Give blocks scoped IDs in line with the current block
@@ -163,8 +458,8 @@ impl<'err, 'builder> BlockVisitor for ExpressionVarIdentifier<'err, 'builder> {
     }
 }
 
-impl<'err, 'builder> StatementVisitor
-    for ExpressionVarIdentifier<'err, 'builder> {
+impl<'err, 'builder, 'modules> StatementVisitor
+    for ExpressionVarIdentifier<'err, 'builder, 'modules> {
 
     fn visit_do_block(&mut self, do_block: &DoBlock) {
         trace!("Visiting do block");
@@ -221,6 +516,114 @@ impl<'err, 'builder> StatementVisitor
         self.current_id.increment();
     }
 
+    fn visit_while_block(&mut self, while_block: &WhileBlock) {
+        trace!("Visiting while block");
+        if self.lvalues.has_source() {
+            debug!("While block used as a value");
+            self.errors.add_error(CheckerError::new(
+                vec![while_block.span()],
+                format!("While loops do not have a value")
+            ));
+        }
+        // Neither the condition nor the loop body return a value.
+        self.lvalues.begin_block();
+        self.visit_expression(while_block.condition());
+        self.begin_loop(while_block.label(), None);
+        self.visit_block(while_block.block());
+        self.end_loop();
+        self.lvalues.end_block();
+    }
+
+    fn visit_for_loop(&mut self, for_loop: &ForLoop) {
+        trace!("Visiting for loop");
+        if self.lvalues.has_source() {
+            debug!("For loop used as a value");
+            self.errors.add_error(CheckerError::new(
+                vec![for_loop.span()],
+                format!("For loops do not have a value")
+            ));
+        }
+        self.lvalues.begin_block();
+        // The range is evaluated outside of the loop variable's scope.
+        visit::walk_range(self, for_loop.range());
+
+        let ident = for_loop.ident();
+        if let Some(var_id) = self.builder.get(ident.name()) {
+            let origin_span = self.builder.info_for(var_id)
+                .expect("checked expect");
+            debug!("Found an already defined loop variable");
+            let err_text = format!("Variable {} is already declared",
+                ident.name());
+            self.errors.add_error(CheckerError::new(
+                vec![ident.span(), *origin_span], err_text
+            ));
+            self.lvalues.end_block();
+            return
+        }
+
+        // The loop variable is defined in its own scope around the block.
+        let var_id = self.current_id.clone();
+        self.builder.new_scope();
+        self.builder.define_local(ident.name().into(),
+                                  var_id.clone(),
+                                  ident.span());
+        trace!("Created id {:?} for loop var {}", var_id, ident.name());
+        ident.set_id(var_id);
+        self.current_id.increment();
+
+        self.begin_loop(for_loop.label(), None);
+        self.visit_block(for_loop.block());
+        self.end_loop();
+
+        self.builder.pop();
+        self.lvalues.end_block();
+    }
+
+    fn visit_break_stmt(&mut self, break_: &Break) {
+        trace!("Visiting break statement");
+        let target = self.target_loop("break", break_.label(), break_.span());
+        let loop_id = target.and_then(|ix| self.loops[ix].loop_id.clone());
+        let value = match break_.value() {
+            Some(value) => value,
+            None => {
+                if let Some(ix) = target {
+                    let scope = &mut self.loops[ix];
+                    scope.empty_break = scope.empty_break.or(Some(break_.span()));
+                }
+                return
+            }
+        };
+        match loop_id {
+            Some(loop_id) => {
+                trace!("Adding loop id source to break value");
+                let ix = target.expect("Loop id came from target");
+                let scope = &mut self.loops[ix];
+                scope.value_break = scope.value_break.or(Some(break_.span()));
+                break_.set_source(loop_id.clone());
+                self.lvalues.add_source(loop_id.clone());
+                self.visit_expression(value);
+                if self.lvalues.has_top_source(&loop_id) {
+                    self.lvalues.pop_source();
+                }
+            },
+            None => {
+                if target.is_some() {
+                    debug!("Found break with value out of a non-loop");
+                    self.errors.add_error(CheckerError::new(
+                        vec![break_.span()],
+                        format!("`break` can only give a value to `loop`")
+                    ));
+                }
+                self.visit_expression(value);
+            }
+        }
+    }
+
+    fn visit_continue_stmt(&mut self, continue_: &Continue) {
+        trace!("Visiting continue statement");
+        self.target_loop("continue", continue_.label(), continue_.span());
+    }
+
     fn visit_declaration(&mut self, declaration: &Declaration) {
         trace!("Visiting declaration of {}", declaration.name());
         let lvalue = declaration.ident();
@@ -238,15 +641,56 @@ impl<'err, 'builder> StatementVisitor
             ));
             return
         }
+        // The variable's ID is taken before visiting the rvalue so the rvalue
+        // can be sourced to it, like an assignment's is.
+        let decl_id = self.current_id.clone();
+        self.current_id.increment();
         trace!("Checking rvalue");
+        self.lvalues.add_source(decl_id.clone());
         self.visit_expression(declaration.value());
-        let decl_id = self.current_id.clone();
+        if self.lvalues.has_top_source(&decl_id) {
+            self.lvalues.pop_source();
+        }
         self.builder.define_local(declaration.name().into(),
                                   decl_id.clone(),
                                   declaration.span());
         trace!("Created id {:?} for var {}", decl_id, lvalue.name());
+        if declaration.is_mut() {
+            self.mutable_vars.insert(decl_id.clone());
+        }
         lvalue.set_id(decl_id);
-        self.current_id.increment();
+    }
+
+    fn visit_tuple_declaration(&mut self, tuple_decl: &TupleDeclaration) {
+        trace!("Visiting tuple declaration");
+        // The tuple is not the value of any of the declared variables.
+        self.lvalues.begin_block();
+        self.visit_expression(tuple_decl.value());
+        self.lvalues.end_block();
+        for binding in tuple_decl.bindings().iter()
+                .filter_map(|binding| binding.as_ref()) {
+            if let Some(var_id) = self.builder.get(binding.name()) {
+                let origin_span = self.builder.info_for(var_id)
+                    .expect("checked expect");
+                debug!("Found an already defined variable");
+                let err_text = format!("Variable {} is already declared",
+                    binding.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![binding.span(), *origin_span], err_text
+                ));
+                continue
+            }
+            let binding_id = self.current_id.clone();
+            self.current_id.increment();
+            self.builder.define_local(binding.name().into(),
+                                      binding_id.clone(),
+                                      binding.span());
+            trace!("Created id {:?} for var {}", binding_id, binding.name());
+            if tuple_decl.is_mut() {
+                self.mutable_vars.insert(binding_id.clone());
+            }
+            binding.set_id(binding_id);
+        }
     }
 
     fn visit_return_stmt(&mut self, return_stmt: &Return) {
@@ -262,15 +706,152 @@ impl<'err, 'builder> StatementVisitor
     }
 }
 
-impl<'err, 'builder> ExpressionVisitor
-    for ExpressionVarIdentifier<'err, 'builder> {
+impl<'err, 'builder, 'modules> ExpressionVisitor
+    for ExpressionVarIdentifier<'err, 'builder, 'modules> {
 
-    fn visit_literal_expr(&mut self, _literal: &Literal) { }
+    fn visit_literal_expr(&mut self, literal: &Literal) {
+        // Literals are given IDs so their types can be inferred.
+        literal.set_id(self.current_id.clone());
+        self.current_id.increment();
+    }
 
     fn visit_if_expr(&mut self, if_expr: &IfExpression) {
         visit::walk_if_expr(self, if_expr);
     }
 
+    fn visit_match_expr(&mut self, match_expr: &Match) {
+        trace!("Visiting match");
+        match_expr.set_id(self.current_id.clone());
+        self.current_id.increment();
+
+        let has_lvalue = self.lvalues.has_source();
+        if has_lvalue {
+            trace!("Found expression match");
+            let source = self.lvalues.pop_source().expect("Checked expect");
+            match_expr.set_source(source);
+        }
+
+        self.lvalues.begin_block();
+        self.visit_expression(match_expr.value());
+        self.lvalues.end_block();
+
+        // Each arm is identified in its own scope.
+        for arm in match_expr.arms() {
+            self.builder.new_scope();
+            visit::walk_pattern(self, arm.pattern());
+            if let Pattern::Variant(ref variant) = *arm.pattern() {
+                self.identify_variant_pattern(variant);
+            }
+            if let Some(guard) = arm.guard() {
+                self.lvalues.begin_block();
+                self.visit_expression(guard);
+                self.lvalues.end_block();
+            }
+            if has_lvalue {
+                trace!("Mapping arm to match");
+                self.lvalues.add_source(match_expr.id().clone());
+            }
+            else {
+                self.lvalues.begin_block();
+            }
+            self.visit_block(arm.block());
+            // Block visiting pops the source of valued arms.
+            if !has_lvalue {
+                self.lvalues.end_block();
+            }
+            self.builder.pop();
+        }
+    }
+
+    fn visit_loop_expr(&mut self, loop_expr: &Loop) {
+        trace!("Visiting loop");
+        loop_expr.set_id(self.current_id.clone());
+        self.current_id.increment();
+
+        // The loop body doesn't return a value, its `break`s do.
+        self.lvalues.begin_block();
+        self.begin_loop(loop_expr.label(), Some(loop_expr.id().clone()));
+        self.visit_block(loop_expr.block());
+        let scope = self.end_loop();
+        self.lvalues.end_block();
+
+        if let (Some(value_span), Some(empty_span)) =
+                (scope.value_break, scope.empty_break) {
+            debug!("Found loop with breaks with and without values");
+            self.errors.add_error(CheckerError::new(
+                vec![empty_span, value_span],
+                format!("Loop has `break`s both with and without a value")
+            ));
+        }
+    }
+
+    fn visit_field_access(&mut self, field_access: &FieldAccess) {
+        // The struct value is not the value of any lvalue.
+        self.lvalues.begin_block();
+        visit::walk_field_access(self, field_access);
+        self.lvalues.end_block();
+    }
+
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate) {
+        self.lvalues.begin_block();
+        visit::walk_struct_update(self, struct_update);
+        self.lvalues.end_block();
+    }
+
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression) {
+        // The elements are not the value of any lvalue.
+        self.lvalues.begin_block();
+        visit::walk_tuple_expr(self, tuple);
+        self.lvalues.end_block();
+    }
+
+    fn visit_array_expr(&mut self, array: &ArrayExpression) {
+        // Arrays are given IDs so their types can be inferred.
+        array.set_id(self.current_id.clone());
+        self.current_id.increment();
+        // The elements are not the value of any lvalue.
+        self.lvalues.begin_block();
+        visit::walk_array_expr(self, array);
+        self.lvalues.end_block();
+    }
+
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat) {
+        repeat.set_id(self.current_id.clone());
+        self.current_id.increment();
+        self.lvalues.begin_block();
+        self.visit_expression(repeat.value());
+        self.lvalues.end_block();
+    }
+
+    fn visit_index_expr(&mut self, index: &IndexExpression) {
+        self.lvalues.begin_block();
+        visit::walk_index_expr(self, index);
+        self.lvalues.end_block();
+    }
+
+    fn visit_slice_expr(&mut self, slice: &SliceExpression) {
+        // Slices are given IDs so their types can be checked.
+        slice.set_id(self.current_id.clone());
+        self.current_id.increment();
+        self.lvalues.begin_block();
+        visit::walk_slice_expr(self, slice);
+        self.lvalues.end_block();
+    }
+
+    fn visit_method_call(&mut self, method_call: &MethodCall) {
+        trace!("Visiting call of method {}", method_call.method().name());
+        self.lvalues.begin_block();
+        visit::walk_method_call(self, method_call);
+        self.lvalues.end_block();
+        // The builtin methods of growable arrays change them.
+        let method = method_call.method().name();
+        if method == "push" || method == "pop" {
+            let action = if method == "push" { "push to" } else { "pop from" };
+            self.check_mutable_root(method_call.root_var(), method_call.span(),
+                action, "Only growable array variables can be changed");
+        }
+    }
+
     fn visit_unary_op(&mut self, un_op: &UnaryOperation) {
         visit::walk_unary_op(self, un_op);
     }
@@ -283,8 +864,24 @@ impl<'err, 'builder> ExpressionVisitor
         trace!("Visiting assignment to {}", assign.lvalue().name());
         // Give the required rvalue to the expression
         // Enables https://github.com/immington-industries/protosnirk/issues/27
-        let lvalue_id = assign.lvalue().id().clone();
-        if lvalue_id.is_default() {
+        let lvalue = assign.lvalue();
+        if let Some(var_id) = self.builder.get(lvalue.name()).cloned() {
+            lvalue.set_id(var_id);
+        }
+        let lvalue_id = lvalue.id().clone();
+        if !lvalue_id.is_default() && self.capture(lvalue) {
+            debug!("Found assignment to captured var");
+            let mut spans = vec![assign.span()];
+            if let Some(origin_span) = self.builder.info_for(&lvalue_id) {
+                spans.push(*origin_span);
+            }
+            self.errors.add_error(CheckerError::new(
+                spans,
+                format!("Cannot assign to captured variable {}",
+                    assign.lvalue().name())
+            ));
+        }
+        else if lvalue_id.is_default() {
             trace!("Found assignment to unknown var");
             let error_message = format!(
                 "Unknown variable {} to assign to",
@@ -295,20 +892,43 @@ impl<'err, 'builder> ExpressionVisitor
                 error_message
             ));
         }
+        else if !self.mutable_vars.contains(&lvalue_id) {
+            debug!("Found assignment to immutable var");
+            let mut spans = vec![assign.span()];
+            if let Some(origin_span) = self.builder.info_for(&lvalue_id) {
+                spans.push(*origin_span);
+            }
+            self.errors.add_error(CheckerError::new(
+                spans,
+                format!("Cannot assign to immutable variable {}",
+                    assign.lvalue().name())
+            ));
+        }
         self.lvalues.add_source(lvalue_id);
         self.visit_expression(assign.rvalue());
         if self.lvalues.has_top_source(&assign.lvalue().id()) {
             self.lvalues.pop_source();
         }
-        self.visit_var_ref(assign.lvalue());
+    }
+
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment) {
+        trace!("Visiting assignment to an element");
+        self.visit_index_expr(assign.lvalue());
+        self.check_mutable_root(assign.lvalue().root_var(), assign.span(),
+            "assign to",
+            "Only elements of array variables can be assigned to");
+        self.lvalues.begin_block();
+        self.visit_expression(assign.rvalue());
+        self.lvalues.end_block();
     }
 
     fn visit_var_ref(&mut self, ident: &Identifier) {
         trace!("Visiting reference to {}", ident.name());
         if let Some(var_id) = self.builder.get(ident.name()).cloned() {
             ident.set_id(var_id);
+            self.capture(ident);
         }
-        else {
+        else if !self.check_private_item(ident.name(), ident.span()) {
             debug!("Emitting error: unknown ident {}", ident.name());
             // Unknown var
             let err_text = format!("Unknown reference to {}",
@@ -323,12 +943,16 @@ impl<'err, 'builder> ExpressionVisitor
         if let Some(fn_id) = self.builder.get(fn_call.text()).cloned() {
             // Set fn ident
             fn_call.ident().set_id(fn_id);
+            self.capture(fn_call.ident());
+            // Type arguments of generic fns are scoped under the call
+            fn_call.set_call_id(self.current_id.clone());
+            self.current_id.increment();
             // Check args
             for arg in fn_call.args() {
                 self.visit_expression(arg.expression());
             }
         }
-        else {
+        else if !self.check_private_item(fn_call.text(), fn_call.span()) {
             // Args are not checked if name is not known
             let err_text = format!("Unknown function {}", fn_call.text());
             self.errors.add_error(CheckerError::new(
@@ -336,4 +960,57 @@ impl<'err, 'builder> ExpressionVisitor
             ));
         }
     }
+
+    fn visit_lambda(&mut self, lambda: &Lambda) {
+        trace!("Visiting lambda");
+        lambda.set_id(self.current_id.clone());
+        self.current_id.push();
+
+        self.closures.push(ClosureScope {
+            depth: self.builder.depth(),
+            captures: Vec::new()
+        });
+        self.builder.new_scope();
+        for &(ref param, ref _param_type) in lambda.params() {
+            if let Some(var_id) = self.builder.get(param.name()) {
+                let origin_span = self.builder.info_for(var_id)
+                    .expect("checked expect");
+                debug!("Found an already defined lambda param");
+                self.errors.add_error(CheckerError::new(
+                    vec![param.span(), *origin_span],
+                    format!("Variable {} is already declared", param.name())
+                ));
+                continue
+            }
+            let param_id = self.current_id.clone();
+            self.current_id.increment();
+            self.builder.define_local(param.name().into(),
+                                      param_id.clone(),
+                                      param.span());
+            trace!("Created id {:?} for lambda param {}", param_id, param.name());
+            param.set_id(param_id);
+        }
+
+        // `break`, `continue` and `return` can't leave the lambda.
+        let outer_loops = ::std::mem::replace(&mut self.loops, Vec::new());
+        let outer_fn_id = ::std::mem::replace(&mut self.current_fn_id,
+                                              lambda.id().clone());
+        let lambda_id = lambda.id().clone();
+        self.lvalues.begin_block();
+        self.lvalues.add_source(lambda_id.clone());
+        self.visit_expression(lambda.body());
+        if self.lvalues.has_top_source(&lambda_id) {
+            self.lvalues.pop_source();
+        }
+        self.lvalues.end_block();
+        self.loops = outer_loops;
+        self.current_fn_id = outer_fn_id;
+
+        self.builder.pop();
+        let closure = self.closures.pop().expect("Lambda scope was pushed");
+        lambda.set_captures(closure.captures);
+
+        self.current_id.pop();
+        self.current_id.increment();
+    }
 }
@@ -2,66 +2,99 @@
 
 use ast::{*, visit::*};
 use check::{CheckerError, ErrorCollector};
-use identify::NameScopeBuilder;
+use identify::{NameScopeBuilder, ModuleInterface, ConcreteType};
+
+use std::collections::HashMap;
 
 /// Identifies names of items that can be used in expressions,
 /// namely function definitions.
-pub struct ItemVarIdentifier<'err, 'builder> {
+pub struct ItemVarIdentifier<'err, 'builder, 'modules> {
     errors: &'err mut ErrorCollector,
     builder: &'builder mut NameScopeBuilder,
+    modules: &'modules HashMap<String, ModuleInterface>,
     current_id: ScopedId
 }
 
-impl<'err, 'builder> ItemVarIdentifier<'err, 'builder> {
+impl<'err, 'builder, 'modules> ItemVarIdentifier<'err, 'builder, 'modules> {
     pub fn new(errors: &'err mut ErrorCollector,
                builder: &'builder mut NameScopeBuilder,
+               modules: &'modules HashMap<String, ModuleInterface>,
                current_id: ScopedId)
-               -> ItemVarIdentifier<'err, 'builder> {
+               -> ItemVarIdentifier<'err, 'builder, 'modules> {
         ItemVarIdentifier {
             errors,
             builder,
+            modules,
             current_id
         }
     }
-}
-
-impl<'err, 'builder> UnitVisitor for ItemVarIdentifier<'err, 'builder> {
-    fn visit_unit(&mut self, unit: &Unit) {
-        trace!("Visting a unit");
-        // items are defined on the top level of the ScopedId.
-        // We're passed in a ScopedId which is assumed to be non-default
-        // so that the first item doesn't get a default scopedId
-        self.builder.new_scope();
-
-        visit::walk_unit(self, unit);
 
-        self.current_id.increment();
-    }
-}
-
-impl<'err, 'builder> ItemVisitor for ItemVarIdentifier<'err, 'builder> {
-    fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
-        trace!("Visiting fn definition {}", block_fn.name());
-        if let Some(previous_def_id) = self.builder.get(block_fn.name()) {
+    /// Give an item of another module an ID under its name in this unit.
+    fn import_item(&mut self, import: &Import, item: &str, local_name: String) {
+        if let Some(previous_def_id) = self.builder.get(&local_name) {
             let previous_span = self.builder.info_for(previous_def_id)
                 .expect("checked expect");
-            // fn has been previously defined
-            debug!("Emitting error: {} already declared", block_fn.name());
+            debug!("Emitting error: {} already declared", local_name);
             self.errors.add_error(CheckerError::new(
-                vec![block_fn.span(), *previous_span],
-                format!("Function {} is already declared", block_fn.name())
+                vec![import.span(), *previous_span],
+                format!("Imported item {} is already declared", local_name)
             ));
             return
         }
-        // If it was not in the builder its ID should be default.
-        debug_assert!(block_fn.ident().id().is_default(),
-            "Block fn {:?} already had an ID", block_fn);
+        trace!("Created id {:?} for imported item {}",
+            self.current_id, local_name);
+        self.builder.define_local(local_name,
+                                  self.current_id.clone(),
+                                  import.span());
+        import.add_imported(item.to_string(), self.current_id.clone());
+        self.current_id.increment();
+    }
+
+    /// Declare the name of an item which could not be imported, so that its
+    /// uses are not also reported as unknown.
+    fn declare_failed_import(&mut self, import: &Import) {
+        let local_name = match import.item_name() {
+            Some(item_name) => import.local_name(item_name),
+            None => return
+        };
+        if self.builder.get(&local_name).is_some() {
+            return
+        }
+        trace!("Created id {:?} for failed import {}",
+            self.current_id, local_name);
+        self.builder.define_local(local_name,
+                                  self.current_id.clone(),
+                                  import.span());
+        self.current_id.increment();
+    }
 
+    /// Check that the fields of a struct or variant have different names.
+    fn check_field_names(&mut self, owner: &str,
+                         fields: &[(Identifier, TypeExpression)]) -> bool {
+        let mut field_names = Vec::with_capacity(fields.len());
+        for &(ref field, ref _field_type) in fields {
+            if field_names.contains(&field.name()) {
+                debug!("Emitting error: {} in {} already declared",
+                    field.name(), owner);
+                self.errors.add_error(CheckerError::new(
+                    vec![field.span()],
+                    format!("Field {} of {} is already declared",
+                        field.name(), owner)
+                ));
+                return false
+            }
+            field_names.push(field.name());
+        }
+        true
+    }
+
+    /// Give a block fn and its params their IDs.
+    ///
+    /// The fn's name is defined by the caller, as impl methods are not
+    /// visible in the item scope.
+    fn name_fn(&mut self, block_fn: &BlockFnDeclaration) {
         let fn_id = self.current_id.clone();
         trace!("Created id {:?} for block fn {}", fn_id, block_fn.name());
-        self.builder.define_local(block_fn.name().to_string(),
-                                  fn_id.clone(),
-                                  block_fn.span());
         block_fn.set_id(fn_id);
 
         // Also name the params, in a new scope.
@@ -113,6 +146,256 @@ impl<'err, 'builder> ItemVisitor for ItemVarIdentifier<'err, 'builder> {
         self.current_id.pop();
         self.current_id.increment();
     }
+}
+
+impl<'err, 'builder, 'modules> UnitVisitor
+    for ItemVarIdentifier<'err, 'builder, 'modules> {
+
+    fn visit_unit(&mut self, unit: &Unit) {
+        trace!("Visting a unit");
+        // items are defined on the top level of the ScopedId.
+        // We're passed in a ScopedId which is assumed to be non-default
+        // so that the first item doesn't get a default scopedId
+        self.builder.new_scope();
+
+        visit::walk_unit(self, unit);
+
+        self.current_id.increment();
+    }
+}
+
+impl<'err, 'builder, 'modules> ItemVisitor
+    for ItemVarIdentifier<'err, 'builder, 'modules> {
+
+    fn visit_block_fn_decl(&mut self, block_fn: &BlockFnDeclaration) {
+        trace!("Visiting fn definition {}", block_fn.name());
+        if let Some(previous_def_id) = self.builder.get(block_fn.name()) {
+            let previous_span = self.builder.info_for(previous_def_id)
+                .expect("checked expect");
+            // fn has been previously defined
+            debug!("Emitting error: {} already declared", block_fn.name());
+            self.errors.add_error(CheckerError::new(
+                vec![block_fn.span(), *previous_span],
+                format!("Function {} is already declared", block_fn.name())
+            ));
+            return
+        }
+        // If it was not in the builder its ID should be default.
+        debug_assert!(block_fn.ident().id().is_default(),
+            "Block fn {:?} already had an ID", block_fn);
+
+        self.builder.define_local(block_fn.name().to_string(),
+                                  self.current_id.clone(),
+                                  block_fn.span());
+        self.name_fn(block_fn);
+    }
+
+    fn visit_extern_fn_decl(&mut self, extern_fn: &ExternFnDeclaration) {
+        trace!("Visiting extern fn {}", extern_fn.name());
+        if let Some(previous_def_id) = self.builder.get(extern_fn.name()) {
+            let previous_span = self.builder.info_for(previous_def_id)
+                .expect("checked expect");
+            debug!("Emitting error: {} already declared", extern_fn.name());
+            self.errors.add_error(CheckerError::new(
+                vec![extern_fn.span(), *previous_span],
+                format!("Function {} is already declared", extern_fn.name())
+            ));
+            return
+        }
+        // Extern fns have no body, so their params are only named by calls.
+        let mut param_names = Vec::with_capacity(extern_fn.params().len());
+        for &(ref param, ref _param_type) in extern_fn.params() {
+            if param_names.contains(&param.name()) {
+                debug!("Emitting error: {} in {} already declared",
+                    param.name(), extern_fn.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![param.span()],
+                    format!("Parameter {} of function {} is already declared",
+                        param.name(), extern_fn.name())
+                ));
+                return
+            }
+            param_names.push(param.name());
+        }
+        trace!("Created id {:?} for extern fn {}",
+            self.current_id, extern_fn.name());
+        self.builder.define_local(extern_fn.name().to_string(),
+                                  self.current_id.clone(),
+                                  extern_fn.span());
+        extern_fn.set_id(self.current_id.clone());
+        self.current_id.increment();
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting const {}", const_decl.name());
+        if let Some(previous_def_id) = self.builder.get(const_decl.name()) {
+            let previous_span = self.builder.info_for(previous_def_id)
+                .expect("checked expect");
+            debug!("Emitting error: {} already declared", const_decl.name());
+            self.errors.add_error(CheckerError::new(
+                vec![const_decl.span(), *previous_span],
+                format!("Const {} is already declared", const_decl.name())
+            ));
+            return
+        }
+        trace!("Created id {:?} for const {}",
+            self.current_id, const_decl.name());
+        self.builder.define_local(const_decl.name().to_string(),
+                                  self.current_id.clone(),
+                                  const_decl.span());
+        const_decl.set_id(self.current_id.clone());
+        self.current_id.increment();
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting global {}", global.name());
+        if let Some(previous_def_id) = self.builder.get(global.name()) {
+            let previous_span = self.builder.info_for(previous_def_id)
+                .expect("checked expect");
+            debug!("Emitting error: {} already declared", global.name());
+            self.errors.add_error(CheckerError::new(
+                vec![global.span(), *previous_span],
+                format!("Global {} is already declared", global.name())
+            ));
+            return
+        }
+        // Globals are named like functions, so they can be used by items
+        // declared before them.
+        trace!("Created id {:?} for global {}",
+            self.current_id, global.name());
+        self.builder.define_local(global.name().to_string(),
+                                  self.current_id.clone(),
+                                  global.span());
+        global.set_id(self.current_id.clone());
+        self.current_id.increment();
+    }
+
+    fn visit_import(&mut self, import: &Import) {
+        trace!("Visiting import of {}", import.path().name());
+        let modules = self.modules;
+        let interface = match modules.get(import.module_name()) {
+            Some(interface) => interface,
+            None => {
+                debug!("Emitting error: unknown module {}", import.module_name());
+                self.errors.add_error(CheckerError::new(
+                    vec![import.path().span()],
+                    format!("Unknown module {}", import.module_name())
+                ));
+                self.declare_failed_import(import);
+                return
+            }
+        };
+        let items = match import.item_name() {
+            Some(item_name) => {
+                match interface.items().iter()
+                        .find(|&&(ref name, _)| name == item_name) {
+                    Some(item) => vec![item],
+                    None if interface.is_private(item_name) => {
+                        debug!("Emitting error: {} in {} is private",
+                            item_name, interface.name());
+                        self.errors.add_error(CheckerError::new(
+                            vec![import.path().span()],
+                            format!("Item {} of module {} is not pub",
+                                item_name, interface.name())
+                        ));
+                        self.declare_failed_import(import);
+                        return
+                    },
+                    None => {
+                        debug!("Emitting error: no item {} in {}",
+                            item_name, interface.name());
+                        self.errors.add_error(CheckerError::new(
+                            vec![import.path().span()],
+                            format!("Module {} has no item {}",
+                                interface.name(), item_name)
+                        ));
+                        self.declare_failed_import(import);
+                        return
+                    }
+                }
+            },
+            None => interface.items().iter().collect::<Vec<_>>()
+        };
+        for &(ref item, ref item_ty) in items {
+            self.import_item(import, item, import.local_name(item));
+            // Variants are named by themselves, like those of the unit's own
+            // enums.
+            if let ConcreteType::Enum(ref enum_ty) = *item_ty {
+                for variant in enum_ty.variants() {
+                    self.import_item(import, variant.name(),
+                                     variant.name().to_string());
+                }
+            }
+        }
+    }
+
+    fn visit_trait_decl(&mut self, trait_decl: &TraitDeclaration) {
+        trace!("Visiting trait {}", trait_decl.name());
+        if let Some(previous_def_id) = self.builder.get(trait_decl.name()) {
+            let previous_span = self.builder.info_for(previous_def_id)
+                .expect("checked expect");
+            debug!("Emitting error: {} already declared", trait_decl.name());
+            self.errors.add_error(CheckerError::new(
+                vec![trait_decl.ident().span(), *previous_span],
+                format!("Trait {} is already declared", trait_decl.name())
+            ));
+            return
+        }
+        let mut method_names = Vec::with_capacity(trait_decl.methods().len());
+        for method in trait_decl.methods() {
+            if method_names.contains(&method.name()) {
+                debug!("Emitting error: {} already declared in trait {}",
+                    method.name(), trait_decl.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![method.span()],
+                    format!("Method {} of trait {} is already declared",
+                        method.name(), trait_decl.name())
+                ));
+                return
+            }
+            method_names.push(method.name());
+        }
+        trace!("Created id {:?} for trait {}",
+            self.current_id, trait_decl.name());
+        trait_decl.set_id(self.current_id.clone());
+        self.builder.define_global(
+            trait_decl.name().to_string(),
+            self.current_id.clone(),
+            trait_decl.span());
+
+        self.current_id.increment();
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        trace!("Visiting an impl block");
+        // Associated functions of a type are namespaced by it, i.e.
+        // `Point::origin()`.
+        let type_name = match *impl_block.type_expr() {
+            TypeExpression::Named(ref named) if impl_block.trait_ident().is_none() =>
+                Some(named.ident().name()),
+            _ => None
+        };
+        for method in impl_block.methods() {
+            // Methods are only called through their receiver, so they are
+            // named without being put in scope.
+            if let (false, Some(type_name)) = (method.is_method(), type_name) {
+                let path = format!("{}::{}", type_name, method.name());
+                if let Some(previous_def_id) = self.builder.get(&path) {
+                    let previous_span = self.builder.info_for(previous_def_id)
+                        .expect("checked expect");
+                    debug!("Emitting error: {} already declared", path);
+                    self.errors.add_error(CheckerError::new(
+                        vec![method.span(), *previous_span],
+                        format!("Function {} is already declared", path)
+                    ));
+                    continue
+                }
+                self.builder.define_global(path, self.current_id.clone(),
+                                           method.span());
+            }
+            self.name_fn(method);
+        }
+    }
 
     fn visit_typedef(&mut self, typedef: &Typedef) {
         trace!("Visiting type alias {}", typedef.name());
@@ -140,4 +423,94 @@ impl<'err, 'builder> ItemVisitor for ItemVarIdentifier<'err, 'builder> {
 
         self.current_id.increment();
     }
+
+    fn visit_struct_decl(&mut self, struct_decl: &StructDeclaration) {
+        trace!("Visiting struct {}", struct_decl.name());
+        // Structs are named like functions so that they can be constructed
+        // with call syntax, i.e. `Point(x: 1, y: 2)`.
+        if let Some(previous_def_id) = self.builder.get(struct_decl.name()) {
+            let previous_span = self.builder.info_for(previous_def_id)
+                .expect("checked expect");
+            debug!("Emitting error: {} already declared", struct_decl.name());
+            self.errors.add_error(CheckerError::new(
+                vec![struct_decl.span(), *previous_span],
+                format!("Struct {} is already declared", struct_decl.name())
+            ));
+            return
+        }
+
+        // The struct is still declared, so that its uses aren't also errors.
+        self.check_field_names(&format!("struct {}", struct_decl.name()),
+                               struct_decl.fields());
+
+        trace!("Created id {:?} for struct {}",
+            self.current_id, struct_decl.name());
+        self.builder.define_local(struct_decl.name().to_string(),
+                                  self.current_id.clone(),
+                                  struct_decl.span());
+        struct_decl.set_id(self.current_id.clone());
+
+        self.current_id.increment();
+    }
+
+    fn visit_enum_decl(&mut self, enum_decl: &EnumDeclaration) {
+        trace!("Visiting enum {}", enum_decl.name());
+        // Variants are named like functions so that they can be constructed
+        // with call syntax, i.e. `Circle(radius: 1.0)`.
+        let mut valid = true;
+        let mut declared = Vec::with_capacity(enum_decl.variants().len() + 1);
+        declared.push(enum_decl.ident());
+        for variant in enum_decl.variants() {
+            declared.push(variant.ident());
+        }
+        for (ix, ident) in declared.iter().enumerate() {
+            let kind = if ix == 0 { "Enum" } else { "Variant" };
+            if let Some(previous_def_id) = self.builder.get(ident.name()) {
+                let previous_span = self.builder.info_for(previous_def_id)
+                    .expect("checked expect");
+                debug!("Emitting error: {} already declared", ident.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![ident.span(), *previous_span],
+                    format!("{} {} is already declared", kind, ident.name())
+                ));
+                valid = false;
+            }
+            else if let Some(previous) = declared[..ix].iter()
+                    .find(|previous| previous.name() == ident.name()) {
+                debug!("Emitting error: {} already declared in enum {}",
+                    ident.name(), enum_decl.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![ident.span(), previous.span()],
+                    format!("{} {} is already declared", kind, ident.name())
+                ));
+                valid = false;
+            }
+        }
+        if !valid {
+            return
+        }
+        // The enum is still declared, so that its uses aren't also errors.
+        for variant in enum_decl.variants() {
+            self.check_field_names(&format!("variant {}", variant.name()),
+                                   variant.fields());
+        }
+
+        trace!("Created id {:?} for enum {}",
+            self.current_id, enum_decl.name());
+        self.builder.define_local(enum_decl.name().to_string(),
+                                  self.current_id.clone(),
+                                  enum_decl.span());
+        enum_decl.set_id(self.current_id.clone());
+        self.current_id.increment();
+
+        for variant in enum_decl.variants() {
+            trace!("Created id {:?} for variant {}",
+                self.current_id, variant.name());
+            self.builder.define_local(variant.name().to_string(),
+                                      self.current_id.clone(),
+                                      variant.span());
+            variant.set_id(self.current_id.clone());
+            self.current_id.increment();
+        }
+    }
 }
@@ -120,4 +120,20 @@ impl<T: Debug + Hash + Eq, I: Debug> ScopeBuilder<T, I> {
         }
         None
     }
+
+    /// Get the index of the innermost scope which defines the given key.
+    pub fn scope_level<K: ?Sized + Debug>(&self, key: &K) -> Option<usize>
+        where T: Borrow<K>, K: Hash + Eq + Debug {
+        for (ix, scope) in self.scopes.iter().enumerate().rev() {
+            if scope.contains_key(key.borrow()) {
+                return Some(ix)
+            }
+        }
+        None
+    }
+
+    /// Get the number of scopes on the stack.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
 }
@@ -1,9 +1,11 @@
 //!
 
+use lex::Span;
 use ast::ScopedId;
-use identify::{ConcreteType, NamedType};
+use identify::{ConcreteType, NamedType, TupleType, ArrayType, GrowableType,
+               SliceType, FnType, ModuleInterface};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// These are NamedTypes which are "injected" into the default
 /// type scope. This means that we parse things like `float` or `bool`
@@ -13,6 +15,21 @@ pub const PRIMITIVE_TYPE_NAMES: &[&'static str] = &[
     "()",
     "bool",
     "float",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "str",
+];
+
+/// Alternate names for primitive types, which refer to the same type.
+pub const PRIMITIVE_TYPE_ALIASES: &[(&'static str, &'static str)] = &[
+    // Default integer type, given to integer literals without other info.
+    ("int", "i64"),
 ];
 
 #[derive(Debug, PartialEq, Clone)]
@@ -21,6 +38,41 @@ pub struct TypeScopeBuilder {
     names: HashMap<String, ScopedId>,
     /// ScopedIds for other types (function types)
     types: HashMap<ScopedId, ConcreteType>,
+    /// ScopedIds of tuple types, which are not declared
+    tuples: HashMap<TupleType, ScopedId>,
+    /// ScopedIds of array types, which are not declared
+    arrays: HashMap<ArrayType, ScopedId>,
+    /// ScopedIds of growable array types, which are not declared
+    growables: HashMap<GrowableType, ScopedId>,
+    /// ScopedIds of slice types, which are not declared
+    slices: HashMap<SliceType, ScopedId>,
+    /// ScopedIds of the types of function values, which are shared by
+    /// functions with the same signature
+    functions: HashMap<FnType, ScopedId>,
+    /// ScopedIds of type parameters, which are shared by generic items
+    /// using the same parameter name
+    params: HashMap<String, ScopedId>,
+    /// Type parameters which are in scope while a generic item is identified
+    active_params: Vec<String>,
+    /// Parameters and types of generic typedefs, which are instantiated
+    /// with type arguments when they are used
+    generic_typedefs: HashMap<String, (Vec<String>, ConcreteType)>,
+    /// Type parameters of generic functions
+    fn_type_params: HashMap<ScopedId, Vec<String>>,
+    /// Traits which the type parameters of generic functions are bound by
+    fn_bounds: HashMap<ScopedId, HashMap<String, Vec<String>>>,
+    /// Method signatures of traits, whose receiver has type `Self`
+    traits: HashMap<String, Vec<(String, FnType)>>,
+    /// Implementations of traits for types
+    impls: HashMap<(String, ConcreteType), Span>,
+    /// Methods implemented for types, by name
+    methods: HashMap<(ConcreteType, String), ScopedId>,
+    /// Functions defined outside of protosnirk
+    foreign_fns: HashSet<ScopedId>,
+    /// Type named by `Self` while an impl block is identified
+    self_type: Option<ScopedId>,
+    /// Modules which can be imported, by name
+    modules: HashMap<String, ModuleInterface>,
     current_id: ScopedId
 }
 
@@ -37,8 +89,31 @@ impl TypeScopeBuilder {
                 ConcreteType::Named(NamedType::new(primitive_type.to_string())));
             curr_id.increment();
         }
+        for &(alias, primitive_type) in PRIMITIVE_TYPE_ALIASES.iter() {
+            let primitive_id = names[primitive_type].clone();
+            names.insert(alias.to_string(), primitive_id);
+        }
 
-        TypeScopeBuilder { names, types, current_id: curr_id }
+        TypeScopeBuilder {
+            names, types,
+            tuples: HashMap::new(),
+            arrays: HashMap::new(),
+            growables: HashMap::new(),
+            slices: HashMap::new(),
+            functions: HashMap::new(),
+            params: HashMap::new(),
+            active_params: Vec::new(),
+            generic_typedefs: HashMap::new(),
+            fn_type_params: HashMap::new(),
+            fn_bounds: HashMap::new(),
+            traits: HashMap::new(),
+            impls: HashMap::new(),
+            methods: HashMap::new(),
+            foreign_fns: HashSet::new(),
+            self_type: None,
+            modules: HashMap::new(),
+            current_id: curr_id
+        }
     }
 
     pub fn get_type(&self, id: &ScopedId) -> Option<&ConcreteType> {
@@ -46,6 +121,12 @@ impl TypeScopeBuilder {
     }
 
     pub fn named_type_id(&self, name: &str) -> Option<&ScopedId> {
+        if name == "Self" && self.self_type.is_some() {
+            return self.self_type.as_ref()
+        }
+        if self.active_params.iter().any(|param| param == name) {
+            return self.params.get(name)
+        }
         self.names.get(name)
     }
 
@@ -58,6 +139,254 @@ impl TypeScopeBuilder {
         self.types.insert(id, ty);
     }
 
+    /// Reserve the name of a type whose concrete type is not yet known.
+    pub fn add_type_name(&mut self, name: String, id: ScopedId) {
+        self.names.insert(name, id);
+    }
+
+    /// Get the `ScopedId` of a tuple type, if it has been used.
+    pub fn tuple_type_id(&self, tuple: &TupleType) -> Option<&ScopedId> {
+        self.tuples.get(tuple)
+    }
+
+    /// Get the `ScopedId` of a tuple type, giving it a new one if it has not
+    /// been used yet.
+    pub fn add_tuple_type(&mut self, tuple: TupleType) -> ScopedId {
+        if let Some(existing) = self.tuples.get(&tuple) {
+            return existing.clone()
+        }
+        let id = self.current_id.clone();
+        self.current_id.increment();
+        self.types.insert(id.clone(), ConcreteType::Tuple(tuple.clone()));
+        self.tuples.insert(tuple, id.clone());
+        id
+    }
+
+    /// Get the `ScopedId` of an array type, giving it a new one if it has not
+    /// been used yet.
+    pub fn add_array_type(&mut self, array: ArrayType) -> ScopedId {
+        if let Some(existing) = self.arrays.get(&array) {
+            return existing.clone()
+        }
+        let id = self.current_id.clone();
+        self.current_id.increment();
+        self.types.insert(id.clone(), ConcreteType::Array(array.clone()));
+        self.arrays.insert(array, id.clone());
+        id
+    }
+
+    /// Get the `ScopedId` of a growable array type, giving it a new one if it
+    /// has not been used yet.
+    pub fn add_growable_type(&mut self, growable: GrowableType) -> ScopedId {
+        if let Some(existing) = self.growables.get(&growable) {
+            return existing.clone()
+        }
+        let id = self.current_id.clone();
+        self.current_id.increment();
+        self.types.insert(id.clone(), ConcreteType::Growable(growable.clone()));
+        self.growables.insert(growable, id.clone());
+        id
+    }
+
+    /// Get the `ScopedId` of a slice type, giving it a new one if it has not
+    /// been used yet.
+    pub fn add_slice_type(&mut self, slice: SliceType) -> ScopedId {
+        if let Some(existing) = self.slices.get(&slice) {
+            return existing.clone()
+        }
+        let id = self.current_id.clone();
+        self.current_id.increment();
+        self.types.insert(id.clone(), ConcreteType::Slice(slice.clone()));
+        self.slices.insert(slice, id.clone());
+        id
+    }
+
+    /// Get the `ScopedId` of a function type, giving it a new one if it has
+    /// not been used yet.
+    ///
+    /// Declared functions have their own `ScopedId`s, so this is the type of
+    /// a function when it is used as a value.
+    pub fn add_fn_type(&mut self, fn_type: FnType) -> ScopedId {
+        if let Some(existing) = self.functions.get(&fn_type) {
+            return existing.clone()
+        }
+        let id = self.current_id.clone();
+        self.current_id.increment();
+        self.types.insert(id.clone(), ConcreteType::Function(fn_type.clone()));
+        self.functions.insert(fn_type, id.clone());
+        id
+    }
+
+    /// Get the `ScopedId` of a type parameter, giving it a new one if it has
+    /// not been used yet.
+    pub fn add_type_param(&mut self, name: &str) -> ScopedId {
+        if let Some(existing) = self.params.get(name) {
+            return existing.clone()
+        }
+        let id = self.current_id.clone();
+        self.current_id.increment();
+        self.types.insert(id.clone(),
+            ConcreteType::Parameter(NamedType::new(name.to_string())));
+        self.params.insert(name.to_string(), id.clone());
+        id
+    }
+
+    /// Bring the type parameters of a generic item into scope, so that they
+    /// are found by `named_type_id`.
+    pub fn begin_type_params(&mut self, names: &[String]) {
+        for name in names {
+            self.add_type_param(name);
+        }
+        self.active_params = names.to_vec();
+    }
+
+    /// Remove the type parameters of a generic item from scope.
+    pub fn end_type_params(&mut self) {
+        self.active_params.clear();
+    }
+
+    /// Get the `ScopedId` of any concrete type, giving structural types a new
+    /// one if they have not been used yet.
+    pub fn add_concrete_type(&mut self, ty: ConcreteType) -> Option<ScopedId> {
+        match ty {
+            ConcreteType::Named(ref named) =>
+                self.names.get(named.name()).cloned(),
+            ConcreteType::Record(ref record) =>
+                self.names.get(record.name()).cloned(),
+            ConcreteType::Enum(ref enum_ty) =>
+                self.names.get(enum_ty.name()).cloned(),
+            ConcreteType::Parameter(ref param) =>
+                Some(self.add_type_param(param.name())),
+            ConcreteType::Tuple(tuple) => Some(self.add_tuple_type(tuple)),
+            ConcreteType::Array(array) => Some(self.add_array_type(array)),
+            ConcreteType::Growable(growable) =>
+                Some(self.add_growable_type(growable)),
+            ConcreteType::Slice(slice) => Some(self.add_slice_type(slice)),
+            ConcreteType::Function(fn_type) => Some(self.add_fn_type(fn_type))
+        }
+    }
+
+    /// Add a generic typedef, whose type mentions its type parameters.
+    pub fn add_generic_typedef(&mut self, name: String, id: ScopedId,
+                               params: Vec<String>, ty: ConcreteType) {
+        self.names.insert(name.clone(), id);
+        self.generic_typedefs.insert(name, (params, ty));
+    }
+
+    /// Get the type parameters and type of a generic typedef.
+    pub fn generic_typedef(&self, name: &str)
+                           -> Option<&(Vec<String>, ConcreteType)> {
+        self.generic_typedefs.get(name)
+    }
+
+    /// Record the type parameters of a generic function.
+    pub fn add_fn_type_params(&mut self, id: ScopedId, names: Vec<String>) {
+        self.fn_type_params.insert(id, names);
+    }
+
+    /// Get the type parameters of a function, if it is generic.
+    pub fn fn_type_params(&self, id: &ScopedId) -> Option<&[String]> {
+        self.fn_type_params.get(id).map(|names| names.as_slice())
+    }
+
+    /// Record the traits which the type parameters of a generic function
+    /// are bound by.
+    pub fn add_fn_bounds(&mut self, id: ScopedId,
+                         bounds: HashMap<String, Vec<String>>) {
+        self.fn_bounds.insert(id, bounds);
+    }
+
+    /// Get the traits which a type parameter of a function is bound by.
+    pub fn fn_bounds(&self, id: &ScopedId, param: &str) -> &[String] {
+        self.fn_bounds.get(id)
+            .and_then(|bounds| bounds.get(param))
+            .map_or(&[], |traits| traits.as_slice())
+    }
+
+    /// Add a module which can be imported.
+    pub fn add_module(&mut self, interface: ModuleInterface) {
+        self.modules.insert(interface.name().to_string(), interface);
+    }
+
+    /// Get a module which can be imported.
+    pub fn module(&self, name: &str) -> Option<&ModuleInterface> {
+        self.modules.get(name)
+    }
+
+    /// Get the modules which can be imported, by name.
+    pub fn modules(&self) -> &HashMap<String, ModuleInterface> {
+        &self.modules
+    }
+
+    /// Get the type of a method of a type from one of the modules.
+    pub fn imported_method(&self, ty: &ConcreteType, name: &str)
+                           -> Option<&FnType> {
+        self.modules.values()
+            .filter_map(|module| module.method(ty, name))
+            .map(|&(ref fn_type, _)| fn_type)
+            .next()
+    }
+
+    /// Mark a function as defined outside of protosnirk.
+    pub fn add_foreign_fn(&mut self, id: ScopedId) {
+        self.foreign_fns.insert(id);
+    }
+
+    /// Whether a function is defined outside of protosnirk.
+    pub fn is_foreign_fn(&self, id: &ScopedId) -> bool {
+        self.foreign_fns.contains(id)
+    }
+
+    /// Add a trait with the signatures of its methods.
+    pub fn add_trait(&mut self, name: String, methods: Vec<(String, FnType)>) {
+        self.traits.insert(name, methods);
+    }
+
+    /// Get the method signatures of a trait.
+    pub fn trait_methods(&self, name: &str) -> Option<&[(String, FnType)]> {
+        self.traits.get(name).map(|methods| methods.as_slice())
+    }
+
+    /// Set the type which `Self` refers to inside of an impl block.
+    pub fn set_self_type(&mut self, id: Option<ScopedId>) {
+        self.self_type = id;
+    }
+
+    /// Record that a trait is implemented for a type.
+    ///
+    /// Returns the span of the existing impl if there is one.
+    pub fn add_impl(&mut self, trait_name: String, ty: ConcreteType,
+                    span: Span) -> Option<Span> {
+        let key = (trait_name, ty);
+        if let Some(existing) = self.impls.get(&key) {
+            return Some(*existing)
+        }
+        self.impls.insert(key, span);
+        None
+    }
+
+    /// Whether a trait is implemented for a type.
+    pub fn implements(&self, ty: &ConcreteType, trait_name: &str) -> bool {
+        self.impls.contains_key(&(trait_name.to_string(), ty.clone()))
+    }
+
+    /// Add a method of a type, returning the `ScopedId` of an existing
+    /// method with the same name.
+    pub fn add_method(&mut self, ty: ConcreteType, name: String,
+                      id: ScopedId) -> Option<ScopedId> {
+        let key = (ty, name);
+        if let Some(existing) = self.methods.get(&key) {
+            return Some(existing.clone())
+        }
+        self.methods.insert(key, id);
+        None
+    }
+
+    /// Get the `ScopedId` of a method of a type.
+    pub fn method(&self, ty: &ConcreteType, name: &str) -> Option<&ScopedId> {
+        self.methods.get(&(ty.clone(), name.to_string()))
+    }
+
     pub fn add_named_type(&mut self, name: String, id: ScopedId, ty: ConcreteType) {
         self.names.insert(name.clone(), id.clone());
         self.types.insert(id, ty);
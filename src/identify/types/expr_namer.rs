@@ -35,12 +35,69 @@ impl<'err, 'builder> ItemVisitor for ExprTypeIdentifier<'err, 'builder> {
             trace!("Skipping unidentified block fn {}", block_fn.name());
             return
         }
+        // Types in the body of a generic fn can use its type parameters.
+        let type_params = block_fn.type_params().iter()
+            .map(|type_param| type_param.name().to_string())
+            .collect::<Vec<_>>();
+        self.builder.begin_type_params(&type_params);
         self.visit_block(block_fn.block());
+        self.builder.end_type_params();
+    }
+
+    fn visit_extern_fn_decl(&mut self, _extern_fn: &ExternFnDeclaration) {
+        // skip, only visiting expressions
+    }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) {
+        // skip, only visiting expressions
+    }
+
+    fn visit_import(&mut self, _import: &Import) {
+        // skip, only visiting expressions
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting const {}", const_decl.name());
+        if const_decl.id().is_default() {
+            trace!("Skipping unidentified const {}", const_decl.name());
+            return
+        }
+        self.visit_expression(const_decl.value());
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting global {}", global.name());
+        if global.id().is_default() {
+            trace!("Skipping unidentified global {}", global.name());
+            return
+        }
+        self.visit_expression(global.value());
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        trace!("Visiting an impl block");
+        // Types in the methods' bodies can use `Self`.
+        let type_id = impl_block.type_expr().id().clone();
+        if type_id.is_default() {
+            trace!("Skipping impl of unidentified type");
+            return
+        }
+        self.builder.set_self_type(Some(type_id));
+        visit::walk_impl_block(self, impl_block);
+        self.builder.set_self_type(None);
     }
 
     fn visit_typedef(&mut self, _typedef: &Typedef) {
         // skip, only visiting expressions
     }
+
+    fn visit_struct_decl(&mut self, _struct_decl: &StructDeclaration) {
+        // skip, only visiting expressions
+    }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDeclaration) {
+        // skip, only visiting expressions
+    }
 }
 
 impl<'err, 'builder> BlockVisitor for ExprTypeIdentifier<'err, 'builder> {
@@ -51,8 +108,8 @@ impl<'err, 'builder> BlockVisitor for ExprTypeIdentifier<'err, 'builder> {
 }
 
 // The goal of being an ExpressionVisitor is to find places where types are
-// explicitly used within a function. For now this occurs only within a
-// declaration, which is an expression:
+// explicitly used within a function. This occurs within declarations and
+// lambdas, which are expressions:
 // https://github.com/immington-industries/protosnirk/issues/30
 
 impl<'err, 'builder> StatementVisitor for ExprTypeIdentifier<'err, 'builder> {
@@ -72,12 +129,37 @@ impl<'err, 'builder> StatementVisitor for ExprTypeIdentifier<'err, 'builder> {
             TypeIdentifier::new(self.errors, self.builder)
                 .visit_type_expr(decl_ty);
         }
+        self.visit_expression(declaration.value());
+    }
+
+    fn visit_tuple_declaration(&mut self, tuple_decl: &TupleDeclaration) {
+        if let Some(ref decl_ty) = tuple_decl.type_decl() {
+            TypeIdentifier::new(self.errors, self.builder)
+                .visit_type_expr(decl_ty);
+        }
+        self.visit_expression(tuple_decl.value());
     }
 
     fn visit_do_block(&mut self, do_block: &DoBlock) {
         trace!("Visiting a do block");
         visit::walk_do_block(self, do_block);
     }
+
+    fn visit_while_block(&mut self, while_block: &WhileBlock) {
+        trace!("Visiting a while block");
+        visit::walk_while_block(self, while_block);
+    }
+
+    fn visit_for_loop(&mut self, for_loop: &ForLoop) {
+        trace!("Visiting a for loop");
+        visit::walk_for_loop(self, for_loop);
+    }
+
+    fn visit_break_stmt(&mut self, break_: &Break) {
+        visit::walk_break(self, break_);
+    }
+
+    fn visit_continue_stmt(&mut self, _continue: &Continue) { }
 }
 
 impl<'err, 'builder> ExpressionVisitor
@@ -91,6 +173,46 @@ impl<'err, 'builder> ExpressionVisitor
         visit::walk_if_expr(self, if_expr);
     }
 
+    fn visit_match_expr(&mut self, match_expr: &Match) {
+        visit::walk_match_expr(self, match_expr);
+    }
+
+    fn visit_loop_expr(&mut self, loop_expr: &Loop) {
+        visit::walk_loop_expr(self, loop_expr);
+    }
+
+    fn visit_field_access(&mut self, field_access: &FieldAccess) {
+        visit::walk_field_access(self, field_access);
+    }
+
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate) {
+        visit::walk_struct_update(self, struct_update);
+    }
+
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression) {
+        visit::walk_tuple_expr(self, tuple);
+    }
+
+    fn visit_array_expr(&mut self, array: &ArrayExpression) {
+        visit::walk_array_expr(self, array);
+    }
+
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat) {
+        self.visit_expression(repeat.value());
+    }
+
+    fn visit_index_expr(&mut self, index: &IndexExpression) {
+        visit::walk_index_expr(self, index);
+    }
+
+    fn visit_slice_expr(&mut self, slice: &SliceExpression) {
+        visit::walk_slice_expr(self, slice);
+    }
+
+    fn visit_method_call(&mut self, method_call: &MethodCall) {
+        visit::walk_method_call(self, method_call);
+    }
+
     fn visit_unary_op(&mut self, unary_op: &UnaryOperation) {
         visit::walk_unary_op(self, unary_op);
     }
@@ -109,4 +231,20 @@ impl<'err, 'builder> ExpressionVisitor
     fn visit_assignment(&mut self, assign: &Assignment) {
         self.visit_expression(assign.rvalue());
     }
+
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment) {
+        visit::walk_index_assignment(self, assign);
+    }
+
+    fn visit_lambda(&mut self, lambda: &Lambda) {
+        for &(ref _param, ref param_ty) in lambda.params() {
+            TypeIdentifier::new(self.errors, self.builder)
+                .visit_type_expr(param_ty);
+        }
+        if let Some(return_ty) = lambda.return_type() {
+            TypeIdentifier::new(self.errors, self.builder)
+                .visit_type_expr(return_ty);
+        }
+        visit::walk_lambda(self, lambda);
+    }
 }
@@ -1,19 +1,24 @@
 //! Builds the `TypeGraph` using code within functions
 
+use lex::Span;
 use ast::{*, visit::*};
-use identify::TypeScopeBuilder;
+use identify::{ArrayType, ConcreteType, EnumType, FnType, GrowableType, RecordType,
+               SliceType, TupleType, TypeScopeBuilder};
 use identify::types::{TypeGraph, InferenceSource};
 use check::{CheckerError, ErrorCollector};
 
 use petgraph::graph::NodeIndex;
 
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 
 /// Creates type equations for functions.
 #[derive(Debug)]
 pub struct ExprTypographer<'err, 'builder, 'graph> {
     /// Constructs mapping from ScopedId -> ConcreteType
-    builder: &'builder TypeScopeBuilder,
+    ///
+    /// Tuple and array types are added to the builder as they are inferred.
+    builder: &'builder mut TypeScopeBuilder,
     /// Collects errors
     errors: &'err mut ErrorCollector,
     /// Performs type inference on expressions
@@ -22,10 +27,45 @@ pub struct ExprTypographer<'err, 'builder, 'graph> {
     current_type: NodeIndex,
     /// Return type of the current function
     fn_ret_type: NodeIndex,
+    /// `ScopedId` of the current function, whose type parameters' bounds
+    /// give them methods
+    current_fn: ScopedId,
+    /// `loop`s which have been given a value by a `break`
+    valued_loops: HashSet<ScopedId>,
+    /// Tuple and array values whose types have not been inferred yet
+    pending_values: Vec<PendingValue>,
+}
+
+/// A tuple or array value whose type is inferred from how it's used, or from
+/// the types of its elements.
+#[derive(Debug)]
+enum PendingValue {
+    Tuple {
+        tuple_ix: NodeIndex,
+        element_ixs: Vec<NodeIndex>,
+        span: Span
+    },
+    /// The elements of arrays all have the type of `element_ix`.
+    Array {
+        array_ix: NodeIndex,
+        element_ix: NodeIndex,
+        len: u64,
+        span: Span
+    }
+}
+
+impl PendingValue {
+    /// The type index of the tuple or array value.
+    fn value_ix(&self) -> NodeIndex {
+        match *self {
+            PendingValue::Tuple { tuple_ix, .. } => tuple_ix,
+            PendingValue::Array { array_ix, .. } => array_ix
+        }
+    }
 }
 
 impl<'err, 'builder, 'graph> ExprTypographer<'err, 'builder, 'graph> {
-    pub fn new(builder: &'builder TypeScopeBuilder,
+    pub fn new(builder: &'builder mut TypeScopeBuilder,
                errors: &'err mut ErrorCollector,
                graph: &'graph mut TypeGraph)
                -> ExprTypographer<'err, 'builder, 'graph> {
@@ -35,6 +75,9 @@ impl<'err, 'builder, 'graph> ExprTypographer<'err, 'builder, 'graph> {
             graph,
             current_type: NodeIndex::default(),
             fn_ret_type: NodeIndex::default(),
+            current_fn: ScopedId::default(),
+            valued_loops: HashSet::new(),
+            pending_values: Vec::new(),
         }
     }
 
@@ -44,6 +87,910 @@ impl<'err, 'builder, 'graph> ExprTypographer<'err, 'builder, 'graph> {
             .and_then(|unary_id| self.graph.get_type(unary_id))
             .expect(&format!("Did not have primitive {}", name))
     }
+
+    /// Get the type index of a concrete type, if it is in the graph.
+    ///
+    /// Tuple, array, and slice types are added to the graph if they are not
+    /// in it yet.
+    fn concrete_type_ix(&mut self, concrete: &ConcreteType) -> Option<NodeIndex> {
+        match *concrete {
+            ConcreteType::Named(ref named) => {
+                self.builder.named_type_id(named.name())
+                    .and_then(|type_id| self.graph.get_type(type_id))
+            },
+            ConcreteType::Record(ref record) => {
+                self.builder.named_type_id(record.name())
+                    .and_then(|type_id| self.graph.get_type(type_id))
+            },
+            ConcreteType::Enum(ref enum_ty) => {
+                self.builder.named_type_id(enum_ty.name())
+                    .and_then(|type_id| self.graph.get_type(type_id))
+            },
+            // Values of a type parameter only have that parameter's type
+            // inside the body of a generic function.
+            ConcreteType::Parameter(ref param) => {
+                let param_id = self.builder.add_type_param(param.name());
+                Some(self.graph.add_type(param_id))
+            },
+            ConcreteType::Tuple(ref tuple) => {
+                let tuple_id = self.builder.add_tuple_type(tuple.clone());
+                Some(self.graph.add_type(tuple_id))
+            },
+            ConcreteType::Array(ref array) => {
+                let array_id = self.builder.add_array_type(array.clone());
+                Some(self.graph.add_type(array_id))
+            },
+            ConcreteType::Growable(ref growable) => {
+                let growable_id = self.builder.add_growable_type(growable.clone());
+                Some(self.graph.add_type(growable_id))
+            },
+            ConcreteType::Slice(ref slice) => {
+                let slice_id = self.builder.add_slice_type(slice.clone());
+                Some(self.graph.add_type(slice_id))
+            },
+            // Declared functions have their own types, but function values
+            // have the type shared by functions with the same signature.
+            ConcreteType::Function(ref fn_type) => {
+                let fn_id = self.builder.add_fn_type(fn_type.clone());
+                Some(self.graph.add_type(fn_id))
+            }
+        }
+    }
+
+    /// Find the concrete type which a value has been inferred to so far.
+    ///
+    /// The types of tuples and arrays which the value could be are inferred
+    /// first.
+    fn known_type_of(&mut self, value_ix: NodeIndex) -> Option<ConcreteType> {
+        self.resolve_pending_from(value_ix);
+        let builder = &self.builder;
+        self.graph.known_type(value_ix)
+            .and_then(|type_id| builder.get_type(&type_id))
+            .cloned()
+    }
+
+    /// Add the type arguments of a call to a generic function to the graph.
+    ///
+    /// Each type argument is a variable of the call. Parameters and return
+    /// types which are a type parameter are the same as its type argument,
+    /// and other parameters mentioning type parameters are used to find the
+    /// type arguments from the known types of their arguments.
+    fn visit_generic_call(&mut self, fn_call: &FnCall, fn_ix: NodeIndex,
+                          fn_type: &FnType, type_params: &[String]) {
+        trace!("Visiting a call to generic fn {}", fn_call.text());
+        let type_arg_ixs = type_params.iter()
+            .zip(fn_call.type_arg_ids(type_params.len()))
+            .map(|(param, type_arg_id)|
+                (param.clone(), self.graph.add_variable(type_arg_id)))
+            .collect::<HashMap<_, _>>();
+
+        let mut structured_args = Vec::new();
+        for arg in fn_call.args() {
+            self.visit_expression(arg.expression());
+            let expr_ty = self.current_type;
+            let arg_infer = self.graph.add_named_call_arg(
+                arg.name().name().into(), fn_ix);
+            self.graph.add_equality(arg_infer, expr_ty,
+                InferenceSource::CallArgument(fn_call.ident().clone()));
+
+            let param_ty = match fn_type.params().iter()
+                    .find(|&&(ref name, _)| name == arg.name().name()) {
+                Some(&(_, ref param_ty)) => param_ty.clone(),
+                None => continue
+            };
+            if let ConcreteType::Parameter(ref param) = param_ty {
+                if let Some(&type_arg_ix) = type_arg_ixs.get(param.name()) {
+                    // t_arg = t_type_arg
+                    self.graph.add_equality(arg_infer, type_arg_ix,
+                        InferenceSource::TypeArgument(fn_call.ident().clone()));
+                    continue
+                }
+            }
+            if type_params.iter().any(|param| param_ty.mentions_parameter(param)) {
+                structured_args.push((arg, arg_infer, param_ty));
+            }
+            else if let Some(param_ty_ix) = self.concrete_type_ix(&param_ty) {
+                // t_arg: t_param
+                self.graph.add_inference(arg_infer, param_ty_ix,
+                    InferenceSource::CallArgument(fn_call.ident().clone()));
+            }
+        }
+
+        // Type arguments found from other arguments are used to check
+        // arguments such as arrays of a type parameter.
+        let mut type_args = HashMap::new();
+        for (param, &type_arg_ix) in &type_arg_ixs {
+            let known_ty = self.known_type_of(type_arg_ix);
+            let builder = &self.builder;
+            let graph = &self.graph;
+            if let Some(type_arg) = known_ty.or_else(||
+                    graph.known_type_or_default(type_arg_ix)
+                        .and_then(|type_id| builder.get_type(&type_id))
+                        .cloned()) {
+                type_args.insert(param.clone(), type_arg);
+            }
+        }
+        let inferred_params = type_args.keys().cloned().collect::<HashSet<_>>();
+        for (arg, arg_infer, param_ty) in structured_args {
+            if let Some(arg_ty) = self.known_type_of(arg_infer) {
+                if !param_ty.bind_parameters(&arg_ty, &mut type_args) {
+                    debug!("Emitting error: argument {} of {} has type {}",
+                        arg.name().name(), fn_call.text(), arg_ty);
+                    self.errors.add_error(CheckerError::new(
+                        vec![arg.expression().span()],
+                        format!("Expected {} for argument {} of {} - got {}",
+                            param_ty, arg.name().name(), fn_call.text(),
+                            arg_ty)
+                    ));
+                    continue
+                }
+            }
+            if !self.check_type_args(fn_call, &param_ty, type_params,
+                                     &type_args) {
+                continue
+            }
+            if let Some(param_ty_ix) = self.concrete_type_ix(
+                    &param_ty.substitute(&type_args)) {
+                // t_arg: t_param
+                self.graph.add_inference(arg_infer, param_ty_ix,
+                    InferenceSource::CallArgument(fn_call.ident().clone()));
+            }
+        }
+        for (param, type_arg) in &type_args {
+            // Numeric type arguments keep their default until they're used.
+            if inferred_params.contains(param) {
+                continue
+            }
+            if let Some(type_arg_ix) = self.concrete_type_ix(type_arg) {
+                // t_type_arg: t_known
+                self.graph.add_inference(type_arg_ixs[param], type_arg_ix,
+                    InferenceSource::TypeArgument(fn_call.ident().clone()));
+            }
+        }
+
+        // t_current = t_return(fn)
+        let fn_return_type = self.graph.add_call_return_type(fn_ix);
+        self.current_type = fn_return_type;
+        let return_ty = fn_type.return_ty();
+        if let ConcreteType::Parameter(ref param) = *return_ty {
+            if let Some(&type_arg_ix) = type_arg_ixs.get(param.name()) {
+                // t_return = t_type_arg
+                self.graph.add_equality(fn_return_type, type_arg_ix,
+                    InferenceSource::TypeArgument(fn_call.ident().clone()));
+                return
+            }
+        }
+        if !self.check_type_args(fn_call, return_ty, type_params, &type_args) {
+            return
+        }
+        if let Some(return_ty_ix) = self.concrete_type_ix(
+                &return_ty.substitute(&type_args)) {
+            self.graph.add_inference(fn_return_type, return_ty_ix,
+                InferenceSource::CallReturnType(fn_call.ident().clone()));
+        }
+    }
+
+    /// Check that the type arguments of the type parameters mentioned by a
+    /// parameter or return type of a generic function are known.
+    fn check_type_args(&mut self, fn_call: &FnCall, ty: &ConcreteType,
+                       type_params: &[String],
+                       type_args: &HashMap<String, ConcreteType>) -> bool {
+        let unknown = type_params.iter().find(|param|
+            ty.mentions_parameter(param) && !type_args.contains_key(*param));
+        if let Some(param) = unknown {
+            debug!("Emitting error: unknown type argument {} of {}",
+                param, fn_call.text());
+            self.errors.add_error(CheckerError::new(
+                vec![fn_call.span()],
+                format!("Could not infer type parameter {} of {}",
+                    param, fn_call.text())
+            ));
+            return false
+        }
+        true
+    }
+
+    /// Infer the types of the pending tuples and arrays which a value could
+    /// be.
+    fn resolve_pending_from(&mut self, value_ix: NodeIndex) {
+        loop {
+            let reachable = {
+                let graph = &self.graph;
+                self.pending_values.iter().rposition(|pending|
+                    graph.has_path(value_ix, pending.value_ix()))
+            };
+            match reachable {
+                Some(ix) => {
+                    let pending = self.pending_values.remove(ix);
+                    self.resolve_value(pending);
+                },
+                None => return
+            }
+        }
+    }
+
+    /// Infer the types of all of the pending tuples and arrays.
+    ///
+    /// Values whose types are known from how they are used are resolved
+    /// first, so that their elements can be inferred from them.
+    fn resolve_pending(&mut self) {
+        while !self.pending_values.is_empty() {
+            let known = {
+                let graph = &self.graph;
+                self.pending_values.iter().position(|pending|
+                    graph.known_type(pending.value_ix()).is_some())
+            };
+            // Otherwise, the last value may contain the others.
+            let ix = known.unwrap_or(self.pending_values.len() - 1);
+            let pending = self.pending_values.remove(ix);
+            self.resolve_value(pending);
+        }
+    }
+
+    /// Require an operand of `and`, `or`, or `not` to be a `bool`.
+    ///
+    /// Operands which are numbers or strings written out are reported here,
+    /// so that the error is about the operator rather than the operand.
+    fn add_bool_operand(&mut self, operand: &Expression,
+                        operand_type: NodeIndex, operator: &str) {
+        let not_bool = match *operand {
+            Expression::Literal(ref literal) => match *literal.value() {
+                LiteralValue::Bool(_) => false,
+                _ => true
+            },
+            Expression::UnaryOp(ref unary_op) => match unary_op.operator() {
+                UnaryOperator::Negation | UnaryOperator::Addition => true,
+                UnaryOperator::Not => false
+            },
+            _ => false
+        };
+        if not_bool {
+            debug!("Emitting error: non-bool operand of {}", operator);
+            self.errors.add_error(CheckerError::new(
+                vec![operand.span()],
+                format!("`{}` requires bool operands", operator)
+            ));
+            return
+        }
+        let bool_type = self.primitive_type_ix("bool");
+        // t_operand = t_bool
+        self.graph.add_inference(operand_type, bool_type,
+            InferenceSource::BooleanOperator);
+    }
+
+    /// Infer the type of a tuple or array value, either from how it's used
+    /// or from the types of its elements.
+    fn resolve_value(&mut self, pending: PendingValue) {
+        match pending {
+            PendingValue::Tuple { tuple_ix, element_ixs, span } =>
+                self.resolve_tuple(tuple_ix, element_ixs, span),
+            PendingValue::Array { array_ix, element_ix, len, span } =>
+                self.resolve_array(array_ix, element_ix, len, span)
+        }
+    }
+
+    /// Find the type of an element of a tuple or array from the values it
+    /// is inferred from, using `int` for numbers without other types.
+    ///
+    /// `kind` is `"Tuple"` or `"Array"`, and is used in errors.
+    fn element_type_of(&mut self, element_ix: NodeIndex, kind: &str,
+                       span: Span) -> Option<ConcreteType> {
+        // Elements may be tuples or arrays themselves.
+        self.resolve_pending_from(element_ix);
+        let element_id = match self.graph.known_type_or_default(element_ix) {
+            Some(element_id) => element_id,
+            None => {
+                debug!("Emitting error: unknown {} element", kind);
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Could not determine the type of {} elements",
+                        kind.to_lowercase())
+                ));
+                return None
+            }
+        };
+        if Some(&element_id) == self.builder.named_type_id("()") {
+            debug!("Emitting error: {} element is ()", kind);
+            self.errors.add_error(CheckerError::new(
+                vec![span],
+                format!("{} elements cannot have type ()", kind)
+            ));
+            return None
+        }
+        let element_ty = self.builder.get_type(&element_id)
+            .expect("Inferred element to unknown type")
+            .clone();
+        // t_element: t_element_type
+        let element_ty_ix = self.graph.add_type(element_id);
+        self.graph.add_inference(element_ix, element_ty_ix,
+            InferenceSource::Inferred);
+        Some(element_ty)
+    }
+
+    /// Find the type a tuple or array value has been inferred to from how
+    /// it's used, without inferring the types of other pending values.
+    fn used_type_of(&self, value_ix: NodeIndex) -> Option<ConcreteType> {
+        let builder = &self.builder;
+        self.graph.known_type(value_ix)
+            .and_then(|type_id| builder.get_type(&type_id))
+            .cloned()
+    }
+
+    /// Infer the type of a tuple value.
+    fn resolve_tuple(&mut self, tuple_ix: NodeIndex,
+                     element_ixs: Vec<NodeIndex>, span: Span) {
+        trace!("Resolving tuple at {}", span);
+        match self.used_type_of(tuple_ix) {
+            Some(ConcreteType::Tuple(ref tuple_ty))
+                    if tuple_ty.elements().len() == element_ixs.len() => {
+                for (ix, element_ty) in tuple_ty.elements().iter().enumerate() {
+                    // t_element: t_tuple_element
+                    if let Some(element_ty_ix) = self.concrete_type_ix(element_ty) {
+                        self.graph.add_inference(element_ixs[ix],
+                            element_ty_ix, InferenceSource::TupleElement(ix));
+                    }
+                }
+                return
+            },
+            Some(other) => {
+                debug!("Emitting error: expected {}, got a tuple", other);
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Expected {} - got a tuple of {} elements",
+                        other, element_ixs.len())
+                ));
+                return
+            },
+            None => {}
+        }
+        let mut elements = Vec::with_capacity(element_ixs.len());
+        for &element_ix in &element_ixs {
+            match self.element_type_of(element_ix, "Tuple", span) {
+                Some(element_ty) => elements.push(element_ty),
+                None => return
+            }
+        }
+        let tuple_id = self.builder.add_tuple_type(TupleType::new(elements));
+        let tuple_ty_ix = self.graph.add_type(tuple_id);
+        // t_tuple: t_tuple_type
+        self.graph.add_inference(tuple_ix, tuple_ty_ix,
+            InferenceSource::TupleValue);
+    }
+
+    /// Infer the type of an array value.
+    fn resolve_array(&mut self, array_ix: NodeIndex, element_ix: NodeIndex,
+                     len: u64, span: Span) {
+        trace!("Resolving array at {}", span);
+        match self.used_type_of(array_ix) {
+            Some(ConcreteType::Array(ref array_ty)) if array_ty.len() == len => {
+                // t_element: t_array_element
+                if let Some(element_ty_ix) =
+                        self.concrete_type_ix(array_ty.element()) {
+                    self.graph.add_inference(element_ix, element_ty_ix,
+                        InferenceSource::ArrayElement);
+                }
+                return
+            },
+            // Growable arrays start with the elements of an array value.
+            Some(ConcreteType::Growable(ref growable_ty)) => {
+                // t_element: t_growable_element
+                if let Some(element_ty_ix) =
+                        self.concrete_type_ix(growable_ty.element()) {
+                    self.graph.add_inference(element_ix, element_ty_ix,
+                        InferenceSource::ArrayElement);
+                }
+                return
+            },
+            Some(other) => {
+                debug!("Emitting error: expected {}, got an array", other);
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Expected {} - got an array of {} elements",
+                        other, len)
+                ));
+                return
+            },
+            None => {}
+        }
+        let element_ty = match self.element_type_of(element_ix, "Array", span) {
+            Some(element_ty) => element_ty,
+            None => return
+        };
+        let array_id = self.builder.add_array_type(
+            ArrayType::new(element_ty, len));
+        let array_ty_ix = self.graph.add_type(array_id);
+        // t_array: t_array_type
+        self.graph.add_inference(array_ix, array_ty_ix,
+            InferenceSource::ArrayValue);
+    }
+
+    /// Find the type of a value which is indexed or sliced, and the type of
+    /// its elements.
+    ///
+    /// The value must be an array, growable array, or slice whose type is
+    /// already known.
+    fn elements_of(&mut self, value_ix: NodeIndex, span: Span)
+                   -> Option<(ConcreteType, ConcreteType)> {
+        // Numbers are never arrays, so they can be reported as their
+        // default type.
+        let value_ty = self.known_type_of(value_ix).or_else(|| {
+            let builder = &self.builder;
+            self.graph.known_type_or_default(value_ix)
+                .and_then(|type_id| builder.get_type(&type_id))
+                .cloned()
+        });
+        let element_ty = match value_ty {
+            Some(ConcreteType::Array(ref array)) => array.element().clone(),
+            Some(ConcreteType::Growable(ref growable)) =>
+                growable.element().clone(),
+            Some(ConcreteType::Slice(ref slice)) => slice.element().clone(),
+            Some(other) => {
+                debug!("Emitting error: {} is not an array", other);
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Expected an array - got {}", other)
+                ));
+                return None
+            },
+            None => {
+                debug!("Emitting error: unknown array type");
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Could not determine the array type of value")
+                ));
+                return None
+            }
+        };
+        value_ty.map(|value_ty| (value_ty, element_ty))
+    }
+
+    /// Infer the type of an accessed element of an array, growable array,
+    /// or slice.
+    ///
+    /// Returns the type index of the element and the type of the value.
+    fn index_element(&mut self, index: &IndexExpression)
+                     -> (NodeIndex, Option<ConcreteType>) {
+        self.visit_expression(index.value());
+        let value_ix = self.current_type;
+
+        // t_index: int
+        self.visit_expression(index.index());
+        let int_ix = self.primitive_type_ix("int");
+        self.graph.add_inference(self.current_type, int_ix,
+            InferenceSource::ArrayIndex);
+
+        let element_ix = self.graph.add_expression();
+        let (value_ty, element_ty) =
+            match self.elements_of(value_ix, index.value().span()) {
+                Some(types) => types,
+                None => return (element_ix, None)
+            };
+        // Constant indices into arrays are checked when the array is built.
+        if let ConcreteType::Array(ref array) = value_ty {
+            if let Expression::Literal(ref literal) = *index.index() {
+                if let LiteralValue::Int(value) = *literal.value() {
+                    if value >= array.len() {
+                        debug!("Emitting error: index {} out of bounds", value);
+                        self.errors.add_error(CheckerError::new(
+                            vec![index.index().span()],
+                            format!("Index {} is out of bounds of {}",
+                                value, value_ty)
+                        ));
+                    }
+                }
+            }
+        }
+        // t_element: t_array_element
+        if let Some(element_ty_ix) = self.concrete_type_ix(&element_ty) {
+            self.graph.add_inference(element_ix, element_ty_ix,
+                InferenceSource::ArrayElement);
+        }
+        (element_ix, Some(value_ty))
+    }
+
+    /// Infer the types of a call of a method of a growable array.
+    ///
+    /// `push(value: T)` adds an element, and `pop()` removes the last one.
+    fn visit_growable_method(&mut self, method_call: &MethodCall,
+                             growable: &GrowableType) -> NodeIndex {
+        let method = method_call.method();
+        let element_ty_ix = self.concrete_type_ix(growable.element());
+        match method.name() {
+            "push" => {
+                let mut has_value = false;
+                for arg in method_call.args() {
+                    self.visit_expression(arg.expression());
+                    if arg.name().name() != "value" || has_value {
+                        debug!("Emitting error: bad argument {} of push",
+                            arg.name().name());
+                        self.errors.add_error(CheckerError::new(
+                            vec![arg.span()],
+                            format!("Method push takes one argument, value")
+                        ));
+                        continue
+                    }
+                    has_value = true;
+                    // t_arg: t_element
+                    if let Some(element_ty_ix) = element_ty_ix {
+                        self.graph.add_inference(self.current_type,
+                            element_ty_ix,
+                            InferenceSource::MethodArgument(method.clone()));
+                    }
+                }
+                if !has_value {
+                    debug!("Emitting error: missing argument of push");
+                    self.errors.add_error(CheckerError::new(
+                        vec![method_call.span()],
+                        format!("Missing argument value of method push")
+                    ));
+                }
+                self.primitive_type_ix("()")
+            },
+            "pop" => {
+                for arg in method_call.args() {
+                    self.visit_expression(arg.expression());
+                    debug!("Emitting error: argument given to pop");
+                    self.errors.add_error(CheckerError::new(
+                        vec![arg.span()],
+                        format!("Method pop takes no arguments")
+                    ));
+                }
+                // t_call: t_element
+                let call_ix = self.graph.add_expression();
+                if let Some(element_ty_ix) = element_ty_ix {
+                    self.graph.add_inference(call_ix, element_ty_ix,
+                        InferenceSource::MethodReturnType(method.clone()));
+                }
+                call_ix
+            },
+            other => {
+                debug!("Emitting error: unknown method {}", other);
+                self.errors.add_error(CheckerError::new(
+                    vec![method.span()],
+                    format!("Growable array {} has no method {}",
+                        ConcreteType::Growable(growable.clone()), other)
+                ));
+                for arg in method_call.args() {
+                    self.visit_expression(arg.expression());
+                }
+                self.graph.add_expression()
+            }
+        }
+    }
+
+    /// Find the signature of a trait method called on a value, with `Self`
+    /// replaced by the value's type.
+    ///
+    /// Values of a type parameter have the methods of the traits bounding
+    /// it, and other types have the methods of the traits implemented for
+    /// them.
+    fn trait_method_type(&mut self, method: &Identifier,
+                         receiver_ty: &ConcreteType) -> Option<FnType> {
+        if let ConcreteType::Parameter(ref param) = *receiver_ty {
+            let mut found: Vec<(&str, &FnType)> = Vec::new();
+            for trait_name in self.builder.fn_bounds(&self.current_fn,
+                                                     param.name()) {
+                let trait_methods = self.builder.trait_methods(trait_name)
+                    .expect("Bound by unknown trait");
+                for &(ref name, ref fn_type) in trait_methods {
+                    if name == method.name() {
+                        found.push((trait_name, fn_type));
+                    }
+                }
+            }
+            let error = match found.len() {
+                0 => format!("{} has no method {}",
+                        receiver_ty, method.name()),
+                1 => {
+                    let self_args = vec![("Self".to_string(),
+                                          receiver_ty.clone())]
+                        .into_iter().collect::<HashMap<_, _>>();
+                    match ConcreteType::Function(found[0].1.clone())
+                            .substitute(&self_args) {
+                        ConcreteType::Function(fn_type) =>
+                            return Some(fn_type),
+                        other => unreachable!("Substituted fn to {}", other)
+                    }
+                },
+                _ => format!("Method {} of {} is ambiguous between traits {} and {}",
+                        method.name(), receiver_ty, found[0].0, found[1].0)
+            };
+            debug!("Emitting error: {}", error);
+            self.errors.add_error(CheckerError::new(
+                vec![method.span()], error
+            ));
+            return None
+        }
+        // Later passes find the called method through its identifier.
+        let method_ty = self.builder.method(receiver_ty, method.name())
+            .and_then(|method_id| {
+                method.set_id(method_id.clone());
+                self.builder.get_type(method_id)
+            })
+            .cloned()
+            // Methods of imported types are in their module's interface.
+            .or_else(|| self.builder.imported_method(receiver_ty, method.name())
+                .map(|fn_type| ConcreteType::Function(fn_type.clone())));
+        match method_ty {
+            Some(ConcreteType::Function(fn_type)) => Some(fn_type),
+            _ => {
+                debug!("Emitting error: {} has no method {}",
+                    receiver_ty, method.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![method.span()],
+                    format!("{} has no method {}",
+                        receiver_ty, method.name())
+                ));
+                None
+            }
+        }
+    }
+
+    /// Infer the types of a call of a trait method.
+    ///
+    /// The receiver is passed as `self`, and the other arguments are named.
+    fn visit_trait_method(&mut self, method_call: &MethodCall,
+                          fn_type: &FnType) -> NodeIndex {
+        let method = method_call.method();
+        let params = &fn_type.params()[1..];
+        let mut given: Vec<&str> = Vec::with_capacity(params.len());
+        for arg in method_call.args() {
+            self.visit_expression(arg.expression());
+            let arg_name = arg.name().name();
+            let param_ty = match params.iter()
+                    .find(|&&(ref name, _)| name == arg_name) {
+                Some(&(_, ref param_ty)) if !given.contains(&arg_name) =>
+                    param_ty,
+                Some(_) => {
+                    debug!("Emitting error: argument {} given twice",
+                        arg_name);
+                    self.errors.add_error(CheckerError::new(
+                        vec![arg.span()],
+                        format!("Argument {} of method {} is already given",
+                            arg_name, method.name())
+                    ));
+                    continue
+                },
+                None => {
+                    debug!("Emitting error: unknown argument {}", arg_name);
+                    self.errors.add_error(CheckerError::new(
+                        vec![arg.span()],
+                        format!("Method {} has no parameter {}",
+                            method.name(), arg_name)
+                    ));
+                    continue
+                }
+            };
+            given.push(arg_name);
+            // t_arg: t_param
+            if let Some(param_ty_ix) = self.concrete_type_ix(param_ty) {
+                self.graph.add_inference(self.current_type, param_ty_ix,
+                    InferenceSource::MethodArgument(method.clone()));
+            }
+        }
+        for &(ref name, ref _param_ty) in params {
+            if !given.contains(&name.as_str()) {
+                debug!("Emitting error: missing argument {}", name);
+                self.errors.add_error(CheckerError::new(
+                    vec![method_call.span()],
+                    format!("Missing argument {} of method {}",
+                        name, method.name())
+                ));
+            }
+        }
+        // t_call: t_return
+        let call_ix = self.graph.add_expression();
+        if let Some(return_ty_ix) = self.concrete_type_ix(fn_type.return_ty()) {
+            self.graph.add_inference(call_ix, return_ty_ix,
+                InferenceSource::MethodReturnType(method.clone()));
+        }
+        call_ix
+    }
+
+    /// Find the struct type of a value whose fields are used.
+    ///
+    /// The type of the value must already be known, such as from a function
+    /// parameter or the declaration of a variable.
+    fn record_type_of(&mut self, value_ix: NodeIndex, span: Span)
+                      -> Option<RecordType> {
+        let known = self.known_type_of(value_ix);
+        self.expect_record(known, span)
+    }
+
+    /// Emit an error if a value's known type is not a struct.
+    fn expect_record(&mut self, known: Option<ConcreteType>, span: Span)
+                     -> Option<RecordType> {
+        match known {
+            Some(ConcreteType::Record(record)) => Some(record),
+            Some(other) => {
+                debug!("Emitting error: {} is not a struct", other);
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Expected a struct - got {}", other)
+                ));
+                None
+            },
+            None => {
+                debug!("Emitting error: unknown struct type");
+                self.errors.add_error(CheckerError::new(
+                    vec![span],
+                    format!("Could not determine the struct type of value")
+                ));
+                None
+            }
+        }
+    }
+
+    /// Check the fields given to a struct or variant, as in
+    /// `Point(x: 1, y: 2)`.
+    ///
+    /// `kind` is `"Struct"` or `"Variant"`, and is used in errors.
+    fn visit_struct_fields(&mut self, kind: &str, record: &RecordType,
+                           fields: &[CallArgument]) {
+        let mut given = Vec::with_capacity(fields.len());
+        for field in fields {
+            self.visit_expression(field.expression());
+            let field_name = field.name().name();
+            if given.contains(&field_name) {
+                debug!("Emitting error: field {} given twice", field_name);
+                self.errors.add_error(CheckerError::new(
+                    vec![field.span()],
+                    format!("Field {} of {} {} is given more than once",
+                        field_name, kind.to_lowercase(), record.name())
+                ));
+                continue
+            }
+            given.push(field_name);
+            match record.field(field_name) {
+                Some((_ix, field_ty)) => {
+                    // t_value: t_field
+                    if let Some(field_ty_ix) = self.concrete_type_ix(field_ty) {
+                        self.graph.add_inference(self.current_type, field_ty_ix,
+                            InferenceSource::StructField(field.name().clone()));
+                    }
+                },
+                None => {
+                    debug!("Emitting error: {} has no field {}",
+                        record.name(), field_name);
+                    self.errors.add_error(CheckerError::new(
+                        vec![field.name().span()],
+                        format!("{} {} has no field {}",
+                            kind, record.name(), field_name)
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Check the construction of a struct or variant, which is written like
+    /// a call, and has the type at `type_ix`.
+    fn visit_struct_construction(&mut self, fn_call: &FnCall, kind: &str,
+                                 record: &RecordType, type_ix: NodeIndex) {
+        trace!("Visiting construction of {} {}", kind, record.name());
+        self.visit_struct_fields(kind, record, fn_call.args());
+        for &(ref field_name, _) in record.fields() {
+            if !fn_call.args().iter()
+                    .any(|arg| arg.name().name() == field_name) {
+                debug!("Emitting error: missing field {}", field_name);
+                self.errors.add_error(CheckerError::new(
+                    vec![fn_call.span()],
+                    format!("Missing field {} of {} {}",
+                        field_name, kind.to_lowercase(), record.name())
+                ));
+            }
+        }
+        // t_construct: t_struct
+        let construct_ix = self.graph.add_expression();
+        self.graph.add_inference(construct_ix, type_ix,
+            InferenceSource::StructValue);
+        self.current_type = construct_ix;
+    }
+
+    /// Get the type index of an enum, which is added to the graph with its
+    /// declaration.
+    fn enum_type_ix(&self, enum_ty: &EnumType) -> NodeIndex {
+        self.builder.named_type_id(enum_ty.name())
+            .and_then(|type_id| self.graph.get_type(type_id))
+            .expect("Enum type was not added to the graph")
+    }
+
+    /// Find the variant of an enum which is named by an identifier.
+    ///
+    /// Emits an error if the identifier names the enum itself.
+    fn variant_of<'a>(&mut self, enum_ty: &'a EnumType, ident: &Identifier)
+                      -> Option<&'a RecordType> {
+        match enum_ty.variant(ident.name()) {
+            Some((_tag, variant)) => Some(variant),
+            None => {
+                debug!("Emitting error: enum {} used as a value",
+                    enum_ty.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![ident.span()],
+                    format!("Enum {} must be constructed with one of its variants",
+                        enum_ty.name())
+                ));
+                None
+            }
+        }
+    }
+
+    /// Check the variant of a pattern, and infer the types of the variables
+    /// which its fields are bound to.
+    fn visit_variant_pattern(&mut self, pattern: &VariantPattern,
+                             value_ix: NodeIndex) {
+        trace!("Visiting variant pattern {}", pattern.name());
+        // Bindings are added even if the pattern is invalid so they can still
+        // be referenced in the arm.
+        for field in pattern.fields() {
+            if let Some(binding) = field.binding() {
+                if !binding.id().is_default() {
+                    self.graph.add_variable(binding.id().clone());
+                }
+            }
+        }
+        if pattern.ident().id().is_default() {
+            debug!("Skipping unidentified variant {}", pattern.name());
+            return
+        }
+        let enum_ty = match self.builder.get_type(&pattern.ident().id()) {
+            Some(&ConcreteType::Enum(ref enum_ty)) => enum_ty.clone(),
+            _ => {
+                debug!("Emitting error: {} is not a variant", pattern.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![pattern.ident().span()],
+                    format!("Expected a variant of an enum - got {}",
+                        pattern.name())
+                ));
+                return
+            }
+        };
+        let variant = match self.variant_of(&enum_ty, pattern.ident()) {
+            Some(variant) => variant,
+            None => return
+        };
+        // t_value: t_enum
+        let enum_ix = self.enum_type_ix(&enum_ty);
+        self.graph.add_inference(value_ix, enum_ix,
+            InferenceSource::MatchPattern);
+
+        let mut bound = Vec::with_capacity(pattern.fields().len());
+        for field in pattern.fields() {
+            let field_name = field.field().name();
+            if bound.contains(&field_name) {
+                debug!("Emitting error: field {} bound twice", field_name);
+                self.errors.add_error(CheckerError::new(
+                    vec![field.span()],
+                    format!("Field {} of variant {} is bound more than once",
+                        field_name, variant.name())
+                ));
+                continue
+            }
+            bound.push(field_name);
+            let field_ty = match variant.field(field_name) {
+                Some((_ix, field_ty)) => field_ty,
+                None => {
+                    debug!("Emitting error: {} has no field {}",
+                        variant.name(), field_name);
+                    self.errors.add_error(CheckerError::new(
+                        vec![field.field().span()],
+                        format!("Variant {} has no field {}",
+                            variant.name(), field_name)
+                    ));
+                    continue
+                }
+            };
+            let binding_ix = match field.binding() {
+                Some(binding) if !binding.id().is_default() =>
+                    self.graph.add_variable(binding.id().clone()),
+                _ => continue
+            };
+            // t_binding: t_field
+            if let Some(field_ty_ix) = self.concrete_type_ix(field_ty) {
+                self.graph.add_inference(binding_ix, field_ty_ix,
+                    InferenceSource::VariantField(field.field().clone()));
+            }
+        }
+    }
 }
 
 impl<'err, 'builder, 'graph> UnitVisitor
@@ -83,12 +1030,13 @@ impl<'err, 'builder, 'graph> ItemVisitor
         trace!("fn {} needs ret value? {}",
             block_fn.name(), need_ret_value);
 
-        let fn_ret_type = self.graph.get_type(
-            &block_fn.return_type().id())
-            .expect("Could not determine fn return type");
+        // Tuple types are not added to the graph until they are used.
+        let fn_ret_type = self.graph.add_type(
+            block_fn.return_type().id().clone());
 
         self.fn_ret_type = fn_ret_type;
 
+        self.current_fn = fn_id.clone();
         let fn_ix = self.graph.add_variable(fn_id.clone());
         let fn_ty_ix = self.graph.add_type(fn_id.clone());
 
@@ -124,12 +1072,99 @@ impl<'err, 'builder, 'graph> ItemVisitor
                                      fn_ret_type,
                 InferenceSource::FnReturnType(block_fn.ident().clone()));
         }
+        // Tuples and arrays which were not inferred from their use are given
+        // the types of their elements.
+        self.resolve_pending();
+    }
+
+    fn visit_extern_fn_decl(&mut self, extern_fn: &ExternFnDeclaration) {
+        trace!("Visiting extern fn {}", extern_fn.name());
+        let fn_id = extern_fn.id();
+        if fn_id.is_default() || self.builder.get_type(&fn_id).is_none() {
+            debug!("Skipping extern fn {}, has no type", extern_fn.name());
+            return
+        }
+        let fn_ix = self.graph.add_variable(fn_id.clone());
+        let fn_ty_ix = self.graph.add_type(fn_id.clone());
+
+        // var_f: ty_f
+        self.graph.add_inference(fn_ix, fn_ty_ix,
+            InferenceSource::FnSignature(extern_fn.ident().clone()));
+    }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) {
+        // Only looking at expressions
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting const {}", const_decl.name());
+        let const_id = const_decl.id();
+        if const_id.is_default() || const_decl.type_expr().id().is_default() {
+            debug!("Skipping const {}, has no type", const_decl.name());
+            return
+        }
+        self.visit_expression(const_decl.value());
+
+        // The const's type was added by the `ItemTypographer`.
+        let const_ix = self.graph.add_variable(const_id.clone());
+        // tvar = texpr
+        self.graph.add_equality(const_ix, self.current_type,
+            InferenceSource::Declaration(const_decl.ident().clone()));
+        self.resolve_pending();
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting global {}", global.name());
+        let global_id = global.id();
+        if global_id.is_default() || global.type_expr().id().is_default() {
+            debug!("Skipping global {}, has no type", global.name());
+            return
+        }
+        self.visit_expression(global.value());
+
+        // The global's type was added by the `ItemTypographer`.
+        let global_ix = self.graph.add_variable(global_id.clone());
+        // tvar = texpr
+        self.graph.add_equality(global_ix, self.current_type,
+            InferenceSource::Declaration(global.ident().clone()));
+        self.resolve_pending();
+    }
+
+    fn visit_import(&mut self, import: &Import) {
+        trace!("Visiting import of {}", import.path().name());
+        for &(ref item, ref id) in import.imported().iter() {
+            // Imported fns can be called like the unit's own fns.
+            match self.builder.get_type(id) {
+                Some(&ConcreteType::Function(_)) => {},
+                _ => continue
+            }
+            trace!("Adding imported fn {} to graph", item);
+            let fn_ix = self.graph.add_variable(id.clone());
+            let fn_ty_ix = self.graph.add_type(id.clone());
+
+            // var_f: ty_f
+            self.graph.add_inference(fn_ix, fn_ty_ix,
+                InferenceSource::FnSignature(import.path().clone()));
+        }
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        trace!("Visiting an impl block");
+        visit::walk_impl_block(self, impl_block);
     }
 
     fn visit_typedef(&mut self, _typedef: &Typedef) {
         // Only looking at expressions
     }
 
+    fn visit_struct_decl(&mut self, _struct_decl: &StructDeclaration) {
+        // Only looking at expressions
+    }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDeclaration) {
+        // Only looking at expressions
+    }
+
 }
 
 impl<'err, 'builder, 'graph> BlockVisitor
@@ -155,7 +1190,7 @@ impl<'err, 'builder, 'graph> BlockVisitor
             trace!("Checking block {:?} with source {:?}",
                 block.id(), block.source().as_ref().expect("Checked"));
             let block_ix = self.graph.add_variable(block.id().clone());
-            self.graph.add_inference(block_ix, self.current_type,
+            self.graph.add_equality(block_ix, self.current_type,
                 InferenceSource::ImplicitReturn);
             self.current_type = block_ix;
         }
@@ -199,7 +1234,7 @@ impl<'err, 'builder, 'graph> StatementVisitor
             if valued_if {
                 trace!("Conditional block must match: {:?} == {:?}",
                     if_block_type, self.current_type);
-                self.graph.add_inference(if_block_type, self.current_type,
+                self.graph.add_equality(if_block_type, self.current_type,
                     InferenceSource::IfBranchesSame);
             }
         }
@@ -208,7 +1243,7 @@ impl<'err, 'builder, 'graph> StatementVisitor
             trace!("Checking block else");
             self.visit_block(block);
             if valued_if {
-                self.graph.add_inference(self.current_type, if_block_type,
+                self.graph.add_equality(self.current_type, if_block_type,
                     InferenceSource::IfBranchesSame);
             }
         }
@@ -224,6 +1259,72 @@ impl<'err, 'builder, 'graph> StatementVisitor
         }
     }
 
+    fn visit_while_block(&mut self, while_block: &WhileBlock) {
+        trace!("Visiting while block");
+        let bool_ty_ix = self.primitive_type_ix("bool");
+        self.visit_expression(while_block.condition());
+        // tcond = tbool
+        self.graph.add_inference(self.current_type, bool_ty_ix,
+            InferenceSource::WhileConditionalBool);
+
+        self.visit_block(while_block.block());
+
+        // while loops are always `()`
+        self.current_type = self.primitive_type_ix("()");
+    }
+
+    fn visit_for_loop(&mut self, for_loop: &ForLoop) {
+        trace!("Visiting for loop over {}", for_loop.ident().name());
+        let ident = for_loop.ident();
+        if ident.id().is_default() {
+            debug!("Skipping for loop without ID");
+            return
+        }
+        let range = for_loop.range();
+        let var_ix = self.graph.add_variable(ident.id().clone());
+        let numeric_type = self.graph.numeric();
+
+        // t_var = t_start = t_end = t_step
+        self.visit_expression(range.start());
+        self.graph.add_equality(var_ix, self.current_type,
+            InferenceSource::ForLoopRange(ident.clone()));
+        self.visit_expression(range.end());
+        self.graph.add_equality(var_ix, self.current_type,
+            InferenceSource::ForLoopRange(ident.clone()));
+        if let Some(step) = range.step() {
+            self.visit_expression(step);
+            self.graph.add_equality(var_ix, self.current_type,
+                InferenceSource::ForLoopRange(ident.clone()));
+        }
+        // t_var: t_number
+        self.graph.add_inference(var_ix, numeric_type,
+            InferenceSource::ForLoopRange(ident.clone()));
+
+        self.visit_block(for_loop.block());
+
+        // for loops are always `()`
+        self.current_type = self.primitive_type_ix("()");
+    }
+
+    fn visit_break_stmt(&mut self, break_: &Break) {
+        if let Some(value) = break_.value() {
+            self.visit_expression(value);
+            if let Some(ref loop_id) = *break_.source() {
+                trace!("Break value must match loop {:?}", loop_id);
+                // t_loop = t_break_value
+                let loop_ix = self.graph.add_variable(loop_id.clone());
+                self.graph.add_equality(loop_ix, self.current_type,
+                    InferenceSource::LoopBreak);
+                self.valued_loops.insert(loop_id.clone());
+            }
+        }
+        self.current_type = self.primitive_type_ix("()");
+    }
+
+    fn visit_continue_stmt(&mut self, _continue: &Continue) {
+        self.current_type = self.primitive_type_ix("()");
+    }
+
     fn visit_declaration(&mut self, decl: &Declaration) {
         trace!("Visiting declaration of {}", decl.ident().name());
 
@@ -233,20 +1334,84 @@ impl<'err, 'builder, 'graph> StatementVisitor
         let var_ix = self.graph.add_variable(decl.id().clone());
 
         if let Some(ty_expr) = decl.type_decl() {
-            let ty_ix = self.graph.get_type(&ty_expr.id())
-                .expect("Did not have type for existing type");
+            let ty_ix = self.graph.add_type(ty_expr.id().clone());
 
             // t_var: ty_expr
             self.graph.add_inference(var_ix, ty_ix,
                                      InferenceSource::ExplicitDecl(decl.ident().clone()));
         }
         // tvar = texpr
-        self.graph.add_inference(var_ix, self.current_type,
+        self.graph.add_equality(var_ix, self.current_type,
                                  InferenceSource::Declaration(decl.ident().clone()));
 
         self.current_type = self.primitive_type_ix("()");
     }
 
+    fn visit_tuple_declaration(&mut self, decl: &TupleDeclaration) {
+        trace!("Visiting tuple declaration");
+
+        self.visit_expression(decl.value());
+
+        // t_tuple = t_value
+        let tuple_ix = self.graph.add_expression();
+        self.graph.add_equality(tuple_ix, self.current_type,
+            InferenceSource::TupleDeclaration);
+
+        if let Some(ty_expr) = decl.type_decl() {
+            if !ty_expr.id().is_default() {
+                let ty_ix = self.graph.add_type(ty_expr.id().clone());
+                // t_tuple: ty_expr
+                self.graph.add_inference(tuple_ix, ty_ix,
+                    InferenceSource::TupleDeclaration);
+            }
+        }
+
+        // Bindings are added even if the value is invalid so they can still
+        // be referenced.
+        let binding_ixs = decl.bindings().iter().map(|binding| {
+            match *binding {
+                Some(ref ident) if !ident.id().is_default() =>
+                    Some(self.graph.add_variable(ident.id().clone())),
+                _ => None
+            }
+        }).collect::<Vec<_>>();
+
+        match self.known_type_of(tuple_ix) {
+            Some(ConcreteType::Tuple(ref tuple))
+                    if tuple.elements().len() == binding_ixs.len() => {
+                for (ix, binding_ix) in binding_ixs.into_iter().enumerate() {
+                    let binding_ix = match binding_ix {
+                        Some(binding_ix) => binding_ix,
+                        None => continue
+                    };
+                    // t_binding: t_element
+                    if let Some(element_ty_ix) =
+                            self.concrete_type_ix(&tuple.elements()[ix]) {
+                        self.graph.add_inference(binding_ix, element_ty_ix,
+                            InferenceSource::TupleElement(ix));
+                    }
+                }
+            },
+            Some(other) => {
+                debug!("Emitting error: cannot destructure {}", other);
+                self.errors.add_error(CheckerError::new(
+                    vec![decl.value().span()],
+                    format!("Expected a tuple of {} elements - got {}",
+                        binding_ixs.len(), other)
+                ));
+            },
+            None => {
+                debug!("Emitting error: unknown tuple type");
+                self.errors.add_error(CheckerError::new(
+                    vec![decl.value().span()],
+                    format!("Could not determine the tuple type of value")
+                ));
+            }
+        }
+
+        self.current_type = self.primitive_type_ix("()");
+    }
+
     fn visit_return_stmt(&mut self, return_: &Return) {
         trace!("Visiting return type");
         // Expr matches block's return.
@@ -283,6 +1448,79 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
             debug!("Skipping unidentified var {}", ident.name());
             return
         }
+        let ident_ty = self.builder.get_type(&ident.id()).cloned();
+        match ident_ty {
+            Some(ConcreteType::Record(ref record)) => {
+                debug!("Emitting error: struct {} used as a value",
+                    record.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![ident.span()],
+                    format!("Struct {} must be constructed with its fields",
+                        record.name())
+                ));
+                return
+            },
+            // Variants without fields can be used as values.
+            Some(ConcreteType::Enum(ref enum_ty)) => {
+                let variant = match self.variant_of(enum_ty, ident) {
+                    Some(variant) => variant,
+                    None => return
+                };
+                if !variant.fields().is_empty() {
+                    debug!("Emitting error: variant {} used as a value",
+                        variant.name());
+                    self.errors.add_error(CheckerError::new(
+                        vec![ident.span()],
+                        format!("Variant {} must be constructed with its fields",
+                            variant.name())
+                    ));
+                    return
+                }
+                let enum_ix = self.enum_type_ix(enum_ty);
+                // t_variant: t_enum
+                let variant_ix = self.graph.add_expression();
+                self.graph.add_inference(variant_ix, enum_ix,
+                    InferenceSource::VariantValue(ident.clone()));
+                self.current_type = variant_ix;
+                return
+            },
+            // Extern fns are not compiled to protosnirk's calling convention.
+            Some(ConcreteType::Function(_))
+                    if self.builder.is_foreign_fn(&ident.id()) => {
+                debug!("Emitting error: extern fn {} used as a value",
+                    ident.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![ident.span()],
+                    format!("Extern function {} must be called",
+                        ident.name())
+                ));
+                return
+            },
+            // Declared functions can be used as values.
+            Some(ConcreteType::Function(_))
+                    if self.builder.fn_type_params(&ident.id()).is_some() => {
+                debug!("Emitting error: generic fn {} used as a value",
+                    ident.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![ident.span()],
+                    format!("Generic function {} must be called",
+                        ident.name())
+                ));
+                return
+            },
+            Some(ConcreteType::Function(ref fn_type)) => {
+                let fn_value_ix = self.graph.add_expression();
+                if let Some(fn_ty_ix) = self.concrete_type_ix(
+                        &ConcreteType::Function(fn_type.clone())) {
+                    // t_fn_value: t_fn_type
+                    self.graph.add_inference(fn_value_ix, fn_ty_ix,
+                        InferenceSource::FnValue(ident.clone()));
+                }
+                self.current_type = fn_value_ix;
+                return
+            },
+            _ => {}
+        }
         self.current_type = self.graph.variable(&ident.id())
             .expect("Graph did not contain identified variable");
     }
@@ -331,35 +1569,120 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
         self.visit_expression(if_expr.else_expr());
         let right_ty_id = self.current_type;
 
-        // We do not point them at each other here to avoid a loop.
-        // I don't think inference can handle this right now.
-
         // ty_if_cond = ty_if_else
-        self.graph.add_inference(right_ty_id, left_ty_id,
+        self.graph.add_equality(right_ty_id, left_ty_id,
             InferenceSource::IfBranchesSame);
 
-        // ty_if_expr: ty_if_cond
-        self.graph.add_inference(if_expr_ty, left_ty_id,
-            InferenceSource::IfBranchesSame);
-        // ty_if_expr: ty_if_else
-        self.graph.add_inference(if_expr_ty, left_ty_id,
+        // ty_if_expr = ty_if_cond
+        self.graph.add_equality(if_expr_ty, left_ty_id,
             InferenceSource::IfBranchesSame);
 
         self.current_type = if_expr_ty;
     }
 
+    fn visit_match_expr(&mut self, match_expr: &Match) {
+        trace!("Visiting match");
+        if match_expr.id().is_default() {
+            debug!("Skipping match without ID");
+            return
+        }
+        self.visit_expression(match_expr.value());
+        let value_ix = self.current_type;
+
+        let valued_match = match_expr.has_source();
+        let match_ix = self.graph.add_variable(match_expr.id().clone());
+        let bool_ty_ix = self.primitive_type_ix("bool");
+
+        for arm in match_expr.arms() {
+            trace!("Checking match arm");
+            let literals = match *arm.pattern() {
+                Pattern::Wildcard(_) => vec![],
+                Pattern::Literal(ref literal) => vec![literal],
+                Pattern::Range(ref range) => vec![range.start(), range.end()],
+                Pattern::Variant(ref variant) => {
+                    self.visit_variant_pattern(variant, value_ix);
+                    vec![]
+                }
+            };
+            for literal in literals {
+                self.visit_literal_expr(literal.literal());
+                // t_pattern = t_value
+                self.graph.add_equality(value_ix, self.current_type,
+                    InferenceSource::MatchPattern);
+                // Negative literals and ranges are only allowed on numbers.
+                if literal.is_negative() || arm.pattern().is_range() {
+                    let numeric_ix = self.graph.numeric();
+                    self.graph.add_inference(self.current_type, numeric_ix,
+                        InferenceSource::MatchPattern);
+                }
+            }
+            if let Some(guard) = arm.guard() {
+                self.visit_expression(guard);
+                // t_guard = tbool
+                self.graph.add_inference(self.current_type, bool_ty_ix,
+                    InferenceSource::MatchGuardBool);
+            }
+            self.visit_block(arm.block());
+            if valued_match {
+                // t_match = t_arm
+                self.graph.add_equality(match_ix, self.current_type,
+                    InferenceSource::MatchArmsSame);
+            }
+        }
+
+        if valued_match {
+            self.current_type = match_ix;
+        }
+        else {
+            self.current_type = self.primitive_type_ix("()");
+        }
+    }
+
+    fn visit_loop_expr(&mut self, loop_expr: &Loop) {
+        trace!("Visiting loop");
+        if loop_expr.id().is_default() {
+            debug!("Skipping loop without ID");
+            return
+        }
+        let loop_ix = self.graph.add_variable(loop_expr.id().clone());
+
+        self.visit_block(loop_expr.block());
+
+        // Loops without `break` values are `()`
+        if !self.valued_loops.contains(&loop_expr.id()) {
+            let unit_ix = self.primitive_type_ix("()");
+            self.graph.add_inference(loop_ix, unit_ix,
+                InferenceSource::LoopBreak);
+        }
+        self.current_type = loop_ix;
+    }
+
     fn visit_unary_op(&mut self, unary_op: &UnaryOperation) {
-        let float_type = self.primitive_type_ix("float");
+        let numeric_type = self.graph.numeric();
         // Require a numeric value for `-expr`
         match unary_op.operator() {
             UnaryOperator::Negation | UnaryOperator::Addition => {
                 self.visit_expression(unary_op.inner());
-                // t_expr = tint
-                self.graph.add_inference(self.current_type, float_type,
+                // t_expr: t_number
+                self.graph.add_inference(self.current_type, numeric_type,
                     InferenceSource::NumericOperator);
+                // t_unary_op = t_expr
                 let unary_op_expr_ty = self.graph.add_expression();
-                self.graph.add_inference(unary_op_expr_ty, float_type,
+                self.graph.add_equality(unary_op_expr_ty, self.current_type,
                     InferenceSource::NumericOperator);
+                self.current_type = unary_op_expr_ty;
+            },
+            UnaryOperator::Not => {
+                self.visit_expression(unary_op.inner());
+                let bool_type = self.primitive_type_ix("bool");
+                // t_expr = t_bool
+                let inner_type = self.current_type;
+                self.add_bool_operand(unary_op.inner(), inner_type, "not");
+                // t_unary_op = t_bool
+                let unary_op_expr_ty = self.graph.add_expression();
+                self.graph.add_inference(unary_op_expr_ty, bool_type,
+                    InferenceSource::BooleanOperator);
+                self.current_type = unary_op_expr_ty;
             },
         }
     }
@@ -380,8 +1703,8 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
             Equality | NonEquality => {
                 let bool_type = self.primitive_type_ix("bool");
                 // lhs and rhs must be the same type, result is bool.
-                // tright: tleft
-                self.graph.add_inference(right_type_id, left_type_id,
+                // tright = tleft
+                self.graph.add_equality(right_type_id, left_type_id,
                     InferenceSource::EqualityOperator);
                 // t_binop = t_bool
                 self.graph.add_inference(binop_type,
@@ -389,36 +1712,275 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
                     InferenceSource::EqualityOperator);
             },
             LessThan | GreaterThan | GreaterThanEquals | LessThanEquals => {
-                // lhs and rhs are numeric, result is bool
-                let float_type = self.primitive_type_ix("float");
+                // lhs and rhs are the same numeric type, result is bool
+                let numeric_type = self.graph.numeric();
                 let bool_type = self.primitive_type_ix("bool");
-                // ty_lhs: ty_number
-                self.graph.add_inference(left_type_id, float_type,
+                // ty_rhs = ty_lhs
+                self.graph.add_equality(right_type_id, left_type_id,
                     InferenceSource::NumericOperator);
-                // ty_rhs: ty_number
-                self.graph.add_inference(right_type_id, float_type,
+                // ty_lhs: ty_number
+                self.graph.add_inference(left_type_id, numeric_type,
                     InferenceSource::NumericOperator);
                 // ty_binop = ty_bool
                 self.graph.add_inference(binop_type, bool_type,
                     InferenceSource::BooleanOperator);
             },
-            Addition | Subtraction | Multiplication | Division | Modulus => {
-                // lhs and rhs are numeric, result is numeric
-                let float_type = self.primitive_type_ix("float");
-                // lhs = number
-                self.graph.add_inference(left_type_id, float_type,
+            Addition => {
+                // lhs, rhs, and result are the same number or string
+                let addable_type = self.graph.addable();
+                // rhs = lhs
+                self.graph.add_equality(right_type_id, left_type_id,
+                    InferenceSource::NumericOperator);
+                // tresult = lhs
+                self.graph.add_equality(binop_type, left_type_id,
+                    InferenceSource::NumericOperator);
+                // lhs: number | str
+                self.graph.add_inference(left_type_id, addable_type,
+                    InferenceSource::NumericOperator);
+            },
+            Subtraction | Multiplication | Division | Modulus => {
+                // lhs, rhs, and result are the same numeric type
+                let numeric_type = self.graph.numeric();
+                // rhs = lhs
+                self.graph.add_equality(right_type_id, left_type_id,
                     InferenceSource::NumericOperator);
-                // rhs = number
-                self.graph.add_inference(right_type_id, float_type,
+                // tresult = lhs
+                self.graph.add_equality(binop_type, left_type_id,
                     InferenceSource::NumericOperator);
-                // tresult = number
-                self.graph.add_inference(binop_type, float_type,
+                // lhs: number
+                self.graph.add_inference(left_type_id, numeric_type,
                     InferenceSource::NumericOperator);
             },
+            And | Or => {
+                // lhs, rhs, and result are all bool
+                let bool_type = self.primitive_type_ix("bool");
+                let operator = if bin_op.operator() == And { "and" }
+                               else { "or" };
+                // ty_lhs = ty_bool
+                self.add_bool_operand(bin_op.left(), left_type_id, operator);
+                // ty_rhs = ty_bool
+                self.add_bool_operand(bin_op.right(), right_type_id, operator);
+                // ty_binop = ty_bool
+                self.graph.add_inference(binop_type, bool_type,
+                    InferenceSource::BooleanOperator);
+            },
         }
         self.current_type = binop_type;
     }
 
+    fn visit_tuple_expr(&mut self, tuple: &TupleExpression) {
+        trace!("Visiting tuple of {} elements", tuple.elements().len());
+        let mut element_ixs = Vec::with_capacity(tuple.elements().len());
+        for element in tuple.elements() {
+            self.visit_expression(element);
+            element_ixs.push(self.current_type);
+        }
+        // The tuple's type is inferred from how it's used, or from its
+        // elements when the function has been checked.
+        let tuple_ix = self.graph.add_expression();
+        self.pending_values.push(PendingValue::Tuple {
+            tuple_ix,
+            element_ixs,
+            span: tuple.span()
+        });
+        self.current_type = tuple_ix;
+    }
+
+    fn visit_array_expr(&mut self, array: &ArrayExpression) {
+        trace!("Visiting array of {} elements", array.elements().len());
+        // All of the elements have the same type.
+        let element_ix = self.graph.add_expression();
+        for element in array.elements() {
+            self.visit_expression(element);
+            // t_element = t_value
+            self.graph.add_equality(element_ix, self.current_type,
+                InferenceSource::ArrayElement);
+        }
+        let array_ix = self.graph.add_variable(array.id().clone());
+        self.pending_values.push(PendingValue::Array {
+            array_ix,
+            element_ix,
+            len: array.elements().len() as u64,
+            span: array.span()
+        });
+        self.current_type = array_ix;
+    }
+
+    fn visit_array_repeat(&mut self, repeat: &ArrayRepeat) {
+        trace!("Visiting array of {} repeated values", repeat.len());
+        self.visit_expression(repeat.value());
+        let element_ix = self.graph.add_expression();
+        // t_element = t_value
+        self.graph.add_equality(element_ix, self.current_type,
+            InferenceSource::ArrayElement);
+        let array_ix = self.graph.add_variable(repeat.id().clone());
+        self.pending_values.push(PendingValue::Array {
+            array_ix,
+            element_ix,
+            len: repeat.len(),
+            span: repeat.span()
+        });
+        self.current_type = array_ix;
+    }
+
+    fn visit_index_expr(&mut self, index: &IndexExpression) {
+        trace!("Visiting index of array");
+        let (element_ix, _value_ty) = self.index_element(index);
+        self.current_type = element_ix;
+    }
+
+    fn visit_slice_expr(&mut self, slice: &SliceExpression) {
+        trace!("Visiting slice of array");
+        self.visit_expression(slice.value());
+        let value_ix = self.current_type;
+        let slice_ix = self.graph.add_variable(slice.id().clone());
+        if let Some((_value_ty, element_ty)) =
+                self.elements_of(value_ix, slice.value().span()) {
+            let slice_id = self.builder.add_slice_type(
+                SliceType::new(element_ty));
+            let slice_ty_ix = self.graph.add_type(slice_id);
+            // t_slice: t_slice_type
+            self.graph.add_inference(slice_ix, slice_ty_ix,
+                InferenceSource::SliceValue);
+        }
+        self.current_type = slice_ix;
+    }
+
+    fn visit_method_call(&mut self, method_call: &MethodCall) {
+        trace!("Visiting call of method {}", method_call.method().name());
+        self.visit_expression(method_call.receiver());
+        let receiver_ix = self.current_type;
+        // Numbers can have methods of their default type.
+        let receiver_ty = self.known_type_of(receiver_ix).or_else(|| {
+            let builder = &self.builder;
+            self.graph.known_type_or_default(receiver_ix)
+                .and_then(|type_id| builder.get_type(&type_id))
+                .cloned()
+        });
+        let method_ty = match receiver_ty {
+            Some(ConcreteType::Growable(ref growable)) => {
+                self.current_type =
+                    self.visit_growable_method(method_call, growable);
+                return
+            },
+            Some(ref receiver_ty) =>
+                self.trait_method_type(method_call.method(), receiver_ty),
+            None => {
+                debug!("Emitting error: unknown receiver type");
+                self.errors.add_error(CheckerError::new(
+                    vec![method_call.receiver().span()],
+                    format!("Could not determine the type of value")
+                ));
+                None
+            }
+        };
+        let call_ix = match method_ty {
+            Some(ref fn_type) => self.visit_trait_method(method_call, fn_type),
+            None => {
+                for arg in method_call.args() {
+                    self.visit_expression(arg.expression());
+                }
+                self.graph.add_expression()
+            }
+        };
+        self.current_type = call_ix;
+    }
+
+    fn visit_field_access(&mut self, field_access: &FieldAccess) {
+        trace!("Visiting access of field {}", field_access.field().name());
+        self.visit_expression(field_access.value());
+        let value_ix = self.current_type;
+        let field = field_access.field();
+        let field_ix = self.graph.add_field(field.name().into(), value_ix);
+
+        let value_ty = self.known_type_of(value_ix);
+        let array_kind = match value_ty {
+            Some(ConcreteType::Array(_)) => Some("Array"),
+            Some(ConcreteType::Growable(_)) => Some("Growable array"),
+            Some(ConcreteType::Slice(_)) => Some("Slice"),
+            Some(ConcreteType::Named(ref named)) if named.name() == "str" =>
+                Some("String"),
+            _ => None
+        };
+        if let Some(array_kind) = array_kind {
+            if field.name() == "len" {
+                // t_access: int
+                let int_ix = self.primitive_type_ix("int");
+                self.graph.add_inference(field_ix, int_ix,
+                    InferenceSource::FieldAccess(field.clone()));
+            }
+            else {
+                let array_ty = value_ty.as_ref().expect("Checked");
+                debug!("Emitting error: {} has no field {}",
+                    array_ty, field.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![field.span()],
+                    format!("{} {} has no field {}",
+                        array_kind, array_ty, field.name())
+                ));
+            }
+        }
+        else if let Some(ConcreteType::Tuple(ref tuple)) = value_ty {
+            let tuple_ty = value_ty.as_ref().expect("Checked");
+            match tuple.element(field.name()) {
+                Some((_ix, element_ty)) => {
+                    // t_access: t_element
+                    if let Some(element_ty_ix) = self.concrete_type_ix(element_ty) {
+                        self.graph.add_inference(field_ix, element_ty_ix,
+                            InferenceSource::FieldAccess(field.clone()));
+                    }
+                },
+                None => {
+                    debug!("Emitting error: {} has no element {}",
+                        tuple_ty, field.name());
+                    self.errors.add_error(CheckerError::new(
+                        vec![field.span()],
+                        format!("Tuple {} has no element {}",
+                            tuple_ty, field.name())
+                    ));
+                }
+            }
+        }
+        else if let Some(record) = self.expect_record(value_ty,
+                                                field_access.value().span()) {
+            match record.field(field.name()) {
+                Some((_ix, field_ty)) => {
+                    // t_access: t_field
+                    if let Some(field_ty_ix) = self.concrete_type_ix(field_ty) {
+                        self.graph.add_inference(field_ix, field_ty_ix,
+                            InferenceSource::FieldAccess(field.clone()));
+                    }
+                },
+                None => {
+                    debug!("Emitting error: {} has no field {}",
+                        record.name(), field.name());
+                    self.errors.add_error(CheckerError::new(
+                        vec![field.span()],
+                        format!("Struct {} has no field {}",
+                            record.name(), field.name())
+                    ));
+                }
+            }
+        }
+        self.current_type = field_ix;
+    }
+
+    fn visit_struct_update(&mut self, struct_update: &StructUpdate) {
+        trace!("Visiting struct update");
+        self.visit_expression(struct_update.value());
+        let value_ix = self.current_type;
+
+        if let Some(record) = self.record_type_of(value_ix,
+                                                  struct_update.value().span()) {
+            self.visit_struct_fields("Struct", &record, struct_update.fields());
+        }
+        // t_update = t_value
+        let update_ix = self.graph.add_expression();
+        self.graph.add_equality(update_ix, value_ix,
+            InferenceSource::StructValue);
+        self.current_type = update_ix;
+    }
+
     fn visit_assignment(&mut self, assign: &Assignment) {
         trace!("Visiting assignment");
         self.visit_expression(assign.rvalue());
@@ -435,7 +1997,26 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
             .expect("Graph did not have known lvalue of assignment");
 
         // tleft = tright
-        self.graph.add_inference(self.current_type, lvalue_type,
+        self.graph.add_equality(self.current_type, lvalue_type,
+            InferenceSource::Assignment);
+
+        self.current_type = self.primitive_type_ix("()");
+    }
+
+    fn visit_index_assignment(&mut self, assign: &IndexAssignment) {
+        trace!("Visiting assignment to an element");
+        let (element_ix, value_ty) = self.index_element(assign.lvalue());
+        if let Some(ConcreteType::Slice(_)) = value_ty {
+            debug!("Emitting error: assignment to an element of a slice");
+            self.errors.add_error(CheckerError::new(
+                vec![assign.span()],
+                format!("Elements of slices cannot be assigned to")
+            ));
+        }
+        self.visit_expression(assign.rvalue());
+
+        // t_element = t_right
+        self.graph.add_equality(self.current_type, element_ix,
             InferenceSource::Assignment);
 
         self.current_type = self.primitive_type_ix("()");
@@ -443,14 +2024,21 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
 
     fn visit_literal_expr(&mut self, literal: &Literal) {
         trace!("Visiting literal");
-        // We create a new ID with the literal's type.
+        // Integer literals may be any numeric type, depending on context.
         let literal_type_id =
             match *literal.value() {
                 LiteralValue::Bool(_) => self.primitive_type_ix("bool"),
+                LiteralValue::Int(_) => self.graph.numeric(),
                 LiteralValue::Float(_) => self.primitive_type_ix("float"),
+                LiteralValue::Str(_) => self.primitive_type_ix("str"),
                 LiteralValue::Unit => self.primitive_type_ix("()")
             };
-        let expr_ty = self.graph.add_expression();
+        let expr_ty = if literal.id().is_default() {
+            self.graph.add_expression()
+        }
+        else {
+            self.graph.add_variable(literal.id().clone())
+        };
         self.graph.add_inference(expr_ty, literal_type_id,
             InferenceSource::LiteralValue(literal.clone()));
         self.current_type = expr_ty;
@@ -469,6 +2057,26 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
             return
         }
 
+        // Structs and variants are constructed with call syntax.
+        let fn_id_ty = self.builder.get_type(&fn_id).cloned();
+        match fn_id_ty {
+            Some(ConcreteType::Record(ref record)) => {
+                let struct_ix = self.graph.get_type(&fn_id)
+                    .expect("Struct type was not added to the graph");
+                return self.visit_struct_construction(fn_call, "Struct",
+                                                      record, struct_ix)
+            },
+            Some(ConcreteType::Enum(ref enum_ty)) => {
+                if let Some(variant) = self.variant_of(enum_ty, fn_call.ident()) {
+                    let enum_ix = self.enum_type_ix(enum_ty);
+                    self.visit_struct_construction(fn_call, "Variant",
+                                                   variant, enum_ix);
+                }
+                return
+            },
+            _ => {}
+        }
+
         // Attempt to find the function, either through top-level declaration
         // or through local binding.
         let fn_ix = self.graph.get_type(&fn_id)
@@ -484,6 +2092,40 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
         }
         let fn_ix = fn_ix.expect("Checked");
 
+        // Functions can also be called through variables. Numbers are never
+        // functions, so they can be reported as their default type.
+        let fn_type = match self.builder.get_type(&fn_id).cloned() {
+            Some(ConcreteType::Function(fn_type)) => Some(fn_type),
+            _ => match self.known_type_of(fn_ix).or_else(|| {
+                let builder = &self.builder;
+                self.graph.known_type_or_default(fn_ix)
+                    .and_then(|type_id| builder.get_type(&type_id))
+                    .cloned()
+            }) {
+                Some(ConcreteType::Function(fn_type)) => Some(fn_type),
+                Some(other) => {
+                    debug!("Emitting error: called {} of type {}",
+                        fn_call.text(), other);
+                    self.errors.add_error(CheckerError::new(
+                        vec![fn_call.ident().span()],
+                        format!("Expected a function - {} has type {}",
+                            fn_call.text(), other)
+                    ));
+                    return
+                },
+                None => None
+            }
+        };
+
+        // Generic functions have their own type arguments for each call.
+        let type_params = self.builder.fn_type_params(&fn_id)
+            .map(|type_params| type_params.to_vec());
+        if let (Some(type_params), Some(fn_type)) =
+                (type_params, fn_type.as_ref()) {
+            return self.visit_generic_call(fn_call, fn_ix, fn_type,
+                                           &type_params)
+        }
+
         // We create an indirect node between call arguments and the function
         // type which the graph will simplify later.
         for (_arg_num, arg) in fn_call.args().iter().enumerate() {
@@ -498,12 +2140,121 @@ impl<'err, 'builder, 'graph> ExpressionVisitor
             /*else {
                 self.graph.add_call_arg(arg_num, fn_ix)
             }*/;
-            self.graph.add_inference(arg_infer, expr_ty,
+            self.graph.add_equality(arg_infer, expr_ty,
                 InferenceSource::CallArgument(fn_call.ident().clone()));
+
+            // t_arg: t_param
+            let param_ty = fn_type.as_ref().and_then(|fn_type| {
+                fn_type.params().iter()
+                    .find(|&&(ref name, _)| name == arg.name().name())
+                    .and_then(|&(_, ref param_ty)|
+                        self.concrete_type_ix(param_ty))
+            });
+            if let Some(param_ty_ix) = param_ty {
+                self.graph.add_inference(arg_infer, param_ty_ix,
+                    InferenceSource::CallArgument(fn_call.ident().clone()));
+            }
         }
 
         // t_current = t_return(fn)
         let fn_return_type = self.graph.add_call_return_type(fn_ix);
+        if let Some(return_ty_ix) = fn_type.as_ref().and_then(|fn_type|
+                self.concrete_type_ix(fn_type.return_ty())) {
+            self.graph.add_inference(fn_return_type, return_ty_ix,
+                InferenceSource::CallReturnType(fn_call.ident().clone()));
+        }
         self.current_type = fn_return_type;
     }
+
+    fn visit_lambda(&mut self, lambda: &Lambda) {
+        trace!("Visiting lambda");
+        if lambda.id().is_default() {
+            debug!("Skipping lambda without ID");
+            return
+        }
+        let mut params = Vec::new();
+        let mut known_params = true;
+        for &(ref param_ident, ref param_expr) in lambda.params() {
+            let param_ty_id = param_expr.id().clone();
+            if param_ident.id().is_default() || param_ty_id.is_default() {
+                debug!("Skipping unidentified lambda param {}",
+                    param_ident.name());
+                known_params = false;
+                continue
+            }
+            // var_param: ty_param
+            let param_ix = self.graph.add_variable(param_ident.id().clone());
+            let param_ty_ix = self.graph.add_type(param_ty_id.clone());
+            self.graph.add_inference(param_ix, param_ty_ix,
+                InferenceSource::FnParameter(param_ident.clone()));
+            match self.builder.get_type(&param_ty_id).cloned() {
+                Some(param_ty) =>
+                    params.push((param_ident.name().to_string(), param_ty)),
+                None => known_params = false
+            }
+        }
+
+        // `return`s in the body return from the lambda.
+        let ret_ix = match lambda.return_type() {
+            Some(return_type) if !return_type.id().is_default() =>
+                self.graph.add_type(return_type.id().clone()),
+            _ => self.graph.add_expression()
+        };
+        let outer_ret_ix = self.fn_ret_type;
+        self.fn_ret_type = ret_ix;
+        self.visit_expression(lambda.body());
+        self.fn_ret_type = outer_ret_ix;
+
+        if lambda.return_type().is_some() {
+            // t_body: t_ret
+            self.graph.add_inference(self.current_type, ret_ix,
+                InferenceSource::LambdaReturn);
+        }
+        else {
+            // t_ret = t_body
+            self.graph.add_equality(ret_ix, self.current_type,
+                InferenceSource::LambdaReturn);
+        }
+        let lambda_ix = self.graph.add_variable(lambda.id().clone());
+        self.current_type = lambda_ix;
+        if !known_params {
+            return
+        }
+
+        let return_ty = match lambda.return_type() {
+            Some(return_type) => {
+                match self.builder.get_type(&return_type.id()).cloned() {
+                    Some(return_ty) => return_ty,
+                    None => return
+                }
+            },
+            None => {
+                let known_ty = self.known_type_of(ret_ix);
+                let builder = &self.builder;
+                let graph = &self.graph;
+                match known_ty.or_else(||
+                    graph.known_type_or_default(ret_ix)
+                        .and_then(|type_id| builder.get_type(&type_id))
+                        .cloned()) {
+                    Some(return_ty) => return_ty,
+                    None => {
+                        debug!("Emitting error: unknown lambda return type");
+                        self.errors.add_error(CheckerError::new(
+                            vec![lambda.body().span()],
+                            format!("Could not determine the return type of \
+                                     lambda, it needs to be given with `->`")
+                        ));
+                        return
+                    }
+                }
+            }
+        };
+
+        // t_lambda: t_fn
+        let fn_type = ConcreteType::Function(FnType::new(params, return_ty));
+        if let Some(fn_ty_ix) = self.concrete_type_ix(&fn_type) {
+            self.graph.add_inference(lambda_ix, fn_ty_ix,
+                InferenceSource::LambdaValue);
+        }
+    }
 }
@@ -14,12 +14,48 @@ pub enum InferenceSource {
     FnReturnType(Identifier),
     /// Inference source is the parameter of a function.
     FnParameter(Identifier),
+    /// Inference source is a declared function used as a value.
+    FnValue(Identifier),
+    /// Inference source is a lambda value having its function type.
+    LambdaValue,
+    /// Inference source is the body of a lambda matching its return type.
+    LambdaReturn,
     /// Inference source is a typedef alias.
     Typedef(Identifier),
     /// Inference source is the call argument of a function.
     CallArgument(Identifier),
     /// Inference source is the return type of a call.
     CallReturnType(Identifier),
+    /// Inference source is a type argument of a call to a generic function.
+    TypeArgument(Identifier),
+    /// Inference source is the field of a struct being constructed or updated.
+    StructField(Identifier),
+    /// Inference source is a struct being constructed or updated.
+    StructValue,
+    /// Inference source is the type of an accessed field.
+    FieldAccess(Identifier),
+    /// Inference source is an element of a tuple.
+    TupleElement(usize),
+    /// Inference source is a tuple value.
+    TupleValue,
+    /// Inference source is a tuple being destructured by a declaration.
+    TupleDeclaration,
+    /// Inference source is an element of an array.
+    ArrayElement,
+    /// Inference source is an array value.
+    ArrayValue,
+    /// Inference source is the index of an array being an int.
+    ArrayIndex,
+    /// Inference source is a slice of an array.
+    SliceValue,
+    /// Inference source is the argument of a method call.
+    MethodArgument(Identifier),
+    /// Inference source is the return type of a method call.
+    MethodReturnType(Identifier),
+    /// Inference source is an enum variant being constructed.
+    VariantValue(Identifier),
+    /// Inference source is the field of a variant bound by a pattern.
+    VariantField(Identifier),
     /// Inference source is the declaration of a variable with a given type.
     ExplicitDecl(Identifier),
     /// Inference source is from the rvalue of a variable declaration.
@@ -30,6 +66,18 @@ pub enum InferenceSource {
     IfConditionalBool,
     /// Inference source is the if branches being the same.
     IfBranchesSame,
+    /// Inference source is the conditional of a while loop being a bool.
+    WhileConditionalBool,
+    /// Inference source is a for loop variable matching its range.
+    ForLoopRange(Identifier),
+    /// Inference source is a `break` value matching its `loop`.
+    LoopBreak,
+    /// Inference source is a `match` pattern matching the matched value.
+    MatchPattern,
+    /// Inference source is a `match` arm guard being a bool.
+    MatchGuardBool,
+    /// Inference source is the `match` arms being the same.
+    MatchArmsSame,
     /// Inference source is a `return` matching the fn return type.
     ExplicitReturn,
     /// Inference source is an implicit return matching a block.
@@ -60,6 +108,11 @@ impl fmt::Debug for InferenceSource {
             FnParameter(ref id) => f.debug_tuple("FnParam")
                                  .field(&id.name())
                                  .finish(),
+            FnValue(ref id) => f.debug_tuple("FnValue")
+                             .field(&id.name())
+                             .finish(),
+            LambdaValue => f.write_str("LambdaValue"),
+            LambdaReturn => f.write_str("LambdaReturn"),
             Typedef(ref id) => f.debug_tuple("Typedef")
                                 .field(&id.name())
                                 .finish(),
@@ -69,6 +122,21 @@ impl fmt::Debug for InferenceSource {
             CallReturnType(ref id) => f.debug_tuple("CallReturn")
                                     .field(&id.name())
                                     .finish(),
+            TypeArgument(ref id) => f.debug_tuple("TypeArg")
+                                  .field(&id.name())
+                                  .finish(),
+            StructField(ref id) => f.debug_tuple("StructField")
+                                 .field(&id.name())
+                                 .finish(),
+            FieldAccess(ref id) => f.debug_tuple("FieldAccess")
+                                 .field(&id.name())
+                                 .finish(),
+            VariantValue(ref id) => f.debug_tuple("VariantValue")
+                                  .field(&id.name())
+                                  .finish(),
+            VariantField(ref id) => f.debug_tuple("VariantField")
+                                  .field(&id.name())
+                                  .finish(),
             ExplicitDecl(ref id) => f.debug_tuple("ExplicitLet")
                                   .field(&id.name())
                                   .finish(),
@@ -78,8 +146,32 @@ impl fmt::Debug for InferenceSource {
             LiteralValue(ref lit) => f.debug_tuple("Literal")
                                    .field(&lit.value())
                                    .finish(),
+            TupleElement(index) => f.debug_tuple("TupleElement")
+                                    .field(&index)
+                                    .finish(),
+            StructValue => f.write_str("StructValue"),
+            TupleValue => f.write_str("TupleValue"),
+            TupleDeclaration => f.write_str("TupleLet"),
+            ArrayElement => f.write_str("ArrayElement"),
+            ArrayValue => f.write_str("ArrayValue"),
+            ArrayIndex => f.write_str("ArrayIndex"),
+            SliceValue => f.write_str("SliceValue"),
+            MethodArgument(ref id) => f.debug_tuple("MethodArg")
+                                    .field(&id.name())
+                                    .finish(),
+            MethodReturnType(ref id) => f.debug_tuple("MethodReturn")
+                                      .field(&id.name())
+                                      .finish(),
             IfConditionalBool => f.write_str("IfCond"),
             IfBranchesSame => f.write_str("IfBranchEq"),
+            WhileConditionalBool => f.write_str("WhileCond"),
+            ForLoopRange(ref id) => f.debug_tuple("ForRange")
+                                  .field(&id.name())
+                                  .finish(),
+            LoopBreak => f.write_str("LoopBreak"),
+            MatchPattern => f.write_str("MatchPattern"),
+            MatchGuardBool => f.write_str("MatchGuardBool"),
+            MatchArmsSame => f.write_str("MatchArmsSame"),
             ExplicitReturn => f.write_str("ReturnStmt"),
             ImplicitReturn => f.write_str("ReturnExpr"),
             Assignment => f.write_str("Assign"),
@@ -2,8 +2,12 @@
 
 use ast::{*, visit::*};
 use check::{CheckerError, ErrorCollector};
-use identify::{ConcreteType, FnType, TypeScopeBuilder};
-use identify::types::TypeIdentifier;
+use lex::Span;
+use identify::{ConcreteType, EnumType, FnType, NamedType, RecordType,
+               TypeScopeBuilder};
+use identify::types::{TypeIdentifier, NUMERIC_TYPE_NAMES};
+
+use std::collections::{HashMap, HashSet};
 
 /// Identifies type expressions in items, such as function parameters and
 /// function names as concrete function types.
@@ -19,63 +23,808 @@ impl<'err, 'builder> ItemTypeIdentifier<'err, 'builder> {
                -> ItemTypeIdentifier<'err, 'builder> {
         ItemTypeIdentifier { errors, builder }
     }
-}
 
-impl<'err, 'builder> UnitVisitor for ItemTypeIdentifier<'err, 'builder> {
-    fn visit_unit(&mut self, unit: &Unit) {
-        trace!("Visting a unit");
-        visit::walk_unit(self, unit);
+    /// Reserve the name of a struct or enum type.
+    fn declare_type(&mut self, ident: &Identifier, span: Span) -> bool {
+        trace!("Declaring type {}", ident.name());
+        if ident.id().is_default() {
+            debug!("Skipping type {} with default ID", ident.name());
+            return false
+        }
+        if self.builder.named_type_id(ident.name()).is_some() {
+            debug!("Emitting error: type {} already declared", ident.name());
+            self.errors.add_error(CheckerError::new(
+                vec![span],
+                format!("Type {} is already declared", ident.name())
+            ));
+            return false
+        }
+        self.builder.add_type_name(ident.name().to_string(),
+                                   ident.id().clone());
+        true
     }
-}
 
-impl<'err, 'builder> ItemVisitor for ItemTypeIdentifier<'err, 'builder> {
-    fn visit_block_fn_decl(&mut self, fn_decl: &BlockFnDeclaration) {
-        trace!("Visiting block fn {}", fn_decl.name());
-        if fn_decl.id().is_default() {
-            debug!("Skipping fn {} with default ID", fn_decl.name());
-            return
+    /// Find the types of the fields of a struct or variant.
+    fn resolve_fields(&mut self, owner: &str,
+                      fields: &[(Identifier, TypeExpression)],
+                      failed: &HashSet<ScopedId>) -> Fields {
+        let mut resolved = Vec::with_capacity(fields.len());
+        for &(ref field, ref field_ty_expr) in fields {
+            // Tuples and arrays of structs or enums are identified once those are.
+            let unit_id = self.builder.named_type_id("()")
+                .expect("Primitive").clone();
+            if field_ty_expr.id().is_default()
+                    && is_pending_type(field_ty_expr, &unit_id, failed) {
+                TypeIdentifier::new(self.errors, self.builder)
+                               .visit_type_expr(field_ty_expr);
+                if field_ty_expr.id().is_default() {
+                    trace!("{} field {} has an unresolved tuple or array type",
+                        owner, field.name());
+                    return Fields::Pending
+                }
+            }
+            let field_ty_id = field_ty_expr.id().clone();
+            if field_ty_id.is_default() || failed.contains(&field_ty_id) {
+                debug!("Unable to identify type of {} field {}",
+                    owner, field.name());
+                return Fields::Failed
+            }
+            let field_ty = match self.builder.get_type(&field_ty_id) {
+                Some(field_ty) => field_ty.clone(),
+                None => {
+                    trace!("{} field {} has an unresolved type",
+                        owner, field.name());
+                    return Fields::Pending
+                }
+            };
+            if field_ty_id == *self.builder.named_type_id("()")
+                    .expect("Primitive") {
+                debug!("Emitting error: {} field {} is ()",
+                    owner, field.name());
+                self.errors.add_error(CheckerError::new(
+                    vec![field_ty_expr.span()],
+                    format!("Field {} of {} cannot have type ()",
+                        field.name(), owner)
+                ));
+                return Fields::Failed
+            }
+            resolved.push((field.name().to_string(), field_ty));
         }
+        Fields::Resolved(resolved)
+    }
 
+    /// Give a function its concrete `FnType`, if the types in its signature
+    /// are known.
+    fn identify_fn_type(&mut self, fn_decl: &BlockFnDeclaration) {
         // Declared functions' types are handled here because we do not want
         // to run full type inference at the item level.
-        let mut arg_types = Vec::with_capacity(fn_decl.params().len());
+        if let Some(fn_type) = self.identify_signature(fn_decl.name(),
+                                                       fn_decl.params(),
+                                                       fn_decl.return_type()) {
+            let fn_concrete = ConcreteType::Function(fn_type);
+            trace!("fn {} has concrete type {:?}", fn_decl.name(), fn_concrete);
+            self.builder.add_type(fn_decl.id().clone(), fn_concrete);
+        }
+    }
 
-        for &(ref param_ident, ref param_ty_expr) in fn_decl.params() {
+    /// Give the items imported from another module their types from the
+    /// module's interface.
+    fn identify_import(&mut self, import: &Import) {
+        trace!("Identifying import of {}", import.path().name());
+        let interface = match self.builder.module(import.module_name()) {
+            Some(interface) => interface.clone(),
+            None => return
+        };
+        for &(ref item, ref id) in import.imported().iter() {
+            let item_ty = interface.item(item)
+                .expect("Imported item is not in its module")
+                .clone();
+            // Structs and enums are also types, but variants are not.
+            let is_type = match item_ty {
+                ConcreteType::Record(ref record) => record.name() == item,
+                ConcreteType::Enum(ref enum_ty) => enum_ty.name() == item,
+                _ => false
+            };
+            if is_type {
+                let local_name = import.local_name(item);
+                if self.builder.named_type_id(&local_name).is_some() {
+                    debug!("Emitting error: type {} already declared",
+                        local_name);
+                    self.errors.add_error(CheckerError::new(
+                        vec![import.span()],
+                        format!("Type {} is already declared", local_name)
+                    ));
+                    continue
+                }
+                self.builder.add_type_name(local_name, id.clone());
+            }
+            trace!("Imported item {} has type {}", item, item_ty);
+            self.builder.add_type(id.clone(), item_ty);
+        }
+    }
+
+    /// Check that a function called across the C boundary only uses types
+    /// which C has an equivalent of.
+    fn check_c_signature(&mut self, kind: &str, name: &str,
+                         params: &[(Identifier, TypeExpression)],
+                         return_ty_expr: &TypeExpression,
+                         fn_type: &FnType,
+                         str_params: bool) -> bool {
+        let mut valid = true;
+        for (&(ref param, ref param_ty_expr), &(_, ref param_ty)) in
+                params.iter().zip(fn_type.params()) {
+            let is_c_param = match primitive_name(param_ty) {
+                Some("()") | None => false,
+                Some("str") => str_params,
+                Some(_) => true
+            };
+            if !is_c_param {
+                debug!("Emitting error: {} fn param {} has type {}",
+                    kind, param.name(), param_ty);
+                self.errors.add_error(CheckerError::new(
+                    vec![param_ty_expr.span()],
+                    format!("Parameter {} of {} fn {} cannot have type {}",
+                        param.name(), kind.to_lowercase(), name, param_ty)
+                ));
+                valid = false;
+            }
+        }
+        let return_ty = fn_type.return_ty();
+        if primitive_name(return_ty).map_or(true, |name| name == "str") {
+            debug!("Emitting error: {} fn {} returns {}", kind, name, return_ty);
+            self.errors.add_error(CheckerError::new(
+                vec![return_ty_expr.span()],
+                format!("{} fn {} cannot return {}", kind, name, return_ty)
+            ));
+            valid = false;
+        }
+        valid
+    }
+
+    /// Find the `FnType` of a function or method signature, if the types in
+    /// it are known.
+    fn identify_signature(&mut self, name: &str,
+                          params: &[(Identifier, TypeExpression)],
+                          return_ty: &TypeExpression) -> Option<FnType> {
+        let mut arg_types = Vec::with_capacity(params.len());
+
+        for &(ref param_ident, ref param_ty_expr) in params {
             trace!("Calling TypeIdentifier for {} param {}",
-                fn_decl.name(), param_ident.name());
+                name, param_ident.name());
             TypeIdentifier::new(self.errors, self.builder)
                            .visit_type_expr(param_ty_expr);
             // Stop if we can't idenify a parameter type.
             if param_ty_expr.id().is_default() {
                 debug!("Unable to identify type of {} param {}",
-                    fn_decl.name(), param_ident.name());
-                return
+                    name, param_ident.name());
+                return None
             }
-            let param_ty = self.builder.get_type(&param_ty_expr.id())
-                .expect("TypeIdentifier did not update param's type ID");
+            // Structs which could not be resolved have no type.
+            let param_ty = match self.builder.get_type(&param_ty_expr.id()) {
+                Some(param_ty) => param_ty,
+                None => {
+                    debug!("Unresolved type of {} param {}",
+                        name, param_ident.name());
+                    return None
+                }
+            };
             trace!("{} param {} has type id {:?}",
-                fn_decl.name(), param_ident.name(), param_ty);
+                name, param_ident.name(), param_ty);
             arg_types.push((param_ident.name().to_string(),
                             param_ty.clone()));
         }
-        let return_ty = fn_decl.return_type();
         trace!("Calling TypeIdentifier for {} return type {:?}",
-            fn_decl.name(), return_ty);
+            name, return_ty);
         TypeIdentifier::new(self.errors, self.builder)
                        .visit_type_expr(return_ty);
 
         if return_ty.id().is_default() {
-            debug!("Unable to identify return type of {}", fn_decl.name());
+            debug!("Unable to identify return type of {}", name);
+            return None
+        }
+        let ret_ty = match self.builder.get_type(&return_ty.id()) {
+            Some(ret_ty) => ret_ty.clone(),
+            None => {
+                debug!("Unresolved return type of {}", name);
+                return None
+            }
+        };
+        Some(FnType::new(arg_types, ret_ty))
+    }
+
+    /// Give a trait the signatures of its methods, in which `Self` is a type
+    /// parameter standing for the implementing type.
+    fn declare_trait(&mut self, trait_decl: &TraitDeclaration) {
+        trace!("Declaring trait {}", trait_decl.name());
+        if trait_decl.id().is_default() {
+            debug!("Skipping trait {} with default ID", trait_decl.name());
+            return
+        }
+        let mut methods = Vec::with_capacity(trait_decl.methods().len());
+        self.builder.begin_type_params(&["Self".to_string()]);
+        for method in trait_decl.methods() {
+            let name = format!("{}.{}", trait_decl.name(), method.name());
+            let fn_type = match self.identify_signature(&name,
+                    method.params(), method.return_type()) {
+                Some(fn_type) => fn_type,
+                None => continue
+            };
+            let self_ty = ConcreteType::Parameter(
+                NamedType::new("Self".to_string()));
+            if fn_type.params()[0].1 != self_ty {
+                debug!("Emitting error: receiver of {} is {}",
+                    name, fn_type.params()[0].1);
+                self.errors.add_error(CheckerError::new(
+                    vec![method.params()[0].1.span()],
+                    format!("Method {} of trait {} must take self of type Self",
+                        method.name(), trait_decl.name())
+                ));
+                continue
+            }
+            methods.push((method.name().to_string(), fn_type));
+        }
+        self.builder.end_type_params();
+        self.builder.add_trait(trait_decl.name().to_string(), methods);
+    }
+
+    /// Check that the traits bounding the type parameters of a generic fn
+    /// exist.
+    fn identify_bounds(&mut self, fn_decl: &BlockFnDeclaration)
+                       -> HashMap<String, Vec<String>> {
+        let mut bounds = HashMap::new();
+        for type_param in fn_decl.type_params() {
+            let mut traits = Vec::with_capacity(type_param.bounds().len());
+            for bound in type_param.bounds() {
+                if self.builder.trait_methods(bound.name()).is_none() {
+                    debug!("Emitting error: unknown trait {}", bound.name());
+                    self.errors.add_error(CheckerError::new(
+                        vec![bound.span()],
+                        format!("Unknown trait {}", bound.name())
+                    ));
+                    continue
+                }
+                traits.push(bound.name().to_string());
+            }
+            bounds.insert(type_param.name().to_string(), traits);
+        }
+        bounds
+    }
+
+    /// Attempt to give a struct its `RecordType`.
+    ///
+    /// Returns `false` if the struct has a field whose type has not been
+    /// resolved yet.
+    fn resolve_struct(&mut self, struct_decl: &StructDeclaration,
+                      failed: &mut HashSet<ScopedId>) -> bool {
+        trace!("Resolving struct {}", struct_decl.name());
+        let owner = format!("struct {}", struct_decl.name());
+        let fields = match self.resolve_fields(&owner, struct_decl.fields(),
+                                               failed) {
+            Fields::Resolved(fields) => fields,
+            Fields::Pending => return false,
+            Fields::Failed => {
+                failed.insert(struct_decl.id().clone());
+                return true
+            }
+        };
+        let record = ConcreteType::Record(
+            RecordType::new(struct_decl.name().to_string(), fields));
+        trace!("struct {} has concrete type {:?}", struct_decl.name(), record);
+        self.builder.add_type(struct_decl.id().clone(), record);
+        true
+    }
+
+    /// Attempt to give an enum and its variants its `EnumType`.
+    ///
+    /// Returns `false` if a variant has a field whose type has not been
+    /// resolved yet.
+    fn resolve_enum(&mut self, enum_decl: &EnumDeclaration,
+                    failed: &mut HashSet<ScopedId>) -> bool {
+        trace!("Resolving enum {}", enum_decl.name());
+        let mut variants = Vec::with_capacity(enum_decl.variants().len());
+        for variant in enum_decl.variants() {
+            let owner = format!("variant {}", variant.name());
+            match self.resolve_fields(&owner, variant.fields(), failed) {
+                Fields::Resolved(fields) => {
+                    variants.push(RecordType::new(variant.name().to_string(),
+                                                  fields));
+                },
+                Fields::Pending => return false,
+                Fields::Failed => {
+                    failed.insert(enum_decl.id().clone());
+                    return true
+                }
+            }
+        }
+        let enum_ty = ConcreteType::Enum(
+            EnumType::new(enum_decl.name().to_string(), variants));
+        trace!("enum {} has concrete type {:?}", enum_decl.name(), enum_ty);
+        // Variants are constructed by name, so they have the enum's type.
+        for variant in enum_decl.variants() {
+            self.builder.add_type(variant.id().clone(), enum_ty.clone());
+        }
+        self.builder.add_type(enum_decl.id().clone(), enum_ty);
+        true
+    }
+
+    /// Identify the type which an impl block is for.
+    fn identify_impl_type(&mut self, impl_block: &ImplBlock)
+                          -> Option<ConcreteType> {
+        let type_expr = impl_block.type_expr();
+        TypeIdentifier::new(self.errors, self.builder)
+                       .visit_type_expr(type_expr);
+        let impl_ty = self.builder.get_type(&type_expr.id()).cloned();
+        if impl_ty.is_none() {
+            debug!("Unable to identify type of impl");
+        }
+        impl_ty
+    }
+
+    /// Check that a function of an impl block is not generic or declared
+    /// twice, and identify its type.
+    ///
+    /// Returns the function's type if it can be used.
+    fn identify_impl_fn<'a>(&mut self, method: &'a BlockFnDeclaration,
+                            owner: &str, declared: &mut Vec<&'a str>)
+                            -> Option<ConcreteType> {
+        if method.id().is_default() {
+            debug!("Skipping method {} with default ID", method.name());
+            return None
+        }
+        if declared.contains(&method.name()) {
+            debug!("Emitting error: method {} implemented twice",
+                method.name());
+            self.errors.add_error(CheckerError::new(
+                vec![method.ident().span()],
+                format!("Method {} of {} is already implemented",
+                    method.name(), owner)
+            ));
+            return None
+        }
+        declared.push(method.name());
+        if method.is_generic() {
+            debug!("Emitting error: generic method {}", method.name());
+            self.errors.add_error(CheckerError::new(
+                vec![method.ident().span()],
+                format!("Method {} of {} cannot have type parameters",
+                    method.name(), owner)
+            ));
+            return None
+        }
+        self.identify_fn_type(method);
+        self.builder.get_type(&method.id()).cloned()
+    }
+
+    /// Add a method to the methods of a type.
+    fn add_method(&mut self, impl_ty: &ConcreteType,
+                  method: &BlockFnDeclaration) {
+        if let Some(existing_id) = self.builder.add_method(
+                impl_ty.clone(), method.name().to_string(),
+                method.id().clone()) {
+            debug!("Emitting error: {} already has method {} ({:?})",
+                impl_ty, method.name(), existing_id);
+            self.errors.add_error(CheckerError::new(
+                vec![method.ident().span()],
+                format!("Type {} already has a method {}",
+                    impl_ty, method.name())
+            ));
+        }
+    }
+
+    /// Check that an impl of a trait implements each of its methods with the
+    /// trait's signature.
+    fn identify_trait_impl(&mut self, impl_block: &ImplBlock,
+                           trait_ident: &Identifier) {
+        let trait_name = trait_ident.name();
+        trace!("Identifying impl of {}", trait_name);
+        let trait_methods = match self.builder.trait_methods(trait_name) {
+            Some(trait_methods) => trait_methods.to_vec(),
+            None => {
+                debug!("Emitting error: unknown trait {}", trait_name);
+                self.errors.add_error(CheckerError::new(
+                    vec![trait_ident.span()],
+                    format!("Unknown trait {}", trait_name)
+                ));
+                return
+            }
+        };
+        let impl_ty = match self.identify_impl_type(impl_block) {
+            Some(impl_ty) => impl_ty,
+            None => return
+        };
+        if let Some(existing_span) = self.builder.add_impl(
+                trait_name.to_string(), impl_ty.clone(), impl_block.span()) {
+            debug!("Emitting error: {} already implemented for {}",
+                trait_name, impl_ty);
+            self.errors.add_error(CheckerError::new(
+                vec![impl_block.span(), existing_span],
+                format!("Trait {} is already implemented for {}",
+                    trait_name, impl_ty)
+            ));
+            return
+        }
+
+        // `Self` is the implementing type in the methods' signatures.
+        let self_args = vec![("Self".to_string(), impl_ty.clone())]
+            .into_iter().collect::<HashMap<_, _>>();
+        let owner = format!("trait {}", trait_name);
+        self.builder.set_self_type(Some(impl_block.type_expr().id().clone()));
+        let mut implemented: Vec<&str> = Vec::new();
+        for method in impl_block.methods() {
+            let expected = match trait_methods.iter()
+                    .find(|&&(ref name, _)| name == method.name()) {
+                Some(&(_, ref fn_type)) =>
+                    ConcreteType::Function(fn_type.clone())
+                        .substitute(&self_args),
+                None => {
+                    debug!("Emitting error: {} is not in trait {}",
+                        method.name(), trait_name);
+                    self.errors.add_error(CheckerError::new(
+                        vec![method.ident().span()],
+                        format!("Method {} is not a member of trait {}",
+                            method.name(), trait_name)
+                    ));
+                    continue
+                }
+            };
+            let actual = match self.identify_impl_fn(method, &owner,
+                                                     &mut implemented) {
+                Some(actual) => actual,
+                None => continue
+            };
+            if actual != expected {
+                debug!("Emitting error: method {} has type {}",
+                    method.name(), actual);
+                self.errors.add_error(CheckerError::new(
+                    vec![method.span()],
+                    format!("Method {} of trait {} should have type {} - got {}",
+                        method.name(), trait_name, expected, actual)
+                ));
+                continue
+            }
+            self.add_method(&impl_ty, method);
+        }
+        self.builder.set_self_type(None);
+
+        for &(ref name, ref _fn_type) in &trait_methods {
+            if !implemented.contains(&name.as_str()) {
+                debug!("Emitting error: missing method {} of {}",
+                    name, trait_name);
+                self.errors.add_error(CheckerError::new(
+                    vec![impl_block.span()],
+                    format!("Missing method {} of trait {} for {}",
+                        name, trait_name, impl_ty)
+                ));
+            }
+        }
+    }
+
+    /// Give the methods and associated functions of a struct or enum their
+    /// types.
+    fn identify_inherent_impl(&mut self, impl_block: &ImplBlock) {
+        trace!("Identifying inherent impl");
+        let impl_ty = match self.identify_impl_type(impl_block) {
+            Some(impl_ty) => impl_ty,
+            None => return
+        };
+        match impl_ty {
+            ConcreteType::Record(_) | ConcreteType::Enum(_) => {},
+            ref other => {
+                debug!("Emitting error: inherent impl for {}", other);
+                self.errors.add_error(CheckerError::new(
+                    vec![impl_block.type_expr().span()],
+                    format!("Methods can only be implemented for structs and enums - got {}",
+                        other)
+                ));
+                return
+            }
+        }
+        let owner = impl_ty.to_string();
+        self.builder.set_self_type(Some(impl_block.type_expr().id().clone()));
+        let mut declared: Vec<&str> = Vec::new();
+        for method in impl_block.methods() {
+            let fn_ty = match self.identify_impl_fn(method, &owner,
+                                                    &mut declared) {
+                Some(ConcreteType::Function(fn_ty)) => fn_ty,
+                _ => continue
+            };
+            // Associated functions are named by the names pass.
+            if !method.is_method() {
+                continue
+            }
+            if fn_ty.params()[0].1 != impl_ty {
+                debug!("Emitting error: receiver of {} is {}",
+                    method.name(), fn_ty.params()[0].1);
+                self.errors.add_error(CheckerError::new(
+                    vec![method.params()[0].1.span()],
+                    format!("Method {} of {} must take self of type Self",
+                        method.name(), owner)
+                ));
+                continue
+            }
+            self.add_method(&impl_ty, method);
+        }
+        self.builder.set_self_type(None);
+    }
+}
+
+/// Whether a type expression is a tuple, array, slice, or function type which
+/// can be identified once the structs or enums it contains are resolved.
+///
+/// Types containing unknown types, `()`, or types which failed to resolve
+/// will never be identified.
+fn is_pending_type(ty_expr: &TypeExpression, unit_id: &ScopedId,
+                   failed: &HashSet<ScopedId>) -> bool {
+    let is_pending_element = |element: &TypeExpression| {
+        let element_id = element.id();
+        if element_id.is_default() {
+            is_pending_type(element, unit_id, failed)
+        }
+        else {
+            *element_id != *unit_id && !failed.contains(&element_id)
+        }
+    };
+    match *ty_expr {
+        TypeExpression::Tuple(ref tuple_ty) =>
+            tuple_ty.elements().iter().all(is_pending_element),
+        TypeExpression::Array(ref array_ty) =>
+            is_pending_element(array_ty.element()),
+        TypeExpression::Growable(ref growable_ty) =>
+            is_pending_element(growable_ty.element()),
+        TypeExpression::Slice(ref slice_ty) =>
+            is_pending_element(slice_ty.element()),
+        // Functions may return `()`.
+        TypeExpression::Function(ref fn_ty) =>
+            fn_ty.params().iter()
+                .all(|&(ref _param, ref param_ty)| is_pending_element(param_ty))
+            && (*fn_ty.return_type().id() == *unit_id
+                || is_pending_element(fn_ty.return_type())),
+        TypeExpression::Named(_) => false
+    }
+}
+
+/// The name of a primitive type, which are the only types passed to C.
+fn primitive_name(ty: &ConcreteType) -> Option<&str> {
+    match *ty {
+        ConcreteType::Named(ref named) => Some(named.name()),
+        _ => None
+    }
+}
+
+/// The types of the fields of a struct or variant.
+#[derive(Debug)]
+enum Fields {
+    Resolved(Vec<(String, ConcreteType)>),
+    /// A field has a struct or enum type which is not resolved yet.
+    Pending,
+    /// A field's type could not be identified.
+    Failed
+}
+
+impl<'err, 'builder> UnitVisitor for ItemTypeIdentifier<'err, 'builder> {
+    fn visit_unit(&mut self, unit: &Unit) {
+        trace!("Visting a unit");
+        // Structs and enums are resolved before the other items so that they
+        // can be used by items, or each other, before they are declared.
+        let mut structs = Vec::new();
+        let mut enums = Vec::new();
+        for item in unit.items() {
+            match *item {
+                // Imported types can also be used before they're imported.
+                Item::Import(ref import) => {
+                    self.identify_import(import);
+                },
+                Item::StructDeclaration(ref struct_decl) => {
+                    if self.declare_type(struct_decl.ident(),
+                                         struct_decl.span()) {
+                        structs.push(struct_decl);
+                    }
+                },
+                Item::EnumDeclaration(ref enum_decl) => {
+                    if self.declare_type(enum_decl.ident(),
+                                         enum_decl.span()) {
+                        enums.push(enum_decl);
+                    }
+                },
+                _ => {}
+            }
+        }
+        let field_ty_exprs = structs.iter()
+            .flat_map(|struct_decl| struct_decl.fields())
+            .chain(enums.iter()
+                .flat_map(|enum_decl| enum_decl.variants())
+                .flat_map(|variant| variant.fields()));
+        for &(ref _field, ref field_ty_expr) in field_ty_exprs {
+            TypeIdentifier::new(self.errors, self.builder)
+                           .visit_type_expr(field_ty_expr);
+        }
+        // A type can be resolved once the types of its fields are.
+        let mut failed = HashSet::new();
+        loop {
+            let pending_count = structs.len() + enums.len();
+            structs.retain(|struct_decl|
+                !self.resolve_struct(struct_decl, &mut failed));
+            enums.retain(|enum_decl|
+                !self.resolve_enum(enum_decl, &mut failed));
+            let remaining = structs.len() + enums.len();
+            if remaining == 0 || remaining == pending_count {
+                break
+            }
+        }
+        // The remaining types contain themselves through their fields.
+        for struct_decl in structs {
+            debug!("Emitting error: struct {} is recursive",
+                struct_decl.name());
+            self.errors.add_error(CheckerError::new(
+                vec![struct_decl.span()],
+                format!("Struct {} contains itself", struct_decl.name())
+            ));
+        }
+        for enum_decl in enums {
+            debug!("Emitting error: enum {} is recursive", enum_decl.name());
+            self.errors.add_error(CheckerError::new(
+                vec![enum_decl.span()],
+                format!("Enum {} contains itself", enum_decl.name())
+            ));
+        }
+        // Traits are declared before impls and trait bounds use them.
+        for item in unit.items() {
+            if let Item::TraitDeclaration(ref trait_decl) = *item {
+                self.declare_trait(trait_decl);
+            }
+        }
+        visit::walk_unit(self, unit);
+    }
+}
+
+/// Get the names of the type parameters of a generic item, reporting
+/// parameters which are declared twice.
+fn type_param_names<'a, I>(errors: &mut ErrorCollector, owner: &str,
+                           type_params: I) -> Vec<String>
+                           where I: IntoIterator<Item=&'a Identifier> {
+    let mut names: Vec<String> = Vec::new();
+    for type_param in type_params {
+        if names.iter().any(|name| name == type_param.name()) {
+            debug!("Emitting error: {} type parameter {} declared twice",
+                owner, type_param.name());
+            errors.add_error(CheckerError::new(
+                vec![type_param.span()],
+                format!("Type parameter {} of {} is already declared",
+                    type_param.name(), owner)
+            ));
+            continue
+        }
+        names.push(type_param.name().to_string());
+    }
+    names
+}
+
+
+impl<'err, 'builder> ItemVisitor for ItemTypeIdentifier<'err, 'builder> {
+    fn visit_block_fn_decl(&mut self, fn_decl: &BlockFnDeclaration) {
+        trace!("Visiting block fn {}", fn_decl.name());
+        if fn_decl.id().is_default() {
+            debug!("Skipping fn {} with default ID", fn_decl.name());
             return
         }
-        let ret_ty = self.builder.get_type(&return_ty.id())
-            .expect("TypeIdentifier did not update param's type ID")
-            .clone();
+        let type_params = type_param_names(self.errors,
+            &format!("fn {}", fn_decl.name()),
+            fn_decl.type_params().iter().map(TypeParameter::ident));
+        self.builder.begin_type_params(&type_params);
+        self.identify_fn_type(fn_decl);
+        self.builder.end_type_params();
+        if fn_decl.is_exported() {
+            let fn_type = match self.builder.get_type(&fn_decl.id()) {
+                Some(&ConcreteType::Function(ref fn_type)) => fn_type.clone(),
+                _ => return
+            };
+            // Strings are not passed to C, which doesn't know their length.
+            self.check_c_signature("Exported", fn_decl.name(),
+                                   fn_decl.params(),
+                                   fn_decl.return_type(),
+                                   &fn_type, false);
+        }
+        if fn_decl.is_public() && fn_decl.is_generic() {
+            // Generic fns are only instantiated by calls in their own module.
+            debug!("Emitting error: pub generic fn {}", fn_decl.name());
+            self.errors.add_error(CheckerError::new(
+                vec![fn_decl.span()],
+                format!("Generic function {} cannot be exported with pub",
+                    fn_decl.name())
+            ));
+        }
+        if !type_params.is_empty() {
+            let bounds = self.identify_bounds(fn_decl);
+            self.builder.add_fn_bounds(fn_decl.id().clone(), bounds);
+            self.builder.add_fn_type_params(fn_decl.id().clone(), type_params);
+        }
+    }
 
-        let fn_concrete = ConcreteType::Function(
-            FnType::new(arg_types, ret_ty));
-        trace!("fn {} has concrete type {:?}", fn_decl.name(), fn_concrete);
-        self.builder.add_type(fn_decl.id().clone(), fn_concrete);
+    fn visit_extern_fn_decl(&mut self, extern_fn: &ExternFnDeclaration) {
+        trace!("Visiting extern fn {}", extern_fn.name());
+        if extern_fn.id().is_default() {
+            debug!("Skipping extern fn {} with default ID", extern_fn.name());
+            return
+        }
+        let fn_type = match self.identify_signature(extern_fn.name(),
+                                                    extern_fn.params(),
+                                                    extern_fn.return_type()) {
+            Some(fn_type) => fn_type,
+            None => return
+        };
+        // Strings are passed as pointers to their chars.
+        if !self.check_c_signature("Extern", extern_fn.name(),
+                                   extern_fn.params(),
+                                   extern_fn.return_type(),
+                                   &fn_type, true) {
+            return
+        }
+        trace!("extern fn {} has type {:?}", extern_fn.name(), fn_type);
+        self.builder.add_type(extern_fn.id().clone(),
+                              ConcreteType::Function(fn_type));
+        self.builder.add_foreign_fn(extern_fn.id().clone());
+    }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) {
+        // Traits are declared before other items in `visit_unit`.
+    }
+
+    fn visit_import(&mut self, _import: &Import) {
+        // Imports are identified before other items in `visit_unit`.
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting const {}", const_decl.name());
+        if const_decl.id().is_default() {
+            debug!("Skipping const {} with default ID", const_decl.name());
+            return
+        }
+        TypeIdentifier::new(self.errors, self.builder)
+            .visit_type_expr(const_decl.type_expr());
+        let const_ty = match self.builder.get_type(&const_decl.type_expr().id()) {
+            Some(const_ty) => const_ty.clone(),
+            None => {
+                debug!("Unable to identify type of const {}", const_decl.name());
+                return
+            }
+        };
+        // Consts are evaluated by the compiler, which only knows about
+        // numbers and bools.
+        let is_primitive = match const_ty {
+            ConcreteType::Named(ref named) =>
+                named.name() == "bool" ||
+                NUMERIC_TYPE_NAMES.contains(&named.name()),
+            _ => false
+        };
+        if !is_primitive {
+            debug!("Emitting error: const {} has type {}",
+                const_decl.name(), const_ty);
+            self.errors.add_error(CheckerError::new(
+                vec![const_decl.type_expr().span()],
+                format!("Const {} must be a number or bool, not {}",
+                    const_decl.name(), const_ty)
+            ));
+            return
+        }
+        trace!("const {} has type {:?}", const_decl.name(), const_ty);
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting global {}", global.name());
+        if global.id().is_default() {
+            debug!("Skipping global {} with default ID", global.name());
+            return
+        }
+        TypeIdentifier::new(self.errors, self.builder)
+            .visit_type_expr(global.type_expr());
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        trace!("Visiting an impl block");
+        match impl_block.trait_ident() {
+            Some(trait_ident) => self.identify_trait_impl(impl_block, trait_ident),
+            None => self.identify_inherent_impl(impl_block)
+        }
     }
 
     fn visit_typedef(&mut self, typedef: &Typedef) {
@@ -84,9 +833,13 @@ impl<'err, 'builder> ItemVisitor for ItemTypeIdentifier<'err, 'builder> {
             debug!("Skipping typedef {} with default id", typedef.name());
             return
         }
+        let type_params = type_param_names(self.errors,
+            &format!("typedef {}", typedef.name()), typedef.type_params());
         // Ensure the `ScopedId` of the alias's type_epxr  is set.
+        self.builder.begin_type_params(&type_params);
         TypeIdentifier::new(self.errors, self.builder)
             .visit_type_expr(typedef.type_expr());
+        self.builder.end_type_params();
         let type_expr_id = typedef.type_expr().id();
 
         if type_expr_id.is_default() {
@@ -106,14 +859,34 @@ impl<'err, 'builder> ItemVisitor for ItemTypeIdentifier<'err, 'builder> {
             ));
         }
 
-        let typedef_ty = self.builder.get_type(&typedef.type_expr().id())
-            .expect("TypeIdentifier did not update typedef's type ID")
-            .clone();
+        let typedef_ty = match self.builder.get_type(&type_expr_id) {
+            Some(typedef_ty) => typedef_ty.clone(),
+            None => {
+                debug!("Unresolved type of typedef {}", typedef.name());
+                return
+            }
+        };
 
         // Add the type at the builder level.
+        // Generic typedefs are instantiated when they are used.
+        if !type_params.is_empty() {
+            self.builder.add_generic_typedef(typedef.name().into(),
+                                             typedef.id().clone(),
+                                             type_params,
+                                             typedef_ty);
+            return
+        }
 
         self.builder.add_named_type(typedef.name().into(),
                                     typedef.id().clone(),
                                     typedef_ty);
     }
+
+    fn visit_struct_decl(&mut self, _struct_decl: &StructDeclaration) {
+        // Structs are resolved before other items in `visit_unit`.
+    }
+
+    fn visit_enum_decl(&mut self, _enum_decl: &EnumDeclaration) {
+        // Enums are resolved before other items in `visit_unit`.
+    }
 }
@@ -108,6 +108,81 @@ impl<'builder, 'err, 'graph> ItemVisitor
         // Don't need to explicitly add the return type to the graph.
     }
 
+    fn visit_extern_fn_decl(&mut self, extern_fn: &ExternFnDeclaration) {
+        trace!("Visiting extern fn {}", extern_fn.name());
+        let fn_scope_id = extern_fn.id();
+        if fn_scope_id.is_default() {
+            debug!("Ignoring unnamed extern fn {}", extern_fn.name());
+            return
+        }
+        // Extern fns have no params to infer, only their signature.
+        if self.builder.get_type(&fn_scope_id).is_some() {
+            trace!("Adding type of extern fn {} to graph", extern_fn.name());
+            self.graph.add_type(fn_scope_id.clone());
+        }
+    }
+
+    fn visit_trait_decl(&mut self, _trait_decl: &TraitDeclaration) {
+        // Traits have no values
+    }
+
+    fn visit_const_decl(&mut self, const_decl: &ConstDeclaration) {
+        trace!("Visiting const {}", const_decl.name());
+        let const_ty_id = const_decl.type_expr().id();
+        if const_decl.id().is_default() || const_ty_id.is_default() {
+            debug!("Ignoring unknown const {}", const_decl.name());
+            return
+        }
+        if self.builder.get_type(&const_ty_id).is_none() {
+            debug!("Ignoring const {} of unknown type", const_decl.name());
+            return
+        }
+        // Consts can be used by items declared before them.
+        trace!("Adding const {} to graph", const_decl.name());
+        let const_ix = self.graph.add_variable(const_decl.id().clone());
+        let const_ty_ix = self.graph.add_type(const_ty_id.clone());
+
+        // var_c: ty_c
+        self.graph.add_inference(const_ix, const_ty_ix,
+            InferenceSource::ExplicitDecl(const_decl.ident().clone()));
+    }
+
+    fn visit_global_decl(&mut self, global: &GlobalDeclaration) {
+        trace!("Visiting global {}", global.name());
+        let global_ty_id = global.type_expr().id();
+        if global.id().is_default() || global_ty_id.is_default() {
+            debug!("Ignoring unknown global {}", global.name());
+            return
+        }
+        if self.builder.get_type(&global_ty_id).is_none() {
+            debug!("Ignoring global {} of unknown type", global.name());
+            return
+        }
+        // Globals can be used by items declared before them.
+        trace!("Adding global {} to graph", global.name());
+        let global_ix = self.graph.add_variable(global.id().clone());
+        let global_ty_ix = self.graph.add_type(global_ty_id.clone());
+
+        // var_g: ty_g
+        self.graph.add_inference(global_ix, global_ty_ix,
+            InferenceSource::ExplicitDecl(global.ident().clone()));
+    }
+
+    fn visit_import(&mut self, import: &Import) {
+        trace!("Visiting import of {}", import.path().name());
+        for &(ref item, ref id) in import.imported().iter() {
+            if self.builder.get_type(id).is_some() {
+                trace!("Adding type of imported item {} to graph", item);
+                self.graph.add_type(id.clone());
+            }
+        }
+    }
+
+    fn visit_impl_block(&mut self, impl_block: &ImplBlock) {
+        trace!("Visiting an impl block");
+        visit::walk_impl_block(self, impl_block);
+    }
+
     fn visit_typedef(&mut self, typedef: &Typedef) {
         trace!("Visiting typedef {}", typedef.name());
         if typedef.id().is_default() {
@@ -133,4 +208,48 @@ impl<'builder, 'err, 'graph> ItemVisitor
         self.graph.add_inference(typedef_ix, type_expr_ix,
             InferenceSource::Typedef(typedef.ident().clone()));
     }
+
+    fn visit_struct_decl(&mut self, struct_decl: &StructDeclaration) {
+        trace!("Visiting struct {}", struct_decl.name());
+        if struct_decl.id().is_default() {
+            trace!("Skipping struct {} with default ID", struct_decl.name());
+            return
+        }
+        if self.builder.get_type(&struct_decl.id()).is_none() {
+            debug!("Ignoring struct {} with unknown field types",
+                struct_decl.name());
+            return
+        }
+        trace!("Adding type of struct {} to graph", struct_decl.name());
+        self.graph.add_type(struct_decl.id().clone());
+
+        // Ensure the fields' types are in the graph so that they can be
+        // inferred from field access.
+        for &(ref _field, ref field_ty_expr) in struct_decl.fields() {
+            self.graph.add_type(field_ty_expr.id().clone());
+        }
+    }
+
+    fn visit_enum_decl(&mut self, enum_decl: &EnumDeclaration) {
+        trace!("Visiting enum {}", enum_decl.name());
+        if enum_decl.id().is_default() {
+            trace!("Skipping enum {} with default ID", enum_decl.name());
+            return
+        }
+        if self.builder.get_type(&enum_decl.id()).is_none() {
+            debug!("Ignoring enum {} with unknown field types",
+                enum_decl.name());
+            return
+        }
+        trace!("Adding type of enum {} to graph", enum_decl.name());
+        self.graph.add_type(enum_decl.id().clone());
+
+        // Ensure the fields' types are in the graph so that they can be
+        // inferred from patterns.
+        for variant in enum_decl.variants() {
+            for &(ref _field, ref field_ty_expr) in variant.fields() {
+                self.graph.add_type(field_ty_expr.id().clone());
+            }
+        }
+    }
 }
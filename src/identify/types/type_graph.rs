@@ -4,10 +4,11 @@ use ast::ScopedId;
 use identify::types::InferenceSource;
 
 use petgraph::Directed;
+use petgraph::algo::has_path_connecting;
 use petgraph::graph::{Graph, NodeIndex, EdgeIndex};
 use petgraph::visit::Dfs;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 /// Represents a node in the type inference graph, or
@@ -25,6 +26,17 @@ enum TypeNode {
     CallArg(CallArgSpecifier, NodeIndex),
     /// Type is the return type of a given function.
     CallReturn(NodeIndex),
+    /// Type is the field of a given struct value.
+    Field(String, NodeIndex),
+    /// Type must be one of the numeric primitives.
+    ///
+    /// If no concrete type is found, numeric values default to `int`.
+    Numeric,
+    /// Type must be one of the numeric primitives or `str`, which can be
+    /// used with `+`.
+    ///
+    /// Like `Numeric`, defaults to `int`.
+    Addable,
 }
 
 /// How an argument to a function is specified
@@ -50,23 +62,91 @@ pub struct TypeGraph {
     /// TypeId -> NodeIndex
     types: HashMap<ScopedId, NodeIndex>,
     /// ScopedId -> NodeIndex
-    variables: HashMap<ScopedId, NodeIndex>
+    variables: HashMap<ScopedId, NodeIndex>,
+    /// Concrete types which can be used as numbers
+    numeric_types: HashSet<ScopedId>,
+    /// Node which numeric values must unify with
+    numeric: Option<NodeIndex>,
+    /// Concrete types which can be used with `+`
+    addable_types: HashSet<ScopedId>,
+    /// Node which values used with `+` must unify with
+    addable: Option<NodeIndex>,
+    /// Type given to numeric values without other type information
+    default_numeric: Option<NodeIndex>,
 }
 
 pub const PRIMITIVE_TYPE_NAMES: &[&'static str] = &[
     "()",
     "bool",
     "float",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "str",
 ];
 
+/// Primitive types which can be used with numeric operators.
+pub const NUMERIC_TYPE_NAMES: &[&'static str] = &[
+    "float",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+];
+
+/// Integer types, with their minimum and maximum values.
+pub const INT_TYPE_RANGES: &[(&'static str, i128, i128)] = &[
+    ("i8", i8::min_value() as i128, i8::max_value() as i128),
+    ("i16", i16::min_value() as i128, i16::max_value() as i128),
+    ("i32", i32::min_value() as i128, i32::max_value() as i128),
+    ("i64", i64::min_value() as i128, i64::max_value() as i128),
+    ("u8", 0, u8::max_value() as i128),
+    ("u16", 0, u16::max_value() as i128),
+    ("u32", 0, u32::max_value() as i128),
+    ("u64", 0, u64::max_value() as i128),
+];
+
+/// The name, minimum, and maximum of an integer type.
+pub fn int_type_range(name: &str) -> Option<(&'static str, i128, i128)> {
+    INT_TYPE_RANGES.iter().find(|&&(int_name, _, _)| int_name == name).cloned()
+}
+
+/// Primitive types which can be used with `+` besides the numeric types.
+pub const ADDABLE_TYPE_NAMES: &[&'static str] = &[
+    "str",
+];
+
+/// The type of integer literals which have no other type information,
+/// also known as `int`.
+pub const DEFAULT_NUMERIC_TYPE_NAME: &'static str = "i64";
+
 impl TypeGraph {
     pub fn with_primitives() -> TypeGraph {
         let mut curr_id = ScopedId::default().incremented();
 
         let mut graph = TypeGraph::default();
 
-        for _ in 0 .. PRIMITIVE_TYPE_NAMES.len() {
-            graph.add_type(curr_id.clone());
+        for name in PRIMITIVE_TYPE_NAMES.iter() {
+            let type_ix = graph.add_type(curr_id.clone());
+            if NUMERIC_TYPE_NAMES.contains(name) {
+                graph.numeric_types.insert(curr_id.clone());
+                graph.addable_types.insert(curr_id.clone());
+            }
+            if ADDABLE_TYPE_NAMES.contains(name) {
+                graph.addable_types.insert(curr_id.clone());
+            }
+            if *name == DEFAULT_NUMERIC_TYPE_NAME {
+                graph.default_numeric = Some(type_ix);
+            }
             curr_id.increment();
         }
 
@@ -126,6 +206,31 @@ impl TypeGraph {
         self.graph.add_node(TypeNode::CallReturn(function))
     }
 
+    pub fn add_field(&mut self, name: String,
+                                struct_index: NodeIndex) -> NodeIndex {
+        self.graph.add_node(TypeNode::Field(name, struct_index))
+    }
+
+    /// Get the node which numeric values are inferred to.
+    pub fn numeric(&mut self) -> NodeIndex {
+        if let Some(numeric_ix) = self.numeric {
+            return numeric_ix
+        }
+        let numeric_ix = self.graph.add_node(TypeNode::Numeric);
+        self.numeric = Some(numeric_ix);
+        numeric_ix
+    }
+
+    /// Get the node which values used with `+` are inferred to.
+    pub fn addable(&mut self) -> NodeIndex {
+        if let Some(addable_ix) = self.addable {
+            return addable_ix
+        }
+        let addable_ix = self.graph.add_node(TypeNode::Addable);
+        self.addable = Some(addable_ix);
+        addable_ix
+    }
+
     // Type inference
 
     pub fn add_inference(&mut self, src: NodeIndex,
@@ -134,6 +239,23 @@ impl TypeGraph {
         self.graph.add_edge(src, dest, source)
     }
 
+    /// Add inferences in both directions between two nodes which must have
+    /// the same type.
+    ///
+    /// This allows either node to be inferred from the other, such as a
+    /// literal being inferred from the variable it is assigned to.
+    pub fn add_equality(&mut self, left: NodeIndex,
+                                   right: NodeIndex,
+                                   source: InferenceSource) {
+        self.graph.add_edge(left, right, source.clone());
+        self.graph.add_edge(right, left, source);
+    }
+
+    /// Infer the concrete type of a variable.
+    ///
+    /// If the type cannot be determined, returns the conflicting types which
+    /// were found. A single type is returned if the variable was required to
+    /// be numeric, or used with `+`, but its type can't be.
     pub fn infer_type_of_var(&mut self, var: &ScopedId)
                                         -> Result<(NodeIndex, ScopedId),
                                                    Vec<ScopedId>> {
@@ -154,23 +276,40 @@ impl TypeGraph {
         let var_ix = var_ix.expect("Checked expect");
         let mut dfs = Dfs::new(&self.graph, *var_ix);
         let mut found = Vec::new();
+        let mut is_numeric = false;
+        let mut is_addable = false;
 
         while let Some(next_ix) = dfs.next(&self.graph) {
-            let node = &self.graph[next_ix];
-            if let &TypeNode::ConcreteType(_) = node {
-                found.push(next_ix);
+            match self.graph[next_ix] {
+                TypeNode::ConcreteType(_) => found.push(next_ix),
+                TypeNode::Numeric => is_numeric = true,
+                TypeNode::Addable => is_addable = true,
+                _ => {}
+            }
+        }
+        if found.is_empty() && (is_numeric || is_addable) {
+            if let Some(default_ix) = self.default_numeric {
+                trace!("Using default numeric type for {:?}", var);
+                found.push(default_ix);
             }
         }
         if found.len() == 1 {
-            self.graph.add_edge(var_ix.clone(), found[0],
-                InferenceSource::Inferred);
             let found_ix = found[0];
-            match &self.graph[found_ix] {
-                &TypeNode::ConcreteType(ref id) => {
-                    return Ok((found_ix, id.clone()))
-                }
+            let found_id = match &self.graph[found_ix] {
+                &TypeNode::ConcreteType(ref id) => id.clone(),
                 _ => unreachable!("Did not add non concrete types to found")
+            };
+            if is_numeric && !self.numeric_types.contains(&found_id) {
+                trace!("Type {:?} of {:?} is not numeric", found_id, var);
+                return Err(vec![found_id])
+            }
+            if is_addable && !self.addable_types.contains(&found_id) {
+                trace!("Type {:?} of {:?} can't be added", found_id, var);
+                return Err(vec![found_id])
             }
+            self.graph.add_edge(var_ix.clone(), found_ix,
+                InferenceSource::Inferred);
+            Ok((found_ix, found_id))
         }
         else {
             let found_with_info = found.iter().map(|found_ix| {
@@ -185,6 +324,58 @@ impl TypeGraph {
         }
     }
 
+    /// Find the concrete type a node has been inferred to so far.
+    ///
+    /// Unlike `infer_type_of_var`, this can be used before the graph is
+    /// complete, such as to find the struct type of a value whose field is
+    /// accessed. Returns `None` if there is not exactly one known type.
+    pub fn known_type(&self, ix: NodeIndex) -> Option<ScopedId> {
+        let mut dfs = Dfs::new(&self.graph, ix);
+        let mut found = None;
+        while let Some(next_ix) = dfs.next(&self.graph) {
+            if let TypeNode::ConcreteType(ref id) = self.graph[next_ix] {
+                if found.is_some() {
+                    return None
+                }
+                found = Some(id.clone());
+            }
+        }
+        found
+    }
+
+    /// Find the concrete type a node has been inferred to so far, using the
+    /// default numeric type if it is only known to be numeric.
+    pub fn known_type_or_default(&self, ix: NodeIndex) -> Option<ScopedId> {
+        let mut dfs = Dfs::new(&self.graph, ix);
+        let mut found = None;
+        let mut is_numeric = false;
+        while let Some(next_ix) = dfs.next(&self.graph) {
+            match self.graph[next_ix] {
+                TypeNode::ConcreteType(ref id) => {
+                    if found.is_some() {
+                        return None
+                    }
+                    found = Some(id.clone());
+                },
+                TypeNode::Numeric | TypeNode::Addable => is_numeric = true,
+                _ => {}
+            }
+        }
+        if found.is_none() && is_numeric {
+            if let Some(default_ix) = self.default_numeric {
+                if let TypeNode::ConcreteType(ref id) = self.graph[default_ix] {
+                    found = Some(id.clone());
+                }
+            }
+        }
+        found
+    }
+
+    /// Whether a node's type can be inferred from another node.
+    pub fn has_path(&self, from: NodeIndex, to: NodeIndex) -> bool {
+        has_path_connecting(&self.graph, from, to, None)
+    }
+
     /// Call `dot -Tsvg` on the given file
     pub fn write_svg<P: AsRef<Path>>(&self, path: P) {
         use std::io::Write;
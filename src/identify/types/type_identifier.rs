@@ -4,7 +4,10 @@
 use ast::visit::*;
 use ast::types::*;
 use check::{CheckerError, ErrorCollector};
-use identify::TypeScopeBuilder;
+use identify::{ArrayType, ConcreteType, FnType, GrowableType, SliceType,
+               TupleType, TypeScopeBuilder};
+
+use std::collections::HashMap;
 
 /// Visitor which identifies TypeExpressions,
 /// by assigning their IDs to those found in
@@ -12,21 +15,103 @@ use identify::TypeScopeBuilder;
 #[derive(Debug)]
 pub struct TypeIdentifier<'err, 'builder> {
     errors: &'err mut ErrorCollector,
-    /// New types cannot be defined within type expressions.
-    builder: &'builder TypeScopeBuilder,
+    /// New types cannot be defined within type expressions, but tuple and
+    /// array types are given IDs when they are first used.
+    builder: &'builder mut TypeScopeBuilder,
 }
 
 impl<'err, 'builder> TypeIdentifier<'err, 'builder> {
     pub fn new(errors: &'err mut ErrorCollector,
-               builder: &'builder TypeScopeBuilder)
+               builder: &'builder mut TypeScopeBuilder)
                -> TypeIdentifier<'err, 'builder> {
         TypeIdentifier { errors, builder }
     }
+
+    /// Get the type of the elements of an array, growable array, or slice
+    /// type, if it is known.
+    fn element_type(&mut self, element: &TypeExpression, kind: &str)
+                    -> Option<ConcreteType> {
+        let element_id = element.id().clone();
+        if element_id.is_default() {
+            debug!("Unable to identify {} element type", kind);
+            return None
+        }
+        if Some(&element_id) == self.builder.named_type_id("()") {
+            debug!("Emitting error: {} element is ()", kind);
+            self.errors.add_error(CheckerError::new(
+                vec![element.span()],
+                format!("{} elements cannot have type ()", kind)
+            ));
+            return None
+        }
+        // Structs and enums may not have been resolved yet.
+        let element_ty = self.builder.get_type(&element_id).cloned();
+        if element_ty.is_none() {
+            trace!("{} element has an unresolved type", kind);
+        }
+        element_ty
+    }
+
+    /// Identify a use of a generic typedef as its type with the given type
+    /// arguments substituted.
+    fn instantiate_typedef(&mut self, named_ty: &NamedTypeExpression,
+                           params: &[String], ty: &ConcreteType) {
+        trace!("Instantiating generic typedef {}", named_ty.name());
+        if named_ty.args().len() != params.len() {
+            debug!("Emitting error: wrong type argument count for {}",
+                named_ty.name());
+            self.errors.add_error(CheckerError::new(
+                vec![named_ty.span()],
+                format!("Type {} expects {} type arguments, got {}",
+                    named_ty.name(), params.len(), named_ty.args().len())
+            ));
+            return
+        }
+        let mut args = HashMap::with_capacity(params.len());
+        for (param, arg) in params.iter().zip(named_ty.args()) {
+            let arg_id = arg.id().clone();
+            if arg_id.is_default() {
+                debug!("Unable to identify type argument {} of {}",
+                    param, named_ty.name());
+                return
+            }
+            // Structs and enums may not have been resolved yet.
+            match self.builder.get_type(&arg_id) {
+                Some(arg_ty) => { args.insert(param.clone(), arg_ty.clone()); },
+                None => {
+                    trace!("Type argument {} of {} has an unresolved type",
+                        param, named_ty.name());
+                    return
+                }
+            }
+        }
+        if let Some(type_id) = self.builder.add_concrete_type(
+                ty.substitute(&args)) {
+            trace!("Generic typedef {} has id {:?}", named_ty.name(), type_id);
+            named_ty.set_id(type_id);
+        }
+    }
 }
 
 impl<'err, 'builder> TypeVisitor for TypeIdentifier<'err, 'builder> {
     fn visit_named_type_expr(&mut self, named_ty: &NamedTypeExpression) {
         trace!("Identifying named type {}", named_ty.name());
+        for arg in named_ty.args() {
+            self.visit_type_expr(arg);
+        }
+        if let Some((params, ty)) =
+                self.builder.generic_typedef(named_ty.name()).cloned() {
+            return self.instantiate_typedef(named_ty, &params, &ty)
+        }
+        if !named_ty.args().is_empty() {
+            debug!("Emitting error: type arguments given to {}",
+                named_ty.name());
+            self.errors.add_error(CheckerError::new(
+                vec![named_ty.span()],
+                format!("Type {} does not take type arguments", named_ty.name())
+            ));
+            return
+        }
         if let Some(type_id) =
             self.builder.named_type_id(named_ty.name()) {
             // Found the already defined type.
@@ -40,4 +125,114 @@ impl<'err, 'builder> TypeVisitor for TypeIdentifier<'err, 'builder> {
             ));
         }
     }
+
+    fn visit_tuple_type_expr(&mut self, tuple_ty: &TupleTypeExpression) {
+        trace!("Identifying tuple type");
+        walk_tuple_type_expr(self, tuple_ty);
+        let unit_id = self.builder.named_type_id("()")
+            .expect("Primitive").clone();
+        let mut elements = Vec::with_capacity(tuple_ty.elements().len());
+        for element in tuple_ty.elements() {
+            let element_id = element.id().clone();
+            if element_id.is_default() {
+                debug!("Unable to identify tuple element type");
+                return
+            }
+            if element_id == unit_id {
+                debug!("Emitting error: tuple element is ()");
+                self.errors.add_error(CheckerError::new(
+                    vec![element.span()],
+                    format!("Tuple elements cannot have type ()")
+                ));
+                return
+            }
+            // Structs and enums may not have been resolved yet.
+            match self.builder.get_type(&element_id) {
+                Some(element_ty) => elements.push(element_ty.clone()),
+                None => {
+                    trace!("Tuple element has an unresolved type");
+                    return
+                }
+            }
+        }
+        let tuple_id = self.builder.add_tuple_type(TupleType::new(elements));
+        trace!("Tuple type has id {:?}", tuple_id);
+        tuple_ty.set_id(tuple_id);
+    }
+
+    fn visit_array_type_expr(&mut self, array_ty: &ArrayTypeExpression) {
+        trace!("Identifying array type");
+        walk_array_type_expr(self, array_ty);
+        if let Some(element_ty) = self.element_type(array_ty.element(),
+                                                    "Array") {
+            let array_id = self.builder.add_array_type(
+                ArrayType::new(element_ty, array_ty.len()));
+            trace!("Array type has id {:?}", array_id);
+            array_ty.set_id(array_id);
+        }
+    }
+
+    fn visit_growable_type_expr(&mut self,
+                                growable_ty: &GrowableTypeExpression) {
+        trace!("Identifying growable array type");
+        walk_growable_type_expr(self, growable_ty);
+        if let Some(element_ty) = self.element_type(growable_ty.element(),
+                                                    "Growable array") {
+            let growable_id = self.builder.add_growable_type(
+                GrowableType::new(element_ty));
+            trace!("Growable array type has id {:?}", growable_id);
+            growable_ty.set_id(growable_id);
+        }
+    }
+
+    fn visit_slice_type_expr(&mut self, slice_ty: &SliceTypeExpression) {
+        trace!("Identifying slice type");
+        walk_slice_type_expr(self, slice_ty);
+        if let Some(element_ty) = self.element_type(slice_ty.element(),
+                                                    "Slice") {
+            let slice_id = self.builder.add_slice_type(
+                SliceType::new(element_ty));
+            trace!("Slice type has id {:?}", slice_id);
+            slice_ty.set_id(slice_id);
+        }
+    }
+
+    fn visit_fn_type_expr(&mut self, fn_ty: &FnTypeExpression) {
+        trace!("Identifying fn type");
+        walk_fn_type_expr(self, fn_ty);
+        let mut params = Vec::with_capacity(fn_ty.params().len());
+        for &(ref param, ref param_ty) in fn_ty.params() {
+            let param_id = param_ty.id().clone();
+            if param_id.is_default() {
+                debug!("Unable to identify type of fn type param {}",
+                    param.name());
+                return
+            }
+            // Structs and enums may not have been resolved yet.
+            match self.builder.get_type(&param_id) {
+                Some(param_ty) => params.push((param.name().to_string(),
+                                               param_ty.clone())),
+                None => {
+                    trace!("Fn type param {} has an unresolved type",
+                        param.name());
+                    return
+                }
+            }
+        }
+        let return_id = fn_ty.return_type().id().clone();
+        if return_id.is_default() {
+            debug!("Unable to identify return type of fn type");
+            return
+        }
+        let return_ty = match self.builder.get_type(&return_id) {
+            Some(return_ty) => return_ty.clone(),
+            None => {
+                trace!("Fn type has an unresolved return type");
+                return
+            }
+        };
+        let fn_id = self.builder.add_fn_type(FnType::new(params, return_ty));
+        trace!("Fn type has id {:?}", fn_id);
+        fn_ty.set_id(fn_id);
+    }
 }
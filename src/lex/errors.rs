@@ -0,0 +1,77 @@
+//! Errors emitted by the tokenizer
+
+use lex::Span;
+
+/// Error found by the tokenizer.
+///
+/// The tokenizer does not stop when it finds these: it keeps emitting tokens
+/// so the parser can continue, and collects the errors to be reported
+/// alongside the parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexError {
+    /// Tabs and spaces were mixed in the indentation of a line, or a line
+    /// was indented differently than the rest of the file.
+    MixedIndentation {
+        /// Whether the file is indented with spaces
+        expected_spaces: bool,
+        span: Span
+    },
+    /// A block was indented with a different width than the rest of the file.
+    InconsistentIndentWidth {
+        expected: u32,
+        got: u32,
+        span: Span
+    },
+    /// A line was dedented to a level which was never opened.
+    UnmatchedDedent {
+        /// Indentation levels which were open
+        open_levels: Vec<u32>,
+        got: u32,
+        span: Span
+    },
+    /// Both `\r\n` and `\n` line endings were used in the file.
+    MixedLineEndings {
+        /// Whether the file started out using `\r\n`
+        expected_crlf: bool,
+        span: Span
+    },
+    /// An unresolved git merge conflict, from the `<<<<<<<` marker to the
+    /// `>>>>>>>` marker.
+    MergeConflict {
+        span: Span
+    },
+    /// A merge conflict marker was out of order, such as a `=======` outside
+    /// of a conflict, or a `<<<<<<<` which was never closed.
+    MisplacedMergeMarker {
+        span: Span
+    },
+    /// A string literal was not closed before the end of its line.
+    UnterminatedString {
+        span: Span
+    },
+    /// A string literal used an unknown escape sequence, such as `\q`.
+    InvalidEscape {
+        span: Span
+    },
+    /// A character which can't start any token, such as `#`.
+    UnknownCharacter {
+        span: Span
+    }
+}
+
+impl LexError {
+    /// The area of source text where the error was found.
+    pub fn span(&self) -> Span {
+        match *self {
+            LexError::MixedIndentation { span, .. } |
+            LexError::InconsistentIndentWidth { span, .. } |
+            LexError::UnmatchedDedent { span, .. } |
+            LexError::MixedLineEndings { span, .. } |
+            LexError::MergeConflict { span } |
+            LexError::MisplacedMergeMarker { span } |
+            LexError::UnterminatedString { span } |
+            LexError::InvalidEscape { span } |
+            LexError::UnknownCharacter { span } => span
+        }
+    }
+}
@@ -1,12 +1,14 @@
 //! Contains the lexer which reads protosnirk syntax.
 
 mod span;
+mod errors;
 mod token;
 pub mod tokens;
 mod textiter;
 pub mod tokenizer;
 
 pub use self::span::{Location, Span};
+pub use self::errors::LexError;
 pub use self::token::{Token, TokenData};
 pub use self::tokens::TokenType;
 pub use self::textiter::{TextIter, PeekTextIter};
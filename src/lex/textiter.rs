@@ -1,5 +1,6 @@
 //! Trait for iterating over text
 
+use std::collections::VecDeque;
 use std::iter::{Iterator, Peekable};
 
 use lex::Location;
@@ -8,6 +9,8 @@ use lex::Location;
 /// and keeps track of its location.
 pub trait TextIter : Iterator {
     fn peek(&mut self) -> Option<char>;
+    /// Peek `n` characters past the next one, `peek_nth(0)` being `peek()`.
+    fn peek_nth(&mut self, n: usize) -> Option<char>;
     /// Current location of the iterator.
     fn location(&self) -> Location;
 }
@@ -17,6 +20,8 @@ pub trait TextIter : Iterator {
 pub struct PeekTextIter<T> where T: Iterator<Item=char> {
     /// Iterator which does most of the work
     iter: Peekable<T>,
+    /// Characters taken from `iter` by `peek_nth`
+    lookahead: VecDeque<char>,
     /// Current line in the source
     current_line: u32,
     /// Current column in the source
@@ -28,6 +33,7 @@ impl<T: Iterator<Item=char>> PeekTextIter<T> {
     pub fn new(iter: Peekable<T>) -> PeekTextIter<T> {
         PeekTextIter {
             iter: iter,
+            lookahead: VecDeque::new(),
             current_line: 0,
             current_column: 0,
             current_char: 0
@@ -37,9 +43,22 @@ impl<T: Iterator<Item=char>> PeekTextIter<T> {
 
 impl<T: Iterator<Item=char>> TextIter for PeekTextIter<T> {
     fn peek(&mut self) -> Option<char> {
+        if let Some(&peeked) = self.lookahead.front() {
+            return Some(peeked)
+        }
         self.iter.peek().cloned()
     }
 
+    fn peek_nth(&mut self, n: usize) -> Option<char> {
+        while self.lookahead.len() <= n {
+            match self.iter.next() {
+                Some(next) => self.lookahead.push_back(next),
+                None => return None
+            }
+        }
+        Some(self.lookahead[n])
+    }
+
     fn location(&self) -> Location {
         Location::of()
             .index(self.current_char)
@@ -53,7 +72,8 @@ impl<T: Iterator<Item=char>> Iterator for PeekTextIter<T> {
     type Item = char;
 
     fn next(&mut self) -> Option<char> {
-        let result = self.iter.next();
+        let result = self.lookahead.pop_front()
+            .or_else(|| self.iter.next());
         self.current_char = self.current_char.saturating_add(1);
         match result {
             Some('\n') => {
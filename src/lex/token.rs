@@ -107,6 +107,8 @@ pub enum TokenData {
     UnitLiteral,
     /// Token is boolean literal `true` or `false`
     BoolLiteral,
+    /// Token is a string literal, including its quotes and escapes
+    StringLiteral,
     /// Token is some name
     Ident,
     /// Token is a keyword
@@ -9,24 +9,36 @@ use unicode_categories::UnicodeCategories;
 
 use lex::{tokens, Location,
           TokenizerSymbolRule, CowStr,
-          Token, TokenData,
+          Token, TokenData, LexError, Span,
           TextIter, PeekTextIter};
 
 /// Trait for a tokenizer which can iterate over tokens.
 pub trait Tokenizer {
     fn next(&mut self) -> Token;
+    /// Take the errors the tokenizer has found so far.
+    fn take_errors(&mut self) -> Vec<LexError>;
 }
 
+/// Marker between the two sides of a git merge conflict.
+const GIT_MERGE_SEPARATOR: &str = "=======";
+/// Marker at the end of a git merge conflict.
+const GIT_MERGE_END: &str = ">>>>>>>";
+
 /// If the given char is a symbol.
 pub fn char_is_symbol(ch: char) -> bool {
     ch == '%' || ch == '/' ||
     ch == '(' || ch == ')' ||
+    ch == '[' || ch == ']' ||
     ch == '-' || ch == '*' ||
     ch == ',' || ch == ':' ||
-    ch == '!' ||
+    ch == '!' || ch == '.' ||
+    ch == '\'' || ch == '&' ||
     ch.is_symbol()
 }
 
+/// Characters which can follow a `\` in a string literal.
+const STRING_ESCAPES: &[char] = &['n', 't', 'r', '\\', '"'];
+
 /// If the character is whitespace, but not newlines.
 pub fn char_is_spacing(ch: char) -> bool {
     ch != '\r' && ch != '\n' && ch.is_whitespace()
@@ -52,19 +64,37 @@ pub struct IterTokenizer<I> where I: Iterator<Item=char> {
     keywords: HashSet<CowStr>,
     /// Symbols registered with the tokenizer
     symbols: HashMap<CowStr, TokenizerSymbolRule>,
-    /// Ezpexted number of spaces to indent per indentation level.
+    /// Expected number of spaces to indent per indentation level.
     ///
-    /// Derived from first indent used, can emit warnings for
+    /// Derived from first indent used, emits errors for
     /// inconsistent indenting if spaces do not match.
-    expected_indent_length: u32,
+    expected_indent_length: Option<u32>,
     /// Whether the file is being indented with spaces or tabs.
     ///
     /// An error is emitted if tab and space indenting is mixed.
-    expected_indent_spaces: bool,
+    expected_indent_spaces: Option<bool>,
+    /// Whether the file is using `\r\n` line endings.
+    ///
+    /// Derived from the first newline, an error is emitted if
+    /// line endings are mixed.
+    expected_crlf: Option<bool>,
+    /// Errors found while tokenizing
+    errors: Vec<LexError>,
+    /// Number of parentheses and brackets currently open.
+    ///
+    /// Lines inside parentheses or brackets are continuations and are not
+    /// checked for consistent indentation.
+    paren_depth: u32,
+    /// Stack of indents which opened blocks, used to check indentation.
+    ///
+    /// Unlike `indent_size_stack`, this doesn't include continuation lines.
+    block_indent_stack: Vec<u32>,
     /// Whether whitespace is tokenized as indentation
     tokenizer_state: TokenizerState,
     /// Stack of indents being made.
     indent_size_stack: Vec<u32>,
+    /// Whether the last token was a `.`, so a number is a tuple index.
+    after_dot: bool,
     /// Peekable iterator over the characters
     iter: PeekTextIter<I>
 }
@@ -75,6 +105,10 @@ impl<I: Iterator<Item=char>> Tokenizer for IterTokenizer<I> {
         trace!("> Next token {:?}", next);
         next
     }
+
+    fn take_errors(&mut self) -> Vec<LexError> {
+        ::std::mem::replace(&mut self.errors, Vec::new())
+    }
 }
 
 impl<I: Iterator<Item=char>> IterTokenizer<I> {
@@ -84,13 +118,17 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
             keywords: tokens::default_keywords(),
             symbols: tokens::default_symbols(),
 
-            // Will be overridden later when reading file
-            // If first line beins with a space, error
-            expected_indent_length: 0u32,
-            expected_indent_spaces: true,
+            // Will be set by the first indent and newline in the file
+            expected_indent_length: None,
+            expected_indent_spaces: None,
+            expected_crlf: None,
+            errors: Vec::new(),
+            paren_depth: 0u32,
+            block_indent_stack: vec![0u32],
             // This will discard spacing at the beginning of a file
             tokenizer_state: TokenizerState::LookingForNewline,
             indent_size_stack: vec![0u32],
+            after_dot: false,
 
             iter: PeekTextIter::new(input.peekable())
         }
@@ -100,7 +138,7 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
     pub fn next(&mut self) -> Token {
         trace!(">Calling next on {:?}, peeked {:?}",
             self.tokenizer_state, self.iter.peek());
-        match self.tokenizer_state {
+        let token = match self.tokenizer_state {
             TokenizerState::LookingForIndent =>
                 self.next_indent(),
             TokenizerState::LookingForNewline =>
@@ -109,7 +147,17 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
                 self.next_eof(),
             TokenizerState::EmittingOutdents =>
                 self.next_outdent()
+        };
+        if token.data() == TokenData::Symbol {
+            if token.text() == "(" || token.text() == "[" {
+                self.paren_depth += 1;
+            }
+            else if token.text() == ")" || token.text() == "]" {
+                self.paren_depth = self.paren_depth.saturating_sub(1);
+            }
         }
+        self.after_dot = token.data() == TokenData::Symbol && token.text() == ".";
+        token
     }
 
     /// Emit remaining `BlockEnd` and `EOF` tokens
@@ -133,11 +181,20 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
             self.tokenizer_state = TokenizerState::ReachedEOF;
             return self.next_eof()
         }
-        let mut space_count = 0;
+        let line_start = self.iter.location();
+        let mut space_count = 0u32;
+        let mut saw_spaces = false;
+        let mut saw_tabs = false;
         let mut peeked = peek_attempt.expect("Checked expect");
         // Take all consecutive spaces
         trace!("Taking consecutive spaces starting with {:?}", peeked);
         while char_is_spacing(peeked) {
+            if peeked == '\t' {
+                saw_tabs = true;
+            }
+            else {
+                saw_spaces = true;
+            }
             self.iter.next();
             space_count += 1;
             let next_peek = self.iter.peek();
@@ -145,9 +202,12 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
                 break
             }
             peeked = next_peek.expect("checked expect");
-            // TODO error on mixed tabs/spaces
         }
         trace!("Peeked to {}, with {} spaces", peeked, space_count);
+        // Merge conflicts are skipped without changing indentation.
+        if space_count == 0 && self.skip_merge_markers() {
+            return self.next_indent()
+        }
         // Now that indents are found, go back to regular tokens.
         self.tokenizer_state = TokenizerState::LookingForNewline;
 
@@ -158,6 +218,22 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
         trace!("Indent stack = {:?} (current {})",
             self.indent_size_stack, current_indent);
 
+        // Blank lines don't count towards indentation.
+        if peeked != '\n' && peeked != '\r' && !char_is_spacing(peeked) {
+            let span = Span::from_location(line_start,
+                                           space_count.saturating_sub(1));
+            let chars_ok = !(saw_spaces || saw_tabs)
+                || self.check_indent_chars(span, saw_spaces, saw_tabs);
+            // Lines inside parens, or starting with symbols such as `->` or
+            // comments, may be continuations and can be indented freely.
+            // Parens and loop labels can start a statement.
+            let continuation = peeked != '(' && peeked != '\''
+                && char_is_symbol(peeked);
+            if chars_ok && self.paren_depth == 0 {
+                self.check_indent_width(span, space_count, continuation);
+            }
+        }
+
         // Equal indentation: no starting block, go directly to parsing line
         if space_count == current_indent {
             trace!("Indentation is the same, calling next_line");
@@ -176,6 +252,207 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
         }
     }
 
+    /// Check that a line's indentation doesn't mix tabs and spaces.
+    ///
+    /// Returns `false` if an error was found.
+    fn check_indent_chars(&mut self, span: Span,
+                          saw_spaces: bool, saw_tabs: bool) -> bool {
+        if saw_spaces && saw_tabs {
+            trace!("Found mixed tabs and spaces");
+            self.errors.push(LexError::MixedIndentation {
+                expected_spaces: self.expected_indent_spaces.unwrap_or(true),
+                span
+            });
+            return false
+        }
+        match self.expected_indent_spaces {
+            None => self.expected_indent_spaces = Some(saw_spaces),
+            Some(expected_spaces) if expected_spaces != saw_spaces => {
+                trace!("Indentation does not match the file");
+                self.errors.push(LexError::MixedIndentation {
+                    expected_spaces,
+                    span
+                });
+                return false
+            },
+            Some(_) => {}
+        }
+        true
+    }
+
+    /// Check that a line's indentation lines up with the blocks around it.
+    ///
+    /// Lines which may be continuations, such as `-> float` or `-1 => x`,
+    /// still close the blocks they're dedented from, and open a block if
+    /// they're indented by the file's width. They're not reported otherwise.
+    fn check_indent_width(&mut self, span: Span, space_count: u32,
+                          continuation: bool) {
+        let mut dedented = false;
+        while space_count < *self.block_indent_stack.last()
+                .expect("Block indent stack was missing leading 0") {
+            self.block_indent_stack.pop();
+            dedented = true;
+        }
+        let current_indent = *self.block_indent_stack.last()
+            .expect("Block indent stack was missing leading 0");
+        if space_count == current_indent {
+            return
+        }
+        if continuation {
+            let opens_block = !dedented && self.expected_indent_length
+                == Some(space_count - current_indent);
+            if opens_block {
+                trace!("Continuation line opens a block at {}", space_count);
+                self.block_indent_stack.push(space_count);
+            }
+            return
+        }
+        if dedented {
+            trace!("Dedent to {} was never opened", space_count);
+            self.errors.push(LexError::UnmatchedDedent {
+                open_levels: self.block_indent_stack.clone(),
+                got: space_count,
+                span
+            });
+        }
+        else {
+            let width = space_count - current_indent;
+            match self.expected_indent_length {
+                None => self.expected_indent_length = Some(width),
+                Some(expected) if expected != width => {
+                    trace!("Indent width {} does not match {}", width, expected);
+                    self.errors.push(LexError::InconsistentIndentWidth {
+                        expected,
+                        got: width,
+                        span
+                    });
+                },
+                Some(_) => {}
+            }
+        }
+        // Treat the line as a new block so following lines aren't also errors.
+        self.block_indent_stack.push(space_count);
+    }
+
+    /// Whether the upcoming text starts with the given string.
+    fn peek_is(&mut self, text: &str) -> bool {
+        for (ix, ch) in text.chars().enumerate() {
+            if self.iter.peek_nth(ix) != Some(ch) {
+                return false
+            }
+        }
+        true
+    }
+
+    /// Skip any merge conflict markers at the start of the current line.
+    ///
+    /// Returns whether any lines were skipped.
+    fn skip_merge_markers(&mut self) -> bool {
+        if self.peek_is(&tokens::GitMergeBegin) {
+            self.skip_merge_conflict();
+            true
+        }
+        else if self.peek_is(GIT_MERGE_SEPARATOR) || self.peek_is(GIT_MERGE_END) {
+            trace!("Found a merge marker outside of a conflict");
+            let start = self.iter.location();
+            let (end, _) = self.skip_line();
+            self.errors.push(LexError::MisplacedMergeMarker {
+                span: Span::from(start ..= end)
+            });
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    /// Skip an unresolved git merge conflict, from the `<<<<<<<` marker
+    /// through the line with the `>>>>>>>` marker.
+    ///
+    /// The conflict is a single error. Markers out of order, such as a
+    /// second `=======` or a `>>>>>>>` before any `=======`, are reported
+    /// separately, as is a conflict which is never closed.
+    fn skip_merge_conflict(&mut self) {
+        let start = self.iter.location();
+        trace!("Skipping merge conflict starting at {:?}", start);
+        let (begin_end, mut reached_eof) = self.skip_line();
+        let mut end = begin_end;
+        let mut saw_separator = false;
+        let mut misplaced = Vec::new();
+        loop {
+            if reached_eof || self.iter.peek().is_none() {
+                trace!("Merge conflict was never closed");
+                misplaced.push(Span::from(start ..= begin_end));
+                break
+            }
+            let line_start = self.iter.location();
+            let is_end_marker = self.peek_is(GIT_MERGE_END);
+            let is_misplaced = if self.peek_is(GIT_MERGE_SEPARATOR) {
+                let is_second = saw_separator;
+                saw_separator = true;
+                is_second
+            }
+            else if is_end_marker {
+                !saw_separator
+            }
+            else {
+                self.peek_is(&tokens::GitMergeBegin)
+            };
+            let (line_end, line_reached_eof) = self.skip_line();
+            if is_misplaced {
+                trace!("Found a misplaced merge marker at {:?}", line_start);
+                misplaced.push(Span::from(line_start ..= line_end));
+            }
+            end = line_end;
+            reached_eof = line_reached_eof;
+            if is_end_marker {
+                break
+            }
+        }
+        self.errors.push(LexError::MergeConflict {
+            span: Span::from(start ..= end)
+        });
+        for span in misplaced {
+            self.errors.push(LexError::MisplacedMergeMarker { span });
+        }
+    }
+
+    /// Skip the rest of the current line, and the newline after it.
+    ///
+    /// Returns the location of the line's last character, and whether the
+    /// end of the file was reached.
+    fn skip_line(&mut self) -> (Location, bool) {
+        let mut end = self.iter.location();
+        while let Some(peeked) = self.iter.peek() {
+            if peeked == '\n' {
+                break
+            }
+            else if peeked != '\r' {
+                end = self.iter.location();
+            }
+            self.iter.next();
+        }
+        // Consume the `\n`, if we haven't reached EOF.
+        let reached_eof = self.iter.next().is_none();
+        (end, reached_eof)
+    }
+
+    /// Check that the newline at the given location matches the rest of the file.
+    fn check_line_ending(&mut self, location: Location, crlf: bool) {
+        match self.expected_crlf {
+            None => self.expected_crlf = Some(crlf),
+            Some(expected_crlf) if expected_crlf != crlf => {
+                trace!("Found mixed line endings at {:?}", location);
+                let width = if crlf { 1 } else { 0 };
+                self.errors.push(LexError::MixedLineEndings {
+                    expected_crlf,
+                    span: Span::from_location(location, width)
+                });
+            },
+            Some(_) => {}
+        }
+    }
+
     /// Emit all needed outdents until tabbing lines up.
     fn next_outdent(&mut self) -> Token {
         trace!("Calling next_outdent");
@@ -224,6 +501,12 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
         }
         let mut peek = maybe_peek.expect("checked expect");
 
+        // The first line of the file doesn't go through `next_indent`.
+        if self.iter.location().column() == 0 && self.skip_merge_markers() {
+            self.tokenizer_state = TokenizerState::LookingForIndent;
+            return self.next_indent()
+        }
+
         // Skip spacing if between tokens.
         // TODO for linting purposes, keep track of spaces used.
         // Midline tabs are not appreciated, nor are spaces missing
@@ -253,8 +536,10 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
         trace!("Consumed all the spacing chars");
 
         // We handle \r first, then look at the following \n.
-        // TODO warn on mixed \r\n and \n
+        let newline_location = self.iter.location();
+        let mut crlf = false;
         if peek == '\r' {
+            crlf = true;
             self.iter.next(); // comsume \r
             // Give an error for \r at EOF
             if self.iter.peek().is_none() {
@@ -273,10 +558,17 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
         // We either ran into it after some amount of whitespace, or found it
         // after `\r`. Line is done, parse the indents on the next one.
         if peek == '\n' {
+            self.check_line_ending(newline_location, crlf);
             self.iter.next(); // Original `peek` OR `peek` from the if above
             self.tokenizer_state = TokenizerState::LookingForIndent;
             self.next_indent() // Mutually recursive for emtpy lines
         }
+        else if peek == '"' {
+            self.parse_string_literal()
+        }
+        else if peek.is_number() && self.after_dot {
+            self.parse_tuple_index()
+        }
         else if peek.is_number() {
             self.parse_float_literal()
         } else if peek == '_' || peek.is_letter() {
@@ -285,7 +577,13 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
             self.parse_symbol()
         } else {
             // See https://github.com/snirk-lang/protosnirk/issues/70
-            panic!("Unknown character `{:?}` in next_line", peek);
+            trace!("Skipping unknown character {:?}", peek);
+            let location = self.iter.location();
+            self.iter.next();
+            self.errors.push(LexError::UnknownCharacter {
+                span: Span::from_location(location, 0)
+            });
+            self.next_line()
         }
     }
 
@@ -311,10 +609,10 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
             // as tokens anyway.
             if sym.starts_with("///") {
                 // doc comment - will be implemented later on
-                self.take_while(|ch| ch != '\n', &mut sym);
+                self.take_while(|ch| ch != '\n' && ch != '\r', &mut sym);
                 return self.next()
             } else if sym.starts_with("//") {
-                self.skip_while(|ch| ch != '\n');
+                self.skip_while(|ch| ch != '\n' && ch != '\r');
                 return self.next()
             }
 
@@ -372,13 +670,75 @@ impl<I: Iterator<Item=char>> IterTokenizer<I> {
         }
     }
 
+    /// Parse a string literal, checking its escape sequences.
+    ///
+    /// The token keeps the quotes and escapes of the source text.
+    fn parse_string_literal(&mut self) -> Token {
+        let location = self.iter.location();
+        let mut token_string = String::new();
+        token_string.push(self.iter.next().expect("Checked expect: '\"' after peek()"));
+        loop {
+            match self.iter.peek() {
+                None | Some('\n') | Some('\r') => {
+                    trace!("Found unterminated string at {:?}", location);
+                    let width = token_string.chars().count() as u32;
+                    self.errors.push(LexError::UnterminatedString {
+                        span: Span::from_location(location, width - 1)
+                    });
+                    break
+                },
+                Some('"') => {
+                    token_string.push('"');
+                    self.iter.next();
+                    break
+                },
+                Some('\\') => {
+                    let escape_location = self.iter.location();
+                    token_string.push('\\');
+                    self.iter.next();
+                    match self.iter.peek() {
+                        Some(escaped) if STRING_ESCAPES.contains(&escaped) => {
+                            token_string.push(escaped);
+                            self.iter.next();
+                        },
+                        // Reported as an unterminated string instead.
+                        None | Some('\n') | Some('\r') => {},
+                        Some(_) => {
+                            trace!("Found invalid escape at {:?}", escape_location);
+                            self.errors.push(LexError::InvalidEscape {
+                                span: Span::from_location(escape_location, 1)
+                            });
+                        }
+                    }
+                },
+                Some(ch) => {
+                    token_string.push(ch);
+                    self.iter.next();
+                }
+            }
+        }
+        Token::new(token_string, location, TokenData::StringLiteral)
+    }
+
+    /// Parse the index of a tuple element, such as the `1` in `pair.1`.
+    ///
+    /// Unlike other numbers, the index can be followed by a `.`, as in
+    /// `nested.0.1`.
+    fn parse_tuple_index(&mut self) -> Token {
+        let mut token_string = String::new();
+        let location = self.iter.location();
+        self.take_while(char::is_number, &mut token_string);
+        Token::new(token_string, location, TokenData::NumberLiteral)
+    }
+
     /// Parse a floating point literal
     fn parse_float_literal(&mut self) -> Token {
         let mut token_string = String::new();
         let location = self.iter.location();
         self.take_while(char::is_number, &mut token_string);
         // First part of number done. Is it a decimal?
-        if self.iter.peek().unwrap_or(' ') == '.' {
+        // `0..` is the start of a range rather than a decimal.
+        if self.iter.peek().unwrap_or(' ') == '.' && !self.peek_is("..") {
             // This is a case where tokenization cannot continue.
             // The tokenizer is being rewritten for #46 and will accommodate this.
             // https://github.com/snirk-lang/protosnirk/issues/46
@@ -67,7 +67,8 @@ macro_rules! declare_tokens {
                 match self.data() {
                     TokenData::NumberLiteral
                     | TokenData::UnitLiteral
-                    | TokenData::BoolLiteral => TokenType::Literal,
+                    | TokenData::BoolLiteral
+                    | TokenData::StringLiteral => TokenType::Literal,
                     TokenData::Ident => TokenType::Ident,
                     TokenData::BeginBlock => TokenType::BeginBlock,
                     TokenData::EndBlock => TokenType::EndBlock,
@@ -125,12 +126,20 @@ declare_tokens! {
 
         LeftParen: "("; Complete,
         RightParen: ")"; Complete,
+        LeftBracket: "["; Complete,
+        RightBracket: "]"; Complete,
         // https://github.com/immington-industries/protosnirk/issues/64
         GitMergeBegin: "<<<<<<<"; Complete,
         InlineArrow: "=>"; Complete,
         Arrow: "->"; Complete,
         Comma: ","; Complete,
-        Colon: ":"; Complete,
+        Colon: ":"; CompletePrefix,
+        DoubleColon: "::"; Complete,
+        DotDot: ".."; CompletePrefix,
+        DotDotEquals: "..="; Complete,
+        Quote: "'"; Complete,
+        Ampersand: "&"; Complete,
+        Dot: "."; CompletePrefix,
     }
     symparts {
         "//"; CompletePrefix, // Comments hack, allows // and /// to be parsed.
@@ -138,6 +147,7 @@ declare_tokens! {
         "<<<"; Partial,
         "<<<<"; Partial,
         "<<<<<"; Partial,
+        "<<<<<<"; Partial,
         "!"; Partial,
     }
     keywords {
@@ -149,6 +159,26 @@ declare_tokens! {
         Else: "else",
         Fn: "fn",
         Typedef: "typedef",
+        While: "while",
+        Break: "break",
+        Continue: "continue",
+        For: "for",
+        In: "in",
+        Loop: "loop",
+        Match: "match",
+        And: "and",
+        Or: "or",
+        Not: "not",
+        Struct: "struct",
+        With: "with",
+        Enum: "enum",
+        Trait: "trait",
+        Impl: "impl",
+        Extern: "extern",
+        Export: "export",
+        Import: "import",
+        Pub: "pub",
+        Const: "const",
     }
     tynames {
         Int: "float",
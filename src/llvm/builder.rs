@@ -777,6 +777,7 @@ impl<'ctx> Builder<'ctx> {
         let args_ptrs = unsafe {
             mem::transmute::<&mut [Value<'ctx>], &mut [LLVMValueRef]>(args_ref)
         };
+        let name = CString::new(name).unwrap();
         unsafe {
             Value::from_ref(LLVMBuildCall(self.ptr(),
                           func.ptr(),
@@ -0,0 +1,70 @@
+//! Bindings to LLVM execution engines
+
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::mem;
+
+use libc::c_char;
+
+use llvm_sys::core::LLVMDisposeMessage;
+use llvm_sys::execution_engine::*;
+
+use llvm::{Module, initialize_native_target, initialize_native_asm_printer};
+
+/// Handle to an LLVM MCJIT execution engine, which compiles a module to
+/// machine code in memory so its functions can be called.
+#[derive(Debug)]
+pub struct ExecutionEngine<'ctx> {
+    ptr: LLVMExecutionEngineRef,
+    _lt: PhantomData<&'ctx ()>
+}
+
+impl_llvm_ptr_fmt!(<'ctx> ExecutionEngine);
+
+impl<'ctx> Drop for ExecutionEngine<'ctx> {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMDisposeExecutionEngine(self.ptr())
+        }
+    }
+}
+
+impl<'ctx> ExecutionEngine<'ctx> {
+    llvm_methods! { ExecutionEngine<'ctx> => LLVMExecutionEngineRef }
+
+    /// Create an execution engine for the native target, which takes
+    /// ownership of the module.
+    pub fn for_module(module: Module<'ctx>)
+                      -> Result<ExecutionEngine<'ctx>, String> {
+        if !initialize_native_target() || !initialize_native_asm_printer() {
+            return Err("Unable to initialize the native target".to_string())
+        }
+        let mut engine = 0 as LLVMExecutionEngineRef;
+        let mut error = 0 as *mut c_char;
+        unsafe {
+            LLVMLinkInMCJIT();
+            let failed = LLVMCreateExecutionEngineForModule(
+                &mut engine, module.ptr(), &mut error) > 0;
+            if failed {
+                let cstr_buf = CStr::from_ptr(error);
+                let result = String::from_utf8_lossy(cstr_buf.to_bytes())
+                                     .into_owned();
+                LLVMDisposeMessage(error);
+                return Err(result)
+            }
+            // The engine disposes of the module.
+            mem::forget(module);
+            Ok(ExecutionEngine::from_ref(engine))
+        }
+    }
+
+    /// Get the address of a compiled function, or `None` if the module
+    /// does not define it.
+    pub fn function_address(&self, name: &str) -> Option<u64> {
+        let c_name = CString::new(name).unwrap();
+        match unsafe { LLVMGetFunctionAddress(self.ptr(), c_name.as_ptr()) } {
+            0 => None,
+            address => Some(address)
+        }
+    }
+}
@@ -62,7 +62,7 @@ macro_rules! llvm_passthrough {
     ($(#[$attr:meta])* pub fn $fn_name:ident( $($arg_name:ident : $arg_ty:ty),* )
                            -> $ret_ty:ident <$lt:tt> => $wrapped_name:ident; $($rest:tt)*) => {
         $(#[$attr])*
-        pub fn $fn_name(&self $(, $arg_name : $arg_ty)* ) -> $ret_ty  {
+        pub fn $fn_name(&self $(, $arg_name : $arg_ty)* ) -> $ret_ty<$lt> {
             unsafe {
                 $ret_ty::from_ref($wrapped_name(self.ptr()
                     $(
@@ -92,7 +92,10 @@ pub mod types;
 pub use self::types::Type;
 pub mod pass_manager;
 pub use self::pass_manager::{PassManager, FunctionPassManager};
+pub mod execution_engine;
+pub use self::execution_engine::ExecutionEngine;
 pub mod target;
 pub use self::target::{initialize_all_targets,
                        initialize_native_target,
+                       initialize_native_asm_printer,
                        Target, TargetData, TargetMachine};
@@ -2,12 +2,14 @@
 
 use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
+use std::mem;
 
 use libc::c_char;
 
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
 use llvm_sys::analysis::{LLVMVerifierFailureAction, LLVMVerifyModule};
+use llvm_sys::linker::LLVMLinkModules2;
 use llvm_sys::target::{LLVMSetModuleDataLayout};
 
 use llvm::{Type, Value, TargetData};
@@ -79,6 +81,30 @@ impl<'ctx> Module<'ctx> {
         }
     }
 
+    /// Add a global variable, which is used through a pointer to it.
+    pub fn add_global(&self, name: &str, ty: &Type<'ctx>) -> Value<'ctx> {
+        let c_name = CString::new(name).unwrap();
+        unsafe {
+            Value::from_ref(
+                LLVMAddGlobal(self.ptr(),
+                              ty.ptr(),
+                              c_name.as_ptr() as *const c_char)
+            )
+        }
+    }
+
+    /// The functions declared or defined in the module.
+    pub fn functions(&self) -> Vec<Value<'ctx>> {
+        let mut functions = Vec::new();
+        let mut fn_ptr = unsafe { LLVMGetFirstFunction(self.ptr()) };
+        while !fn_ptr.is_null() {
+            unsafe {
+                functions.push(Value::from_ref(fn_ptr));
+                fn_ptr = LLVMGetNextFunction(fn_ptr);
+            }
+        }
+        functions
+    }
 
     pub fn get_type_by_name(&self, name: &str) -> Option<Type<'ctx>> {
         let c_name = CString::new(name).unwrap();
@@ -99,6 +125,13 @@ impl<'ctx> Module<'ctx> {
         }
     }
 
+    pub fn get_data_layout_str(&self) -> String {
+        unsafe {
+            let layout = CStr::from_ptr(LLVMGetDataLayoutStr(self.ptr()));
+            layout.to_string_lossy().into_owned()
+        }
+    }
+
     pub fn set_target_triple(&self, target_triple: &str) {
         let c_name = CString::new(target_triple).unwrap();
         unsafe {
@@ -106,6 +139,20 @@ impl<'ctx> Module<'ctx> {
         }
     }
 
+    /// Link the contents of another module into this one.
+    ///
+    /// See `LLVMLinkModules2`, which destroys the other module.
+    pub fn link(&self, other: Module<'ctx>) -> Result<(), String> {
+        let failed = unsafe { LLVMLinkModules2(self.ptr(), other.ptr()) };
+        mem::forget(other);
+        if failed > 0 {
+            Err("Unable to link modules".to_string())
+        }
+        else {
+            Ok(())
+        }
+    }
+
     pub fn verify(&self,
                   action: LLVMVerifierFailureAction) -> Result<(), String> {
         let mut error = 0 as *mut c_char;
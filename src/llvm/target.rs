@@ -7,6 +7,8 @@ use llvm_sys::core::LLVMDisposeMessage;
 use llvm_sys::target::*;
 use llvm_sys::target_machine::*;
 
+use llvm::{Module, Type};
+
 pub fn initialize_native_target() -> bool {
     unsafe {
         if LLVM_InitializeNativeTarget() == 1 {
@@ -16,6 +18,12 @@ pub fn initialize_native_target() -> bool {
     }
 }
 
+pub fn initialize_native_asm_printer() -> bool {
+    unsafe {
+        LLVM_InitializeNativeAsmPrinter() == 0
+    }
+}
+
 pub fn initialize_all_targets() {
     unsafe {
         LLVM_InitializeAllTargets()
@@ -165,6 +173,14 @@ impl TargetData {
         }
     }
 
+    /// The size of a type in bytes, including the padding needed to place
+    /// it in an array.
+    pub fn abi_size_of(&self, ty: &Type) -> u64 {
+        unsafe {
+            LLVMABISizeOfType(self.ptr(), ty.ptr())
+        }
+    }
+
     pub fn from_machine(machine: &TargetMachine) -> TargetData {
         unsafe {
             TargetData::from_ref(LLVMCreateTargetDataLayout(machine.ptr()))
@@ -223,6 +239,32 @@ impl TargetMachine {
         }
     }
 
+    /// Compile a module to an object or assembly file.
+    pub fn emit_to_file(&self, module: &Module, path: &str,
+                        file_type: LLVMCodeGenFileType) -> Result<(), String> {
+        let path_str = CString::new(path).unwrap();
+        let mut error_ptr = 0 as *mut c_char;
+        let result = unsafe {
+            LLVMTargetMachineEmitToFile(self.ptr(),
+                                        module.ptr(),
+                                        path_str.as_ptr() as *mut c_char,
+                                        file_type,
+                                        &mut error_ptr)
+        };
+        if result != 0 {
+            unsafe {
+            let cstr_buf = CStr::from_ptr(error_ptr);
+            let error = String::from_utf8_lossy(cstr_buf.to_bytes())
+                                .into_owned();
+            LLVMDisposeMessage(error_ptr);
+            Err(error)
+            }
+        }
+        else {
+            Ok(())
+        }
+    }
+
     pub fn native(opt_level: LLVMCodeGenOptLevel,
                   reloc_mode: LLVMRelocMode,
                   code_model: LLVMCodeModel) -> Result<TargetMachine, String> {
@@ -1,11 +1,11 @@
 //! LLVM Type object.
 
 use std::mem;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::iter::IntoIterator;
 use std::marker::PhantomData;
 
-use libc::{c_uint, c_ulonglong};
+use libc::{c_char, c_uint, c_ulonglong};
 
 use llvm_sys::prelude::*;
 use llvm_sys::LLVMTypeKind;
@@ -141,16 +141,71 @@ impl<'ctx> Type<'ctx> {
 
     // From Core / Types / Structure Types
 
+    pub fn structure<I>(context: &'ctx Context,
+                        elements: I,
+                        packed: bool) -> Type<'ctx>
+    where I: IntoIterator<Item=Type<'ctx>> {
+        let mut elements_vec: Vec<_> = elements.into_iter().collect::<Vec<_>>();
+        let element_count = elements_vec.len() as c_uint;
+        let elements_ref = elements_vec.as_mut_slice();
+        let elements_ptrs = unsafe {
+            mem::transmute::<&mut [Type<'ctx>], &mut [LLVMTypeRef]>(elements_ref)
+        };
+        unsafe {
+            Type::from_ref(LLVMStructTypeInContext(context.ptr(),
+                             elements_ptrs.as_mut_ptr(),
+                             element_count,
+                             packed as LLVMBool))
+        }
+    }
+
+    pub fn named_structure(context: &'ctx Context, name: &str) -> Type<'ctx> {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            Type::from_ref(LLVMStructCreateNamed(context.ptr(),
+                                                 name.as_ptr() as *const c_char))
+        }
+    }
+
+    pub fn set_struct_body<I>(&self, elements: I, packed: bool)
+    where I: IntoIterator<Item=Type<'ctx>> {
+        let mut elements_vec: Vec<_> = elements.into_iter().collect::<Vec<_>>();
+        let element_count = elements_vec.len() as c_uint;
+        let elements_ref = elements_vec.as_mut_slice();
+        let elements_ptrs = unsafe {
+            mem::transmute::<&mut [Type<'ctx>], &mut [LLVMTypeRef]>(elements_ref)
+        };
+        unsafe {
+            LLVMStructSetBody(self.ptr(),
+                              elements_ptrs.as_mut_ptr(),
+                              element_count,
+                              packed as LLVMBool)
+        }
+    }
+
     // From Core / Types / Sequential Types
 
+    pub fn pointer(element: &Type<'ctx>, address_space: u32) -> Type<'ctx> {
+        unsafe {
+            Type::from_ref(LLVMPointerType(element.ptr(),
+                                           address_space as c_uint))
+        }
+    }
+
+    pub fn array(element: &Type<'ctx>, count: u32) -> Type<'ctx> {
+        unsafe {
+            Type::from_ref(LLVMArrayType(element.ptr(), count as c_uint))
+        }
+    }
+
     // From Core / Types / Integer Types
     context_ctors! {
         pub fn int1 <'ctx> = LLVMInt1TypeInContext;
-        pub fn int8 <'ctx> = LLVMInt1TypeInContext;
-        pub fn int16 <'ctx> = LLVMInt1TypeInContext;
-        pub fn int32 <'ctx> = LLVMInt1TypeInContext;
-        pub fn int64 <'ctx> = LLVMInt1TypeInContext;
-        pub fn int128 <'ctx> = LLVMInt1TypeInContext;
+        pub fn int8 <'ctx> = LLVMInt8TypeInContext;
+        pub fn int16 <'ctx> = LLVMInt16TypeInContext;
+        pub fn int32 <'ctx> = LLVMInt32TypeInContext;
+        pub fn int64 <'ctx> = LLVMInt64TypeInContext;
+        pub fn int128 <'ctx> = LLVMInt128TypeInContext;
     }
 
     pub fn int(ctx: &'ctx Context, num_bits: u32) -> Type<'ctx> {
@@ -7,6 +7,7 @@ use libc::{size_t, c_uint};
 
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
+use llvm_sys::{LLVMLinkage, LLVMVisibility};
 use llvm_sys::analysis::{LLVMVerifierFailureAction, LLVMVerifyFunction};
 
 use llvm::BasicBlock;
@@ -57,6 +58,12 @@ impl<'ctx> Value<'ctx> {
         }
     }
 
+    pub fn get_entry_basic_block(&self) -> BasicBlock<'ctx> {
+        unsafe {
+            BasicBlock::from_ref(LLVMGetEntryBasicBlock(self.ptr()))
+        }
+    }
+
     pub fn set_name(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
         unsafe {
@@ -64,6 +71,52 @@ impl<'ctx> Value<'ctx> {
         }
     }
 
+    pub fn get_name(&self) -> String {
+        let mut len: size_t = 0;
+        unsafe {
+            let buf = LLVMGetValueName2(self.ptr(), &mut len);
+            let cstr_buf = CStr::from_ptr(buf);
+            String::from_utf8_lossy(cstr_buf.to_bytes()).into_owned()
+        }
+    }
+
+    // methods on GlobalValue
+
+    /// Whether the global is only declared, such as a function without a
+    /// body.
+    pub fn is_declaration(&self) -> bool {
+        unsafe {
+            LLVMIsDeclaration(self.ptr()) != 0
+        }
+    }
+
+    pub fn set_visibility(&self, visibility: LLVMVisibility) {
+        unsafe {
+            LLVMSetVisibility(self.ptr(), visibility);
+        }
+    }
+
+    pub fn set_linkage(&self, linkage: LLVMLinkage) {
+        unsafe {
+            LLVMSetLinkage(self.ptr(), linkage);
+        }
+    }
+
+    // methods on GlobalVariable
+
+    pub fn set_initializer(&self, value: &Value<'ctx>) {
+        unsafe {
+            LLVMSetInitializer(self.ptr(), value.ptr());
+        }
+    }
+
+    /// Whether the global variable is never stored to.
+    pub fn set_global_constant(&self, is_constant: bool) {
+        unsafe {
+            LLVMSetGlobalConstant(self.ptr(), is_constant as LLVMBool);
+        }
+    }
+
     pub fn verify(&self, action: LLVMVerifierFailureAction) -> bool {
         unsafe {
             LLVMVerifyFunction(self.ptr(), action) == 0
@@ -88,6 +141,14 @@ impl<'ctx> Value<'ctx> {
 
     // From Core / BasicBlock
 
+    // methods on SwitchInst
+
+    pub fn add_case(&self, on_value: &Value<'ctx>, dest: &BasicBlock<'ctx>) {
+        unsafe {
+            LLVMAddCase(self.ptr(), on_value.ptr(), dest.ptr());
+        }
+    }
+
     // methods on PhiNode
 
     pub fn add_incoming<V, B>(&self, values: V, blocks: B)
@@ -1,6 +1,6 @@
 //! Error handling in parsers
 
-use lex::{CowStr, Token, TokenType};
+use lex::{CowStr, Token, TokenType, LexError};
 use ast::{Expression};
 
 /// Result given from main and expression parsers
@@ -25,6 +25,14 @@ pub enum ParseError {
         token_type: TokenType
     },
     EOF,
+    /// The tokenizer found errors in the source text.
+    LexErrors(Vec<LexError>),
+    /// `pub` was used on a kind of item which can't be imported, such as a
+    /// typedef. The token is the item's keyword.
+    InvalidPub {
+        item: &'static str,
+        token: Token
+    },
     LazyString(String)
 }
 
@@ -6,7 +6,7 @@
 use std::borrow::Cow;
 use std::collections::VecDeque;
 
-use lex::{CowStr, Token, TokenType, Span, Tokenizer};
+use lex::{CowStr, Token, TokenType, TokenData, Span, Tokenizer};
 use parse::ParseError;
 use ast::*;
 use parse::parsers::*;
@@ -207,6 +207,27 @@ impl<T: Tokenizer> Parser<T> {
                 let consumed = self.consume();
                 NamedTypeParser { }.parse(self, consumed)
             },
+            TokenType::LeftParen => {
+                trace!("Parsing tuple type expr");
+                let consumed = self.consume();
+                TupleTypeParser { }.parse(self, consumed)
+            },
+            TokenType::LeftBracket => {
+                trace!("Parsing array type expr");
+                let consumed = self.consume();
+                ArrayTypeParser { }.parse(self, consumed)
+            },
+            TokenType::Fn => {
+                trace!("Parsing fn type expr");
+                let consumed = self.consume();
+                FnTypeParser { }.parse(self, consumed)
+            },
+            TokenType::Ampersand => {
+                trace!("Found slice type in type expr");
+                Err(ParseError::LazyString(
+                    "Slices can only be the types of parameters and variables"
+                        .into()))
+            },
             _other => {
                 trace!("Invalid token for type expr");
                 // TODO this is also a bad error
@@ -216,6 +237,71 @@ impl<T: Tokenizer> Parser<T> {
         }
     }
 
+    /// Parses the type of a parameter or variable, which can also be a
+    /// slice type such as `&[int]`.
+    pub fn binding_type_expr(&mut self) -> Result<TypeExpression, ParseError> {
+        use parse::parsers::types::*;
+        if self.next_type() == TokenType::Ampersand {
+            trace!("Parsing slice type expr");
+            let consumed = self.consume();
+            return SliceTypeParser { }.parse(self, consumed)
+        }
+        self.type_expr()
+    }
+
+    /// Parses a pattern, such as `_` or `1..=9`, from the token stream.
+    pub fn pattern(&mut self) -> Result<Pattern, ParseError> {
+        let token = self.consume();
+        PatternParser { }.parse(self, token)
+    }
+
+    /// Parses the named arguments of a call, such as `x: 1, y: 2)`.
+    ///
+    /// Argument parsing assumes the `(` has already been consumed.
+    pub fn call_args(&mut self) -> Result<Vec<CallArgument>, ParseError> {
+        let mut call_args = Vec::new();
+        let mut arg_name = true;
+        loop {
+            if self.next_type() == TokenType::RightParen {
+                self.consume();
+                trace!("Call arguments complete");
+                break
+            }
+            if arg_name {
+                trace!("Parsing an argument");
+                let arg = try!(self.expression(Precedence::Min));
+                if let Expression::VariableRef(ident) = arg {
+                    if self.next_type() == TokenType::Colon {
+                        trace!("Argument {} is a named arg", ident.name());
+                        self.consume();
+                        let arg_value = try!(self.expression(Precedence::Min));
+                        call_args.push(CallArgument::named(ident, arg_value));
+                    }
+                    else {
+                        //call_args.push(CallArgument::implicit(
+                        //    Expression::VariableRef(ident)));
+                        // https://github.com/immington-industries/protosnirk/issues/45
+                        return Err(ParseError::LazyString(
+                            "Non-named params not supported right now".into()))
+                    }
+                }
+                else {
+                    try!(self.consume_type_indented(TokenType::RightParen,
+                                                    IndentationRule::NegateDeindent));
+                    trace!("Call arguments complete");
+                    break
+                }
+                arg_name = false;
+            }
+            else {
+                try!(self.consume_type_indented(TokenType::Comma,
+                                                IndentationRule::NegateDeindent));
+                arg_name = true;
+            }
+        }
+        Ok(call_args)
+    }
+
     /// Parses any expression with the given precedence.
     ///
     /// This parser will push a `NegateDeindent` rule to the rule stack.
@@ -232,10 +318,22 @@ impl<T: Tokenizer> Parser<T> {
 
             If => IfExpressionParser { }.parse(self, token),
 
-            Minus | Plus => UnaryOpExprSymbol { }.parse(self, token),
+            Loop => LoopParser { label: None }.parse(self, token),
+
+            Match => MatchParser { }.parse(self, token),
+
+            Quote => LabelledLoopParser { }.parse(self, token),
+
+            Minus | Plus | Not => UnaryOpExprSymbol { }.parse(self, token),
 
             LeftParen => ParensParser { }.parse(self, token),
 
+            LeftBracket => ArrayParser { }.parse(self, token),
+
+            Ampersand => SliceParser { }.parse(self, token),
+
+            Fn => LambdaParser { }.parse(self, token),
+
             Ident => IdentifierParser { }.parse(self, token),
 
             Literal => LiteralParser { }.parse(self, token),
@@ -261,6 +359,12 @@ impl<T: Tokenizer> Parser<T> {
 
                 LeftParen => FnCallParser { }.parse(self, left, token),
 
+                LeftBracket => IndexParser { }.parse(self, left, token),
+
+                Dot => FieldAccessParser { }.parse(self, left, token),
+
+                With => StructUpdateParser { }.parse(self, left, token),
+
                 LeftAngle | RightAngle =>
                     BinOpExprSymbol { }.parse(self, left, token),
 
@@ -270,6 +374,9 @@ impl<T: Tokenizer> Parser<T> {
                 DoubleEquals | NotEquals =>
                     BinOpExprSymbol { }.parse(self, left, token),
 
+                And | Or =>
+                    BinOpExprSymbol { }.parse(self, left, token),
+
                 PlusEquals | MinusEquals | StarEquals | PercentEquals | SlashEquals =>
                     AssignOpParser { }.parse(self, left, token),
 
@@ -296,6 +403,10 @@ impl<T: Tokenizer> Parser<T> {
                 let token = self.consume();
                 DeclarationParser { }.parse(self, token)
             },
+            Const => {
+                let token = self.consume();
+                ConstDeclarationParser { }.parse(self, token)
+            },
             Return => {
                 let token = self.consume();
                 ReturnParser { }.parse(self, token)
@@ -308,6 +419,26 @@ impl<T: Tokenizer> Parser<T> {
                 let token = self.consume();
                 IfBlockParser { }.parse(self, token)
             },
+            While => {
+                let token = self.consume();
+                WhileBlockParser { label: None }.parse(self, token)
+            },
+            For => {
+                let token = self.consume();
+                ForLoopParser { label: None }.parse(self, token)
+            },
+            Break => {
+                let token = self.consume();
+                BreakParser { }.parse(self, token)
+            },
+            Continue => {
+                let token = self.consume();
+                ContinueParser { }.parse(self, token)
+            },
+            Quote => {
+                let token = self.consume();
+                LabelledLoopParser { }.parse(self, token)
+            },
             _ => {
                 trace!("Using expr parser for statement");
                 self.expression(Precedence::Min)
@@ -337,7 +468,8 @@ impl<T: Tokenizer> Parser<T> {
         return Ok(Block::new(start, found))
     }
 
-    /// Parse an item from a program (a function definition)
+    /// Parse an item from a program (a function, extern function, typedef,
+    /// struct, enum, trait, or impl)
     pub fn item(&mut self) -> Result<Item, ParseError> {
         let token_type = self.next_type();
         let token = self.consume();
@@ -350,19 +482,69 @@ impl<T: Tokenizer> Parser<T> {
                 trace!("Parsing a typedef");
                 TypedefParser { }.parse(self, token)
             },
+            TokenType::Struct => {
+                trace!("Parsing a struct");
+                StructParser { }.parse(self, token)
+            },
+            TokenType::Enum => {
+                trace!("Parsing an enum");
+                EnumParser { }.parse(self, token)
+            },
+            TokenType::Trait => {
+                trace!("Parsing a trait");
+                TraitParser { }.parse(self, token)
+            },
+            TokenType::Impl => {
+                trace!("Parsing an impl");
+                ImplParser { }.parse(self, token)
+            },
+            TokenType::Extern => {
+                trace!("Parsing an extern fn");
+                ExternFnParser { }.parse(self, token)
+            },
+            TokenType::Export => {
+                trace!("Parsing an exported fn");
+                ExportFnParser { }.parse(self, token)
+            },
+            TokenType::Import => {
+                trace!("Parsing an import");
+                ImportParser { }.parse(self, token)
+            },
+            TokenType::Pub => {
+                trace!("Parsing a pub item");
+                PubItemParser { }.parse(self, token)
+            },
+            TokenType::Const => {
+                trace!("Parsing a const");
+                ConstItemParser { }.parse(self, token)
+            },
+            TokenType::Let => {
+                trace!("Parsing a global");
+                GlobalParser { }.parse(self, token)
+            },
             _ => {
                 Err(ParseError::LazyString(format!("Unexpected item token {:?}", token_type)))
             }
         }
     }
 
+    /// Combine a path, such as `math::square`, into a single identifier.
+    pub fn path(&mut self, first: Token) -> Result<Identifier, ParseError> {
+        if self.next_type() != TokenType::DoubleColon {
+            return Ok(Identifier::new(first))
+        }
+        self.consume();
+        let name = try!(self.lvalue());
+        let path = format!("{}::{}", first.text(), name.name());
+        Ok(Identifier::new(Token::new_ident(path, first.start())))
+    }
+
     /// Grab an lvalue from the token stream
     pub fn lvalue(&mut self) -> Result<Identifier, ParseError> {
         let token = self.consume();
         trace!("Getting an lvalue from {}", token);
         if token.get_type() == TokenType::Ident {
-            IdentifierParser { }.parse(self, token)
-                .and_then(|e| e.expect_identifier())
+            Ok(Identifier::new(token))
         } else {
             Err(ParseError::ExpectedToken {
                 expected: TokenType::Ident,
@@ -372,6 +554,104 @@ impl<T: Tokenizer> Parser<T> {
         }
     }
 
+    /// Grab the parameters of a function or method, such as the
+    /// `(bar: int, baz: bool)` in `fn foo(bar: int, baz: bool)`.
+    ///
+    /// A `self` parameter without a type has the type `Self`.
+    pub fn fn_params(&mut self)
+                     -> Result<Vec<(Identifier, TypeExpression)>, ParseError> {
+        // TODO Eventually params should be a separate parser?
+        // altough the fn signature type parser would be a little different
+        // from the first-class-fn type parser.
+
+        // left paren cannot be indented
+        try!(self.consume_type(TokenType::LeftParen));
+        // S1 -> ")", done | name, S2
+        // S2 -> ",", S1 | ")", done
+        let mut params = Vec::new();
+        let mut param_name = true;
+        loop {
+            if self.next_type() == TokenType::RightParen {
+                self.consume(); // right paren
+                break
+            }
+            // name
+            if param_name {
+                self.apply_indentation(IndentationRule::NegateDeindent);
+                let name = try!(self.lvalue());
+                if name.name() == "self" && self.next_type() != TokenType::Colon {
+                    let self_type = Identifier::new(
+                        Token::new_ident("Self", name.span().start()));
+                    params.push((name, TypeExpression::Named(
+                        NamedTypeExpression::new(self_type))));
+                    param_name = false;
+                    continue
+                }
+                try!(self.consume_type(TokenType::Colon));
+                let type_ = try!(self.binding_type_expr());
+                params.push((name, type_));
+                param_name = false;
+            }
+            // comma
+            else {
+                try!(self.consume_type_indented(TokenType::Comma,
+                                                IndentationRule::NegateDeindent));
+                param_name = true;
+            }
+        }
+        Ok(params)
+    }
+
+    /// Grab the type parameters of a generic item, such as the `<K, V>` in
+    /// `typedef Entry<K, V> = (K, V)`, and their trait bounds, such as the
+    /// `T: Shape + Named` in `fn describe<T: Shape + Named>(shape: T)`.
+    /// Items without them have none.
+    pub fn type_params(&mut self) -> Result<Vec<TypeParameter>, ParseError> {
+        let mut params = Vec::new();
+        if self.next_type() != TokenType::LeftAngle {
+            return Ok(params)
+        }
+        self.consume();
+        trace!("Parsing type parameters");
+        loop {
+            let ident = try!(self.lvalue());
+            let mut bounds = Vec::new();
+            if self.next_type() == TokenType::Colon {
+                self.consume();
+                bounds.push(try!(self.lvalue()));
+                while self.next_type() == TokenType::Plus {
+                    self.consume();
+                    bounds.push(try!(self.lvalue()));
+                }
+            }
+            params.push(TypeParameter::new(ident, bounds));
+            if self.next_type() == TokenType::Comma {
+                self.consume();
+                continue
+            }
+            try!(self.consume_type(TokenType::RightAngle));
+            return Ok(params)
+        }
+    }
+
+    /// Grab the length of an array, such as the `5` in `[int: 5]`
+    pub fn array_len(&mut self) -> Result<u64, ParseError> {
+        let token = self.consume();
+        trace!("Getting an array length from {}", token);
+        if token.data() != TokenData::NumberLiteral {
+            return Err(ParseError::ExpectedToken {
+                expected: TokenType::Literal,
+                got: token.get_type(),
+                token: token
+            })
+        }
+        match token.text().parse::<u64>() {
+            Ok(len) => Ok(len),
+            Err(_) => Err(ParseError::LazyString(format!(
+                "Invalid array length {}", token.text())))
+        }
+    }
+
     /// Gets the binary operator used for the given token.
     pub fn binary_operator(&self,
                            token_type: TokenType)
@@ -389,6 +669,8 @@ impl<T: Tokenizer> Parser<T> {
             RightAngle => Ok(BinaryOperator::GreaterThan),
             LessThanEquals => Ok(BinaryOperator::LessThanEquals),
             GreaterThanEquals => Ok(BinaryOperator::GreaterThanEquals),
+            And => Ok(BinaryOperator::And),
+            Or => Ok(BinaryOperator::Or),
             _ => Err(ParseError::UnknownOperator {
                     text: Cow::from(format!("{:?}", token_type)),
                     token_type
@@ -403,6 +685,7 @@ impl<T: Tokenizer> Parser<T> {
         match token_type {
             Minus => Ok(UnaryOperator::Negation),
             Plus => Ok(UnaryOperator::Addition),
+            Not => Ok(UnaryOperator::Not),
             _ => Err(ParseError::UnknownOperator {
                     text: Cow::from(format!("{:?}", token_type)),
                     token_type
@@ -421,6 +704,18 @@ impl<T: Tokenizer> Parser<T> {
 
     /// Parse a program and verify it for errors
     pub fn parse_unit(&mut self) -> Result<Unit, ParseError> {
+        let unit = self.unit();
+        // Indentation errors from the tokenizer can cause strange parse
+        // errors, so they are reported first.
+        let lex_errors = self.tokenizer.take_errors();
+        if !lex_errors.is_empty() {
+            return Err(ParseError::LexErrors(lex_errors))
+        }
+        unit
+    }
+
+    /// Parse the items of a unit.
+    fn unit(&mut self) -> Result<Unit, ParseError> {
         let start = self.peek().start();
         let mut items = Vec::with_capacity(10);
         while self.next_type() != TokenType::EOF {
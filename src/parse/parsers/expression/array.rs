@@ -0,0 +1,50 @@
+//! Parser for `[`.
+
+use lex::{Token, Tokenizer, TokenType};
+use parse::{Parser, ParseResult};
+use ast::*;
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses array literals, and arrays of a repeated value.
+///
+/// # Examples
+/// ```text
+/// [        x,          y + 1              ]
+/// ^  ->element:expression ->element:expression (skip)
+/// ```
+/// ```text
+/// [        0         :      5        ]
+/// ^  ->value:expression (skip) ^len:int (skip)
+/// ```
+#[derive(Debug)]
+pub struct ArrayParser { }
+impl<T: Tokenizer> PrefixParser<Expression, T> for ArrayParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        debug_assert!(token.get_type() == TokenType::LeftBracket,
+                      "Array parser called with non-left-bracket {:?}", token);
+        if parser.next_type() == TokenType::RightBracket {
+            trace!("Parsing an empty array");
+            let end = parser.consume().end();
+            return Ok(Expression::Array(ArrayExpression::new(
+                token.start(), Vec::new(), end)))
+        }
+        let first_expr = try!(parser.expression(Precedence::Min));
+        let first = try!(first_expr.expect_value());
+        if parser.next_type() == TokenType::Colon {
+            trace!("Found a colon, parsing a repeated array");
+            parser.consume();
+            let len = try!(parser.array_len());
+            let end = try!(parser.consume_type(TokenType::RightBracket)).end();
+            return Ok(Expression::ArrayRepeat(ArrayRepeat::new(
+                token.start(), Box::new(first), len, end)))
+        }
+        let mut elements = vec![first];
+        while parser.next_type() == TokenType::Comma {
+            parser.consume();
+            let element_expr = try!(parser.expression(Precedence::Min));
+            elements.push(try!(element_expr.expect_value()));
+        }
+        let end = try!(parser.consume_type(TokenType::RightBracket)).end();
+        Ok(Expression::Array(ArrayExpression::new(token.start(), elements, end)))
+    }
+}
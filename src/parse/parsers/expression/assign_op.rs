@@ -1,6 +1,6 @@
 //! Assign operator parser.
 
-use lex::{Token, Tokenizer};
+use lex::{Token, Tokenizer, TokenType};
 use ast::*;
 use parse::{Parser, ParseResult};
 use parse::parsers::{InfixParser, Precedence};
@@ -20,10 +20,28 @@ pub struct AssignOpParser { }
 impl<T: Tokenizer> InfixParser<Expression, T> for AssignOpParser {
     fn parse(&self, parser: &mut Parser<T>,
              left: Expression, token: Token) -> ParseResult<Expression> {
-        let lvalue = try!(left.expect_identifier());
         let right_expr = try!(parser.expression(Precedence::Min));
         let right_value = try!(right_expr.expect_value());
-        let operator = try!(parser.binary_operator(token.get_type()));
+        // `x += 5` uses the operator of `x + 5`.
+        let operator_type = match token.get_type() {
+            TokenType::PlusEquals => TokenType::Plus,
+            TokenType::MinusEquals => TokenType::Minus,
+            TokenType::StarEquals => TokenType::Star,
+            TokenType::SlashEquals => TokenType::Slash,
+            TokenType::PercentEquals => TokenType::Percent,
+            other => other
+        };
+        let operator = try!(parser.binary_operator(operator_type));
+        // `a[i] += 1` is expanded to `a[i] = a[i] + 1`.
+        if let Expression::Index(index) = left {
+            let right_expr = Expression::BinaryOp(BinaryOperation::new(
+                operator,
+                Box::new(Expression::Index(index.clone())),
+                Box::new(right_value)));
+            return Ok(Expression::IndexAssignment(
+                IndexAssignment::new(index, Box::new(right_expr))))
+        }
+        let lvalue = try!(left.expect_identifier());
         // We parse it here into an expanded expression.
         let right_expr = Expression::BinaryOp(BinaryOperation::new(
             operator,
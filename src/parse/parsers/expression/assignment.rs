@@ -19,6 +19,13 @@ impl<T: Tokenizer> InfixParser<Expression, T> for AssignmentParser {
              left: Expression, _token: Token) -> ParseResult<Expression> {
         debug_assert!(_token.get_type() == TokenType::Equals,
             "Assign parser called with non-assign token {:?}", _token);
+        // Elements of arrays can be assigned to, as in `a[i] = 1`.
+        if let Expression::Index(index) = left {
+            let right_expr = try!(parser.expression(Precedence::Assign));
+            let right = try!(right_expr.expect_value());
+            return Ok(Expression::IndexAssignment(
+                IndexAssignment::new(index, Box::new(right))))
+        }
         let ident = try!(left.expect_identifier());
         let right_expr = try!(parser.expression(Precedence::Assign));
         let right = try!(right_expr.expect_value());
@@ -0,0 +1,38 @@
+//! Field access - inline `.`
+
+use lex::{Token, Tokenizer, TokenType, TokenData};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::InfixParser;
+
+/// Parses field access by handling `.` as an infix operator.
+///
+/// Tuple elements are accessed with their index in place of a field name.
+///
+/// # Examples
+/// ```text
+/// point  .     x
+/// >^expr ^take ^ident
+/// ```
+/// ```text
+/// pair   .     0
+/// >^expr ^take ^index
+/// ```
+#[derive(Debug)]
+pub struct FieldAccessParser { }
+impl<T: Tokenizer> InfixParser<Expression, T> for FieldAccessParser {
+    fn parse(&self, parser: &mut Parser<T>,
+             left: Expression, token: Token) -> ParseResult<Expression> {
+        trace!("Parsing a field access of {:?}", left);
+        debug_assert!(token.get_type() == TokenType::Dot,
+            "FieldAccessParser: called on token {:?}", token);
+        let left = try!(left.expect_value());
+        let field = if parser.peek().data() == TokenData::NumberLiteral {
+            Identifier::new(parser.consume())
+        }
+        else {
+            try!(parser.lvalue())
+        };
+        Ok(Expression::FieldAccess(FieldAccess::new(Box::new(left), field)))
+    }
+}
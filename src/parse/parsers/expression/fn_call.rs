@@ -2,16 +2,22 @@
 
 use lex::{Token, Tokenizer, TokenType, Span};
 use ast::*;
-use parse::{Parser, ParseResult, ParseError, IndentationRule};
-use parse::parsers::{InfixParser, Precedence};
+use parse::{Parser, ParseResult};
+use parse::parsers::InfixParser;
 
 /// Parses function calls by handling `(` as in infix operator.
 ///
+/// Calls of a field access are method calls.
+///
 /// # Examples
 /// ```text
 /// foo(bar    :     otherFnCall(),     baz    )
 ///    >^ident ^take ^expr        ^take ^ident ^take
 /// ```
+/// ```text
+/// values.push(value :     1    )
+/// >^field    >^ident ^take ^expr ^take
+/// ```
 #[derive(Debug)]
 pub struct FnCallParser { }
 impl<T: Tokenizer> InfixParser<Expression, T> for FnCallParser {
@@ -21,48 +27,19 @@ impl<T: Tokenizer> InfixParser<Expression, T> for FnCallParser {
         debug_assert!(token.get_type() == TokenType::LeftParen,
             "FnCallParser: called on token {:?}", token);
         let start = token.start();
+        if let Expression::FieldAccess(field_access) = left {
+            trace!("Parsing a method call");
+            let (receiver, method) = field_access.into_parts();
+            let call_args = try!(parser.call_args());
+            let end = call_args.last()
+                .map(|arg| arg.span().end())
+                .unwrap_or_else(|| method.span().end());
+            return Ok(Expression::MethodCall(MethodCall::new(
+                receiver, method, call_args, end)))
+        }
         let lvalue = try!(left.expect_identifier());
 
-        let mut call_args = Vec::new();
-        let mut arg_name = true;
-        loop {
-            if parser.next_type() == TokenType::RightParen {
-                parser.consume();
-                trace!("Function call complete");
-                break
-            }
-            if arg_name {
-                trace!("Parsing an argument");
-                let arg = try!(parser.expression(Precedence::Min));
-                if let Expression::VariableRef(ident) = arg {
-                    if parser.next_type() == TokenType::Colon {
-                        trace!("Argument {} is a named arg", ident.name());
-                        parser.consume();
-                        let arg_value = try!(parser.expression(Precedence::Min));
-                        call_args.push(CallArgument::named(ident, arg_value));
-                    }
-                    else {
-                        //call_args.push(CallArgument::implicit(
-                        //    Expression::VariableRef(ident)));
-                        // https://github.com/immington-industries/protosnirk/issues/45
-                        return Err(ParseError::LazyString(
-                            "Non-named params not supported right now".into()))
-                    }
-                }
-                else {
-                    try!(parser.consume_type_indented(TokenType::RightParen,
-                                                      IndentationRule::NegateDeindent));
-                    trace!("Function call complete");
-                    break
-                }
-                arg_name = false;
-            }
-            else {
-                try!(parser.consume_type_indented(TokenType::Comma,
-                                                  IndentationRule::NegateDeindent));
-                arg_name = true;
-            }
-        }
+        let call_args = try!(parser.call_args());
         let end = parser.peek().end();
         let call = FnCall::new(Span::from(start ..= end), lvalue, call_args);
         Ok(Expression::FnCall(call))
@@ -1,23 +1,31 @@
 //! Identifier parser
 
-// This is gonna be merged with lvalue parsers
-
 use lex::{Token, Tokenizer};
 use parse::{Parser, ParseResult};
 use ast::*;
 use parse::parsers::PrefixParser;
 
-/// Returns an identifier
+/// Returns an identifier, which may be an associated function of a type or
+/// an item of an imported module
 ///
 /// # Examples
 /// ```text
 /// x
 /// ^:name
 /// ```
+/// ```text
+/// Point :: origin
+/// ^:type ^take ^:name
+/// ```
+/// ```text
+/// math :: square
+/// ^:module ^take ^:name
+/// ```
 #[derive(Debug)]
 pub struct IdentifierParser { }
 impl<T: Tokenizer> PrefixParser<Expression, T> for IdentifierParser {
-    fn parse(&self, _parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
-        Ok(Expression::VariableRef(Identifier::new(token)))
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        let ident = try!(parser.path(token));
+        Ok(Expression::VariableRef(ident))
     }
 }
@@ -3,7 +3,7 @@
 use lex::{Token, Tokenizer, TokenType};
 use ast::*;
 use parse::{Parser, ParseResult};
-use parse::parsers::{PrefixParser, Precedence};
+use parse::parsers::{PrefixParser, Precedence, IfLetParser};
 
 /// Parses block and inline forms of prefix expr/block `if`.
 ///
@@ -13,6 +13,8 @@ use parse::parsers::{PrefixParser, Precedence};
 /// if expr => expr else expr
 /// ```
 ///
+/// `if let` expressions are parsed by `IfLetParser`.
+///
 /// This parser may have been called from an `IfBlockParser`
 /// in order to parse the inline if when it was in the expression form.
 /// However, block if form is not allowed in all expression places.
@@ -23,6 +25,11 @@ impl<T: Tokenizer> PrefixParser<Expression, T> for IfExpressionParser {
             -> ParseResult<Expression> {
         debug_assert!(token.text() == "if",
             "Invlaid token {:?} in IfExpressionParser", token);
+        if parser.next_type() == TokenType::Let {
+            trace!("Parsing if let expression");
+            let let_token = parser.consume();
+            return IfLetParser { }.parse(parser, let_token)
+        }
         trace!("Parsing conditional of if expression");
         let start = token.start();
         let condition = try!(parser.expression(Precedence::Min));
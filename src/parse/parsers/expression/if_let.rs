@@ -0,0 +1,80 @@
+//! If let parser.
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseError, ParseResult};
+use parse::parsers::{PrefixParser, Precedence, IfBlockParser};
+
+/// Parses `if let` blocks and inline `if let` expressions into a `match`.
+///
+/// The pattern becomes the first arm of the match, and the `else` becomes a
+/// `_` arm, which is empty if there is no `else`.
+///
+/// # Examples
+/// ```text
+/// if let pattern  =     expr  =>    expr  else  expr
+///        ^pattern ^take ^expr ^take ^expr ^take ^expr
+///
+/// if let pattern  =     expr  \+    stmt*  \-  [else  (\+    stmt*  \- | if ...)]
+///        ^pattern ^take ^expr ^take ^block     [^take (^take ^block    | ^if     )]
+/// ```
+///
+/// This parser is called by the `if` parsers after they have seen the `let`.
+#[derive(Debug)]
+pub struct IfLetParser { }
+impl<T: Tokenizer> PrefixParser<Expression, T> for IfLetParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        debug_assert!(token.get_type() == TokenType::Let,
+            "Invalid token {:?} in IfLetParser", token);
+        trace!("Parsing pattern of if let");
+        let start = token.start();
+        let pattern = try!(parser.pattern());
+        try!(parser.consume_type(TokenType::Equals));
+        let value = try!(parser.expression(Precedence::Min));
+        let value = try!(value.expect_value());
+
+        let (block, else_block) = if parser.next_type() == TokenType::InlineArrow {
+            trace!("Parsing inline if let");
+            parser.consume();
+            let true_expr = try!(parser.expression(Precedence::Min));
+            try!(parser.consume_type(TokenType::Else));
+            let else_expr = try!(parser.expression(Precedence::Min));
+            (Block::new(true_expr.span().start(),
+                        vec![Statement::Expression(true_expr)]),
+             Block::new(else_expr.span().start(),
+                        vec![Statement::Expression(else_expr)]))
+        }
+        else {
+            trace!("Parsing if let block");
+            try!(parser.consume_type(TokenType::BeginBlock));
+            let block = try!(parser.block());
+            let else_block = if parser.next_type() != TokenType::Else {
+                Block::new(block.span().end(), vec![])
+            }
+            else {
+                let else_token = parser.consume();
+                match parser.next_type() {
+                    TokenType::BeginBlock => {
+                        trace!("Parsing else block of if let");
+                        parser.consume();
+                        try!(parser.block())
+                    },
+                    TokenType::If => {
+                        trace!("Parsing else if of if let");
+                        let if_token = parser.consume();
+                        let else_if = try!(IfBlockParser { }.parse(parser, if_token));
+                        Block::new(else_token.start(), vec![else_if])
+                    },
+                    _ => return Err(ParseError::LazyString(format!(
+                        "Got unexpected token {:?} after an else", parser.peek())))
+                }
+            };
+            (block, else_block)
+        };
+        let arms = vec![
+            MatchArm::new(pattern, None, block),
+            MatchArm::new(Pattern::Wildcard(token.span()), None, else_block)
+        ];
+        Ok(Expression::Match(Match::new(start, Box::new(value), arms)))
+    }
+}
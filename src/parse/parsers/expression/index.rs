@@ -0,0 +1,30 @@
+//! Array indexing - inline `[`
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::{InfixParser, Precedence};
+
+/// Parses indexing of an array by handling `[` as an infix operator.
+///
+/// # Examples
+/// ```text
+/// values  [     i + 1             ]
+/// >^expr  ^take ->index:expression ^take
+/// ```
+#[derive(Debug)]
+pub struct IndexParser { }
+impl<T: Tokenizer> InfixParser<Expression, T> for IndexParser {
+    fn parse(&self, parser: &mut Parser<T>,
+             left: Expression, token: Token) -> ParseResult<Expression> {
+        trace!("Parsing an index of {:?}", left);
+        debug_assert!(token.get_type() == TokenType::LeftBracket,
+            "IndexParser: called on token {:?}", token);
+        let left = try!(left.expect_value());
+        let index_expr = try!(parser.expression(Precedence::Min));
+        let index = try!(index_expr.expect_value());
+        let end = try!(parser.consume_type(TokenType::RightBracket)).end();
+        Ok(Expression::Index(IndexExpression::new(
+            Box::new(left), Box::new(index), end)))
+    }
+}
@@ -0,0 +1,54 @@
+//! Lambda expression parser - prefix `fn`
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses anonymous functions, such as `fn(x: float) => x * scale`.
+///
+/// The return type may be given with `->` before the `=>`, otherwise it is
+/// the type of the body.
+///
+/// # Examples
+/// ```text
+/// fn    (      x      :      float          ) ->      float         =>     x * scale
+/// ^take ^(take)^lvalue^(take)^->param:type  ^ ^(take) ^->return:type ^(take)^->body:expression
+/// ```
+#[derive(Debug)]
+pub struct LambdaParser { }
+impl<T: Tokenizer> PrefixParser<Expression, T> for LambdaParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        debug_assert!(token.get_type() == TokenType::Fn,
+            "LambdaParser: called on token {:?}", token);
+        trace!("Parsing a lambda");
+        try!(parser.consume_type(TokenType::LeftParen));
+        let mut params = Vec::new();
+        if parser.next_type() != TokenType::RightParen {
+            loop {
+                let name = try!(parser.lvalue());
+                try!(parser.consume_type(TokenType::Colon));
+                let param_ty = try!(parser.binding_type_expr());
+                params.push((name, param_ty));
+                if parser.next_type() != TokenType::Comma {
+                    break
+                }
+                parser.consume();
+            }
+        }
+        try!(parser.consume_type(TokenType::RightParen));
+        let return_type = if parser.next_type() == TokenType::Arrow {
+            parser.consume();
+            Some(try!(parser.type_expr()))
+        }
+        else {
+            None
+        };
+        try!(parser.consume_type(TokenType::InlineArrow));
+        trace!("Parsing lambda body");
+        let body_expr = try!(parser.expression(Precedence::Min));
+        let body = try!(body_expr.expect_value());
+        Ok(Expression::Lambda(Lambda::new(
+            token.start(), params, return_type, Box::new(body))))
+    }
+}
@@ -13,10 +13,52 @@ use ast::*;
 /// ^literal
 /// ```
 pub struct LiteralParser { }
+
+/// Replace the escape sequences in the text of a string literal, and remove
+/// its quotes.
+///
+/// The tokenizer has already reported any invalid escapes.
+fn unescape_string(text: &str) -> String {
+    let mut value = String::with_capacity(text.len());
+    // Skip the opening quote.
+    let mut chars = text.chars().skip(1);
+    while let Some(ch) = chars.next() {
+        match ch {
+            // The closing quote, if the string was terminated.
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('r') => value.push('\r'),
+                Some(other) => value.push(other),
+                None => {}
+            },
+            other => value.push(other)
+        }
+    }
+    value
+}
+
+/// Whether a numeric literal is an integer, rather than having a decimal
+/// point or exponent.
+fn is_integer_text(text: &str) -> bool {
+    !text.contains(|ch| ch == '.' || ch == 'e' || ch == 'E')
+}
+
 impl<T: Tokenizer> PrefixParser<Expression, T> for LiteralParser {
     fn parse(&self, _parser: &mut Parser<T>, token: Token)
              -> ParseResult<Expression> {
         match token.data() {
+            TokenData::NumberLiteral if is_integer_text(token.text()) => {
+                match token.text().parse::<u64>() {
+                    Ok(val) =>
+                        Ok(Expression::Literal(
+                            Literal::new_int(token, val))),
+                    Err(_) =>
+                        Err(ParseError::LazyString(format!(
+                            "Integer literal {} is too large", token.text())))
+                }
+            },
             TokenData::NumberLiteral => {
                 match token.text().parse::<f64>() {
                     Ok(val) =>
@@ -50,6 +92,10 @@ impl<T: Tokenizer> PrefixParser<Expression, T> for LiteralParser {
             TokenData::UnitLiteral => {
                 Ok(Expression::Literal(Literal::new_unit(token)))
             },
+            TokenData::StringLiteral => {
+                let value = unescape_string(token.text());
+                Ok(Expression::Literal(Literal::new_str(token, value)))
+            },
             // This is an unexpected internal error.
             _ => Err(ParseError::ExpectedToken {
                 expected: TokenType::Literal,
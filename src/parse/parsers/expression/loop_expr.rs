@@ -0,0 +1,32 @@
+//! Loop expression parser.
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::PrefixParser;
+
+/// Parses `loop` expressions.
+///
+/// The `label` is set by `LabelledLoopParser` for labelled loops.
+///
+/// # Examples
+/// ```text
+/// loop  \+ stmt* \-
+/// ^take ^take ^block
+/// ```
+#[derive(Debug)]
+pub struct LoopParser {
+    pub label: Option<Identifier>
+}
+impl<T: Tokenizer> PrefixParser<Expression, T> for LoopParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        debug_assert!(token.get_type() == TokenType::Loop,
+            "Invalid token {:?} in LoopParser", token);
+        let start = self.label.as_ref()
+            .map_or(token.start(), |label| label.span().start());
+        trace!("Parsing loop block");
+        try!(parser.consume_type(TokenType::BeginBlock));
+        let block = try!(parser.block());
+        Ok(Expression::Loop(Loop::new(start, self.label.clone(), block)))
+    }
+}
@@ -0,0 +1,62 @@
+//! Match expression parser.
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses `match` expressions and their arms.
+///
+/// # Examples
+/// ```text
+/// match expr  \+    (pattern  [if    expr]  (=>    expr  | \+    stmt*  \-))+ \-
+/// ^take ^expr ^take  (^pattern [^take ^expr] (^take ^expr | ^take ^block   ))+ ^take
+/// ```
+#[derive(Debug)]
+pub struct MatchParser { }
+impl<T: Tokenizer> PrefixParser<Expression, T> for MatchParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        debug_assert!(token.get_type() == TokenType::Match,
+            "Invalid token {:?} in MatchParser", token);
+        let start = token.start();
+        let value = try!(parser.expression(Precedence::Min));
+        let value = try!(value.expect_value());
+        try!(parser.consume_type(TokenType::BeginBlock));
+
+        let mut arms = Vec::new();
+        loop {
+            match parser.next_type() {
+                TokenType::EndBlock => {
+                    parser.consume();
+                    break
+                },
+                TokenType::EOF => break,
+                _ => {}
+            }
+            trace!("Parsing match arm");
+            let pattern = try!(parser.pattern());
+            let guard = if parser.next_type() == TokenType::If {
+                trace!("Parsing match arm guard");
+                parser.consume();
+                let guard = try!(parser.expression(Precedence::Min));
+                Some(try!(guard.expect_value()))
+            }
+            else {
+                None
+            };
+            let block = if parser.next_type() == TokenType::InlineArrow {
+                trace!("Parsing inline match arm");
+                parser.consume();
+                let expr = try!(parser.expression(Precedence::Min));
+                Block::new(expr.span().start(), vec![Statement::Expression(expr)])
+            }
+            else {
+                trace!("Parsing match arm block");
+                try!(parser.consume_type(TokenType::BeginBlock));
+                try!(parser.block())
+            };
+            arms.push(MatchArm::new(pattern, guard, block));
+        }
+        Ok(Expression::Match(Match::new(start, Box::new(value), arms)))
+    }
+}
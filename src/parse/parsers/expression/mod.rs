@@ -5,6 +5,15 @@ mod assignment;
 mod assign_op;
 mod if_expr;
 mod fn_call;
+mod loop_expr;
+mod match_expr;
+mod field_access;
+mod struct_update;
+mod if_let;
+mod array;
+mod index;
+mod slice;
+mod lambda;
 
 pub use self::literal::LiteralParser;
 pub use self::identifier::IdentifierParser;
@@ -13,6 +22,15 @@ pub use self::assignment::AssignmentParser;
 pub use self::assign_op::AssignOpParser;
 pub use self::if_expr::IfExpressionParser;
 pub use self::fn_call::FnCallParser;
+pub use self::loop_expr::LoopParser;
+pub use self::match_expr::MatchParser;
+pub use self::field_access::FieldAccessParser;
+pub use self::struct_update::StructUpdateParser;
+pub use self::if_let::IfLetParser;
+pub use self::array::ArrayParser;
+pub use self::index::IndexParser;
+pub use self::slice::SliceParser;
+pub use self::lambda::LambdaParser;
 
 use lex::{Token, Tokenizer};
 use parse::{Parser, ParseResult};
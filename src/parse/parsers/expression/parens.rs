@@ -1,29 +1,41 @@
 //! Parser for `(`.
 
-// This parser will be one of the first to be heavily
-// overloaded (tuple parsing vs expression recedence in expr prefix).
-
 use lex::{Token, Tokenizer, TokenType};
 use parse::{Parser, ParseResult};
 use ast::*;
 use parse::parsers::{PrefixParser, Precedence};
 
-/// Parses expressions wrapped in parentheses
+/// Parses expressions wrapped in parentheses, and tuples of expressions
 ///
 /// # Examples
 /// ```text
 /// (        x + 1          )
 /// ^  ->right:expression (skip)
 /// ```
+/// ```text
+/// (        x,          y + 1              )
+/// ^  ->element:expression ->element:expression (skip)
+/// ```
 #[derive(Debug)]
 pub struct ParensParser { }
 impl<T: Tokenizer> PrefixParser<Expression, T> for ParensParser {
-    fn parse(&self, parser: &mut Parser<T>, _token: Token) -> ParseResult<Expression> {
-        debug_assert!(_token.get_type() == TokenType::LeftParen,
-                      "Parens parser called with non-left-paren {:?}", _token);
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        debug_assert!(token.get_type() == TokenType::LeftParen,
+                      "Parens parser called with non-left-paren {:?}", token);
         let inner_expr = try!(parser.expression(Precedence::Min));
         let inner = try!(inner_expr.expect_value());
-        try!(parser.consume_type(TokenType::RightParen));
-        Ok(inner)
+        if parser.next_type() != TokenType::Comma {
+            try!(parser.consume_type(TokenType::RightParen));
+            return Ok(inner)
+        }
+        trace!("Found a comma, parsing a tuple");
+        let mut elements = vec![inner];
+        while parser.next_type() == TokenType::Comma {
+            parser.consume();
+            let element_expr = try!(parser.expression(Precedence::Min));
+            elements.push(try!(element_expr.expect_value()));
+        }
+        let end = try!(parser.consume_type(TokenType::RightParen)).end();
+        Ok(Expression::Tuple(TupleExpression::new(token.start(), elements, end)))
     }
 }
@@ -0,0 +1,27 @@
+//! Slice of an array - prefix `&`
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses slices of arrays by handling `&` as a prefix operator.
+///
+/// # Examples
+/// ```text
+/// &     values
+/// ^take ->value:expression
+/// ```
+#[derive(Debug)]
+pub struct SliceParser { }
+impl<T: Tokenizer> PrefixParser<Expression, T> for SliceParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        debug_assert!(token.get_type() == TokenType::Ampersand,
+            "SliceParser: called on token {:?}", token);
+        trace!("Parsing a slice");
+        let precedence = Precedence::for_token(token.get_type(), true);
+        let value_expr = try!(parser.expression(precedence));
+        let value = try!(value_expr.expect_value());
+        Ok(Expression::Slice(SliceExpression::new(token.start(), Box::new(value))))
+    }
+}
@@ -0,0 +1,34 @@
+//! Struct update - inline `with`
+
+use lex::{Token, Tokenizer, TokenType, Span};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::InfixParser;
+
+/// Parses struct updates by handling `with` as an infix operator.
+///
+/// The updated fields are given like the arguments of a function call.
+///
+/// # Examples
+/// ```text
+/// point  with  (     x     :     1     )
+/// >^expr ^take ^take ^ident ^take ^expr ^take
+/// ```
+#[derive(Debug)]
+pub struct StructUpdateParser { }
+impl<T: Tokenizer> InfixParser<Expression, T> for StructUpdateParser {
+    fn parse(&self, parser: &mut Parser<T>,
+             left: Expression, token: Token) -> ParseResult<Expression> {
+        trace!("Parsing a struct update of {:?}", left);
+        debug_assert!(token.get_type() == TokenType::With,
+            "StructUpdateParser: called on token {:?}", token);
+        let left = try!(left.expect_value());
+        try!(parser.consume_type(TokenType::LeftParen));
+        let fields = try!(parser.call_args());
+        let end = fields.last()
+            .map_or(token.end(), |field| field.span().end());
+        let span = Span::from(left.span().start() ..= end);
+        Ok(Expression::StructUpdate(StructUpdate::new(
+            span, Box::new(left), fields)))
+    }
+}
@@ -0,0 +1,34 @@
+//! Parser for const items
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses a named value which is computed at compile time. Unlike local
+/// consts, const items must declare their type.
+///
+/// # Examples
+/// ```txt
+/// const LIMIT: i64 = 10 * 10
+///
+/// const   name:name  :take  type:type_expr  =take  value:expression
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConstItemParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for ConstItemParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Const,
+            "Unexpected token {:?} to const item parser", token);
+        let start = token.start();
+        let name = try!(parser.lvalue());
+        trace!("Parsing const {}", name.name());
+        try!(parser.consume_type(TokenType::Colon));
+        let type_expr = try!(parser.type_expr());
+        try!(parser.consume_type(TokenType::Equals));
+        let value_expr = try!(parser.expression(Precedence::Min));
+        let value = try!(value_expr.expect_value());
+        Ok(Item::ConstDeclaration(ConstDeclaration::new(
+            start, name, type_expr, Box::new(value))))
+    }
+}
@@ -0,0 +1,67 @@
+//! Parser for enum declarations
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult, ParseError};
+use parse::parsers::PrefixParser;
+
+/// Parses enum declarations, with one variant on each indented line.
+///
+/// # Examples
+/// ```txt
+/// enum Shape
+///     Circle(radius: float)
+///     Rect(width: float, height: float)
+///     Empty
+///
+/// enum Shape \+ Circle(radius: float) Rect(width: float, height: float) Empty \-
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnumParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for EnumParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Enum,
+            "Unexpected token {:?} to enum parser", token);
+        let start = token.start();
+        let name = try!(parser.lvalue());
+
+        if parser.next_type() != TokenType::BeginBlock {
+            return Err(ParseError::LazyString(format!(
+                "Expected the variants of enum {} on indented lines",
+                name.name())))
+        }
+        parser.consume();
+
+        let mut variants = Vec::new();
+        let mut end = name.span().end();
+        while parser.next_type() != TokenType::EndBlock
+                && parser.next_type() != TokenType::EOF {
+            let variant_name = try!(parser.lvalue());
+            end = variant_name.span().end();
+            let mut fields = Vec::new();
+            if parser.next_type() == TokenType::LeftParen {
+                trace!("Parsing fields of variant {}", variant_name.name());
+                parser.consume();
+                loop {
+                    let field = try!(parser.lvalue());
+                    try!(parser.consume_type(TokenType::Colon));
+                    let type_ = try!(parser.type_expr());
+                    fields.push((field, type_));
+                    if parser.next_type() == TokenType::Comma {
+                        parser.consume();
+                        continue
+                    }
+                    end = try!(parser.consume_type(TokenType::RightParen))
+                        .span().end();
+                    break
+                }
+            }
+            variants.push(EnumVariant::new(variant_name, fields, end));
+        }
+        parser.consume(); // EndBlock or EOF
+
+        Ok(Item::EnumDeclaration(EnumDeclaration::new(
+            start, name, variants, end
+        )))
+    }
+}
@@ -0,0 +1,39 @@
+//! Parser for exported function declarations
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult, ParseError};
+use parse::parsers::PrefixParser;
+use parse::parsers::item::FnDeclarationParser;
+
+/// Parses a function which is exported under its own name so that it can be
+/// called from C.
+///
+/// # Examples
+/// ```txt
+/// export fn area(w: float, h: float) -> float
+///     w * h
+///
+/// export fn_declaration
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExportFnParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for ExportFnParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Export,
+            "Unexpected token {:?} to export fn parser", token);
+        let start = token.start();
+        let fn_token = try!(parser.consume_type(TokenType::Fn));
+        match try!(FnDeclarationParser { }.parse(parser, fn_token)) {
+            Item::BlockFnDeclaration(block_fn) => {
+                if block_fn.is_generic() {
+                    return Err(ParseError::LazyString(format!(
+                        "Exported fn {} cannot have type parameters",
+                        block_fn.name())))
+                }
+                Ok(Item::BlockFnDeclaration(block_fn.into_exported(start)))
+            },
+            other => panic!("Fn parser returned non-fn item {:?}", other)
+        }
+    }
+}
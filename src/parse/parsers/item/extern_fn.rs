@@ -0,0 +1,51 @@
+//! Parser for extern function declarations
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult, ParseError};
+use parse::parsers::PrefixParser;
+
+/// Parses the signature of a function defined outside of protosnirk.
+///
+/// # Examples
+/// ```txt
+/// extern fn sqrt(x: float) -> float
+///
+/// extern fn ^lvalue (params) -> type_expr
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExternFnParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for ExternFnParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Extern,
+            "Unexpected token {:?} to extern fn parser", token);
+        let start = token.start();
+        try!(parser.consume_type(TokenType::Fn));
+        let name = try!(parser.lvalue());
+        if parser.next_type() == TokenType::LeftAngle {
+            return Err(ParseError::LazyString(format!(
+                "Extern fn {} cannot have type parameters", name.name())))
+        }
+        let params = try!(parser.fn_params());
+
+        let mut end = parser.peek().start();
+        let return_ty = if parser.next_type() == TokenType::Arrow {
+            parser.consume();
+            let return_ty = try!(parser.type_expr());
+            end = return_ty.span().end();
+            return_ty
+        }
+        else {
+            TypeExpression::Named(NamedTypeExpression::new(Identifier::new(
+                Token::new_ident("()", name.span().start()))))
+        };
+
+        if parser.next_type() == TokenType::BeginBlock {
+            return Err(ParseError::LazyString(format!(
+                "Extern fn {} cannot have a body", name.name())))
+        }
+        Ok(Item::ExternFnDeclaration(ExternFnDeclaration::new(
+            start, name, params, return_ty, end
+        )))
+    }
+}
@@ -2,7 +2,7 @@
 
 use lex::{Token, Tokenizer, TokenType};
 use ast::*;
-use parse::{Parser, ParseResult, IndentationRule};
+use parse::{Parser, ParseResult};
 use parse::parsers::PrefixParser;
 
 /// Parses a function declaration.
@@ -15,6 +15,9 @@ use parse::parsers::PrefixParser;
 ///     stmt*
 ///
 /// fn foo (bar, baz, \+ bliz) -> int \- \+ stmt* \-
+///
+/// fn first<T>(values: &[T]) -> T
+///     stmt*
 /// ```
 #[derive(Debug, PartialEq, Clone)]
 pub struct FnDeclarationParser { }
@@ -24,40 +27,10 @@ impl<T: Tokenizer> PrefixParser<Item, T> for FnDeclarationParser {
             "Unexpected token {:?} to fn parser", token);
         let start = token.start();
         let name = try!(parser.lvalue());
+        let type_params = try!(parser.type_params());
 
         // Args
-
-        // TODO Eventually params should be a separate parser?
-        // altough the fn signature type parser would be a little different
-        // from the first-class-fn type parser.
-
-        // left paren cannot be indented
-        try!(parser.consume_type(TokenType::LeftParen));
-        // S1 -> ")", done | name, S2
-        // S2 -> ",", S1 | ")", done
-        let mut params = Vec::new();
-        let mut param_name = true;
-        loop {
-            if parser.next_type() == TokenType::RightParen {
-                parser.consume(); // right paren
-                break
-            }
-            // name
-            if param_name {
-                parser.apply_indentation(IndentationRule::NegateDeindent);
-                let name = try!(parser.lvalue());
-                try!(parser.consume_type(TokenType::Colon));
-                let type_ = try!(parser.type_expr());
-                params.push((name, type_));
-                param_name = false;
-            }
-            // comma
-            else {
-                try!(parser.consume_type_indented(TokenType::Comma,
-                                                  IndentationRule::NegateDeindent));
-                param_name = true;
-            }
-        }
+        let params = try!(parser.fn_params());
 
         // Explicitly differentiating between omitted return type for block fns
         // This is gonna be `None` for inline fns
@@ -75,7 +48,7 @@ impl<T: Tokenizer> PrefixParser<Item, T> for FnDeclarationParser {
         try!(parser.consume_type(TokenType::BeginBlock));
         let block = try!(parser.block());
         Ok(Item::BlockFnDeclaration(BlockFnDeclaration::new(
-            start, name, params, return_ty, explicit, block
+            start, name, type_params, params, return_ty, explicit, block
         )))
     }
 }
@@ -0,0 +1,38 @@
+//! Parser for global variables
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses a variable of the unit. Unlike local variables, globals must
+/// declare their type.
+///
+/// # Examples
+/// ```txt
+/// let mut count: i64 = 0
+///
+/// let  mut?:mutable  name:name  :take  type:type_expr  =take  value:expression
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct GlobalParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for GlobalParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Let,
+            "Unexpected token {:?} to global parser", token);
+        let start = token.start();
+        let is_mutable = parser.next_type() == TokenType::Mut;
+        if is_mutable {
+            parser.consume();
+        }
+        let name = try!(parser.lvalue());
+        trace!("Parsing global {}", name.name());
+        try!(parser.consume_type(TokenType::Colon));
+        let type_expr = try!(parser.type_expr());
+        try!(parser.consume_type(TokenType::Equals));
+        let value_expr = try!(parser.expression(Precedence::Min));
+        let value = try!(value_expr.expect_value());
+        Ok(Item::GlobalDeclaration(GlobalDeclaration::new(
+            start, name, is_mutable, type_expr, Box::new(value))))
+    }
+}
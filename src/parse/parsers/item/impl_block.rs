@@ -0,0 +1,74 @@
+//! Parser for impl blocks
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult, ParseError};
+use parse::parsers::PrefixParser;
+use parse::parsers::item::FnDeclarationParser;
+
+/// Parses the implementation of a trait for a type, or of a type's own
+/// methods, with its functions on indented lines.
+///
+/// # Examples
+/// ```txt
+/// impl Shape for Circle
+///     fn area(self) -> float
+///         3.14 * self.radius * self.radius
+///
+/// impl  ^trait ^take ^type_expr \+ fn* \-
+/// ```
+/// ```txt
+/// impl Circle
+///     fn unit() -> Circle
+///         Circle(radius: 1.0)
+///
+/// impl  ^type_expr \+ fn* \-
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImplParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for ImplParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Impl,
+            "Unexpected token {:?} to impl parser", token);
+        let start = token.start();
+        let first_type = try!(parser.type_expr());
+        let (trait_name, type_) = if parser.next_type() == TokenType::For {
+            parser.consume();
+            let trait_name = match first_type {
+                TypeExpression::Named(ref named) if named.args().is_empty() =>
+                    named.ident().clone(),
+                _ => return Err(ParseError::LazyString(format!(
+                    "Expected the name of a trait before for")))
+            };
+            (Some(trait_name), try!(parser.type_expr()))
+        }
+        else {
+            (None, first_type)
+        };
+
+        if parser.next_type() != TokenType::BeginBlock {
+            return Err(ParseError::LazyString(format!(
+                "Expected the functions of an impl on indented lines")))
+        }
+        parser.consume();
+
+        let mut methods = Vec::new();
+        let mut end = type_.span().end();
+        while parser.next_type() != TokenType::EndBlock
+                && parser.next_type() != TokenType::EOF {
+            let fn_token = try!(parser.consume_type(TokenType::Fn));
+            match try!(FnDeclarationParser { }.parse(parser, fn_token)) {
+                Item::BlockFnDeclaration(method) => {
+                    end = method.span().end();
+                    methods.push(method);
+                },
+                other => unreachable!("Fn parser returned {:?}", other)
+            }
+        }
+        parser.consume(); // EndBlock or EOF
+
+        Ok(Item::ImplBlock(ImplBlock::new(
+            start, trait_name, type_, methods, end
+        )))
+    }
+}
@@ -0,0 +1,32 @@
+//! Parser for imports of other modules
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult, ParseError};
+use parse::parsers::PrefixParser;
+
+/// Parses an import of a module, or of one of its items.
+///
+/// # Examples
+/// ```txt
+/// import math
+/// import math::square
+///
+/// import ^path
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for ImportParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Import,
+            "Unexpected token {:?} to import parser", token);
+        let start = token.start();
+        let module = try!(parser.consume_type(TokenType::Ident));
+        let path = try!(parser.path(module));
+        if parser.next_type() == TokenType::DoubleColon {
+            return Err(ParseError::LazyString(format!(
+                "Cannot import from {}, which is not a module", path.name())))
+        }
+        Ok(Item::Import(Import::new(start, path)))
+    }
+}
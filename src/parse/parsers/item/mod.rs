@@ -1,5 +1,25 @@
 mod function;
 mod typedef;
+mod struct_decl;
+mod enum_decl;
+mod trait_decl;
+mod impl_block;
+mod extern_fn;
+mod export_fn;
+mod import;
+mod pub_item;
+mod const_item;
+mod global;
 
 pub use self::function::FnDeclarationParser;
 pub use self::typedef::TypedefParser;
+pub use self::struct_decl::StructParser;
+pub use self::enum_decl::EnumParser;
+pub use self::trait_decl::TraitParser;
+pub use self::impl_block::ImplParser;
+pub use self::extern_fn::ExternFnParser;
+pub use self::export_fn::ExportFnParser;
+pub use self::import::ImportParser;
+pub use self::pub_item::PubItemParser;
+pub use self::const_item::ConstItemParser;
+pub use self::global::GlobalParser;
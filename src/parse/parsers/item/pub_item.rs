@@ -0,0 +1,63 @@
+//! Parser for items which are visible outside of their module
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult, ParseError};
+use parse::parsers::PrefixParser;
+
+/// Parses a function, struct, or enum which can be imported by other units.
+///
+/// Other kinds of items, such as typedefs and consts, are private to their
+/// unit and can't be `pub`.
+///
+/// # Examples
+/// ```txt
+/// pub fn square(x: float) -> float
+///     x * x
+///
+/// pub struct Point
+///     x: float
+///     y: float
+///
+/// pub item
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct PubItemParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for PubItemParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Pub,
+            "Unexpected token {:?} to pub item parser", token);
+        let start = token.start();
+        let item = match parser.next_type() {
+            TokenType::Fn | TokenType::Export
+                | TokenType::Struct | TokenType::Enum => None,
+            TokenType::Typedef => Some("typedef"),
+            TokenType::Const => Some("const"),
+            TokenType::Let => Some("global"),
+            TokenType::Trait => Some("trait"),
+            TokenType::Impl => Some("impl"),
+            TokenType::Extern => Some("extern fn"),
+            TokenType::Import => Some("import"),
+            other => {
+                return Err(ParseError::LazyString(format!(
+                    "Expected a fn, struct, or enum after pub, got {:?}",
+                    other)))
+            }
+        };
+        if let Some(item) = item {
+            return Err(ParseError::InvalidPub {
+                item,
+                token: parser.peek().clone()
+            })
+        }
+        match try!(parser.item()) {
+            Item::BlockFnDeclaration(block_fn) =>
+                Ok(Item::BlockFnDeclaration(block_fn.into_public(start))),
+            Item::StructDeclaration(struct_decl) =>
+                Ok(Item::StructDeclaration(struct_decl.into_public(start))),
+            Item::EnumDeclaration(enum_decl) =>
+                Ok(Item::EnumDeclaration(enum_decl.into_public(start))),
+            other => panic!("Item parser returned unexpected item {:?}", other)
+        }
+    }
+}
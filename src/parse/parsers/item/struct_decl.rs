@@ -0,0 +1,50 @@
+//! Parser for struct declarations
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult, ParseError};
+use parse::parsers::PrefixParser;
+
+/// Parses struct declarations, with one field on each indented line.
+///
+/// # Examples
+/// ```txt
+/// struct Point
+///     x: float
+///     y: float
+///
+/// struct Point \+ x: float y: float \-
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct StructParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for StructParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Struct,
+            "Unexpected token {:?} to struct parser", token);
+        let start = token.start();
+        let name = try!(parser.lvalue());
+
+        if parser.next_type() != TokenType::BeginBlock {
+            return Err(ParseError::LazyString(format!(
+                "Expected the fields of struct {} on indented lines",
+                name.name())))
+        }
+        parser.consume();
+
+        let mut fields = Vec::new();
+        let mut end = name.span().end();
+        while parser.next_type() != TokenType::EndBlock
+                && parser.next_type() != TokenType::EOF {
+            let field = try!(parser.lvalue());
+            try!(parser.consume_type(TokenType::Colon));
+            let type_ = try!(parser.type_expr());
+            end = type_.span().end();
+            fields.push((field, type_));
+        }
+        parser.consume(); // EndBlock or EOF
+
+        Ok(Item::StructDeclaration(StructDeclaration::new(
+            start, name, fields, end
+        )))
+    }
+}
@@ -0,0 +1,66 @@
+//! Parser for trait declarations
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult, ParseError};
+use parse::parsers::PrefixParser;
+
+/// Parses trait declarations, with one method signature on each indented
+/// line.
+///
+/// # Examples
+/// ```txt
+/// trait Shape
+///     fn area(self) -> float
+///     fn scale(self, factor: float) -> float
+///
+/// trait Shape \+ fn area(self) -> float \-
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraitParser { }
+impl<T: Tokenizer> PrefixParser<Item, T> for TraitParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Item> {
+        debug_assert!(token.get_type() == TokenType::Trait,
+            "Unexpected token {:?} to trait parser", token);
+        let start = token.start();
+        let name = try!(parser.lvalue());
+
+        if parser.next_type() != TokenType::BeginBlock {
+            return Err(ParseError::LazyString(format!(
+                "Expected the methods of trait {} on indented lines",
+                name.name())))
+        }
+        parser.consume();
+
+        let mut methods = Vec::new();
+        let mut end = name.span().end();
+        while parser.next_type() != TokenType::EndBlock
+                && parser.next_type() != TokenType::EOF {
+            let fn_start = try!(parser.consume_type(TokenType::Fn)).start();
+            let method = try!(parser.lvalue());
+            let params = try!(parser.fn_params());
+            if params.first().map(|&(ref param, _)| param.name()) != Some("self") {
+                return Err(ParseError::LazyString(format!(
+                    "Method {} of trait {} must take self first",
+                    method.name(), name.name())))
+            }
+            // Like block fns, methods without a return type return `()`.
+            let return_ty = if parser.next_type() == TokenType::Arrow {
+                parser.consume();
+                try!(parser.type_expr())
+            }
+            else {
+                TypeExpression::Named(NamedTypeExpression::new(Identifier::new(
+                    Token::new_ident("()", method.span().start()))))
+            };
+            end = parser.peek().start();
+            methods.push(MethodSignature::new(
+                fn_start, method, params, return_ty, end));
+        }
+        parser.consume(); // EndBlock or EOF
+
+        Ok(Item::TraitDeclaration(TraitDeclaration::new(
+            start, name, methods, end
+        )))
+    }
+}
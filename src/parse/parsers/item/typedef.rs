@@ -2,7 +2,7 @@
 
 use lex::{Token, Tokenizer, TokenType};
 use ast::*;
-use parse::{Parser, ParseResult};
+use parse::{Parser, ParseResult, ParseError};
 use parse::parsers::{PrefixParser};
 
 /// Parses type alias declarations.
@@ -11,6 +11,9 @@ use parse::parsers::{PrefixParser};
 /// ```txt
 /// typedef Foo    =     float
 /// ^take   ^ident ^take ^type_expr
+///
+/// typedef Pair   <T>          =    (T, T)
+/// ^take   ^ident ^type_params ^take ^type_expr
 /// ```
 #[derive(Debug, PartialEq, Clone)]
 pub struct TypedefParser { }
@@ -20,13 +23,22 @@ impl<T: Tokenizer> PrefixParser<Item, T> for TypedefParser {
             "Unexpected token {:?} to type alias parser", token);
         let start = token.start();
         let name = try!(parser.lvalue());
+        let mut type_params = Vec::new();
+        for type_param in try!(parser.type_params()) {
+            if !type_param.bounds().is_empty() {
+                return Err(ParseError::LazyString(format!(
+                    "Type parameter {} of typedef {} cannot have bounds",
+                    type_param.name(), name.name())))
+            }
+            type_params.push(type_param.ident().clone());
+        }
 
         try!(parser.consume_type(TokenType::Equals));
 
         let type_ = try!(parser.type_expr());
 
         Ok(Item::Typedef(Typedef::new(
-            start, name, type_
+            start, name, type_params, type_
         )))
     }
 }
@@ -3,12 +3,14 @@ mod statement;
 mod item;
 pub mod types;
 mod precedence;
+mod pattern;
 
 pub use self::expression::*;
 pub use self::statement::*;
 pub use self::item::*;
 pub use self::types::*;
 pub use self::precedence::Precedence;
+pub use self::pattern::PatternParser;
 
 use lex::{Token, Tokenizer};
 use parse::{Parser, ParseResult};
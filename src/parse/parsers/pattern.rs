@@ -0,0 +1,114 @@
+//! Pattern parser.
+
+use lex::{Location, Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseError, ParseResult};
+use parse::parsers::{PrefixParser, LiteralParser, IdentifierParser};
+
+/// Parses patterns used in `match` arms.
+///
+/// # Examples
+/// ```text
+/// _
+/// ^take
+///
+/// [-]    1        [..    [-]    9       ]
+/// [^take] ^literal [^take [^take] ^literal]
+///
+/// Variant [(    field  :     binding [,     field  :     binding]* )    ]
+/// ^take   [^take ^ident ^take ^ident  [^take ^ident ^take ^ident ]* ^take]
+/// ```
+#[derive(Debug)]
+pub struct PatternParser { }
+
+impl PatternParser {
+    /// Parse a literal, which may be negated, in a pattern.
+    fn literal<T: Tokenizer>(&self, parser: &mut Parser<T>, token: Token)
+                             -> ParseResult<LiteralPattern> {
+        let start = token.start();
+        let negative = token.get_type() == TokenType::Minus;
+        let literal_token = if negative { parser.consume() } else { token };
+        if literal_token.get_type() != TokenType::Literal {
+            return Err(ParseError::ExpectedToken {
+                expected: TokenType::Literal,
+                got: literal_token.get_type(),
+                token: literal_token
+            })
+        }
+        let literal = match try!(LiteralParser { }.parse(parser, literal_token)) {
+            Expression::Literal(literal) => literal,
+            other => unreachable!("LiteralParser returned {:?}", other)
+        };
+        let is_value = match *literal.value() {
+            LiteralValue::Unit | LiteralValue::Str(_) => false,
+            _ => true
+        };
+        if !is_value {
+            return Err(ParseError::LazyString(format!(
+                "Expected a bool or number in pattern, got {:?}", literal)))
+        }
+        Ok(LiteralPattern::new(start, literal, negative))
+    }
+
+    /// Parse the fields of a variant pattern after the `(`, and where the
+    /// pattern ends.
+    fn fields<T: Tokenizer>(&self, parser: &mut Parser<T>)
+                            -> ParseResult<(Vec<FieldPattern>, Location)> {
+        let mut fields = Vec::new();
+        loop {
+            let field = try!(parser.lvalue());
+            try!(parser.consume_type(TokenType::Colon));
+            let binding = try!(parser.lvalue());
+            let end = binding.span().end();
+            let binding = if binding.name() == "_" { None }
+                          else { Some(binding) };
+            fields.push(FieldPattern::new(field, binding, end));
+            if parser.next_type() == TokenType::Comma {
+                parser.consume();
+                continue
+            }
+            let end = try!(parser.consume_type(TokenType::RightParen))
+                .span().end();
+            return Ok((fields, end))
+        }
+    }
+}
+
+impl<T: Tokenizer> PrefixParser<Pattern, T> for PatternParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Pattern> {
+        trace!("Parsing pattern starting with {:?}", token);
+        match token.get_type() {
+            TokenType::Ident if token.text() == "_" => {
+                Ok(Pattern::Wildcard(token.span()))
+            },
+            TokenType::Ident => {
+                let ident = try!(IdentifierParser { }.parse(parser, token)
+                    .and_then(|ident| ident.expect_identifier()));
+                if parser.next_type() != TokenType::LeftParen {
+                    let end = ident.span().end();
+                    return Ok(Pattern::Variant(
+                        VariantPattern::new(ident, vec![], end)))
+                }
+                trace!("Parsing fields of variant pattern {}", ident.name());
+                parser.consume();
+                let (fields, end) = try!(self.fields(parser));
+                Ok(Pattern::Variant(VariantPattern::new(ident, fields, end)))
+            },
+            TokenType::Minus | TokenType::Literal => {
+                let start = try!(self.literal(parser, token));
+                let inclusive = match parser.next_type() {
+                    TokenType::DotDot => false,
+                    TokenType::DotDotEquals => true,
+                    _ => return Ok(Pattern::Literal(start))
+                };
+                trace!("Parsing range pattern");
+                parser.consume();
+                let end_token = parser.consume();
+                let end = try!(self.literal(parser, end_token));
+                Ok(Pattern::Range(RangePattern::new(start, end, inclusive)))
+            },
+            _ => Err(ParseError::LazyString(format!(
+                "Expected a pattern, got {:?}", token)))
+        }
+    }
+}
@@ -10,6 +10,10 @@ pub enum Precedence {
     Return,
     /// Assignment and declaration statements
     Assign,
+    /// The `or` keyword
+    OrKeyword,
+    /// The `and` keyword
+    AndKeyword,
     ///  The `==` and `!=` operators
     Equality,
     /// Less than and greater than
@@ -20,11 +24,12 @@ pub enum Precedence {
     MulDiv,
     /// The remainder operator
     Modulo,
-    /// Negate or positive operator
+    /// Negate or positive operator, and `#` and `&`
     NumericPrefix,
     /// The `not` keyword
     NotKeyword,
-    /// Parens binder, used for both prefix and infix fns
+    /// Parens binder, used for both prefix and infix fns, as well as
+    /// field access and `with`
     Paren,
     /// Extra value on the end
     Max
@@ -42,6 +47,8 @@ impl Precedence {
             | StarEquals
             | SlashEquals
             | PercentEquals => Precedence::Assign,
+            Or => Precedence::OrKeyword,
+            And => Precedence::AndKeyword,
             DoubleEquals | NotEquals => Precedence::Equality,
             LeftAngle | RightAngle | LessThanEquals | GreaterThanEquals => Precedence::EqualityCompare,
             Plus | Minus => {
@@ -50,7 +57,17 @@ impl Precedence {
             },
             Star | Slash => Precedence::MulDiv,
             Percent => Precedence::Modulo,
-            LeftParen => Precedence::Paren,
+            // `not` has no infix form, so a line starting with it isn't
+            // part of the line before it.
+            Not => {
+                if prefix { Precedence::NotKeyword }
+                else { Precedence::Min }
+            },
+            Ampersand => {
+                if prefix { Precedence::NumericPrefix }
+                else { Precedence::Min }
+            },
+            LeftParen | LeftBracket | Dot | With => Precedence::Paren,
             _ => Precedence::Min
         }
     }
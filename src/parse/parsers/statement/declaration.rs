@@ -1,19 +1,24 @@
 //! Parses variable declarations
 
-// This will become more complex with tuple declarations
-// and other pattern declaration types.
+// This will become more complex with other pattern declaration types.
 
 use lex::{Token, Tokenizer, TokenType};
 use ast::*;
-use parse::{Parser, ParseResult};
+use parse::{Parser, ParseResult, ParseError};
 use parse::parsers::{PrefixParser, Precedence};
 
+/// Parses variable declarations, including declarations of the elements of
+/// a tuple.
 ///
 /// # Examples
 /// ```text
 /// let mut            x        :      type?   =         6 + 3
 /// ^:.  ^:mutable  ->name:name ^check ^opt   (skip) ->value:expression
 /// ```
+/// ```text
+/// let   (     x,          _          )      =      pair
+/// ^:.   ^take ->name:name ->name:name ^take (skip) ->value:expression
+/// ```
 #[derive(Debug)]
 pub struct DeclarationParser { }
 impl<T: Tokenizer> PrefixParser<Statement, T> for DeclarationParser {
@@ -27,12 +32,15 @@ impl<T: Tokenizer> PrefixParser<Statement, T> for DeclarationParser {
             parser.consume();
         }
         trace!("Found mutability: {}", is_mutable);
+        if parser.next_type() == TokenType::LeftParen {
+            return parse_tuple_declaration(parser, token, is_mutable)
+        }
         let name = try!(parser.lvalue());
         trace!("Got name {:?}", name);
         let decl_type = if parser.next_type() == TokenType::Colon {
             trace!("Found type declaration");
             parser.consume();
-            Some(try!(parser.type_expr()))
+            Some(try!(parser.binding_type_expr()))
         }
         else {
             trace!("No type declaration");
@@ -48,3 +56,81 @@ impl<T: Tokenizer> PrefixParser<Statement, T> for DeclarationParser {
         )))
     }
 }
+
+/// Parses declarations of local consts, whose values are computed at
+/// compile time.
+///
+/// # Examples
+/// ```text
+/// const  x        :      type?   =         6 + 3
+/// ^:.    ->name:name ^check ^opt (skip) ->value:expression
+/// ```
+#[derive(Debug)]
+pub struct ConstDeclarationParser { }
+impl<T: Tokenizer> PrefixParser<Statement, T> for ConstDeclarationParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Statement> {
+        debug_assert!(token.get_type() == TokenType::Const,
+                      "Const parser called with non-const token {:?}", token);
+        trace!("Parsing const declaration for {}", token);
+        let start = token.start();
+        let name = try!(parser.lvalue());
+        let decl_type = if parser.next_type() == TokenType::Colon {
+            trace!("Found type declaration");
+            parser.consume();
+            Some(try!(parser.type_expr()))
+        }
+        else {
+            None
+        };
+        try!(parser.consume_type(TokenType::Equals));
+        let value_expr = try!(parser.expression(Precedence::Min));
+        let value = try!(value_expr.expect_value());
+        Ok(Statement::Declaration(Declaration::new(
+            start, name, false, decl_type, Box::new(value)
+        ).into_const()))
+    }
+}
+
+/// Parses the rest of a declaration of the elements of a tuple, starting
+/// with the `(`.
+fn parse_tuple_declaration<T: Tokenizer>(parser: &mut Parser<T>,
+                                         token: Token,
+                                         is_mutable: bool)
+                                         -> ParseResult<Statement> {
+    trace!("Parsing tuple declaration");
+    try!(parser.consume_type(TokenType::LeftParen));
+    let mut bindings = Vec::new();
+    loop {
+        let name = try!(parser.lvalue());
+        trace!("Got tuple element name {:?}", name);
+        if name.name() == "_" {
+            bindings.push(None);
+        }
+        else {
+            bindings.push(Some(name));
+        }
+        if parser.next_type() != TokenType::Comma {
+            break
+        }
+        parser.consume();
+    }
+    try!(parser.consume_type(TokenType::RightParen));
+    if bindings.len() < 2 {
+        return Err(ParseError::LazyString(
+            "Expected at least two names in a tuple declaration".into()))
+    }
+    let decl_type = if parser.next_type() == TokenType::Colon {
+        trace!("Found type declaration");
+        parser.consume();
+        Some(try!(parser.type_expr()))
+    }
+    else {
+        None
+    };
+    try!(parser.consume_type(TokenType::Equals));
+    let value_expr = try!(parser.expression(Precedence::Min));
+    let value = try!(value_expr.expect_value());
+    Ok(Statement::TupleDeclaration(TupleDeclaration::new(
+        token.start(), bindings, is_mutable, decl_type, Box::new(value)
+    )))
+}
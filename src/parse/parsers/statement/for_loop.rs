@@ -0,0 +1,68 @@
+//! For loop parser.
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseError, ParseResult};
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses for loops over ranges.
+///
+/// The `label` is set by `LabelledLoopParser` for labelled loops. `by` is
+/// only a keyword after the range, so it can still be used as a name.
+///
+/// # Examples
+/// ```text
+/// for   i      in    0     ..     n    [by    2   ]  \+    stmt* \-
+/// ^take ^ident ^take ^expr ^take  ^expr [^take ^expr] ^take ^block
+/// ```
+#[derive(Debug)]
+pub struct ForLoopParser {
+    pub label: Option<Identifier>
+}
+impl<T: Tokenizer> PrefixParser<Statement, T> for ForLoopParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Statement> {
+        debug_assert!(token.get_type() == TokenType::For,
+            "Invalid token {:?} in ForLoopParser", token);
+        let start = self.label.as_ref()
+            .map_or(token.start(), |label| label.span().start());
+        let ident = try!(parser.lvalue());
+        trace!("Parsing range of for loop over {}", ident.name());
+        try!(parser.consume_type(TokenType::In));
+
+        let range_start = try!(parser.expression(Precedence::Min));
+        let range_start = try!(range_start.expect_value());
+        let inclusive = match parser.next_type() {
+            TokenType::DotDot => false,
+            TokenType::DotDotEquals => true,
+            _ => {
+                return Err(ParseError::LazyString(format!(
+                    "Expected `..` or `..=` in for loop range, got {:?}",
+                    parser.peek())))
+            }
+        };
+        parser.consume();
+        let range_end = try!(parser.expression(Precedence::Min));
+        let range_end = try!(range_end.expect_value());
+        let has_step = parser.next_type() == TokenType::Ident
+            && parser.peek().text() == "by";
+        let step = if has_step {
+            trace!("Found range step");
+            parser.consume();
+            let step = try!(parser.expression(Precedence::Min));
+            Some(Box::new(try!(step.expect_value())))
+        }
+        else {
+            None
+        };
+        let range = Range::new(Box::new(range_start),
+                               Box::new(range_end),
+                               inclusive,
+                               step);
+
+        trace!("Parsing for loop block");
+        try!(parser.consume_type(TokenType::BeginBlock));
+        let block = try!(parser.block());
+        Ok(Statement::ForLoop(ForLoop::new(start, self.label.clone(),
+                                           ident, range, block)))
+    }
+}
@@ -3,7 +3,7 @@
 use lex::{Token, Tokenizer, TokenType};
 use ast::*;
 use parse::{Parser, ParseError, ParseResult};
-use parse::parsers::{PrefixParser, Precedence};
+use parse::parsers::{PrefixParser, Precedence, IfLetParser};
 
 /// Parses if blocks and inline if expressions.
 ///
@@ -18,12 +18,20 @@ use parse::parsers::{PrefixParser, Precedence};
 /// This parser is allowed to assume it can parse an inline if expr
 /// instead, but the inline if parser should assume that it is parsing
 /// a context where only [inline] expressions are allowed.
+///
+/// `if let` is parsed by `IfLetParser` into a `match` statement.
 #[derive(Debug)]
 pub struct IfBlockParser { }
 impl<T: Tokenizer> PrefixParser<Statement, T> for IfBlockParser {
     fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Statement> {
         debug_assert!(token.get_type() == TokenType::If,
             "Invalid token {:?} in IfBlockParser", token);
+        if parser.next_type() == TokenType::Let {
+            trace!("Parsing if let statement");
+            let let_token = parser.consume();
+            let if_let = try!(IfLetParser { }.parse(parser, let_token));
+            return Ok(Statement::Expression(if_let))
+        }
         trace!("Parsing conditional of if statement");
         let block_start = token.start();
         let condition = try!(parser.expression(Precedence::Min));
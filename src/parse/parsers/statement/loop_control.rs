@@ -0,0 +1,62 @@
+//! Parsers for `break` and `continue`.
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses the optional `'label` of a `break` or `continue` on the same line.
+fn parse_label<T: Tokenizer>(parser: &mut Parser<T>, token: &Token)
+                             -> ParseResult<Option<Identifier>> {
+    if parser.peek_is_newline(token)
+        || parser.next_type() != TokenType::Quote {
+        return Ok(None)
+    }
+    parser.consume();
+    parser.lvalue().map(Some)
+}
+
+/// Parses break statements.
+///
+/// # Examples
+/// ```text
+/// break ['label] [x + 1]
+/// ^take [^take   ] [->value:expression]
+/// ```
+#[derive(Debug)]
+pub struct BreakParser { }
+impl<T: Tokenizer> PrefixParser<Statement, T> for BreakParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Statement> {
+        debug_assert!(token.get_type() == TokenType::Break,
+            "Invalid token {:?} in BreakParser", token);
+        let start = token.start();
+        let label = try!(parse_label(parser, &token));
+        // As with `return`, the value must be on the same line.
+        if parser.peek_is_newline(&token)
+            || parser.next_type() == TokenType::EOF {
+            return Ok(Statement::Break(Break::new(start, label, None)))
+        }
+        trace!("Parsing break value");
+        let value = try!(parser.expression(Precedence::Return));
+        let value = try!(value.expect_value());
+        Ok(Statement::Break(Break::new(start, label, Box::new(value))))
+    }
+}
+
+/// Parses continue statements.
+///
+/// # Examples
+/// ```text
+/// continue ['label]
+/// ^take    [^take  ]
+/// ```
+#[derive(Debug)]
+pub struct ContinueParser { }
+impl<T: Tokenizer> PrefixParser<Statement, T> for ContinueParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Statement> {
+        debug_assert!(token.get_type() == TokenType::Continue,
+            "Invalid token {:?} in ContinueParser", token);
+        let label = try!(parse_label(parser, &token));
+        Ok(Statement::Continue(Continue::new(token.start(), label)))
+    }
+}
@@ -0,0 +1,58 @@
+//! Labelled loop parser.
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseError, ParseResult};
+use parse::parsers::{PrefixParser, LoopParser, WhileBlockParser, ForLoopParser};
+
+/// Parses the `'label:` of a loop, then the loop itself.
+///
+/// Any loop may be labelled as a statement, but only `loop` may be labelled
+/// as an expression.
+///
+/// # Examples
+/// ```text
+/// '     outer  :     loop|while|for ...
+/// ^take ^ident ^take ^loop parser
+/// ```
+#[derive(Debug)]
+pub struct LabelledLoopParser { }
+
+impl LabelledLoopParser {
+    /// Parse the label name and the `:` after it.
+    fn label<T: Tokenizer>(&self, parser: &mut Parser<T>, token: &Token)
+                           -> ParseResult<Identifier> {
+        debug_assert!(token.get_type() == TokenType::Quote,
+            "Invalid token {:?} in LabelledLoopParser", token);
+        let label = try!(parser.lvalue());
+        trace!("Parsing loop labelled {}", label.name());
+        try!(parser.consume_type(TokenType::Colon));
+        Ok(label)
+    }
+}
+
+impl<T: Tokenizer> PrefixParser<Statement, T> for LabelledLoopParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Statement> {
+        let label = Some(try!(self.label(parser, &token)));
+        let loop_token = parser.consume();
+        match loop_token.get_type() {
+            TokenType::While =>
+                WhileBlockParser { label }.parse(parser, loop_token),
+            TokenType::For =>
+                ForLoopParser { label }.parse(parser, loop_token),
+            TokenType::Loop =>
+                LoopParser { label }.parse(parser, loop_token)
+                    .map(Statement::Expression),
+            _ => Err(ParseError::LazyString(format!(
+                "Expected a loop after label, got {:?}", loop_token)))
+        }
+    }
+}
+
+impl<T: Tokenizer> PrefixParser<Expression, T> for LabelledLoopParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Expression> {
+        let label = Some(try!(self.label(parser, &token)));
+        let loop_token = try!(parser.consume_type(TokenType::Loop));
+        LoopParser { label }.parse(parser, loop_token)
+    }
+}
@@ -1,9 +1,17 @@
 mod do_block;
 mod return_stmt;
 mod if_block;
+mod while_block;
+mod for_loop;
+mod loop_control;
+mod loop_label;
 mod declaration;
 
 pub use self::do_block::DoBlockParser;
 pub use self::return_stmt::ReturnParser;
 pub use self::if_block::IfBlockParser;
-pub use self::declaration::DeclarationParser;
+pub use self::while_block::WhileBlockParser;
+pub use self::for_loop::ForLoopParser;
+pub use self::loop_control::{BreakParser, ContinueParser};
+pub use self::loop_label::LabelledLoopParser;
+pub use self::declaration::{DeclarationParser, ConstDeclarationParser};
@@ -0,0 +1,35 @@
+//! While loop parser.
+
+use lex::{Token, Tokenizer, TokenType};
+use ast::*;
+use parse::{Parser, ParseResult};
+use parse::parsers::{PrefixParser, Precedence};
+
+/// Parses while loops.
+///
+/// The `label` is set by `LabelledLoopParser` for labelled loops.
+///
+/// # Examples
+/// ```text
+/// while expr \+ stmt* \-
+/// ^take ^expr ^block
+/// ```
+#[derive(Debug)]
+pub struct WhileBlockParser {
+    pub label: Option<Identifier>
+}
+impl<T: Tokenizer> PrefixParser<Statement, T> for WhileBlockParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<Statement> {
+        debug_assert!(token.get_type() == TokenType::While,
+            "Invalid token {:?} in WhileBlockParser", token);
+        trace!("Parsing conditional of while loop");
+        let start = self.label.as_ref()
+            .map_or(token.start(), |label| label.span().start());
+        let condition = try!(parser.expression(Precedence::Min));
+        let condition = try!(condition.expect_value());
+        trace!("Parsing while block");
+        try!(parser.consume_type(TokenType::BeginBlock));
+        let block = try!(parser.block());
+        Ok(Statement::WhileBlock(WhileBlock::new(start, self.label.clone(), condition, block)))
+    }
+}
@@ -0,0 +1,54 @@
+//! Function type parser
+
+use lex::{Token, TokenType, Tokenizer};
+use ast::{Identifier, NamedTypeExpression};
+use ast::types::{TypeExpression, FnTypeExpression};
+use parse::{Parser, ParseResult};
+use parse::parsers::PrefixParser;
+
+/// Parses function types, such as `fn(x: int) -> int`.
+///
+/// The return type is `()` if it is not given.
+///
+/// # Examples
+/// ```text
+/// fn    (      x      :      int            ,      y: int ) ->      int
+/// ^skip ^(take)^lvalue^(take)^->param:type  ^(take)...    ^ ^(take) ^->return:type
+/// ```
+#[derive(Debug)]
+pub struct FnTypeParser { }
+
+impl<T: Tokenizer> PrefixParser<TypeExpression, T> for FnTypeParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<TypeExpression> {
+        debug_assert!(token.get_type() == TokenType::Fn,
+            "Fn type parser called with token {:?}", token);
+        trace!("Parsing fn type");
+        try!(parser.consume_type(TokenType::LeftParen));
+        let mut params = Vec::new();
+        if parser.next_type() != TokenType::RightParen {
+            loop {
+                let name = try!(parser.lvalue());
+                try!(parser.consume_type(TokenType::Colon));
+                let param_ty = try!(parser.binding_type_expr());
+                params.push((name, param_ty));
+                if parser.next_type() != TokenType::Comma {
+                    break
+                }
+                parser.consume();
+            }
+        }
+        let mut end = try!(parser.consume_type(TokenType::RightParen)).end();
+        let return_type = if parser.next_type() == TokenType::Arrow {
+            parser.consume();
+            let return_type = try!(parser.type_expr());
+            end = return_type.span().end();
+            return_type
+        }
+        else {
+            TypeExpression::Named(NamedTypeExpression::new(Identifier::new(
+                Token::new_ident("()", token.start()))))
+        };
+        Ok(TypeExpression::Function(FnTypeExpression::new(
+            token.start(), params, Box::new(return_type), end)))
+    }
+}
@@ -1,29 +1,40 @@
-//mod named;
-//mod array;
+mod named;
+mod array;
+mod slice;
+mod function;
 
-//pub use self::named::NamedTypeParser;
-//pub use self::array::ArrayTypeParser;
+pub use self::named::NamedTypeParser;
+pub use self::array::ArrayTypeParser;
+pub use self::slice::SliceTypeParser;
+pub use self::function::FnTypeParser;
 
 use lex::{Token, TokenType, Tokenizer};
 
-use ast::Identifier;
-use ast::types::{TypeExpression, NamedTypeExpression};
+use ast::types::{TypeExpression, TupleTypeExpression};
 use parse::{Parser, ParseResult};
 use parse::parsers::PrefixParser;
 
-/// `Identifier` parser for type expressions.
+/// Parser for tuple types, such as `(int, bool)`.
 ///
-/// Will be replaced when types become more complicated
+/// A single type in parentheses is that type.
 #[derive(Debug)]
-pub struct NamedTypeParser { }
-impl<T: Tokenizer> PrefixParser<TypeExpression, T> for NamedTypeParser {
-    fn parse(&self, _parser: &mut Parser<T>, token: Token)
+pub struct TupleTypeParser { }
+impl<T: Tokenizer> PrefixParser<TypeExpression, T> for TupleTypeParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token)
              -> ParseResult<TypeExpression> {
-        debug_assert!(token.get_type() == TokenType::Ident,
-            "NamedTypeParser called with non-name token {:?}", token);
-        trace!("Parsing named type {}", token.text());
-        Ok(TypeExpression::Named(NamedTypeExpression::new(
-            Identifier::new(token)
-        )))
+        debug_assert!(token.get_type() == TokenType::LeftParen,
+            "TupleTypeParser called with non-left-paren token {:?}", token);
+        trace!("Parsing tuple type");
+        let mut elements = vec![try!(parser.type_expr())];
+        while parser.next_type() == TokenType::Comma {
+            parser.consume();
+            elements.push(try!(parser.type_expr()));
+        }
+        let end = try!(parser.consume_type(TokenType::RightParen)).end();
+        if elements.len() == 1 {
+            return Ok(elements.pop().expect("Checked expect"))
+        }
+        Ok(TypeExpression::Tuple(TupleTypeExpression::new(
+            token.start(), elements, end)))
     }
 }
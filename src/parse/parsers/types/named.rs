@@ -1,8 +1,8 @@
 //! Parser for named types.
 use lex::{Token, TokenType, Tokenizer};
-use parse::ast::*;
+use ast::types::{TypeExpression, NamedTypeExpression};
 use parse::{Parser, ParseError, ParseResult};
-use parse::parsers::{PrefixParser, IdentifierParser};
+use parse::parsers::PrefixParser;
 
 /// Parses "named" type expressions, including generics.
 /// # Examples
@@ -12,65 +12,39 @@ use parse::parsers::{PrefixParser, IdentifierParser};
 /// ```
 /// ```text
 /// List         <   T       >
-/// ^ name:name,     ^params:genericparam
+/// ^ name:name,     ^args:type_expr
+/// ```
 #[derive(Debug)]
-pub struct NamedTypeParser;
+pub struct NamedTypeParser { }
 
 impl<T: Tokenizer> PrefixParser<TypeExpression, T> for NamedTypeParser {
-    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<TypeExpression> {
-        debug_assert!(token.type() == TokenType::Identifier,
+    fn parse(&self, parser: &mut Parser<T>, token: Token)
+             -> ParseResult<TypeExpression> {
+        debug_assert!(token.get_type() == TokenType::Ident,
             "NamedTypeParser called with non-name token {:?}", token);
         trace!("Parsing named type {}", token.text());
-        let ident = Identifier::new(token);
-        if parser.next_type() == TokenType::LeftAngle {
-            // TODO this should be split into its own parser.
-            // Ideally we have "comma-separated" and "generic bounds".
-            // However, the infrastructure is still pretty simple, but the AST is too.
-            // Once generic parameter bounds begin to be added, they will need their
-            // own expression grammar and parsers.
-            trace!("Found an open bracket, parsing generic params.");
-            let mut params = Vec::new();
-            let mut expect_comma = false;
-            loop {
-                let next = parser.consume();
-                match next.type() {
-                    TokenType::RightAngle => {
-                        if params.is_empty() {
-                            return Err(ParseError::LazyString("No params specified".into()))
-                        }
-                        else if expect_comma {
-                            return Err(ParseError::LazyString("Expected comma".into()))
-                        }
-                        else {
-                            let kind = GenericType::new(ident, params);
-                            return TypeExpression::new(TypeKind::Generic(kind))
-                        }
-                    },
-                    TokenType::Comma => {
-                        if !expect_comma {
-                            return Err(ParseError::LazyString("Unexpected comma"))
-                        }
-                        else {
-                            expect_comma = false;
-                            // Continue with the next token
-                        }
-                    },
-                    TokenType::Identifier => {
-                        let generic_ident = IdentifierParser { }.parse(parser, next);
-                        let param = GenericParameter::Named(NamedType::new(generic_ident));
-                        params.push(param);
-                        expect_comma = true;
-                    },
-                    other => {
-                        return Err(ParseError::LazyString(
-                            "Expected generic type parameter".into()))
-                    }
-                }
-            }
+        let ident = try!(parser.path(token));
+        if parser.next_type() != TokenType::LeftAngle {
+            return Ok(TypeExpression::Named(NamedTypeExpression::new(ident)))
         }
-        else { // Not `LeftAngle`
-            let kind = NamedType::new(ident);
-            return TypeExpression::new(TypeKind::Named(kind))
+        // Generic bounds will eventually need their own grammar, but type
+        // arguments are just comma-separated type expressions.
+        trace!("Found an open angle, parsing type arguments");
+        parser.consume();
+        let mut args = Vec::new();
+        loop {
+            if parser.next_type() == TokenType::RightAngle && args.is_empty() {
+                return Err(ParseError::LazyString(format!(
+                    "No type arguments given to {}", ident.name())))
+            }
+            args.push(try!(parser.type_expr()));
+            if parser.next_type() == TokenType::Comma {
+                parser.consume();
+                continue
+            }
+            let end = try!(parser.consume_type(TokenType::RightAngle)).end();
+            return Ok(TypeExpression::Named(
+                NamedTypeExpression::with_args(ident, args, end)))
         }
     }
 }
@@ -0,0 +1,29 @@
+//! Slice type parser
+
+use lex::{Token, TokenType, Tokenizer};
+use ast::types::{TypeExpression, SliceTypeExpression};
+use parse::{Parser, ParseResult};
+use parse::parsers::PrefixParser;
+
+/// Parses slice types, such as `&[int]`
+///
+/// # Examples
+/// ```text
+/// &      [      int             ]
+/// ^(skip)^(take)^->element:type ^(take)
+/// ```
+#[derive(Debug)]
+pub struct SliceTypeParser { }
+
+impl<T: Tokenizer> PrefixParser<TypeExpression, T> for SliceTypeParser {
+    fn parse(&self, parser: &mut Parser<T>, token: Token) -> ParseResult<TypeExpression> {
+        debug_assert!(token.get_type() == TokenType::Ampersand,
+            "Slice type parser called with token {:?}", token);
+        trace!("Parsing slice type");
+        try!(parser.consume_type(TokenType::LeftBracket));
+        let element = try!(parser.type_expr());
+        let end = try!(parser.consume_type(TokenType::RightBracket)).end();
+        Ok(TypeExpression::Slice(SliceTypeExpression::new(
+            token.start(), Box::new(element), end)))
+    }
+}
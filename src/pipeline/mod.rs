@@ -2,16 +2,19 @@
 
 use lex::IterTokenizer;
 use parse::{Parser, ParseError};
-use ast::{Unit, visit::UnitVisitor};
+use ast::{Unit, Item, ScopedId, TypeExpression, visit::UnitVisitor};
 use identify::{
-    NameScopeBuilder, TypeScopeBuilder, ASTIdentifier, ASTTypeChecker, TypeGraph};
-use check::{ErrorCollector, TypeConcretifier, TypeMapping};
-use compile::{ModuleCompiler, SimpleModuleProvider};
+    NameScopeBuilder, TypeScopeBuilder, ASTIdentifier, ASTTypeChecker, TypeGraph,
+    ConcreteType, FnType, ModuleInterface, module_symbol};
+use check::{ErrorCollector, ConstEvaluator, ConstValues, GlobalInitChecker,
+            MatchChecker, TypeConcretifier, TypeMapping};
+use compile::{ModuleCompiler, ModuleProvider, SimpleModuleProvider, OutputMode,
+              generate_c_header, write_module};
 use llvm::{Context, Builder};
 
 use std::collections::HashMap;
-use std::fs::File;
-use std::path::Path;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 use std::str::Chars;
 use std::io::{self, Read};
 
@@ -28,9 +31,17 @@ pub enum CompilationError {
         type_builder: TypeScopeBuilder,
         graph: TypeGraph,
         errors: ErrorCollector
+    },
+    /// An imported module could not be loaded.
+    ModuleError {
+        module: String,
+        message: String
     }
 }
 
+/// Extension of the files which imported modules are loaded from.
+pub const MODULE_EXTENSION: &str = "snirk";
+
 #[derive(Debug)]
 pub struct Runner<'input> {
     iter: IterTokenizer<Chars<'input>>,
@@ -80,6 +91,25 @@ impl IdentifyRunner {
         }
     }
 
+    /// Names of the modules imported by the unit.
+    pub fn imported_modules(&self) -> Vec<String> {
+        let mut modules = Vec::new();
+        for item in self.unit.items() {
+            if let Item::Import(ref import) = *item {
+                let module = import.module_name().to_string();
+                if !modules.contains(&module) {
+                    modules.push(module);
+                }
+            }
+        }
+        modules
+    }
+
+    /// Allow the unit to import the items of a checked module.
+    pub fn add_module(&mut self, interface: ModuleInterface) {
+        self.type_builder.add_module(interface);
+    }
+
     pub fn identify(mut self) -> Result<CheckRunner, CompilationError> {
         ASTIdentifier::new(&mut self.name_builder,
                            &mut self.type_builder,
@@ -143,6 +173,27 @@ impl CheckRunner {
             tc.visit_unit(&self.unit);
             tc.into_results()
         };
+        // Patterns can only be checked once the matched types are known.
+        if self.errors.errors().is_empty() {
+            MatchChecker::new(&mut self.errors, &results)
+                .visit_unit(&self.unit);
+        }
+        // Consts are computed using the types of their values.
+        let (consts, global_values) = if self.errors.errors().is_empty() {
+            let mut evaluator = ConstEvaluator::new(&mut self.errors,
+                                                    &results,
+                                                    &self.unit);
+            evaluator.visit_unit(&self.unit);
+            evaluator.into_values()
+        }
+        else {
+            (ConstValues::new(), ConstValues::new())
+        };
+        // Only the globals which couldn't be computed run initializers.
+        if self.errors.errors().is_empty() {
+            GlobalInitChecker::new(&mut self.errors, &global_values)
+                .visit_unit(&self.unit);
+        }
         if !self.errors.errors().is_empty() {
             error!("CheckRunner: failed to type concretify");
             Err(CompilationError::CheckingError {
@@ -153,8 +204,132 @@ impl CheckRunner {
             })
         }
         else {
-            Ok(CheckedUnit::new(self.unit, self.name, results))
+            let interface = self.interface();
+            let imported_fns = self.imported_fns();
+            let imported_methods = self.imported_methods();
+            Ok(CheckedUnit::new(self.unit, self.name, results, consts,
+                                global_values, interface, imported_fns,
+                                imported_methods))
+        }
+    }
+
+    /// The items of the unit which other units can import.
+    fn interface(&self) -> ModuleInterface {
+        let mut interface = ModuleInterface::new(self.name.clone());
+        let mut public_types = Vec::new();
+        for item in self.unit.items() {
+            match *item {
+                Item::StructDeclaration(ref struct_decl)
+                        if struct_decl.is_public() =>
+                    public_types.push(struct_decl.name()),
+                Item::EnumDeclaration(ref enum_decl) if enum_decl.is_public() =>
+                    public_types.push(enum_decl.name()),
+                _ => {}
+            }
         }
+        for item in self.unit.items() {
+            match *item {
+                Item::BlockFnDeclaration(ref block_fn)
+                        if !block_fn.is_public() => {
+                    interface.add_private(block_fn.name().to_string());
+                },
+                Item::StructDeclaration(ref struct_decl)
+                        if !struct_decl.is_public() => {
+                    interface.add_private(struct_decl.name().to_string());
+                },
+                Item::EnumDeclaration(ref enum_decl)
+                        if !enum_decl.is_public() => {
+                    interface.add_private(enum_decl.name().to_string());
+                    for variant in enum_decl.variants() {
+                        interface.add_private(variant.name().to_string());
+                    }
+                },
+                Item::BlockFnDeclaration(ref block_fn)
+                        if !block_fn.is_generic() => {
+                    let fn_type = match self.type_builder.get_type(&block_fn.id()) {
+                        Some(&ConcreteType::Function(ref fn_type)) => fn_type.clone(),
+                        other => panic!("Block fn had non-fn type {:?}", other)
+                    };
+                    // Exported fns keep their names for C.
+                    let symbol = if block_fn.is_exported() {
+                        block_fn.name().to_string()
+                    }
+                    else {
+                        module_symbol(&self.name, block_fn.name())
+                    };
+                    interface.add_fn(block_fn.name().to_string(), fn_type, symbol);
+                },
+                Item::StructDeclaration(ref struct_decl) => {
+                    let record = self.type_builder.get_type(&struct_decl.id())
+                        .expect("Struct had no type").clone();
+                    interface.add_type(struct_decl.name().to_string(), record);
+                },
+                Item::EnumDeclaration(ref enum_decl) => {
+                    let enum_ty = self.type_builder.get_type(&enum_decl.id())
+                        .expect("Enum had no type").clone();
+                    interface.add_type(enum_decl.name().to_string(), enum_ty);
+                },
+                // The methods of `pub` types are linked like `pub` fns.
+                Item::ImplBlock(ref impl_block) => {
+                    let is_public = match *impl_block.type_expr() {
+                        TypeExpression::Named(ref named) =>
+                            public_types.contains(&named.ident().name()),
+                        _ => false
+                    };
+                    if !is_public {
+                        continue
+                    }
+                    for method in impl_block.methods() {
+                        if !method.is_method() {
+                            continue
+                        }
+                        let fn_type = match self.type_builder.get_type(&method.id()) {
+                            Some(&ConcreteType::Function(ref fn_type)) => fn_type.clone(),
+                            other => panic!("Method had non-fn type {:?}", other)
+                        };
+                        let self_ty = fn_type.params()[0].1.clone();
+                        let symbol = module_symbol(&self.name,
+                            &format!("{}.{}", self_ty, method.name()));
+                        interface.add_method(self_ty, method.name().to_string(),
+                                             fn_type, symbol);
+                    }
+                },
+                _ => {}
+            }
+        }
+        interface
+    }
+
+    /// The functions the unit imports, with the names they're linked as.
+    fn imported_fns(&self) -> Vec<(ScopedId, String)> {
+        let mut imported_fns = Vec::new();
+        for item in self.unit.items() {
+            let import = match *item {
+                Item::Import(ref import) => import,
+                _ => continue
+            };
+            let interface = self.type_builder.module(import.module_name())
+                .expect("Imported module was not added");
+            for &(ref item, ref id) in import.imported().iter() {
+                if let Some(symbol) = interface.symbol(item) {
+                    imported_fns.push((id.clone(), symbol.to_string()));
+                }
+            }
+        }
+        imported_fns
+    }
+
+    /// The methods of the imported modules' types, which the unit may call.
+    fn imported_methods(&self) -> Vec<(ConcreteType, String, FnType, String)> {
+        let mut imported_methods = Vec::new();
+        for interface in self.type_builder.modules().values() {
+            for (&(ref self_ty, ref name), &(ref fn_type, ref symbol)) in
+                    interface.methods() {
+                imported_methods.push((self_ty.clone(), name.clone(),
+                                       fn_type.clone(), symbol.clone()));
+            }
+        }
+        imported_methods
     }
 }
 
@@ -162,11 +337,23 @@ impl CheckRunner {
 pub struct CheckedUnit {
     unit: Unit,
     name: String,
-    map: TypeMapping
+    map: TypeMapping,
+    consts: ConstValues,
+    global_values: ConstValues,
+    interface: ModuleInterface,
+    imported_fns: Vec<(ScopedId, String)>,
+    imported_methods: Vec<(ConcreteType, String, FnType, String)>
 }
 impl CheckedUnit {
-    fn new(unit: Unit, name: String, map: TypeMapping) -> CheckedUnit {
-        CheckedUnit { unit, name, map }
+    fn new(unit: Unit, name: String, map: TypeMapping, consts: ConstValues,
+           global_values: ConstValues, interface: ModuleInterface,
+           imported_fns: Vec<(ScopedId, String)>,
+           imported_methods: Vec<(ConcreteType, String, FnType, String)>)
+           -> CheckedUnit {
+        CheckedUnit {
+            unit, name, map, consts, global_values, interface, imported_fns,
+            imported_methods
+        }
     }
 
     pub fn unit(&self) -> &Unit {
@@ -176,6 +363,114 @@ impl CheckedUnit {
     pub fn type_map(&self) -> &TypeMapping {
         &self.map
     }
+
+    /// The values of the unit's consts.
+    pub fn consts(&self) -> &ConstValues {
+        &self.consts
+    }
+
+    /// The items of the unit which other units can import.
+    pub fn interface(&self) -> &ModuleInterface {
+        &self.interface
+    }
+}
+
+/// Runner which checks a unit along with the modules it imports.
+///
+/// A module `math` imported with `import math` is loaded from `math.snirk`
+/// in the module directory. Modules are checked before the units which
+/// import them.
+#[derive(Debug)]
+pub struct ProgramRunner {
+    module_dir: PathBuf,
+    /// Checked modules, each after the modules it imports.
+    modules: Vec<CheckedUnit>,
+    /// Modules which are being checked, for finding import cycles.
+    loading: Vec<String>
+}
+
+impl ProgramRunner {
+    pub fn new<P: AsRef<Path>>(module_dir: P) -> ProgramRunner {
+        ProgramRunner {
+            module_dir: module_dir.as_ref().to_path_buf(),
+            modules: Vec::new(),
+            loading: Vec::new()
+        }
+    }
+
+    pub fn check(mut self, main: IdentifyRunner)
+                 -> Result<CheckedProgram, CompilationError> {
+        let main = try!(self.check_unit(main));
+        Ok(CheckedProgram { main, modules: self.modules })
+    }
+
+    fn check_unit(&mut self, mut runner: IdentifyRunner)
+                  -> Result<CheckedUnit, CompilationError> {
+        for module in runner.imported_modules() {
+            if !self.modules.iter().any(|checked| checked.name == module) {
+                try!(self.load_module(&module));
+            }
+            let interface = self.modules.iter()
+                .find(|checked| checked.name == module)
+                .expect("Module was checked")
+                .interface()
+                .clone();
+            runner.add_module(interface);
+        }
+        runner.identify().and_then(|identified| identified.check())
+    }
+
+    fn load_module(&mut self, module: &str) -> Result<(), CompilationError> {
+        if let Some(ix) = self.loading.iter().position(|name| name == module) {
+            let mut cycle = self.loading[ix..].to_vec();
+            cycle.push(module.to_string());
+            debug!("Emitting error: import cycle {}", cycle.join(" -> "));
+            return Err(CompilationError::ModuleError {
+                module: module.to_string(),
+                message: format!("Modules cannot import each other: {}",
+                    cycle.join(" -> "))
+            })
+        }
+        let path = self.module_dir.join(module).with_extension(MODULE_EXTENSION);
+        trace!("Loading module {} from {}", module, path.display());
+        let mut buffer = String::new();
+        try!(File::open(&path)
+            .and_then(|mut file| file.read_to_string(&mut buffer))
+            .map_err(|err| CompilationError::ModuleError {
+                module: module.to_string(),
+                message: format!("Unable to read {}: {}", path.display(), err)
+            }));
+        let runner = try!(Runner::from_string(&buffer, module.to_string())
+            .parse()
+            .map_err(|err| CompilationError::ModuleError {
+                module: module.to_string(),
+                message: format!("Unable to parse {}: {:?}",
+                    path.display(), err)
+            }));
+
+        self.loading.push(module.to_string());
+        let checked = self.check_unit(runner);
+        self.loading.pop();
+        self.modules.push(try!(checked));
+        Ok(())
+    }
+}
+
+/// A unit and the modules it imports, which are compiled together.
+#[derive(Debug)]
+pub struct CheckedProgram {
+    main: CheckedUnit,
+    modules: Vec<CheckedUnit>
+}
+
+impl CheckedProgram {
+    pub fn main(&self) -> &CheckedUnit {
+        &self.main
+    }
+
+    pub fn modules(&self) -> &[CheckedUnit] {
+        &self.modules
+    }
 }
 
 pub struct CompileRunner<'ctx> {
@@ -188,6 +483,54 @@ impl<'ctx> CompileRunner<'ctx> {
 
     pub fn compile(&mut self, unit: CheckedUnit, optimizations: bool)
                    -> SimpleModuleProvider<'ctx> {
+        self.compile_unit(unit, false, optimizations)
+    }
+
+    /// Compile a unit and the modules it imports, and link them into the
+    /// unit's module.
+    pub fn compile_program(&mut self, program: CheckedProgram,
+                           optimizations: bool)
+                           -> Result<SimpleModuleProvider<'ctx>, String> {
+        let provider = self.compile_unit(program.main, false, optimizations);
+        for module in program.modules {
+            trace!("Linking module {}", module.name);
+            let module_provider = self.compile_unit(module, true, optimizations);
+            try!(provider.module().link(module_provider.into_module()));
+        }
+        Ok(provider)
+    }
+
+    /// Compile a program and write it to `path` as an object file or shared
+    /// library.
+    ///
+    /// A shared library is written along with a C header declaring the
+    /// `export fn`s of the program's unit, at `path` with the `h` extension.
+    pub fn write_program(&mut self, program: CheckedProgram,
+                         optimizations: bool, mode: OutputMode, path: &Path)
+                         -> Result<(), String> {
+        let header = match mode {
+            OutputMode::SharedLibrary => {
+                let name = path.file_stem()
+                    .map_or(program.main.name.clone(),
+                            |stem| stem.to_string_lossy().into());
+                Some(generate_c_header(&program.main.unit, &program.main.map,
+                                       &name))
+            },
+            OutputMode::Object => None
+        };
+        let provider = try!(self.compile_program(program, optimizations));
+        try!(write_module(provider.module(), mode, path));
+        if let Some(header) = header {
+            let header_path = path.with_extension("h");
+            trace!("Writing C header {}", header_path.display());
+            try!(fs::write(&header_path, header).map_err(|err|
+                format!("Unable to write {}: {}", header_path.display(), err)));
+        }
+        Ok(())
+    }
+
+    fn compile_unit(&mut self, unit: CheckedUnit, is_module: bool,
+                    optimizations: bool) -> SimpleModuleProvider<'ctx> {
         let module = self.context.new_module(&unit.name);
         {
             let builder = Builder::new(&self.context);
@@ -203,6 +546,18 @@ impl<'ctx> CompileRunner<'ctx> {
                     &mut ir_code,
                     &mut scopes,
                     optimizations);
+                compiler.set_consts(unit.consts, unit.global_values);
+                if is_module {
+                    compiler.set_module_name(&unit.name);
+                }
+                for &(ref id, ref symbol) in &unit.imported_fns {
+                    compiler.declare_imported_fn(id, symbol);
+                }
+                for &(ref self_ty, ref name, ref fn_type, ref symbol) in
+                        &unit.imported_methods {
+                    compiler.declare_imported_method(self_ty, name, fn_type,
+                                                     symbol);
+                }
                 compiler.visit_unit(&unit.unit);
 
                 let (provider, _types) = compiler.decompose();
@@ -6,6 +6,7 @@ extern crate protosnirk;
 #[macro_use]
 extern crate log;
 extern crate env_logger;
+extern crate libc;
 
 #[macro_use]
 extern crate derive_integration_tests;
@@ -15,8 +16,11 @@ use std::env;
 use std::fs::File;
 use std::io::{Read, Write};
 
-use protosnirk::llvm::{Context};
-use protosnirk::pipeline::{Runner, CompileRunner, CompilationError};
+use protosnirk::llvm::{Context, Module, ExecutionEngine};
+use protosnirk::lex::LexError;
+use protosnirk::parse::ParseError;
+use protosnirk::pipeline::{Runner, ProgramRunner, CompileRunner, CompilationError};
+use protosnirk::compile::OutputMode;
 
 fn init_logs() {
     use env_logger::{Builder, Target};
@@ -74,6 +78,16 @@ impl TestMode {
     }
 }
 
+/// The value a test's `main` returns, given by a `// main returns` line
+/// such as `// main returns 12`, `// main returns 2.5`, or
+/// `// main returns true`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MainResult {
+    Int(i64),
+    Float(f64),
+    Bool(bool)
+}
+
 #[derive(Debug)]
 pub struct Test {
     name: String,
@@ -133,6 +147,40 @@ impl Test {
     pub fn mode(&self) -> TestMode {
         self.mode
     }
+
+    /// The value `main` must return, given by a `// main returns N` line.
+    ///
+    /// Tests with this line are run after they compile, and their `main`
+    /// must return an integer.
+    pub fn expected_result(&self) -> Option<MainResult> {
+        self.content.lines()
+            .filter_map(|line| line.trim().splitn(2, "// main returns ").nth(1))
+            .next()
+            .map(|value| match value.trim() {
+                "true" => MainResult::Bool(true),
+                "false" => MainResult::Bool(false),
+                float if float.contains('.') => MainResult::Float(float.parse()
+                    .expect("Expected a float after `// main returns`")),
+                int => MainResult::Int(int.parse()
+                    .expect("Expected a value after `// main returns`"))
+            })
+    }
+
+    /// The lex errors the test must fail to parse with, in order, given by
+    /// `// lex error: Kind at span` lines.
+    pub fn expected_lex_errors(&self) -> Vec<String> {
+        self.content.lines()
+            .filter_map(|line| line.trim().splitn(2, "// lex error: ").nth(1))
+            .map(|error| error.trim().to_string())
+            .collect()
+    }
+
+    pub fn expected_parse_error(&self) -> Option<String> {
+        self.content.lines()
+            .filter_map(|line| line.trim().splitn(2, "// parse error: ").nth(1))
+            .map(|error| error.trim().to_string())
+            .next()
+    }
 }
 
 type TestResult = Result<(), String>;
@@ -156,7 +204,8 @@ fn compile_runner(test: Test) -> TestResult {
                 parse_error))
         }
         else {
-            return Ok(()) // Test successful
+            return check_lex_errors(&test, &parse_error)
+                .and_then(|_| check_parse_error(&test, &parse_error))
         }
     }
     else if test.mode() == TestMode::ParseFail {
@@ -165,22 +214,27 @@ fn compile_runner(test: Test) -> TestResult {
 
     info!("Test parsed sucessfully.\n");
 
-    let compile_result = parse_result.expect("Checked for bad parse result")
-        .identify()
-        .and_then(|identified| identified.check());
+    // Modules imported by the test are next to it.
+    let module_dir = Path::new(test.path()).parent()
+        .expect("Test had no directory");
+    let compile_result = ProgramRunner::new(module_dir)
+        .check(parse_result.expect("Checked for bad parse result"));
 
     if let Err(errors) =  compile_result {
         if test.mode() != TestMode::CompileFail {
             if let Ok(print_ast) = env::var("SNIRK_PRINT_AST") {
                 let unit = match errors {
-                    CompilationError::IdentificationError { ref unit, .. } => unit,
-                    CompilationError::CheckingError { ref unit, .. } => unit
+                    CompilationError::IdentificationError { ref unit, .. } => Some(unit),
+                    CompilationError::CheckingError { ref unit, .. } => Some(unit),
+                    CompilationError::ModuleError { .. } => None
                 };
-                if print_ast.to_lowercase() == "full" {
-                    info!("AST:\n{:#?}\n", unit);
-                }
-                else {
-                    info!("AST:\n{:?}\n", unit);
+                if let Some(unit) = unit {
+                    if print_ast.to_lowercase() == "full" {
+                        info!("AST:\n{:#?}\n", unit);
+                    }
+                    else {
+                        info!("AST:\n{:?}\n", unit);
+                    }
                 }
             }
             if let Some(file_path) = graph_file_path {
@@ -215,10 +269,167 @@ fn compile_runner(test: Test) -> TestResult {
     {
         let context = Context::new();
         let mut compiler = CompileRunner::new(&context);
-        let _module = compiler.compile(checked, false);
+        let linked = compiler.compile_program(checked, false);
+        let provider = match linked {
+            Ok(provider) => provider,
+            Err(message) =>
+                return Err(format!("Failed to link {}: {}", test.path(), message))
+        };
+        if let Some(expected) = test.expected_result() {
+            return run_main(&test, provider.into_module(), expected)
+        }
+    }
+    Ok(())
+}
+
+fn check_lex_errors(test: &Test, error: &ParseError) -> TestResult {
+    let expected = test.expected_lex_errors();
+    if expected.is_empty() {
+        return Ok(())
+    }
+    let found = match *error {
+        ParseError::LexErrors(ref errors) =>
+            errors.iter().map(describe_lex_error).collect::<Vec<_>>(),
+        _ => return Err(format!("Test {} failed to parse without lex errors: {:#?}",
+                                test.path(), error))
+    };
+    if found != expected {
+        return Err(format!("Test {} had lex errors {:#?}, expected {:#?}",
+                           test.path(), found, expected))
+    }
+    Ok(())
+}
+
+fn check_parse_error(test: &Test, error: &ParseError) -> TestResult {
+    let expected = match test.expected_parse_error() {
+        Some(expected) => expected,
+        None => return Ok(())
+    };
+    let found = describe_parse_error(error);
+    if found != expected {
+        return Err(format!("Test {} had parse error {}, expected {}",
+                           test.path(), found, expected))
     }
     Ok(())
 }
 
+/// Describe a parse error by its kind, such as `EOF`, or with the item
+/// and span of `InvalidPub`, such as `InvalidPub const at line 2, column 4 to 9`.
+fn describe_parse_error(error: &ParseError) -> String {
+    if let ParseError::InvalidPub { item, ref token } = *error {
+        return format!("InvalidPub {} at {}", item, token.span())
+    }
+    let debug = format!("{:?}", error);
+    debug.split(|c: char| !c.is_alphanumeric()).next()
+        .unwrap_or_default().to_string()
+}
+
+/// Describe a lex error as `Kind at span`, such as
+/// `MergeConflict at line 4, column 0 to line 8, column 19`.
+fn describe_lex_error(error: &LexError) -> String {
+    let debug = format!("{:?}", error);
+    let kind = debug.split(|c: char| !c.is_alphanumeric()).next()
+        .unwrap_or_default();
+    format!("{} at {}", kind, error.span())
+}
+
+fn run_main(test: &Test, module: Module, expected: MainResult) -> TestResult {
+    use std::mem;
+
+    let engine = match ExecutionEngine::for_module(module) {
+        Ok(engine) => engine,
+        Err(message) =>
+            return Err(format!("Failed to run {}: {}", test.path(), message))
+    };
+    let main_address = match engine.function_address("main") {
+        Some(address) => address,
+        None => return Err(format!("Test {} has no main", test.path()))
+    };
+    // The expected value gives main's return type.
+    let result = unsafe {
+        match expected {
+            MainResult::Int(_) => MainResult::Int(
+                mem::transmute::<_, extern "C" fn() -> i64>(main_address)()),
+            MainResult::Float(_) => MainResult::Float(
+                mem::transmute::<_, extern "C" fn() -> f64>(main_address)()),
+            MainResult::Bool(_) => MainResult::Bool(
+                mem::transmute::<_, extern "C" fn() -> bool>(main_address)())
+        }
+    };
+    info!("Test {} returned {:?}", test.name(), result);
+    if result != expected {
+        return Err(format!("Test {} returned {:?}, expected {:?}",
+                           test.path(), result, expected))
+    }
+    Ok(())
+}
+
+/// Write the `export fn`s of a unit to a shared library, and call them
+/// through the library and header it's written with.
+#[test]
+fn export_shared_library() {
+    use std::ffi::CString;
+    use std::fs;
+    use std::mem;
+
+    init_logs();
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/compile/item/export");
+    let mut buffer = String::new();
+    let parsed = Runner::from_file(test_dir.join("export-library-ok.protosnirk"),
+                                   &mut buffer)
+        .expect("Unable to read test")
+        .parse()
+        .expect("Unable to parse test");
+    let checked = ProgramRunner::new(&test_dir)
+        .check(parsed)
+        .expect("Unable to check test");
+
+    let dir = env::temp_dir()
+        .join(format!("protosnirk-export-{}", ::std::process::id()));
+    fs::create_dir_all(&dir).expect("Unable to create output dir");
+    let library_path = dir.join("libgeometry.so");
+    let context = Context::new();
+    CompileRunner::new(&context)
+        .write_program(checked, false, OutputMode::SharedLibrary, &library_path)
+        .expect("Unable to write shared library");
+
+    let header = fs::read_to_string(dir.join("libgeometry.h"))
+        .expect("Unable to read header");
+    info!("Header:\n{}", header);
+    assert!(header.starts_with("#ifndef LIBGEOMETRY_H\n#define LIBGEOMETRY_H\n"));
+    assert!(header.contains("\ndouble hypotenuse_squared(double a, double b);\n"));
+    assert!(header.contains("\nbool is_even(int64_t n);\n"));
+    assert!(header.contains("\nint32_t answer(void);\n"));
+    assert!(!header.contains("square("), "Header declared a private fn");
+
+    let library_name = CString::new(library_path.to_string_lossy().as_bytes())
+        .expect("Library path had a null");
+    unsafe {
+        let library = libc::dlopen(library_name.as_ptr(), libc::RTLD_NOW);
+        assert!(!library.is_null(), "Unable to load {}", library_path.display());
+        let symbol = |name: &str| {
+            let name = CString::new(name).expect("Symbol had a null");
+            let address = libc::dlsym(library, name.as_ptr());
+            assert!(!address.is_null(), "Library has no {:?}", name);
+            address
+        };
+        let hypotenuse_squared: extern "C" fn(f64, f64) -> f64 =
+            mem::transmute(symbol("hypotenuse_squared"));
+        let is_even: extern "C" fn(i64) -> bool =
+            mem::transmute(symbol("is_even"));
+        let answer: extern "C" fn() -> i32 = mem::transmute(symbol("answer"));
+        assert_eq!(hypotenuse_squared(3.0, 4.0), 25.0);
+        assert!(is_even(10));
+        assert!(!is_even(7));
+        assert_eq!(answer(), 42);
+        let square = CString::new("square").expect("Symbol had a null");
+        assert!(libc::dlsym(library, square.as_ptr()).is_null(),
+            "Library exported a private fn");
+        libc::dlclose(library);
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+
 #[derive(IntegrationTests)]
 struct _Placeholder;